@@ -0,0 +1,801 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use crate::{
+    backend::render,
+    config::OutputConfig,
+    state::{BackendData, Common, Data},
+    utils::prelude::*,
+    wayland::protocols::screencopy::{BufferParams, Session as ScreencopySession},
+};
+use anyhow::{Context, Result};
+use smithay::{
+    backend::{
+        allocator::Fourcc,
+        egl::{EGLContext, EGLDevice, EGLDisplay},
+        input::{
+            AbsolutePositionEvent, Axis, AxisSource, ButtonState, Device, DeviceCapability, Event,
+            GestureBeginEvent, GestureEndEvent, GestureSwipeBeginEvent, GestureSwipeEndEvent,
+            GestureSwipeUpdateEvent, InputBackend, InputEvent, KeyState, KeyboardKeyEvent,
+            PointerAxisEvent, PointerButtonEvent, PointerMotionAbsoluteEvent, UnusedEvent,
+        },
+        renderer::{
+            damage::{OutputDamageTracker, RenderOutputResult},
+            gles::GlesRenderbuffer,
+            glow::GlowRenderer,
+            Offscreen,
+        },
+    },
+    output::{Mode, Output, PhysicalProperties, Scale, Subpixel},
+    reexports::{
+        calloop::{
+            channel::{self, Sender},
+            ping,
+            timer::{TimeoutAction, Timer},
+            EventLoop, LoopHandle,
+        },
+        wayland_protocols::wp::presentation_time::server::wp_presentation_feedback,
+        wayland_server::DisplayHandle,
+    },
+    utils::{Physical, Size, Transform},
+};
+use std::{cell::RefCell, path::PathBuf, time::Duration};
+use tracing::{error, info};
+
+#[cfg(feature = "debug")]
+use crate::state::Fps;
+
+use super::render::init_shaders;
+
+const REFRESH_RATE: i32 = 60_000;
+
+pub struct HeadlessState {
+    _egl: Option<EGLDisplay>,
+    /// `None` if the outputs are not rendered at all
+    pub renderer: Option<GlowRenderer>,
+    surfaces: Vec<Surface>,
+    input: Sender<HeadlessInputEvent>,
+}
+
+impl HeadlessState {
+    /// Queue to inject input events into the compositor
+    pub fn input_sender(&self) -> Sender<HeadlessInputEvent> {
+        self.input.clone()
+    }
+
+    pub fn add_output(
+        &mut self,
+        handle: LoopHandle<'_, Data>,
+        size: Size<i32, Physical>,
+        scale: f64,
+        position: (i32, i32),
+    ) -> Result<Output> {
+        let name = format!("HEADLESS-{}", self.surfaces.len());
+        let props = PhysicalProperties {
+            size: (0, 0).into(),
+            subpixel: Subpixel::Unknown,
+            make: "COSMIC".to_string(),
+            model: name.clone(),
+        };
+        let mode = Mode {
+            size,
+            refresh: REFRESH_RATE,
+        };
+        let output = Output::new(name, props);
+        output.add_mode(mode);
+        output.set_preferred(mode);
+        output.change_current_state(
+            Some(mode),
+            Some(Transform::Normal),
+            Some(Scale::Fractional(scale)),
+            Some(position.into()),
+        );
+        output.user_data().insert_if_missing(|| {
+            RefCell::new(OutputConfig {
+                mode: ((size.w, size.h), Some(REFRESH_RATE as u32)),
+                scale,
+                position,
+                ..Default::default()
+            })
+        });
+
+        let buffer = self.create_buffer(size)?;
+
+        let output_ref = output.clone();
+        let (ping, source) =
+            ping::make_ping().with_context(|| "Failed to create output event loop source")?;
+        let _token = handle
+            .insert_source(source, move |_, _, data| {
+                let headless_state = data.state.backend.headless();
+                if let Some(surface) = headless_state
+                    .surfaces
+                    .iter_mut()
+                    .find(|s| s.output == output_ref)
+                {
+                    surface.dirty = false;
+                    let Some(renderer) = headless_state.renderer.as_mut() else {
+                        // nothing is ever shown, just like on a blanked output
+                        data.state.common.shell.session_lock_presented(&output_ref);
+                        return;
+                    };
+                    if let Err(err) = surface.render_output(renderer, &mut data.state.common) {
+                        error!(?err, "Error rendering.");
+                    }
+                    surface.pending = true;
+                    surface.schedule_vblank(&data.state.common.event_loop_handle);
+                }
+            })
+            .with_context(|| "Failed to add output to event loop")?;
+
+        self.surfaces.push(Surface {
+            buffer,
+            size,
+            age: 0,
+            damage_tracker: OutputDamageTracker::from_output(&output),
+            output: output.clone(),
+            render: ping.clone(),
+            dirty: false,
+            pending: self.renderer.is_some(),
+            screencopy: Vec::new(),
+            #[cfg(feature = "debug")]
+            fps: self.renderer.as_mut().map(Fps::new),
+        });
+
+        // schedule first render
+        ping.ping();
+        Ok(output)
+    }
+
+    fn create_buffer(&mut self, size: Size<i32, Physical>) -> Result<Option<GlesRenderbuffer>> {
+        let Some(renderer) = self.renderer.as_mut() else {
+            return Ok(None);
+        };
+        Offscreen::<GlesRenderbuffer>::create_buffer(
+            renderer,
+            Fourcc::Abgr8888,
+            (size.w, size.h).into(),
+        )
+        .map(Some)
+        .with_context(|| "Failed to create offscreen buffer")
+    }
+
+    pub fn schedule_render(
+        &mut self,
+        output: &Output,
+        screencopy: Option<Vec<(ScreencopySession, BufferParams)>>,
+    ) {
+        if let Some(surface) = self.surfaces.iter_mut().find(|s| s.output == *output) {
+            surface.dirty = true;
+            if let Some(sessions) = screencopy {
+                surface.screencopy.extend(sessions);
+            }
+            if !surface.pending {
+                surface.render.ping();
+            }
+        }
+    }
+
+    pub fn apply_config_for_output(
+        &mut self,
+        output: &Output,
+        test_only: bool,
+    ) -> Result<(), anyhow::Error> {
+        let config = output
+            .user_data()
+            .get::<RefCell<OutputConfig>>()
+            .unwrap()
+            .borrow()
+            .clone();
+        if !config.enabled {
+            anyhow::bail!("Cannot disable headless outputs");
+        }
+
+        let size = config.mode_size();
+        let needs_resize = self
+            .surfaces
+            .iter()
+            .find(|s| s.output == *output)
+            .with_context(|| "Unknown output")?
+            .size
+            != size;
+        if needs_resize && !test_only {
+            let buffer = self.create_buffer(size)?;
+            let surface = self
+                .surfaces
+                .iter_mut()
+                .find(|s| s.output == *output)
+                .unwrap();
+            surface.buffer = buffer;
+            surface.size = size;
+            surface.age = 0;
+            output.add_mode(Mode {
+                size,
+                refresh: REFRESH_RATE,
+            });
+        }
+        Ok(())
+    }
+}
+
+pub struct Surface {
+    buffer: Option<GlesRenderbuffer>,
+    size: Size<i32, Physical>,
+    age: usize,
+    damage_tracker: OutputDamageTracker,
+    screencopy: Vec<(ScreencopySession, BufferParams)>,
+    output: Output,
+    render: ping::Ping,
+    dirty: bool,
+    pending: bool,
+    #[cfg(feature = "debug")]
+    fps: Option<Fps>,
+}
+
+impl Surface {
+    pub fn render_output(&mut self, renderer: &mut GlowRenderer, state: &mut Common) -> Result<()> {
+        let buffer = self
+            .buffer
+            .clone()
+            .with_context(|| "No buffer to render into")?;
+        match render::render_output::<_, _, GlesRenderbuffer, _>(
+            None,
+            renderer,
+            buffer.clone(),
+            &mut self.damage_tracker,
+            self.age,
+            state,
+            &self.output,
+            render::CursorMode::All,
+            if !self.screencopy.is_empty() {
+                Some((buffer, &self.screencopy))
+            } else {
+                None
+            },
+            #[cfg(not(feature = "debug"))]
+            None,
+            #[cfg(feature = "debug")]
+            self.fps.as_mut(),
+        ) {
+            Ok(RenderOutputResult { damage, states, .. }) => {
+                self.screencopy.clear();
+                // we keep rendering into the same buffer
+                self.age = 1;
+                #[cfg(feature = "debug")]
+                if let Some(fps) = self.fps.as_mut() {
+                    fps.displayed();
+                }
+                state.send_frames(&self.output, &states, |_| None);
+                // the frame is shown right away, so it counts as presented for the session lock
+                state.shell.session_lock_presented(&self.output);
+                if damage.is_some() {
+                    let mut output_presentation_feedback =
+                        state.take_presentation_feedback(&self.output, &states);
+                    output_presentation_feedback.presented(
+                        state.clock.now(),
+                        self.output
+                            .current_mode()
+                            .map(|mode| mode.refresh as u32)
+                            .unwrap_or_default(),
+                        0,
+                        wp_presentation_feedback::Kind::empty(),
+                    )
+                }
+            }
+            Err(err) => {
+                for (session, params) in self.screencopy.drain(..) {
+                    state.still_pending(session, params)
+                }
+                self.age = 0;
+                anyhow::bail!("Rendering failed: {}", err);
+            }
+        };
+
+        Ok(())
+    }
+
+    /// There is no display to wait for, so emulate the next vblank with a timer
+    fn schedule_vblank(&self, handle: &LoopHandle<'_, Data>) {
+        let output = self.output.clone();
+        let frame_duration = Duration::from_secs_f64(1_000.0 / REFRESH_RATE as f64);
+        if let Err(err) =
+            handle.insert_source(Timer::from_duration(frame_duration), move |_, _, data| {
+                if let BackendData::Headless(headless_state) = &mut data.state.backend {
+                    if let Some(surface) = headless_state
+                        .surfaces
+                        .iter_mut()
+                        .find(|s| s.output == output)
+                    {
+                        if surface.dirty {
+                            surface.render.ping();
+                        } else {
+                            surface.pending = false;
+                        }
+                    }
+                }
+                TimeoutAction::Drop
+            })
+        {
+            error!(?err, "Failed to schedule next frame, are we shutting down?");
+        }
+    }
+}
+
+/// Size and scale of the virtual outputs, read from `COSMIC_HEADLESS_OUTPUTS`.
+///
+/// The variable takes a comma separated list of `<width>x<height>[@<scale>]`,
+/// e.g. `1920x1080,2560x1440@2`. Defaults to a single 1920x1080 output.
+fn output_configs() -> Result<Vec<(Size<i32, Physical>, f64)>> {
+    let Ok(var) = std::env::var("COSMIC_HEADLESS_OUTPUTS") else {
+        return Ok(vec![((1920, 1080).into(), 1.0)]);
+    };
+
+    var.split(',')
+        .map(|output| {
+            let output = output.trim();
+            let (size, scale) = match output.split_once('@') {
+                Some((size, scale)) => (
+                    size,
+                    scale
+                        .parse::<f64>()
+                        .with_context(|| format!("Invalid scale for output {}", output))?,
+                ),
+                None => (output, 1.0),
+            };
+            let (width, height) = size
+                .split_once('x')
+                .with_context(|| format!("Invalid size for output {}", output))?;
+            let width = width
+                .parse::<i32>()
+                .with_context(|| format!("Invalid width for output {}", output))?;
+            let height = height
+                .parse::<i32>()
+                .with_context(|| format!("Invalid height for output {}", output))?;
+            Ok(((width, height).into(), scale))
+        })
+        .collect()
+}
+
+pub fn init_backend(
+    dh: &DisplayHandle,
+    event_loop: &mut EventLoop<Data>,
+    state: &mut State,
+) -> Result<()> {
+    let outputs = output_configs()?;
+
+    // Prefer a software device, there is likely no gpu anyway
+    let mut devices = EGLDevice::enumerate()
+        .with_context(|| "Failed to enumerate EGL devices")?
+        .collect::<Vec<_>>();
+    anyhow::ensure!(!devices.is_empty(), "No EGL device found");
+    let idx = devices
+        .iter()
+        .position(|device| {
+            device
+                .extensions()
+                .iter()
+                .any(|ext| ext == "EGL_MESA_device_software")
+        })
+        .unwrap_or(0);
+    let device = devices.swap_remove(idx);
+    let render_node = device.try_get_render_node().ok().flatten();
+
+    // Initialize EGL
+    let egl = EGLDisplay::new(device).with_context(|| "Failed to create EGL display")?;
+    // Create the OpenGL context
+    let context = EGLContext::new(&egl).with_context(|| "Failed to create EGL context")?;
+    // Create a renderer
+    let mut renderer =
+        unsafe { GlowRenderer::new(context) }.with_context(|| "Failed to initialize renderer")?;
+
+    init_shaders(&mut renderer).expect("Failed to initialize renderer");
+    match render_node {
+        Some(node) => super::x11::init_egl_client_side(dh, state, &node, &mut renderer)?,
+        None => info!("Software rendering, clients are limited to shm buffers."),
+    }
+
+    init(event_loop, state, Some((egl, renderer)), outputs)?;
+    state.launch_xwayland(None);
+
+    Ok(())
+}
+
+/// Initializes the headless backend without any rendering.
+///
+/// Clients still get mapped and receive input, but never get frame callbacks
+/// and screencopy fails. Used to drive the compositor in tests.
+pub fn init_backend_without_renderer(
+    event_loop: &mut EventLoop<Data>,
+    state: &mut State,
+    outputs: Vec<(Size<i32, Physical>, f64)>,
+) -> Result<()> {
+    init(event_loop, state, None, outputs)
+}
+
+fn init(
+    event_loop: &mut EventLoop<Data>,
+    state: &mut State,
+    renderer: Option<(EGLDisplay, GlowRenderer)>,
+    outputs: Vec<(Size<i32, Physical>, f64)>,
+) -> Result<()> {
+    let (egl, renderer) = renderer.unzip();
+    let (input, input_source) = channel::channel();
+    state.backend = BackendData::Headless(HeadlessState {
+        _egl: egl,
+        renderer,
+        surfaces: Vec::new(),
+        input,
+    });
+
+    let mut x = 0;
+    for (size, scale) in outputs {
+        let output = state
+            .backend
+            .headless()
+            .add_output(event_loop.handle(), size, scale, (x, 0))
+            .with_context(|| "Failed to create wl_output")?;
+        x += (size.w as f64 / scale).round() as i32;
+        state
+            .common
+            .output_configuration_state
+            .add_heads(std::iter::once(&output));
+        state.common.shell.add_output(&output);
+    }
+    let seats = state.common.seats().cloned().collect::<Vec<_>>();
+    state.common.config.read_outputs(
+        &mut state.common.output_configuration_state,
+        &mut state.backend,
+        &mut state.common.shell,
+        seats.iter().cloned(),
+        &state.common.event_loop_handle,
+    );
+
+    event_loop
+        .handle()
+        .insert_source(input_source, |event, _, data| {
+            if let channel::Event::Msg(event) = event {
+                data.state.process_headless_event(event);
+            }
+        })
+        .map_err(|_| anyhow::anyhow!("Failed to insert headless input queue into event loop"))?;
+    // the seat doesn't exist yet, announce our device once the event loop runs
+    event_loop.handle().insert_idle(|data| {
+        data.state
+            .process_input_event(InputEvent::<HeadlessInput>::DeviceAdded {
+                device: HeadlessVirtualDevice,
+            });
+    });
+
+    Ok(())
+}
+
+/// Input event, that can be injected into the headless backend via [`HeadlessState::input_sender`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeadlessInputEvent {
+    /// Press or release a key, given as evdev keycode
+    Key { key_code: u32, state: KeyState },
+    /// Move the pointer on the active output, relative to its size (0.0 to 1.0)
+    PointerMotionAbsolute { x: f64, y: f64 },
+    /// Press or release a mouse button, given as evdev button code
+    PointerButton { button: u32, state: ButtonState },
+    /// Scroll by the given amount
+    PointerAxis { horizontal: f64, vertical: f64 },
+    /// Put the given amount of fingers on the touchpad, starting a swipe
+    GestureSwipeBegin { fingers: u32 },
+    /// Move the fingers of the current swipe by the given amount
+    GestureSwipeUpdate { dx: f64, dy: f64 },
+    /// Lift the fingers of the current swipe
+    GestureSwipeEnd { cancelled: bool },
+}
+
+impl State {
+    pub fn process_headless_event(&mut self, event: HeadlessInputEvent) {
+        let time = Into::<Duration>::into(self.common.clock.now()).as_micros() as u64;
+        let event = match event {
+            HeadlessInputEvent::Key { key_code, state } => InputEvent::Keyboard {
+                event: HeadlessKeyboardKeyEvent {
+                    time,
+                    key_code,
+                    state,
+                },
+            },
+            HeadlessInputEvent::PointerMotionAbsolute { x, y } => {
+                InputEvent::PointerMotionAbsolute {
+                    event: HeadlessPointerMotionAbsoluteEvent { time, x, y },
+                }
+            }
+            HeadlessInputEvent::PointerButton { button, state } => InputEvent::PointerButton {
+                event: HeadlessPointerButtonEvent {
+                    time,
+                    button,
+                    state,
+                },
+            },
+            HeadlessInputEvent::PointerAxis {
+                horizontal,
+                vertical,
+            } => InputEvent::PointerAxis {
+                event: HeadlessPointerAxisEvent {
+                    time,
+                    horizontal,
+                    vertical,
+                },
+            },
+            HeadlessInputEvent::GestureSwipeBegin { fingers } => InputEvent::GestureSwipeBegin {
+                event: HeadlessGestureSwipeBeginEvent { time, fingers },
+            },
+            HeadlessInputEvent::GestureSwipeUpdate { dx, dy } => InputEvent::GestureSwipeUpdate {
+                event: HeadlessGestureSwipeUpdateEvent { time, dx, dy },
+            },
+            HeadlessInputEvent::GestureSwipeEnd { cancelled } => InputEvent::GestureSwipeEnd {
+                event: HeadlessGestureSwipeEndEvent { time, cancelled },
+            },
+        };
+
+        self.process_input_event(event);
+        for output in self.common.shell.outputs() {
+            self.backend.headless().schedule_render(output, None);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct HeadlessInput;
+
+/// The single keyboard and pointer device of the headless backend
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HeadlessVirtualDevice;
+
+impl Device for HeadlessVirtualDevice {
+    fn id(&self) -> String {
+        "headless".into()
+    }
+
+    fn name(&self) -> String {
+        "headless virtual device".into()
+    }
+
+    fn has_capability(&self, capability: DeviceCapability) -> bool {
+        matches!(
+            capability,
+            DeviceCapability::Keyboard | DeviceCapability::Pointer
+        )
+    }
+
+    fn usb_id(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    fn syspath(&self) -> Option<PathBuf> {
+        None
+    }
+}
+
+#[derive(Debug)]
+pub struct HeadlessKeyboardKeyEvent {
+    time: u64,
+    key_code: u32,
+    state: KeyState,
+}
+
+impl Event<HeadlessInput> for HeadlessKeyboardKeyEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> HeadlessVirtualDevice {
+        HeadlessVirtualDevice
+    }
+}
+
+impl KeyboardKeyEvent<HeadlessInput> for HeadlessKeyboardKeyEvent {
+    fn key_code(&self) -> u32 {
+        self.key_code
+    }
+
+    fn state(&self) -> KeyState {
+        self.state
+    }
+
+    fn count(&self) -> u32 {
+        1
+    }
+}
+
+#[derive(Debug)]
+pub struct HeadlessPointerMotionAbsoluteEvent {
+    time: u64,
+    x: f64,
+    y: f64,
+}
+
+impl Event<HeadlessInput> for HeadlessPointerMotionAbsoluteEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> HeadlessVirtualDevice {
+        HeadlessVirtualDevice
+    }
+}
+
+impl AbsolutePositionEvent<HeadlessInput> for HeadlessPointerMotionAbsoluteEvent {
+    fn x(&self) -> f64 {
+        self.x
+    }
+
+    fn y(&self) -> f64 {
+        self.y
+    }
+
+    fn x_transformed(&self, width: i32) -> f64 {
+        self.x * width as f64
+    }
+
+    fn y_transformed(&self, height: i32) -> f64 {
+        self.y * height as f64
+    }
+}
+
+impl PointerMotionAbsoluteEvent<HeadlessInput> for HeadlessPointerMotionAbsoluteEvent {}
+
+#[derive(Debug)]
+pub struct HeadlessPointerButtonEvent {
+    time: u64,
+    button: u32,
+    state: ButtonState,
+}
+
+impl Event<HeadlessInput> for HeadlessPointerButtonEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> HeadlessVirtualDevice {
+        HeadlessVirtualDevice
+    }
+}
+
+impl PointerButtonEvent<HeadlessInput> for HeadlessPointerButtonEvent {
+    fn button_code(&self) -> u32 {
+        self.button
+    }
+
+    fn state(&self) -> ButtonState {
+        self.state
+    }
+}
+
+#[derive(Debug)]
+pub struct HeadlessPointerAxisEvent {
+    time: u64,
+    horizontal: f64,
+    vertical: f64,
+}
+
+impl Event<HeadlessInput> for HeadlessPointerAxisEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> HeadlessVirtualDevice {
+        HeadlessVirtualDevice
+    }
+}
+
+impl PointerAxisEvent<HeadlessInput> for HeadlessPointerAxisEvent {
+    fn amount(&self, axis: Axis) -> Option<f64> {
+        Some(match axis {
+            Axis::Horizontal => self.horizontal,
+            Axis::Vertical => self.vertical,
+        })
+    }
+
+    fn amount_discrete(&self, _axis: Axis) -> Option<f64> {
+        None
+    }
+
+    fn source(&self) -> AxisSource {
+        AxisSource::Continuous
+    }
+}
+
+#[derive(Debug)]
+pub struct HeadlessGestureSwipeBeginEvent {
+    time: u64,
+    fingers: u32,
+}
+
+impl Event<HeadlessInput> for HeadlessGestureSwipeBeginEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> HeadlessVirtualDevice {
+        HeadlessVirtualDevice
+    }
+}
+
+impl GestureBeginEvent<HeadlessInput> for HeadlessGestureSwipeBeginEvent {
+    fn fingers(&self) -> u32 {
+        self.fingers
+    }
+}
+
+impl GestureSwipeBeginEvent<HeadlessInput> for HeadlessGestureSwipeBeginEvent {}
+
+#[derive(Debug)]
+pub struct HeadlessGestureSwipeUpdateEvent {
+    time: u64,
+    dx: f64,
+    dy: f64,
+}
+
+impl Event<HeadlessInput> for HeadlessGestureSwipeUpdateEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> HeadlessVirtualDevice {
+        HeadlessVirtualDevice
+    }
+}
+
+impl GestureSwipeUpdateEvent<HeadlessInput> for HeadlessGestureSwipeUpdateEvent {
+    fn delta_x(&self) -> f64 {
+        self.dx
+    }
+
+    fn delta_y(&self) -> f64 {
+        self.dy
+    }
+}
+
+#[derive(Debug)]
+pub struct HeadlessGestureSwipeEndEvent {
+    time: u64,
+    cancelled: bool,
+}
+
+impl Event<HeadlessInput> for HeadlessGestureSwipeEndEvent {
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn device(&self) -> HeadlessVirtualDevice {
+        HeadlessVirtualDevice
+    }
+}
+
+impl GestureEndEvent<HeadlessInput> for HeadlessGestureSwipeEndEvent {
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
+
+impl GestureSwipeEndEvent<HeadlessInput> for HeadlessGestureSwipeEndEvent {}
+
+impl InputBackend for HeadlessInput {
+    type Device = HeadlessVirtualDevice;
+    type KeyboardKeyEvent = HeadlessKeyboardKeyEvent;
+    type PointerAxisEvent = HeadlessPointerAxisEvent;
+    type PointerButtonEvent = HeadlessPointerButtonEvent;
+    type PointerMotionEvent = UnusedEvent;
+    type PointerMotionAbsoluteEvent = HeadlessPointerMotionAbsoluteEvent;
+    type GestureSwipeBeginEvent = HeadlessGestureSwipeBeginEvent;
+    type GestureSwipeUpdateEvent = HeadlessGestureSwipeUpdateEvent;
+    type GestureSwipeEndEvent = HeadlessGestureSwipeEndEvent;
+    type GesturePinchBeginEvent = UnusedEvent;
+    type GesturePinchUpdateEvent = UnusedEvent;
+    type GesturePinchEndEvent = UnusedEvent;
+    type GestureHoldBeginEvent = UnusedEvent;
+    type GestureHoldEndEvent = UnusedEvent;
+    type TouchDownEvent = UnusedEvent;
+    type TouchUpEvent = UnusedEvent;
+    type TouchMotionEvent = UnusedEvent;
+    type TouchCancelEvent = UnusedEvent;
+    type TouchFrameEvent = UnusedEvent;
+    type TabletToolAxisEvent = UnusedEvent;
+    type TabletToolProximityEvent = UnusedEvent;
+    type TabletToolTipEvent = UnusedEvent;
+    type TabletToolButtonEvent = UnusedEvent;
+    type SpecialEvent = UnusedEvent;
+}
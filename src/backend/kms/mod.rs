@@ -68,6 +68,7 @@ use smithay::{
     utils::{DeviceFd, Size, Transform},
     wayland::{
         dmabuf::{get_dmabuf, DmabufFeedbackBuilder, DmabufGlobal},
+        pointer_gestures::PointerGesturesState,
         relative_pointer::RelativePointerManagerState,
         seat::WaylandFocus,
         shm::{shm_format_to_fourcc, with_buffer_contents},
@@ -123,6 +124,9 @@ pub struct Surface {
     pending: bool,
     dirty: bool,
     render_timer_token: Option<RegistrationToken>,
+    dpms_off: bool,
+    /// Whether the queued frame was rendered while the session is locked
+    locked_frame: bool,
     fps: Fps,
     feedback: HashMap<DrmNode, SurfaceDmabufFeedback>,
 }
@@ -347,6 +351,8 @@ pub fn init_backend(
 
     // Create relative pointer global
     RelativePointerManagerState::new::<State>(&dh);
+    // Create pointer gestures global
+    PointerGesturesState::new::<State>(&dh);
 
     state.launch_xwayland(Some(primary));
 
@@ -485,6 +491,12 @@ impl State {
                                             );
                                         }
 
+                                        if std::mem::take(&mut surface.locked_frame) {
+                                            data.state
+                                                .common
+                                                .shell
+                                                .session_lock_presented(&surface.output);
+                                        }
                                         surface.pending = false;
                                         (surface.dirty
                                             || data.state.common.shell.animations_going())
@@ -898,6 +910,8 @@ impl Device {
             pending: false,
             dirty: false,
             render_timer_token: None,
+            dpms_off: false,
+            locked_frame: false,
             fps: Fps::new(renderer.as_mut()),
             feedback: HashMap::new(),
         };
@@ -1019,7 +1033,9 @@ impl Surface {
         #[cfg(feature = "debug")]
         puffin::profile_function!();
 
-        if self.surface.is_none() {
+        if self.surface.is_none() || self.dpms_off {
+            // blanked outputs count as locked
+            state.shell.session_lock_presented(&self.output);
             return Ok(());
         }
 
@@ -1077,8 +1093,14 @@ impl Surface {
                     None
                 };
 
+                let locked = state.shell.session_lock.is_some();
                 match compositor.queue_frame(feedback) {
-                    Ok(()) | Err(FrameError::EmptyFrame) => {}
+                    Ok(()) => self.locked_frame = locked,
+                    // nothing changed, so a locked frame is on screen already
+                    Err(FrameError::EmptyFrame) if locked => {
+                        state.shell.session_lock_presented(&self.output)
+                    }
+                    Err(FrameError::EmptyFrame) => {}
                     Err(err) => {
                         return Err(err).with_context(|| "Failed to submit result for display")
                     }
@@ -1201,6 +1223,35 @@ impl KmsState {
         self.session.change_vt(num).map_err(Into::into)
     }
 
+    pub fn set_dpms(&mut self, loop_handle: &LoopHandle<'_, Data>, on: bool) {
+        for surface in self
+            .devices
+            .values_mut()
+            .flat_map(|d| d.surfaces.values_mut())
+        {
+            if surface.dpms_off != on {
+                continue;
+            }
+            surface.dpms_off = !on;
+            let Some(compositor) = surface.surface.as_mut() else {
+                continue;
+            };
+            if on {
+                // the next commit does a full modeset again
+                compositor.reset_buffers();
+            } else {
+                if let Err(err) = compositor.surface().clear_state() {
+                    warn!(?err, "Failed to turn off output {}.", surface.output.name());
+                }
+                if let Some(token) = surface.render_timer_token.take() {
+                    loop_handle.remove(token);
+                }
+                surface.scheduled = false;
+                surface.pending = false;
+            }
+        }
+    }
+
     pub fn apply_config_for_output(
         &mut self,
         output: &Output,
@@ -1427,7 +1478,7 @@ impl KmsState {
             .flat_map(|(node, d)| d.surfaces.iter_mut().map(move |(c, s)| (node, c, s)))
             .find(|(_, _, s)| s.output == *output)
         {
-            if surface.surface.is_none() {
+            if surface.surface.is_none() || surface.dpms_off {
                 if let Some(sessions) = screencopy_sessions {
                     loop_handle.insert_idle(move |data| {
                         for (session, params) in sessions.into_iter() {
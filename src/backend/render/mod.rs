@@ -46,6 +46,7 @@ use smithay::{
             buffer_dimensions,
             damage::{Error as RenderError, OutputDamageTracker, OutputNoMode, RenderOutputResult},
             element::{
+                surface::render_elements_from_surface_tree,
                 utils::{Relocate, RelocateRenderElement},
                 AsRenderElements, Element, Id, RenderElement,
             },
@@ -84,6 +85,8 @@ pub type GlMultiError = MultiError<GbmGlesBackend<GlowRenderer>, GbmGlesBackend<
 pub static CLEAR_COLOR: [f32; 4] = [0.153, 0.161, 0.165, 1.0];
 pub static GROUP_COLOR: [f32; 3] = [0.788, 0.788, 0.788];
 pub static FOCUS_INDICATOR_COLOR: [f32; 3] = [0.580, 0.921, 0.921];
+/// Shown on locked outputs without a (working) lock surface
+pub static LOCK_COLOR: [f32; 3] = [0.0, 0.0, 0.0];
 
 pub static OUTLINE_SHADER: &str = include_str!("./shaders/rounded_outline.frag");
 pub static RECTANGLE_SHADER: &str = include_str!("./shaders/rounded_rectangle.frag");
@@ -370,6 +373,8 @@ where
 
     let scale = output.current_scale().fractional_scale();
     let mut elements = Vec::new();
+    // client content dragged around must not show up above the lock surfaces
+    let locked = state.shell.session_lock.is_some();
 
     for seat in state.seats() {
         let pointer = match seat.get_pointer() {
@@ -393,6 +398,10 @@ where
             );
         }
 
+        if locked {
+            continue;
+        }
+
         if let Some(wl_surface) = get_dnd_icon(seat) {
             elements.extend(
                 cursor::draw_dnd_icon(renderer, &wl_surface, location.to_i32_round(), scale)
@@ -476,6 +485,72 @@ where
         }
     }
 
+    // while locked, nothing but the lock surface may be visible
+    if let Some(session_lock) = state.shell.session_lock.as_ref() {
+        let output_scale = output.current_scale().fractional_scale();
+        if let Some(surface) = session_lock.surfaces.get(output) {
+            let surface_elements: Vec<WorkspaceRenderElement<R>> =
+                render_elements_from_surface_tree(
+                    renderer,
+                    surface.wl_surface(),
+                    (0, 0),
+                    Scale::from(output_scale),
+                    1.0,
+                );
+            elements.extend(surface_elements.into_iter().map(Into::into));
+        }
+        // the locker might have crashed or not yet drawn anything, the session stays hidden anyway
+        elements.push(
+            Into::<WorkspaceRenderElement<R>>::into(Into::<CosmicMappedRenderElement<R>>::into(
+                BackdropShader::element(
+                    renderer,
+                    Key::Static(session_lock.backdrop_id.clone()),
+                    Rectangle::from_loc_and_size((0, 0), output.geometry().size),
+                    0.,
+                    1.0,
+                    LOCK_COLOR,
+                ),
+            ))
+            .into(),
+        );
+        return Ok(elements);
+    }
+
+    if let Some(switcher) = state.shell.switcher.as_ref() {
+        elements.extend(
+            switcher
+                .render_elements(renderer, output)
+                .into_iter()
+                .map(|element| WorkspaceRenderElement::from(element).into()),
+        );
+    }
+
+    // the overview replaces the workspace, but keeps all layer surfaces
+    if let Some(expose) = state.shell.expose.as_ref() {
+        elements.extend(
+            foreground_layer_elements(renderer, output, false, exclude_workspace_overview)
+                .into_iter()
+                .map(Into::into),
+        );
+        elements.extend(
+            expose
+                .render_elements(
+                    &state.shell,
+                    renderer,
+                    output,
+                    state.config.static_conf.active_hint,
+                )
+                .into_iter()
+                .map(|element| WorkspaceRenderElement::from(element).into()),
+        );
+        elements.extend(
+            background_layer_elements(renderer, output, exclude_workspace_overview)
+                .into_iter()
+                .map(Into::into),
+        );
+        return Ok(elements);
+    }
+
     state
         .shell
         .space_for_handle_mut(&current.0)
@@ -543,6 +618,26 @@ where
                 }
             });
 
+            // always-on-top windows stay above tiled, floating and fullscreen windows
+            elements.extend(
+                workspace
+                    .render_always_on_top::<R>(
+                        renderer,
+                        output,
+                        (!move_active && is_active_space).then_some(&last_active_seat),
+                        &overview,
+                        state.config.static_conf.active_hint,
+                    )
+                    .into_iter()
+                    .map(|w_element| {
+                        CosmicElement::Workspace(RelocateRenderElement::from_element(
+                            w_element,
+                            offset.to_physical_precise_round(output_scale),
+                            Relocate::Relative,
+                        ))
+                    }),
+            );
+
             elements.extend(
                 workspace
                     .render_output::<R>(
@@ -589,6 +684,26 @@ where
 
     let is_active_space = workspace.outputs().any(|o| o == &active_output);
 
+    // always-on-top windows stay above tiled, floating and fullscreen windows
+    elements.extend(
+        workspace
+            .render_always_on_top::<R>(
+                renderer,
+                output,
+                (!move_active && is_active_space).then_some(&last_active_seat),
+                &overview,
+                state.config.static_conf.active_hint,
+            )
+            .into_iter()
+            .map(|w_element| {
+                CosmicElement::Workspace(RelocateRenderElement::from_element(
+                    w_element,
+                    offset.to_physical_precise_round(output_scale),
+                    Relocate::Relative,
+                ))
+            }),
+    );
+
     elements.extend(
         workspace
             .render_output::<R>(
@@ -86,6 +86,8 @@ impl WinitState {
                 #[cfg(feature = "debug")]
                 self.fps.displayed();
                 state.send_frames(&self.output, &states, |_| None);
+                // the frame is shown right away, so it counts as presented for the session lock
+                state.shell.session_lock_presented(&self.output);
                 if damage.is_some() {
                     let mut output_presentation_feedback =
                         state.take_presentation_feedback(&self.output, &states);
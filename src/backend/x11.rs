@@ -250,6 +250,8 @@ impl Surface {
                 #[cfg(feature = "debug")]
                 self.fps.displayed();
                 state.send_frames(&self.output, &states, |_| None);
+                // the frame is shown right away, so it counts as presented for the session lock
+                state.shell.session_lock_presented(&self.output);
                 if damage.is_some() {
                     let mut output_presentation_feedback =
                         state.take_presentation_feedback(&self.output, &states);
@@ -477,7 +479,7 @@ pub fn init_backend(
     Ok(())
 }
 
-fn init_egl_client_side<R>(
+pub(super) fn init_egl_client_side<R>(
     dh: &DisplayHandle,
     state: &mut State,
     render_node: &DrmNode,
@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Small cli to talk to the cosmic-comp ipc socket
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Value};
+use std::{
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    path::PathBuf,
+};
+
+const USAGE: &str = "\
+Usage: cosmic-comp-msg [-s <socket>] [-r] <command> [<args>...]
+
+Commands:
+    action <action>         Run an action, e.g. `Close`, `Workspace(2)` or `{\"Spawn\": \"foot\"}`
+    workspaces              List workspaces
+    tree                    Show the tiling trees
+    outputs                 List outputs
+    seats                   List seats
+    windows                 List windows
+    layouts                 Show the keyboard layouts of all seats
+    subscribe <event>...    Print events as they happen (focus, workspace, window, keyboard_layout, binding_mode)
+
+Options:
+    -s, --socket <socket>   Path of the ipc socket, defaults to $COSMIC_COMP_SOCK
+    -r, --raw               Print replies without pretty printing
+    -h, --help              Print this help";
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("Error: {:#}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let mut socket = None;
+    let mut raw = false;
+    let mut command = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-s" | "--socket" => {
+                socket = Some(PathBuf::from(
+                    args.next().with_context(|| "Missing socket path")?,
+                ))
+            }
+            "-r" | "--raw" => raw = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ => {
+                command.push(arg);
+                command.extend(args.by_ref());
+            }
+        }
+    }
+
+    let (name, params) = command
+        .split_first()
+        .ok_or_else(|| anyhow!("No command given\n\n{}", USAGE))?;
+    let request = match name.as_str() {
+        "action" => {
+            let action = params.join(" ");
+            if action.is_empty() {
+                bail!("Missing action");
+            }
+            json!({ "request": "command", "action": parse_action(&action) })
+        }
+        "workspaces" => json!({ "request": "get_workspaces" }),
+        "tree" => json!({ "request": "get_tiling_tree" }),
+        "outputs" => json!({ "request": "get_outputs" }),
+        "seats" => json!({ "request": "get_seats" }),
+        "windows" => json!({ "request": "get_windows" }),
+        "layouts" => json!({ "request": "get_keyboard_layouts" }),
+        "subscribe" => {
+            if params.is_empty() {
+                bail!("No events to subscribe to");
+            }
+            json!({ "request": "subscribe", "events": params })
+        }
+        x => bail!("Unknown command {}\n\n{}", x, USAGE),
+    };
+
+    let socket = match socket {
+        Some(socket) => socket,
+        None => default_socket()?,
+    };
+    let mut stream = UnixStream::connect(&socket)
+        .with_context(|| format!("Failed to connect to {}", socket.display()))?;
+    let mut bytes = serde_json::to_vec(&request)?;
+    bytes.push(b'\n');
+    stream
+        .write_all(&bytes)
+        .with_context(|| "Failed to send request")?;
+
+    let mut lines = BufReader::new(stream).lines();
+    let reply: Value = serde_json::from_str(
+        &lines
+            .next()
+            .with_context(|| "Compositor closed the connection")?
+            .with_context(|| "Failed to read reply")?,
+    )
+    .with_context(|| "Invalid reply")?;
+    if reply["reply"] == "error" {
+        bail!("{}", reply["message"].as_str().unwrap_or("Unknown error"));
+    }
+
+    if name == "subscribe" {
+        for line in lines {
+            let event: Value = serde_json::from_str(&line.with_context(|| "Failed to read event")?)
+                .with_context(|| "Invalid event")?;
+            print(&event, raw)?;
+        }
+    } else if reply["reply"] != "success" {
+        print(&reply, raw)?;
+    }
+
+    Ok(())
+}
+
+fn default_socket() -> Result<PathBuf> {
+    if let Some(path) = std::env::var_os("COSMIC_COMP_SOCK") {
+        return Ok(PathBuf::from(path));
+    }
+    let display = std::env::var("WAYLAND_DISPLAY")
+        .with_context(|| "Neither COSMIC_COMP_SOCK nor WAYLAND_DISPLAY are set")?;
+    let runtime_dir =
+        std::env::var_os("XDG_RUNTIME_DIR").with_context(|| "XDG_RUNTIME_DIR is not set")?;
+    Ok(PathBuf::from(runtime_dir).join(format!("cosmic-comp-{}.sock", display)))
+}
+
+/// Parses an action either as json or in the notation used by the config, like `Workspace(2)`
+fn parse_action(action: &str) -> Value {
+    let action = action.trim();
+    if let Ok(value) = serde_json::from_str(action) {
+        return value;
+    }
+    match action.split_once('(') {
+        Some((name, arg)) if arg.ends_with(')') => {
+            let arg = arg[..arg.len() - 1].trim();
+            let arg = serde_json::from_str(arg).unwrap_or_else(|_| Value::from(arg));
+            json!({ name.trim(): arg })
+        }
+        _ => Value::from(action),
+    }
+}
+
+fn print(value: &Value, raw: bool) -> Result<()> {
+    let output = if raw {
+        serde_json::to_string(value)?
+    } else {
+        serde_json::to_string_pretty(value)?
+    };
+    println!("{}", output);
+    Ok(())
+}
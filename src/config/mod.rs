@@ -1,12 +1,17 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use crate::{
-    shell::{focus::FocusDirection, layout::tiling::Direction, Shell, WorkspaceAmount},
+    shell::{
+        focus::FocusDirection,
+        layout::tiling::{Direction, LayoutMode, SavedTree},
+        Shell, SwitcherScope, WorkspaceAmount,
+    },
     state::{BackendData, Data, State},
     wayland::protocols::output_configuration::OutputConfigurationState,
 };
+use anyhow::Context;
+use inotify::{Inotify, WatchMask};
 use serde::{Deserialize, Serialize};
-use smithay::input::Seat;
 pub use smithay::{
     backend::input::KeyState,
     input::keyboard::{keysyms as KeySyms, Keysym, ModifiersState},
@@ -20,29 +25,92 @@ pub use smithay::{
     },
     utils::{Logical, Physical, Point, Size, Transform},
 };
-use std::{cell::RefCell, collections::HashMap, fs::OpenOptions, path::PathBuf};
+use smithay::{
+    input::Seat,
+    reexports::calloop::{generic::Generic, Interest, Mode as TriggerMode, PostAction},
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    ffi::OsString,
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+};
 use tracing::{debug, error, info, warn};
 
 mod types;
 pub use self::types::*;
+mod window_rules;
+pub use self::window_rules::*;
 
 pub struct Config {
     pub static_conf: StaticConfig,
     pub dynamic_conf: DynamicConfig,
+    /// Outputs devices are assigned to by udev (`WL_OUTPUT`), as reported by libinput
+    device_outputs: HashMap<String, String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, PartialEq)]
 pub struct StaticConfig {
     pub key_bindings: HashMap<KeyPattern, Action>,
+    /// Named sets of bindings replacing `key_bindings` while active, see [`Action::EnterMode`]
+    #[serde(default)]
+    pub binding_modes: HashMap<String, HashMap<KeyPattern, Action>>,
+    /// Sequences of keys pressed one after another
+    #[serde(default)]
+    pub chord_bindings: HashMap<Vec<KeyPattern>, Action>,
+    /// Milliseconds to wait for the next key of a chord
+    #[serde(default = "default_chord_timeout")]
+    pub chord_timeout: u32,
+    #[serde(default)]
+    pub button_bindings: HashMap<ButtonPattern, Action>,
+    #[serde(default)]
+    pub scroll_bindings: HashMap<ScrollPattern, Action>,
+    #[serde(default)]
+    pub gesture_bindings: HashMap<GesturePattern, Action>,
     pub workspace_mode: WorkspaceMode,
     pub workspace_amount: WorkspaceAmount,
     #[serde(default = "default_workspace_layout")]
     pub workspace_layout: WorkspaceLayout,
     pub tiling_enabled: bool,
+    /// Arrangement of tiled windows on new workspaces
+    #[serde(default)]
+    pub tiling_layout: LayoutMode,
     #[serde(default = "default_active_hint")]
     pub active_hint: u8,
     #[serde(default = "default_gaps")]
     pub gaps: (u8, u8),
+    #[serde(default = "default_window_rules")]
+    pub window_rules: Vec<WindowRule>,
+    /// Seconds of inactivity after which outputs are turned off
+    #[serde(default)]
+    pub dpms_timeout: Option<u32>,
+    #[serde(default)]
+    pub workspaces: Vec<WorkspaceDefinition>,
+}
+
+impl Default for StaticConfig {
+    fn default() -> StaticConfig {
+        StaticConfig {
+            key_bindings: HashMap::new(),
+            binding_modes: HashMap::new(),
+            chord_bindings: HashMap::new(),
+            chord_timeout: default_chord_timeout(),
+            button_bindings: HashMap::new(),
+            scroll_bindings: HashMap::new(),
+            gesture_bindings: HashMap::new(),
+            workspace_mode: WorkspaceMode::Global,
+            workspace_amount: WorkspaceAmount::Dynamic,
+            workspace_layout: WorkspaceLayout::Vertical,
+            tiling_enabled: false,
+            tiling_layout: LayoutMode::Manual,
+            active_hint: default_active_hint(),
+            gaps: default_gaps(),
+            window_rules: default_window_rules(),
+            dpms_timeout: None,
+            workspaces: Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
@@ -57,9 +125,34 @@ pub enum WorkspaceLayout {
     Horizontal,
 }
 
+/// A named workspace, that exists even while empty
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct WorkspaceDefinition {
+    pub name: String,
+    /// Connector of the output to place the workspace on, if workspaces are output bound
+    #[serde(default)]
+    pub output: Option<String>,
+    #[serde(default)]
+    pub tiling_enabled: Option<bool>,
+    #[serde(default)]
+    pub gaps: Option<(u8, u8)>,
+    /// Orientation of new tiling groups, instead of following the window's aspect ratio
+    #[serde(default)]
+    pub orientation: Option<crate::shell::layout::Orientation>,
+    #[serde(default)]
+    pub layout: Option<LayoutMode>,
+}
+
 pub struct DynamicConfig {
     outputs: (Option<PathBuf>, OutputsConfig),
     inputs: (Option<PathBuf>, InputsConfig),
+    layouts: (Option<PathBuf>, LayoutsConfig),
+}
+
+/// Tiling layouts of all workspaces, restored after a restart
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct LayoutsConfig {
+    pub workspaces: HashMap<String, Vec<SavedTree>>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -97,6 +190,10 @@ fn default_gaps() -> (u8, u8) {
     (0, 4)
 }
 
+fn default_chord_timeout() -> u32 {
+    1000
+}
+
 fn default_workspace_layout() -> WorkspaceLayout {
     WorkspaceLayout::Vertical
 }
@@ -174,6 +271,10 @@ pub struct InputConfig {
     scroll_config: Option<ScrollConfig>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     tap_config: Option<TapConfig>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    map_to_output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    map_to_area: Option<[f32; 4]>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -213,10 +314,24 @@ impl Config {
         Config {
             static_conf: Self::load_static(xdg.as_ref()),
             dynamic_conf: Self::load_dynamic(xdg.as_ref()),
+            device_outputs: HashMap::new(),
         }
     }
 
-    fn load_static(xdg: Option<&xdg::BaseDirectories>) -> StaticConfig {
+    /// Config, that is neither read from nor persisted to disk
+    pub fn in_memory(static_conf: StaticConfig) -> Config {
+        Config {
+            static_conf,
+            dynamic_conf: DynamicConfig {
+                outputs: (None, Self::load_outputs(&None)),
+                inputs: (None, Self::load_inputs(&None)),
+                layouts: (None, Self::load_layouts(&None)),
+            },
+            device_outputs: HashMap::new(),
+        }
+    }
+
+    fn static_locations(xdg: Option<&xdg::BaseDirectories>) -> Vec<PathBuf> {
         let mut locations = if let Some(base) = xdg {
             vec![
                 base.get_config_file("cosmic-comp.ron"),
@@ -233,149 +348,183 @@ impl Config {
         }
         locations.push(PathBuf::from("/etc/cosmic-comp/config.ron"));
         locations.push(PathBuf::from("/etc/cosmic-comp.ron"));
+        locations
+    }
 
-        for path in locations {
+    fn load_static(xdg: Option<&xdg::BaseDirectories>) -> StaticConfig {
+        for path in Self::static_locations(xdg) {
             debug!("Trying config location: {}", path.display());
             if path.exists() {
                 info!("Using config at {}", path.display());
-                let mut config: StaticConfig =
-                    ron::de::from_reader(OpenOptions::new().read(true).open(path).unwrap())
-                        .expect("Malformed config file");
-
-                let (workspace_previous, workspace_next, output_previous, output_next) =
-                    match config.workspace_layout {
-                        WorkspaceLayout::Horizontal => (
-                            [KeySyms::KEY_Left, KeySyms::KEY_h],
-                            [KeySyms::KEY_Right, KeySyms::KEY_j],
-                            [KeySyms::KEY_Up, KeySyms::KEY_k],
-                            [KeySyms::KEY_Down, KeySyms::KEY_j],
-                        ),
-                        WorkspaceLayout::Vertical => (
-                            [KeySyms::KEY_Up, KeySyms::KEY_k],
-                            [KeySyms::KEY_Down, KeySyms::KEY_j],
-                            [KeySyms::KEY_Left, KeySyms::KEY_h],
-                            [KeySyms::KEY_Right, KeySyms::KEY_j],
-                        ),
-                    };
-
-                fn insert_binding(
-                    key_bindings: &mut HashMap<KeyPattern, Action>,
-                    modifiers: KeyModifiers,
-                    keys: impl Iterator<Item = u32>,
-                    action: Action,
-                ) {
-                    if !key_bindings.values().any(|a| a == &action) {
-                        for key in keys {
-                            let pattern = KeyPattern {
-                                modifiers: modifiers.clone(),
-                                key,
-                            };
-                            if !key_bindings.contains_key(&pattern) {
-                                key_bindings.insert(pattern, action.clone());
-                            }
-                        }
-                    }
+                match Self::parse_static(&path) {
+                    Ok(config) => return config,
+                    Err(err) => error!(?err, "Malformed config file {}.", path.display()),
                 }
+            }
+        }
 
-                insert_binding(
-                    &mut config.key_bindings,
-                    KeyModifiers {
-                        logo: true,
-                        ctrl: true,
-                        ..Default::default()
-                    },
-                    workspace_previous.iter().copied(),
-                    Action::PreviousWorkspace,
-                );
-                insert_binding(
-                    &mut config.key_bindings,
-                    KeyModifiers {
-                        logo: true,
-                        ctrl: true,
-                        ..Default::default()
-                    },
-                    workspace_next.iter().copied(),
-                    Action::NextWorkspace,
-                );
-                insert_binding(
-                    &mut config.key_bindings,
-                    KeyModifiers {
-                        logo: true,
-                        ctrl: true,
-                        shift: true,
-                        ..Default::default()
-                    },
-                    workspace_previous.iter().copied(),
-                    Action::MoveToPreviousWorkspace,
-                );
-                insert_binding(
-                    &mut config.key_bindings,
-                    KeyModifiers {
-                        logo: true,
-                        ctrl: true,
-                        shift: true,
-                        ..Default::default()
-                    },
-                    workspace_next.iter().copied(),
-                    Action::MoveToNextWorkspace,
-                );
+        StaticConfig::default()
+    }
 
-                insert_binding(
-                    &mut config.key_bindings,
-                    KeyModifiers {
-                        logo: true,
-                        ctrl: true,
-                        ..Default::default()
-                    },
-                    output_previous.iter().copied(),
-                    Action::PreviousOutput,
-                );
-                insert_binding(
-                    &mut config.key_bindings,
-                    KeyModifiers {
-                        logo: true,
-                        ctrl: true,
-                        ..Default::default()
-                    },
-                    output_next.iter().copied(),
-                    Action::NextOutput,
-                );
-                insert_binding(
-                    &mut config.key_bindings,
-                    KeyModifiers {
-                        logo: true,
-                        ctrl: true,
-                        shift: true,
-                        ..Default::default()
-                    },
-                    output_previous.iter().copied(),
-                    Action::MoveToPreviousOutput,
-                );
-                insert_binding(
-                    &mut config.key_bindings,
-                    KeyModifiers {
-                        logo: true,
-                        ctrl: true,
-                        shift: true,
-                        ..Default::default()
-                    },
-                    output_next.iter().copied(),
-                    Action::MoveToNextOutput,
-                );
+    /// Re-reads the static config from disk.
+    ///
+    /// Returns `None` if the config currently in use could not be parsed,
+    /// in which case the previous config should be kept.
+    pub fn reload_static(xdg: Option<&xdg::BaseDirectories>) -> Option<StaticConfig> {
+        for path in Self::static_locations(xdg) {
+            if path.exists() {
+                info!("Reloading config at {}", path.display());
+                return match Self::parse_static(&path) {
+                    Ok(config) => Some(config),
+                    Err(err) => {
+                        error!(
+                            ?err,
+                            "Malformed config file {}, keeping previous config.",
+                            path.display()
+                        );
+                        None
+                    }
+                };
+            }
+        }
+
+        Some(StaticConfig::default())
+    }
 
-                return config;
+    fn parse_static(path: &Path) -> anyhow::Result<StaticConfig> {
+        let mut config: StaticConfig =
+            ron::de::from_reader(OpenOptions::new().read(true).open(path)?)?;
+
+        let (workspace_previous, workspace_next, output_previous, output_next) =
+            match config.workspace_layout {
+                WorkspaceLayout::Horizontal => (
+                    [KeySyms::KEY_Left, KeySyms::KEY_h],
+                    [KeySyms::KEY_Right, KeySyms::KEY_j],
+                    [KeySyms::KEY_Up, KeySyms::KEY_k],
+                    [KeySyms::KEY_Down, KeySyms::KEY_j],
+                ),
+                WorkspaceLayout::Vertical => (
+                    [KeySyms::KEY_Up, KeySyms::KEY_k],
+                    [KeySyms::KEY_Down, KeySyms::KEY_j],
+                    [KeySyms::KEY_Left, KeySyms::KEY_h],
+                    [KeySyms::KEY_Right, KeySyms::KEY_j],
+                ),
+            };
+
+        fn insert_binding(
+            key_bindings: &mut HashMap<KeyPattern, Action>,
+            modifiers: KeyModifiers,
+            keys: impl Iterator<Item = u32>,
+            action: Action,
+        ) {
+            if !key_bindings.values().any(|a| a == &action) {
+                for key in keys {
+                    let pattern = KeyPattern::new(modifiers.clone(), key);
+                    if !key_bindings.contains_key(&pattern) {
+                        key_bindings.insert(pattern, action.clone());
+                    }
+                }
             }
         }
 
-        StaticConfig {
-            key_bindings: HashMap::new(),
-            workspace_mode: WorkspaceMode::Global,
-            workspace_amount: WorkspaceAmount::Dynamic,
-            workspace_layout: WorkspaceLayout::Vertical,
-            tiling_enabled: false,
-            active_hint: default_active_hint(),
-            gaps: default_gaps(),
+        insert_binding(
+            &mut config.key_bindings,
+            KeyModifiers {
+                logo: true,
+                ctrl: true,
+                ..Default::default()
+            },
+            workspace_previous.iter().copied(),
+            Action::PreviousWorkspace,
+        );
+        insert_binding(
+            &mut config.key_bindings,
+            KeyModifiers {
+                logo: true,
+                ctrl: true,
+                ..Default::default()
+            },
+            workspace_next.iter().copied(),
+            Action::NextWorkspace,
+        );
+        insert_binding(
+            &mut config.key_bindings,
+            KeyModifiers {
+                logo: true,
+                ctrl: true,
+                shift: true,
+                ..Default::default()
+            },
+            workspace_previous.iter().copied(),
+            Action::MoveToPreviousWorkspace,
+        );
+        insert_binding(
+            &mut config.key_bindings,
+            KeyModifiers {
+                logo: true,
+                ctrl: true,
+                shift: true,
+                ..Default::default()
+            },
+            workspace_next.iter().copied(),
+            Action::MoveToNextWorkspace,
+        );
+
+        insert_binding(
+            &mut config.key_bindings,
+            KeyModifiers {
+                logo: true,
+                ctrl: true,
+                ..Default::default()
+            },
+            output_previous.iter().copied(),
+            Action::PreviousOutput,
+        );
+        insert_binding(
+            &mut config.key_bindings,
+            KeyModifiers {
+                logo: true,
+                ctrl: true,
+                ..Default::default()
+            },
+            output_next.iter().copied(),
+            Action::NextOutput,
+        );
+        insert_binding(
+            &mut config.key_bindings,
+            KeyModifiers {
+                logo: true,
+                ctrl: true,
+                shift: true,
+                ..Default::default()
+            },
+            output_previous.iter().copied(),
+            Action::MoveToPreviousOutput,
+        );
+        insert_binding(
+            &mut config.key_bindings,
+            KeyModifiers {
+                logo: true,
+                ctrl: true,
+                shift: true,
+                ..Default::default()
+            },
+            output_next.iter().copied(),
+            Action::MoveToNextOutput,
+        );
+
+        // the first key of a chord is matched before `key_bindings`
+        for keys in config.chord_bindings.keys() {
+            if let Some(action) = keys.first().and_then(|key| config.key_bindings.get(key)) {
+                warn!(
+                    ?keys,
+                    ?action,
+                    "Chord binding shadows the key binding of its first key.",
+                );
+            }
         }
+
+        Ok(config)
     }
 
     fn load_dynamic(xdg: Option<&xdg::BaseDirectories>) -> DynamicConfig {
@@ -386,9 +535,14 @@ impl Config {
         let input_path = xdg.and_then(|base| base.place_state_file("cosmic-comp/inputs.ron").ok());
         let inputs = Self::load_inputs(&input_path);
 
+        let layout_path =
+            xdg.and_then(|base| base.place_state_file("cosmic-comp/layouts.ron").ok());
+        let layouts = Self::load_layouts(&layout_path);
+
         DynamicConfig {
             outputs: (output_path, outputs),
             inputs: (input_path, inputs),
+            layouts: (layout_path, layouts),
         }
     }
 
@@ -415,8 +569,20 @@ impl Config {
     fn load_inputs(path: &Option<PathBuf>) -> InputsConfig {
         if let Some(path) = path.as_ref() {
             if path.exists() {
-                match ron::de::from_reader(OpenOptions::new().read(true).open(path).unwrap()) {
-                    Ok(config) => return config,
+                match ron::de::from_reader::<_, InputsConfig>(
+                    OpenOptions::new().read(true).open(path).unwrap(),
+                ) {
+                    Ok(config) => {
+                        let unusable = config.xkb.unusable_layouts();
+                        if !unusable.is_empty() {
+                            warn!(
+                                ?unusable,
+                                "Only {} keyboard layouts are supported, ignoring the rest.",
+                                MAX_LAYOUTS,
+                            );
+                        }
+                        return config;
+                    }
                     Err(err) => {
                         warn!(?err, "Failed to read input_config, resetting..");
                         if let Err(err) = std::fs::remove_file(path) {
@@ -433,6 +599,24 @@ impl Config {
         }
     }
 
+    fn load_layouts(path: &Option<PathBuf>) -> LayoutsConfig {
+        if let Some(path) = path.as_ref() {
+            if path.exists() {
+                match ron::de::from_reader(OpenOptions::new().read(true).open(path).unwrap()) {
+                    Ok(config) => return config,
+                    Err(err) => {
+                        warn!(?err, "Failed to read saved layouts, resetting..");
+                        if let Err(err) = std::fs::remove_file(path) {
+                            error!(?err, "Failed to remove saved layouts.");
+                        }
+                    }
+                };
+            }
+        }
+
+        LayoutsConfig::default()
+    }
+
     pub fn read_outputs(
         &mut self,
         output_state: &mut OutputConfigurationState<State>,
@@ -590,9 +774,20 @@ impl Config {
         self.dynamic_conf.inputs().xkb.clone()
     }
 
+    /// Replaces the keymap configuration, used by keyboards added afterwards
+    #[cfg(test)]
+    pub fn set_xkb_config(&mut self, xkb: XkbConfig) {
+        self.dynamic_conf.inputs_mut().xkb = xkb;
+    }
+
     pub fn read_device(&mut self, device: &mut InputDevice) {
         use std::collections::hash_map::Entry;
 
+        if let Some(output) = device.output_name() {
+            self.device_outputs
+                .insert(device.name().into(), output.into());
+        }
+
         let mut inputs = self.dynamic_conf.inputs_mut();
         match inputs.devices.entry(device.name().into()) {
             Entry::Occupied(entry) => {
@@ -836,10 +1031,195 @@ impl Config {
                     } else {
                         None
                     },
+                    map_to_output: None,
+                    map_to_area: None,
                 });
             }
         }
     }
+
+    /// Returns the output name and area (x, y, width, height relative to the output size)
+    /// an absolute input device, like a tablet or touchscreen, is mapped to.
+    ///
+    /// Without a configured output, the one assigned by udev is used, if any.
+    pub fn device_mapping(&self, device: &str) -> (Option<&str>, Option<[f32; 4]>) {
+        let config = self.dynamic_conf.inputs().devices.get(device);
+        let output = config
+            .and_then(|config| config.map_to_output.as_deref())
+            .or_else(|| self.device_outputs.get(device).map(String::as_str));
+        (output, config.and_then(|config| config.map_to_area))
+    }
+}
+
+/// Watches the static config locations and applies changes to a running session.
+pub fn watch_static_config(handle: LoopHandle<'static, Data>) -> anyhow::Result<()> {
+    let xdg = xdg::BaseDirectories::new().ok();
+    let locations = Config::static_locations(xdg.as_ref());
+
+    let mut inotify = Inotify::init().with_context(|| "Failed to initialize inotify")?;
+    let mut file_names: Vec<OsString> = Vec::new();
+    let mut directories: Vec<PathBuf> = Vec::new();
+    for path in locations.iter() {
+        if let Some(name) = path.file_name() {
+            if !file_names.iter().any(|n| n == name) {
+                file_names.push(name.to_os_string());
+            }
+        }
+        if let Some(dir) = path.parent() {
+            if !directories.iter().any(|d| d == dir) {
+                directories.push(dir.to_path_buf());
+            }
+        }
+    }
+    let mut missing = watch_config_directories(&mut inotify, &directories);
+
+    handle
+        .insert_source(
+            Generic::new(inotify, Interest::READ, TriggerMode::Level),
+            move |_, inotify, data: &mut Data| {
+                let mut buffer = [0; 4096];
+                let mut changed = false;
+                let mut created = false;
+                loop {
+                    match inotify.read_events(&mut buffer) {
+                        Ok(events) => {
+                            let mut empty = true;
+                            for event in events {
+                                empty = false;
+                                if let Some(name) = event.name {
+                                    changed |= file_names.iter().any(|n| n == name);
+                                    created |= missing.iter().any(|n| n == name);
+                                }
+                            }
+                            if empty {
+                                break;
+                            }
+                        }
+                        Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => break,
+                        Err(err) => {
+                            warn!(?err, "Failed to read config watcher events.");
+                            return Ok(PostAction::Remove);
+                        }
+                    }
+                }
+
+                if created {
+                    // the config may have been moved in along with its directory
+                    missing = watch_config_directories(inotify, &directories);
+                    changed = true;
+                }
+                if changed {
+                    data.state.reload_static_config();
+                }
+                Ok(PostAction::Continue)
+            },
+        )
+        .with_context(|| "Failed to init the config watcher source.")?;
+
+    Ok(())
+}
+
+/// Watches the given directories, or the closest existing parent of missing ones.
+///
+/// Returns the names of the missing directories below the watched parents,
+/// so they can be watched themselves once they are created.
+fn watch_config_directories(inotify: &mut Inotify, directories: &[PathBuf]) -> Vec<OsString> {
+    let mut missing = Vec::new();
+    for dir in directories {
+        let mut watched = dir.as_path();
+        let mut missing_child = None;
+        while !watched.exists() {
+            let Some(parent) = watched.parent() else {
+                break;
+            };
+            missing_child = watched.file_name();
+            watched = parent;
+        }
+        if let Some(name) = missing_child {
+            missing.push(name.to_os_string());
+        }
+
+        // watch the directories instead of the files,
+        // editors like to replace files instead of writing to them.
+        if let Err(err) = inotify.add_watch(
+            watched,
+            WatchMask::CLOSE_WRITE
+                | WatchMask::MOVED_TO
+                | WatchMask::MOVED_FROM
+                | WatchMask::DELETE
+                | WatchMask::CREATE,
+        ) {
+            warn!(
+                ?err,
+                "Failed to watch config directory {}.",
+                watched.display()
+            );
+        }
+    }
+    missing
+}
+
+impl State {
+    pub fn reload_static_config(&mut self) {
+        let xdg = xdg::BaseDirectories::new().ok();
+        let Some(new_conf) = Config::reload_static(xdg.as_ref()) else { return };
+        if new_conf == self.common.config.static_conf {
+            debug!("Static config unchanged.");
+            return;
+        }
+        let old_conf = std::mem::replace(&mut self.common.config.static_conf, new_conf);
+        let new_conf = &self.common.config.static_conf;
+
+        // key bindings, workspace_layout and active_hint are looked up on demand,
+        // everything else needs to be applied to the shell.
+        let seat = self.common.last_active_seat().clone();
+        let rules_changed = old_conf.window_rules != new_conf.window_rules;
+        let shell = &mut self.common.shell;
+        if old_conf.workspace_mode != new_conf.workspace_mode {
+            shell.set_mode(new_conf.workspace_mode);
+        }
+        if old_conf.workspace_amount != new_conf.workspace_amount {
+            shell.set_workspace_amount(new_conf.workspace_amount);
+        }
+        if old_conf.gaps != new_conf.gaps {
+            shell.set_gaps(new_conf.gaps);
+        }
+        if old_conf.tiling_layout != new_conf.tiling_layout {
+            shell.set_tiling_layout(new_conf.tiling_layout, &seat);
+        }
+        if old_conf.workspaces != new_conf.workspaces {
+            shell.set_workspace_definitions(new_conf.workspaces.clone(), &seat);
+        }
+        if old_conf.tiling_enabled != new_conf.tiling_enabled {
+            shell.tiling_enabled = new_conf.tiling_enabled;
+            shell
+                .workspaces
+                .update_tiling_status(&seat, new_conf.tiling_enabled);
+        }
+        if rules_changed {
+            let windows = shell
+                .workspaces
+                .spaces()
+                .flat_map(|workspace| workspace.windows())
+                .collect::<Vec<_>>();
+            for window in windows {
+                window.reset_window_rules();
+                Shell::reapply_window_rules(self, &window);
+            }
+        }
+
+        for output in self
+            .common
+            .shell
+            .outputs()
+            .cloned()
+            .collect::<Vec<_>>()
+            .into_iter()
+        {
+            self.backend
+                .schedule_render(&self.common.event_loop_handle, &output, None);
+        }
+    }
 }
 
 pub struct PersistenceGuard<'a, T: Serialize>(Option<PathBuf>, &'a mut T);
@@ -860,11 +1240,13 @@ impl<'a, T: Serialize> std::ops::DerefMut for PersistenceGuard<'a, T> {
 impl<'a, T: Serialize> Drop for PersistenceGuard<'a, T> {
     fn drop(&mut self) {
         if let Some(path) = self.0.as_ref() {
+            // write to a temporary file first, so an interrupted write doesn't lose the old state
+            let tmp_path = path.with_extension("ron.tmp");
             let writer = match OpenOptions::new()
                 .create(true)
                 .truncate(true)
                 .write(true)
-                .open(path)
+                .open(&tmp_path)
             {
                 Ok(writer) => writer,
                 Err(err) => {
@@ -874,6 +1256,11 @@ impl<'a, T: Serialize> Drop for PersistenceGuard<'a, T> {
             };
             if let Err(err) = ron::ser::to_writer_pretty(writer, &self.1, Default::default()) {
                 warn!(?err, "Failed to persist {}", path.display());
+                let _ = std::fs::remove_file(&tmp_path);
+                return;
+            }
+            if let Err(err) = std::fs::rename(&tmp_path, path) {
+                warn!(?err, "Failed to persist {}", path.display());
             }
         }
     }
@@ -895,6 +1282,14 @@ impl DynamicConfig {
     pub fn inputs_mut<'a>(&'a mut self) -> PersistenceGuard<'a, InputsConfig> {
         PersistenceGuard(self.inputs.0.clone(), &mut self.inputs.1)
     }
+
+    pub fn layouts(&self) -> &LayoutsConfig {
+        &self.layouts.1
+    }
+
+    pub fn layouts_mut<'a>(&'a mut self) -> PersistenceGuard<'a, LayoutsConfig> {
+        PersistenceGuard(self.layouts.0.clone(), &mut self.layouts.1)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
@@ -965,8 +1360,15 @@ pub struct KeyPattern {
     #[serde(deserialize_with = "deserialize_KeyModifiers")]
     pub modifiers: KeyModifiers,
     /// The actual key, that was pressed
-    #[serde(deserialize_with = "deserialize_Keysym")]
+    #[serde(deserialize_with = "deserialize_Keysym", default)]
     pub key: u32,
+    /// Evdev keycode matched instead of `key`, independent of the keyboard layout
+    #[serde(default)]
+    pub keycode: Option<u32>,
+    /// Trigger when the key is released without pressing anything else in between,
+    /// e.g. to bind a bare `Super_L`
+    #[serde(default)]
+    pub on_release: bool,
 }
 
 impl KeyPattern {
@@ -974,8 +1376,141 @@ impl KeyPattern {
         KeyPattern {
             modifiers: modifiers.into(),
             key,
+            keycode: None,
+            on_release: false,
         }
     }
+
+    pub fn from_keycode(modifiers: impl Into<KeyModifiers>, keycode: u32) -> KeyPattern {
+        KeyPattern {
+            modifiers: modifiers.into(),
+            key: KeySyms::KEY_NoSymbol,
+            keycode: Some(keycode),
+            on_release: false,
+        }
+    }
+
+    /// Whether a key press of `keycode` producing `syms` triggers this binding
+    pub fn matches(&self, modifiers: &ModifiersState, keycode: u32, syms: &[Keysym]) -> bool {
+        self.modifiers == *modifiers
+            && match self.keycode {
+                Some(code) => code == keycode,
+                None => syms.contains(&self.key),
+            }
+    }
+}
+
+/// Description of a pointer button press that might be
+/// handled by the compositor.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Hash)]
+#[serde(deny_unknown_fields)]
+pub struct ButtonPattern {
+    /// What modifiers are expected to be pressed alongside the button
+    #[serde(deserialize_with = "deserialize_KeyModifiers")]
+    pub modifiers: KeyModifiers,
+    pub button: MouseButton,
+}
+
+impl ButtonPattern {
+    pub fn new(modifiers: impl Into<KeyModifiers>, button: MouseButton) -> ButtonPattern {
+        ButtonPattern {
+            modifiers: modifiers.into(),
+            button,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    Side,
+    Extra,
+    Forward,
+    Back,
+}
+
+impl MouseButton {
+    /// Button of the given evdev code
+    pub fn from_code(code: u32) -> Option<MouseButton> {
+        Some(match code {
+            0x110 => MouseButton::Left,
+            0x111 => MouseButton::Right,
+            0x112 => MouseButton::Middle,
+            0x113 => MouseButton::Side,
+            0x114 => MouseButton::Extra,
+            0x115 => MouseButton::Forward,
+            0x116 => MouseButton::Back,
+            _ => return None,
+        })
+    }
+}
+
+/// Description of scrolling that might be
+/// handled by the compositor.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Hash)]
+#[serde(deny_unknown_fields)]
+pub struct ScrollPattern {
+    /// What modifiers are expected to be pressed while scrolling
+    #[serde(deserialize_with = "deserialize_KeyModifiers")]
+    pub modifiers: KeyModifiers,
+    pub direction: ScrollDirection,
+}
+
+impl ScrollPattern {
+    pub fn new(modifiers: impl Into<KeyModifiers>, direction: ScrollDirection) -> ScrollPattern {
+        ScrollPattern {
+            modifiers: modifiers.into(),
+            direction,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Hash)]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Description of a touchpad gesture that might be
+/// handled by the compositor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Hash)]
+#[serde(deny_unknown_fields)]
+pub struct GesturePattern {
+    /// How many fingers are involved
+    pub fingers: u32,
+    /// The kind of gesture performed
+    pub gesture: Gesture,
+}
+
+impl GesturePattern {
+    pub fn new(fingers: u32, gesture: Gesture) -> GesturePattern {
+        GesturePattern { fingers, gesture }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Hash)]
+pub enum Gesture {
+    Swipe(SwipeDirection),
+    Pinch(PinchDirection),
+    Hold,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Hash)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Hash)]
+pub enum PinchDirection {
+    In,
+    Out,
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
@@ -1011,9 +1546,34 @@ pub enum Action {
     Orientation(crate::shell::layout::Orientation),
 
     ToggleStacking,
+    TilingLayout(LayoutMode),
+    /// Saves the tiling layout of the active workspace under the given name
+    SaveLayout(String),
+    /// Rearranges the active workspace following a saved layout
+    LoadLayout(String),
+    /// Hides the focused window until it is restored
+    Minimize,
+    /// Shows or hides the window with the given app id on the active workspace
+    Scratchpad(String),
+    /// Opens the window switcher or selects the next window in it
+    SwitchWindows(SwitcherScope),
+    /// Opens the window switcher or selects the previous window in it
+    SwitchWindowsBackwards(SwitcherScope),
+    /// Shows or hides the overview of all windows and workspaces
+    ToggleExpose,
+    /// Activates the bindings of the named mode, `"default"` returns to `key_bindings`
+    EnterMode(String),
+    /// Switches to the next configured keyboard layout
+    NextLayout,
+    /// Switches to the keyboard layout at the given index
+    SetLayout(u8),
 
     ToggleTiling,
     ToggleWindowFloating,
+    /// Lets the focused window follow workspace switches
+    ToggleSticky,
+    /// Keeps the focused window above all others
+    ToggleAlwaysOnTop,
 
     Maximize,
     Spawn(String),
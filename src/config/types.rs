@@ -13,13 +13,30 @@ pub use smithay::{
 use tracing::warn;
 use xkbcommon::xkb;
 
+/// Xkb supports at most four groups, so only as many layouts can be switched between
+pub const MAX_LAYOUTS: usize = 4;
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct XkbConfig {
     pub rules: String,
     pub model: String,
     pub layout: String,
     pub variant: String,
+    /// Xkb options, e.g. `grp:alt_shift_toggle` to switch between layouts
     pub options: Option<String>,
+    /// Layouts to switch between, replaces `layout` and `variant` if not empty
+    #[serde(default)]
+    pub layouts: Vec<XkbLayout>,
+    /// Remember the active layout per window and restore it on focus
+    #[serde(default)]
+    pub per_window_layout: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct XkbLayout {
+    pub layout: String,
+    #[serde(default)]
+    pub variant: String,
 }
 
 impl Default for XkbConfig {
@@ -30,6 +47,59 @@ impl Default for XkbConfig {
             layout: String::new(),
             variant: String::new(),
             options: None,
+            layouts: Vec::new(),
+            per_window_layout: false,
+        }
+    }
+}
+
+impl XkbConfig {
+    /// All usable layouts in the order they are switched through, see [`MAX_LAYOUTS`]
+    pub fn layouts(&self) -> Vec<XkbLayout> {
+        let mut layouts = self.configured_layouts();
+        layouts.truncate(MAX_LAYOUTS);
+        layouts
+    }
+
+    /// Configured layouts exceeding [`MAX_LAYOUTS`], which are ignored
+    pub fn unusable_layouts(&self) -> Vec<XkbLayout> {
+        self.configured_layouts()
+            .into_iter()
+            .skip(MAX_LAYOUTS)
+            .collect()
+    }
+
+    fn configured_layouts(&self) -> Vec<XkbLayout> {
+        if !self.layouts.is_empty() {
+            return self.layouts.clone();
+        }
+
+        let mut variants = self.variant.split(',');
+        self.layout
+            .split(',')
+            .map(|layout| XkbLayout {
+                layout: layout.trim().to_string(),
+                variant: variants.next().unwrap_or_default().trim().to_string(),
+            })
+            .collect()
+    }
+
+    /// Keymap configuration with every layout as an xkb group, in the order they are configured
+    pub fn keymap_config(&self) -> XkbConfig {
+        let layouts = self.layouts();
+        XkbConfig {
+            layout: layouts
+                .iter()
+                .map(|l| l.layout.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+            variant: layouts
+                .iter()
+                .map(|l| l.variant.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+            layouts: Vec::new(),
+            ..self.clone()
         }
     }
 }
@@ -0,0 +1,265 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+use smithay::xwayland::xwm::WmWindowType;
+
+use crate::shell::CosmicSurface;
+
+/// A regular expression matched against window properties.
+///
+/// Patterns are not anchored, so `Steam` also matches `Steam - News`.
+#[derive(Debug, Clone)]
+pub struct RegexMatcher(Regex);
+
+impl RegexMatcher {
+    pub fn new(pattern: &str) -> Result<RegexMatcher, regex::Error> {
+        Regex::new(pattern).map(RegexMatcher)
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.0.is_match(text)
+    }
+}
+
+impl PartialEq for RegexMatcher {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl<'de> Deserialize<'de> for RegexMatcher {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        RegexMatcher::new(&pattern).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum WindowOrigin {
+    Wayland,
+    X11,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum X11WindowType {
+    DropdownMenu,
+    Dialog,
+    Menu,
+    Notification,
+    Normal,
+    PopupMenu,
+    Splash,
+    Toolbar,
+    Tooltip,
+    Utility,
+}
+
+impl X11WindowType {
+    fn matches(&self, window_type: WmWindowType) -> bool {
+        matches!(
+            (self, window_type),
+            (X11WindowType::DropdownMenu, WmWindowType::DropdownMenu)
+                | (X11WindowType::Dialog, WmWindowType::Dialog)
+                | (X11WindowType::Menu, WmWindowType::Menu)
+                | (X11WindowType::Notification, WmWindowType::Notification)
+                | (X11WindowType::Normal, WmWindowType::Normal)
+                | (X11WindowType::PopupMenu, WmWindowType::PopupMenu)
+                | (X11WindowType::Splash, WmWindowType::Splash)
+                | (X11WindowType::Toolbar, WmWindowType::Toolbar)
+                | (X11WindowType::Tooltip, WmWindowType::Tooltip)
+                | (X11WindowType::Utility, WmWindowType::Utility)
+        )
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DecorationPreference {
+    ServerSide,
+    ClientSide,
+}
+
+/// A user defined rule, applied to windows when they are mapped
+/// and whenever their title or app_id changes.
+///
+/// All given matchers have to match for the rule to apply.
+/// Matching rules are applied in order, later rules override earlier ones.
+#[derive(Debug, Default, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct WindowRule {
+    #[serde(default)]
+    pub app_id: Option<RegexMatcher>,
+    #[serde(default)]
+    pub title: Option<RegexMatcher>,
+    /// WM_CLASS of X11 windows, never matches wayland windows
+    #[serde(default)]
+    pub class: Option<RegexMatcher>,
+    /// _NET_WM_WINDOW_TYPE of X11 windows, never matches wayland windows
+    #[serde(default)]
+    pub window_type: Option<X11WindowType>,
+    #[serde(default)]
+    pub origin: Option<WindowOrigin>,
+
+    #[serde(default)]
+    pub floating: Option<bool>,
+    /// Workspace number (starting at 1) on the target output
+    #[serde(default)]
+    pub workspace: Option<u8>,
+    /// Connector name of the target output
+    #[serde(default)]
+    pub output: Option<String>,
+    /// Initial size of floating windows
+    #[serde(default)]
+    pub size: Option<(i32, i32)>,
+    /// Initial position of floating windows, relative to the output
+    #[serde(default)]
+    pub position: Option<(i32, i32)>,
+    #[serde(default)]
+    pub maximized: Option<bool>,
+    #[serde(default)]
+    pub fullscreen: Option<bool>,
+    #[serde(default)]
+    pub decorations: Option<DecorationPreference>,
+    /// Keeps the window visible on all workspaces of its output, implies floating
+    #[serde(default)]
+    pub sticky: Option<bool>,
+    /// Keeps the window above all other windows, implies floating
+    #[serde(default)]
+    pub always_on_top: Option<bool>,
+}
+
+/// The combined result of all window rules matching a window
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct WindowRuleActions {
+    pub floating: Option<bool>,
+    pub workspace: Option<u8>,
+    pub output: Option<String>,
+    pub size: Option<(i32, i32)>,
+    pub position: Option<(i32, i32)>,
+    pub maximized: Option<bool>,
+    pub fullscreen: Option<bool>,
+    pub decorations: Option<DecorationPreference>,
+    pub sticky: Option<bool>,
+    pub always_on_top: Option<bool>,
+}
+
+impl WindowRuleActions {
+    /// Merges the actions of all rules matching the given window
+    pub fn for_window(rules: &[WindowRule], window: &CosmicSurface) -> WindowRuleActions {
+        let app_id = window.app_id();
+        let title = window.title();
+
+        rules
+            .iter()
+            .filter(|rule| rule.matches(window, &app_id, &title))
+            .fold(WindowRuleActions::default(), |actions, rule| {
+                actions.merge(rule)
+            })
+    }
+
+    fn merge(self, other: &WindowRule) -> WindowRuleActions {
+        WindowRuleActions {
+            floating: other.floating.or(self.floating),
+            workspace: other.workspace.or(self.workspace),
+            output: other.output.clone().or(self.output),
+            size: other.size.or(self.size),
+            position: other.position.or(self.position),
+            maximized: other.maximized.or(self.maximized),
+            fullscreen: other.fullscreen.or(self.fullscreen),
+            decorations: other.decorations.or(self.decorations),
+            sticky: other.sticky.or(self.sticky),
+            always_on_top: other.always_on_top.or(self.always_on_top),
+        }
+    }
+}
+
+impl WindowRule {
+    fn matches(&self, window: &CosmicSurface, app_id: &str, title: &str) -> bool {
+        if let Some(origin) = self.origin {
+            match (origin, window) {
+                (WindowOrigin::Wayland, CosmicSurface::Wayland(_))
+                | (WindowOrigin::X11, CosmicSurface::X11(_)) => {}
+                _ => return false,
+            }
+        }
+
+        if let Some(matcher) = self.app_id.as_ref() {
+            if !matcher.is_match(app_id) {
+                return false;
+            }
+        }
+        if let Some(matcher) = self.title.as_ref() {
+            if !matcher.is_match(title) {
+                return false;
+            }
+        }
+
+        if self.class.is_some() || self.window_type.is_some() {
+            let surface = match window {
+                CosmicSurface::X11(surface) => surface,
+                _ => return false,
+            };
+            if let Some(matcher) = self.class.as_ref() {
+                if !matcher.is_match(&surface.class()) {
+                    return false;
+                }
+            }
+            if let Some(window_type) = self.window_type {
+                if !surface
+                    .window_type()
+                    .map(|ty| window_type.matches(ty))
+                    .unwrap_or(false)
+                {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+pub fn default_window_rules() -> Vec<WindowRule> {
+    [
+        (r"Authy Desktop", r".*"),
+        (r"Com.github.amezin.ddterm", r".*"),
+        (r"Com.github.donadigo.eddy", r".*"),
+        (r".*", r"Discord Updater"),
+        (r"Enpass", r"Enpass Assistant"),
+        (r"Gjs", r"Settings"),
+        (r"Gnome-initial-setup", r".*"),
+        (r"Gnome-terminal", r"Preferences – General"),
+        (r"Guake", r".*"),
+        (r"Io.elementary.sideload", r".*"),
+        (r"KotatogramDesktop", r"Media viewer"),
+        (r"Mozilla VPN", r".*"),
+        (r"update-manager", r"Software Updater"),
+        (r"Solaar", r".*"),
+        (r"Steam", r"^.*?(Guard|Login).*"),
+        (r"", r"Steam"),
+        (r"TelegramDesktop", r"Media viewer"),
+        (r"Zotero", r"Quick Format Citation"),
+        (r"gjs", r".*"),
+        (r"gnome-screenshot", r".*"),
+        (r"ibus-.*", r".*"),
+        (r"jetbrains-toolbox", r".*"),
+        (r"jetbrains-webstorm", r"Customize WebStorm"),
+        (r"jetbrains-webstorm", r"License Activation"),
+        (r"jetbrains-webstorm", r"Welcome to WebStorm"),
+        (r"krunner", r".*"),
+        (r"pritunl", r".*"),
+        (r"re.sonny.Junction", r".*"),
+        (r"system76-driver", r".*"),
+        (r"tilda", r".*"),
+        (r"zoom", r".*"),
+        (r"^.*?action=join.*$", r".*"),
+    ]
+    .into_iter()
+    .map(|(app_id, title)| WindowRule {
+        app_id: Some(RegexMatcher::new(app_id).unwrap()),
+        title: Some(RegexMatcher::new(title).unwrap()),
+        floating: Some(true),
+        ..Default::default()
+    })
+    .collect()
+}
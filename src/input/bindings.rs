@@ -0,0 +1,342 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Matching input against the key bindings of the active binding mode, chords
+//! and pointer bindings
+
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::HashMap,
+};
+
+use smithay::{
+    backend::input::{AxisSource, ButtonState},
+    input::{
+        keyboard::{keysyms, FilterResult, Keysym, ModifiersState},
+        Seat,
+    },
+};
+
+use super::layout;
+use crate::{
+    config::{
+        Action, Config, KeyModifiers, KeyPattern, MouseButton, ScrollDirection, ScrollPattern,
+    },
+    utils::prelude::*,
+};
+
+#[derive(Debug, Default)]
+pub struct BindingState(RefCell<BindingStateInner>);
+
+#[derive(Debug, Default)]
+struct BindingStateInner {
+    /// Active binding mode, `None` for the default `key_bindings`
+    mode: Option<String>,
+    /// Steps of a chord entered so far
+    chord: Vec<KeyPattern>,
+    /// Time of the last chord step
+    chord_time: u32,
+    /// Last pressed key, as long as nothing else was pressed since
+    release: Option<u32>,
+    /// Pressed buttons, that triggered a binding
+    buttons: Vec<u32>,
+    /// Scroll distance of continuous scrolling not yet triggering a binding
+    scroll: (f64, f64),
+}
+
+/// Scroll distance of continuous scrolling that triggers a binding once
+const SCROLL_THRESHOLD: f64 = 40.0;
+
+fn with_state<T>(seat: &Seat<State>, f: impl FnOnce(&mut BindingStateInner) -> T) -> T {
+    seat.user_data().insert_if_missing(BindingState::default);
+    let state = seat.user_data().get::<BindingState>().unwrap();
+    let mut inner = state.0.borrow_mut();
+    f(&mut inner)
+}
+
+pub fn binding_mode(seat: &Seat<State>) -> Option<String> {
+    with_state(seat, |state| state.mode.clone())
+}
+
+pub fn set_binding_mode(seat: &Seat<State>, mode: Option<String>) {
+    with_state(seat, |state| {
+        state.mode = mode;
+        state.chord.clear();
+    })
+}
+
+/// Syms of the pressed key, that bindings are matched against
+struct PressedKey<'a> {
+    seat: &'a Seat<State>,
+    modifiers: &'a ModifiersState,
+    keycode: u32,
+    raw_syms: &'a [Keysym],
+    /// Syms of the first configured layout, looked up once the active layout didn't match
+    base_syms: OnceCell<Vec<Keysym>>,
+}
+
+impl<'a> PressedKey<'a> {
+    fn new(
+        seat: &'a Seat<State>,
+        modifiers: &'a ModifiersState,
+        keycode: u32,
+        raw_syms: &'a [Keysym],
+    ) -> PressedKey<'a> {
+        PressedKey {
+            seat,
+            modifiers,
+            keycode,
+            raw_syms,
+            base_syms: OnceCell::new(),
+        }
+    }
+
+    fn base_syms(&self) -> &[Keysym] {
+        self.base_syms
+            .get_or_init(|| layout::base_syms(self.seat, self.keycode))
+    }
+
+    fn matches(&self, pattern: &KeyPattern) -> bool {
+        pattern.matches(self.modifiers, self.keycode, self.raw_syms)
+            || pattern.matches(self.modifiers, self.keycode, self.base_syms())
+    }
+
+    /// Finds a binding for the key, preferring the syms of the active layout
+    fn find<'b>(
+        &self,
+        bindings: &'b HashMap<KeyPattern, Action>,
+        release: bool,
+    ) -> Option<(&'b KeyPattern, &'b Action)> {
+        let mut bindings = bindings
+            .iter()
+            .filter(|(pattern, _)| pattern.on_release == release);
+        bindings
+            .clone()
+            .find(|(pattern, _)| pattern.matches(self.modifiers, self.keycode, self.raw_syms))
+            .or_else(|| {
+                bindings.find(|(pattern, _)| {
+                    pattern.matches(self.modifiers, self.keycode, self.base_syms())
+                })
+            })
+    }
+
+    fn is_modifier(&self) -> bool {
+        self.raw_syms.iter().any(|sym| {
+            (keysyms::KEY_Shift_L..=keysyms::KEY_Hyper_R).contains(sym)
+                || *sym == keysyms::KEY_ISO_Level3_Shift
+                || *sym == keysyms::KEY_Mode_switch
+        })
+    }
+}
+
+/// Looks up the action bound to a key press of the evdev `keycode`.
+///
+/// Keys continuing a chord or leaving a mode are intercepted without an action.
+pub fn match_key_press(
+    config: &Config,
+    seat: &Seat<State>,
+    modifiers: &ModifiersState,
+    keycode: u32,
+    raw_syms: &[Keysym],
+    time: u32,
+) -> FilterResult<Option<(Action, KeyModifiers)>> {
+    let key = PressedKey::new(seat, modifiers, keycode, raw_syms);
+    let conf = &config.static_conf;
+    with_state(seat, |state| state.release = Some(keycode));
+
+    if let Some(mode) = binding_mode(seat) {
+        let Some(bindings) = conf.binding_modes.get(&mode) else {
+            set_binding_mode(seat, None);
+            return FilterResult::Forward;
+        };
+        if let Some((pattern, action)) = key.find(bindings, false) {
+            return FilterResult::Intercept(Some((action.clone(), pattern.modifiers.clone())));
+        }
+        if raw_syms.contains(&keysyms::KEY_Escape) {
+            set_binding_mode(seat, None);
+            return FilterResult::Intercept(None);
+        }
+        return FilterResult::Forward;
+    }
+
+    if !conf.chord_bindings.is_empty() {
+        let chord = with_state(seat, |state| {
+            if !state.chord.is_empty() && time.wrapping_sub(state.chord_time) > conf.chord_timeout {
+                state.chord.clear();
+            }
+            state.chord.clone()
+        });
+        // modifiers are pressed in between the steps
+        if !chord.is_empty() && key.is_modifier() {
+            return FilterResult::Forward;
+        }
+
+        let step = chord.len();
+        let candidates = conf
+            .chord_bindings
+            .iter()
+            .filter(|(keys, _)| {
+                keys.len() > step && keys[..step] == chord[..] && key.matches(&keys[step])
+            })
+            .collect::<Vec<_>>();
+        if let Some((keys, action)) = candidates.iter().find(|(keys, _)| keys.len() == step + 1) {
+            with_state(seat, |state| state.chord.clear());
+            return FilterResult::Intercept(Some((action.clone(), keys[step].modifiers.clone())));
+        }
+        if let Some((keys, _)) = candidates.first() {
+            let pattern = keys[step].clone();
+            with_state(seat, |state| {
+                state.chord.push(pattern);
+                state.chord_time = time;
+            });
+            return FilterResult::Intercept(None);
+        }
+        if !chord.is_empty() {
+            // keys not continuing the chord abort it, but may still trigger a regular binding
+            with_state(seat, |state| state.chord.clear());
+        }
+    }
+
+    match key.find(&conf.key_bindings, false) {
+        Some((pattern, action)) => {
+            FilterResult::Intercept(Some((action.clone(), pattern.modifiers.clone())))
+        }
+        None => FilterResult::Forward,
+    }
+}
+
+/// Looks up the action bound to releasing the evdev `keycode`, if it was the last key pressed
+pub fn match_key_release(
+    config: &Config,
+    seat: &Seat<State>,
+    modifiers: &ModifiersState,
+    keycode: u32,
+    raw_syms: &[Keysym],
+) -> Option<(Action, KeyModifiers)> {
+    if with_state(seat, |state| state.release.take()) != Some(keycode) {
+        return None;
+    }
+
+    let key = PressedKey::new(seat, modifiers, keycode, raw_syms);
+    let bindings = match binding_mode(seat) {
+        Some(mode) => config.static_conf.binding_modes.get(&mode)?,
+        None => &config.static_conf.key_bindings,
+    };
+    key.find(bindings, true)
+        .map(|(pattern, action)| (action.clone(), pattern.modifiers.clone()))
+}
+
+/// Looks up the action bound to a pointer button.
+///
+/// Releases of buttons, that triggered a binding, are intercepted as well.
+pub fn match_button(
+    config: &Config,
+    seat: &Seat<State>,
+    modifiers: &ModifiersState,
+    button: u32,
+    state: ButtonState,
+) -> FilterResult<Option<(Action, KeyModifiers)>> {
+    if state == ButtonState::Released {
+        return with_state(seat, |state| {
+            match state.buttons.iter().position(|b| *b == button) {
+                Some(idx) => {
+                    state.buttons.remove(idx);
+                    FilterResult::Intercept(None)
+                }
+                None => FilterResult::Forward,
+            }
+        });
+    }
+
+    with_state(seat, |state| state.release = None);
+    let Some(button_pattern) = MouseButton::from_code(button) else {
+        return FilterResult::Forward;
+    };
+    let binding = config
+        .static_conf
+        .button_bindings
+        .iter()
+        .find(|(pattern, _)| pattern.button == button_pattern && pattern.modifiers == *modifiers);
+    match binding {
+        Some((pattern, action)) => {
+            with_state(seat, |state| state.buttons.push(button));
+            FilterResult::Intercept(Some((action.clone(), pattern.modifiers.clone())))
+        }
+        None => FilterResult::Forward,
+    }
+}
+
+/// Looks up the action bound to scrolling by the given amounts.
+///
+/// Scrolling towards a bound direction is intercepted, continuous scrolling only triggers the
+/// binding once it moved far enough.
+pub fn match_scroll(
+    config: &Config,
+    seat: &Seat<State>,
+    modifiers: &ModifiersState,
+    source: AxisSource,
+    (horizontal, vertical): (f64, f64),
+) -> FilterResult<Option<(Action, KeyModifiers)>> {
+    with_state(seat, |state| state.release = None);
+    let modifiers = KeyModifiers {
+        ctrl: modifiers.ctrl,
+        alt: modifiers.alt,
+        shift: modifiers.shift,
+        logo: modifiers.logo,
+    };
+    let binding = |direction| {
+        config
+            .static_conf
+            .scroll_bindings
+            .get_key_value(&ScrollPattern {
+                modifiers: modifiers.clone(),
+                direction,
+            })
+    };
+
+    if scroll_direction((horizontal, vertical))
+        .and_then(binding)
+        .is_none()
+    {
+        with_state(seat, |state| state.scroll = (0.0, 0.0));
+        return FilterResult::Forward;
+    }
+
+    let scroll = with_state(seat, |state| {
+        // every click of a wheel triggers a binding
+        if source == AxisSource::Wheel || source == AxisSource::WheelTilt {
+            state.scroll = (0.0, 0.0);
+            return Some((horizontal, vertical));
+        }
+        state.scroll.0 += horizontal;
+        state.scroll.1 += vertical;
+        let scroll = state.scroll;
+        if scroll.0.abs() >= SCROLL_THRESHOLD || scroll.1.abs() >= SCROLL_THRESHOLD {
+            state.scroll = (0.0, 0.0);
+            Some(scroll)
+        } else {
+            None
+        }
+    });
+    FilterResult::Intercept(
+        scroll
+            .and_then(scroll_direction)
+            .and_then(binding)
+            .map(|(pattern, action)| (action.clone(), pattern.modifiers.clone())),
+    )
+}
+
+fn scroll_direction((horizontal, vertical): (f64, f64)) -> Option<ScrollDirection> {
+    if vertical.abs() >= horizontal.abs() {
+        if vertical > 0.0 {
+            Some(ScrollDirection::Down)
+        } else if vertical < 0.0 {
+            Some(ScrollDirection::Up)
+        } else {
+            None
+        }
+    } else if horizontal > 0.0 {
+        Some(ScrollDirection::Right)
+    } else {
+        Some(ScrollDirection::Left)
+    }
+}
@@ -0,0 +1,272 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Locking and confining the pointer on behalf of clients
+
+use std::{cell::RefCell, time::Duration};
+
+use smithay::{
+    desktop::utils::bbox_from_surface_tree,
+    input::{pointer::MotionEvent, Seat},
+    reexports::wayland_server::{protocol::wl_surface::WlSurface, Resource},
+    utils::{Logical, Point, Rectangle, SERIAL_COUNTER},
+    wayland::{
+        compositor::{with_states, RectangleKind, RegionAttributes, SurfaceAttributes},
+        seat::WaylandFocus,
+    },
+};
+
+use crate::{shell::focus::target::PointerFocusTarget, utils::prelude::*};
+
+/// Smallest distance clients can tell apart, as `wl_fixed` has a precision of 1/256
+const SUBPIXEL: f64 = 1.0 / 256.0;
+
+/// Global origin of the surface locking the pointer, to apply its cursor hint once unlocked
+#[derive(Default)]
+struct LockedSurface(RefCell<Option<(WlSurface, Point<f64, Logical>)>>);
+
+/// Constraint of the surface under the pointer
+pub struct ActiveConstraint {
+    pub locked: bool,
+    /// Pointer focus of the constrained surface
+    pub focus: (PointerFocusTarget, Point<i32, Logical>),
+    /// Global position of the surface origin
+    origin: Point<f64, Logical>,
+    /// Surface-local area of the window, excluding server-side decorations
+    bounds: Rectangle<i32, Logical>,
+    region: Option<RegionAttributes>,
+    /// Input region of the surface, the constraint region only applies within it
+    input_region: Option<RegionAttributes>,
+}
+
+impl ActiveConstraint {
+    fn allows(&self, position: Point<f64, Logical>) -> bool {
+        let local = position - self.origin;
+        self.bounds.to_f64().contains(local)
+            && [&self.region, &self.input_region]
+                .into_iter()
+                .all(|region| {
+                    region
+                        .as_ref()
+                        .map(|region| region.contains(local.to_i32_floor()))
+                        .unwrap_or(true)
+                })
+    }
+
+    /// Surface-local rectangles covering the confined area,
+    /// which may still contain holes cut out by either region.
+    fn areas(&self) -> Vec<Rectangle<i32, Logical>> {
+        let mut areas = vec![self.bounds];
+        for region in [&self.region, &self.input_region].into_iter().flatten() {
+            areas = areas
+                .iter()
+                .flat_map(|area| {
+                    region
+                        .rects
+                        .iter()
+                        .filter(|(kind, _)| *kind == RectangleKind::Add)
+                        .filter_map(|(_, rect)| area.intersection(*rect))
+                })
+                .collect();
+        }
+        areas
+    }
+
+    /// Limits moving the pointer from `current` to `position` to the confined area,
+    /// by moving it to the closest point still inside.
+    pub fn confine(
+        &self,
+        current: Point<f64, Logical>,
+        position: Point<f64, Logical>,
+    ) -> Point<f64, Logical> {
+        if self.allows(position) {
+            return position;
+        }
+
+        let local = position - self.origin;
+        let distance = |point: &Point<f64, Logical>| {
+            let delta = *point - position;
+            delta.x * delta.x + delta.y * delta.y
+        };
+        self.areas()
+            .into_iter()
+            .filter(|area| area.size.w > 0 && area.size.h > 0)
+            .map(|area| {
+                let area = area.to_f64();
+                let clamped = Point::from((
+                    local
+                        .x
+                        .clamp(area.loc.x, area.loc.x + area.size.w - SUBPIXEL),
+                    local
+                        .y
+                        .clamp(area.loc.y, area.loc.y + area.size.h - SUBPIXEL),
+                ));
+                self.origin + clamped
+            })
+            .filter(|point| self.allows(*point))
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .unwrap_or(current)
+    }
+}
+
+impl State {
+    /// Returns the constraint of the surface under the pointer of `seat`, activating it if necessary
+    pub fn pointer_constraint(
+        &mut self,
+        seat: &Seat<State>,
+        position: Point<f64, Logical>,
+    ) -> Option<ActiveConstraint> {
+        // skip looking up the surface under the pointer on every motion
+        if !self.common.pointer_constraints_state.has_constraints() {
+            return None;
+        }
+
+        let output = seat.active_output();
+        let relative_pos = self.common.shell.map_global_to_space(position, &output);
+        let (target, loc) = State::surface_under(
+            position,
+            relative_pos,
+            &output,
+            output.geometry(),
+            &self.common.shell.override_redirect_windows,
+            self.common.shell.session_lock.as_ref(),
+            self.common.shell.active_space(&output),
+        )?;
+        let surface = target.wl_surface()?;
+
+        // only the focused surface may constrain the pointer
+        let keyboard_focus = seat
+            .get_keyboard()
+            .unwrap()
+            .current_focus()
+            .and_then(|target| target.wl_surface());
+        if keyboard_focus.as_ref() != Some(&surface) {
+            return None;
+        }
+
+        let constraint = self.common.pointer_constraints_state.constraint(&surface)?;
+        let (offset, bounds) = match &target {
+            PointerFocusTarget::Element(mapped) => {
+                // stacks and server-side decorations offset the window in its element
+                let window = mapped.active_window();
+                let offset = mapped
+                    .windows()
+                    .find(|(w, _)| w == &window)
+                    .map(|(_, offset)| offset)
+                    .unwrap_or_default();
+                (offset, window.geometry())
+            }
+            PointerFocusTarget::Fullscreen(window) => (Point::default(), window.geometry()),
+            _ => (Point::default(), bbox_from_surface_tree(&surface, (0, 0))),
+        };
+        let input_region = with_states(&surface, |states| {
+            states
+                .cached_state
+                .current::<SurfaceAttributes>()
+                .input_region
+                .clone()
+        });
+        let active = ActiveConstraint {
+            locked: constraint.is_locked(),
+            origin: (loc + offset).to_f64(),
+            bounds,
+            region: constraint.region().cloned(),
+            input_region,
+            focus: (target, loc),
+        };
+
+        if !constraint.is_active() {
+            if !active.allows(position) {
+                return None;
+            }
+            self.common.pointer_constraints_state.activate(&surface);
+        }
+        if active.locked {
+            seat.user_data().insert_if_missing(LockedSurface::default);
+            let locked = seat.user_data().get::<LockedSurface>().unwrap();
+            *locked.0.borrow_mut() = Some((surface, active.origin));
+        }
+        Some(active)
+    }
+}
+
+/// Releases constraints of surfaces, that lost the keyboard focus
+pub fn refresh(state: &mut State) {
+    let active = state
+        .common
+        .pointer_constraints_state
+        .active_constraints()
+        .map(|constraint| constraint.surface().clone())
+        .collect::<Vec<_>>();
+    if active.is_empty() {
+        return;
+    }
+
+    let seats = state.common.seats().cloned().collect::<Vec<_>>();
+    for surface in active {
+        let focused = surface.is_alive()
+            && seats.iter().any(|seat| {
+                seat.get_keyboard()
+                    .unwrap()
+                    .current_focus()
+                    .and_then(|target| target.wl_surface())
+                    .as_ref()
+                    == Some(&surface)
+            });
+        if focused {
+            continue;
+        }
+
+        let hint = state
+            .common
+            .pointer_constraints_state
+            .constraint(&surface)
+            .and_then(|constraint| constraint.cursor_hint());
+        state.common.pointer_constraints_state.deactivate(&surface);
+
+        for seat in seats.iter() {
+            let Some(locked) = seat.user_data().get::<LockedSurface>() else {
+                continue;
+            };
+            let origin = match locked.0.borrow_mut().take() {
+                Some((locked_surface, origin)) if locked_surface == surface => origin,
+                other => {
+                    *locked.0.borrow_mut() = other;
+                    continue;
+                }
+            };
+            if let Some(hint) = hint.filter(|_| surface.is_alive()) {
+                warp_pointer(state, seat, origin + hint);
+            }
+        }
+    }
+}
+
+fn warp_pointer(state: &mut State, seat: &Seat<State>, position: Point<f64, Logical>) {
+    let output = state
+        .common
+        .shell
+        .outputs()
+        .find(|output| output.geometry().to_f64().contains(position))
+        .cloned()
+        .unwrap_or_else(|| seat.active_output());
+    let relative_pos = state.common.shell.map_global_to_space(position, &output);
+    let under = State::surface_under(
+        position,
+        relative_pos,
+        &output,
+        output.geometry(),
+        &state.common.shell.override_redirect_windows,
+        state.common.shell.session_lock.as_ref(),
+        state.common.shell.active_space(&output),
+    );
+    let time = Duration::from(state.common.clock.now()).as_millis() as u32;
+    seat.get_pointer().unwrap().motion(
+        state,
+        under,
+        &MotionEvent {
+            location: position,
+            serial: SERIAL_COUNTER.next_serial(),
+            time,
+        },
+    );
+}
@@ -0,0 +1,429 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use crate::{
+    config::{Action, Gesture, GesturePattern, KeyModifiers, PinchDirection, SwipeDirection},
+    state::State,
+    utils::prelude::*,
+};
+use smithay::{
+    input::{
+        pointer::{
+            GestureHoldBeginEvent, GestureHoldEndEvent, GesturePinchBeginEvent,
+            GesturePinchEndEvent, GesturePinchUpdateEvent, GestureSwipeBeginEvent,
+            GestureSwipeEndEvent, GestureSwipeUpdateEvent,
+        },
+        Seat,
+    },
+    output::Output,
+    utils::{Logical, Point, SERIAL_COUNTER},
+};
+use std::cell::RefCell;
+
+/// Distance a swipe has to travel, before its direction is considered known
+const SWIPE_THRESHOLD: f64 = 16.0;
+/// Scale change a pinch needs, before its direction is considered known
+const PINCH_THRESHOLD: f64 = 0.15;
+
+/// Touchpad gesture currently in progress on a seat
+#[derive(Default)]
+pub struct GestureState(RefCell<Option<ActiveGesture>>);
+
+#[derive(Debug, Clone)]
+enum ActiveGesture {
+    /// Bindings exist for the amount of fingers, but it isn't clear yet, which one matches.
+    ///
+    /// Clients already receive the gesture, until it turns out to be bound.
+    Pending { fingers: u32, kind: PendingGesture },
+    /// Not bound, send to clients instead
+    Forwarded,
+    /// Bound action already triggered, the rest of the gesture is ignored
+    Handled,
+    /// Interactive workspace switch following the fingers
+    WorkspaceSwipe {
+        output: Output,
+        direction: SwipeDirection,
+        progress: f64,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PendingGesture {
+    Swipe { delta: Point<f64, Logical> },
+    Pinch,
+    Hold,
+}
+
+impl GestureState {
+    fn take(&self) -> Option<ActiveGesture> {
+        self.0.borrow_mut().take()
+    }
+
+    fn get(&self) -> Option<ActiveGesture> {
+        self.0.borrow().clone()
+    }
+
+    fn set(&self, gesture: ActiveGesture) {
+        *self.0.borrow_mut() = Some(gesture);
+    }
+}
+
+impl State {
+    fn has_gesture_binding(&self, fingers: u32, kind: impl Fn(&Gesture) -> bool) -> bool {
+        // gestures are forwarded to the lock surface while the session is locked
+        self.common.shell.session_lock.is_none()
+            && self
+                .common
+                .config
+                .static_conf
+                .gesture_bindings
+                .keys()
+                .any(|pattern| pattern.fingers == fingers && kind(&pattern.gesture))
+    }
+
+    fn gesture_binding(&self, fingers: u32, gesture: Gesture) -> Option<Action> {
+        if self.common.shell.session_lock.is_some() {
+            return None;
+        }
+        self.common
+            .config
+            .static_conf
+            .gesture_bindings
+            .get(&GesturePattern::new(fingers, gesture))
+            .cloned()
+    }
+
+    pub(super) fn gesture_swipe_begin(&mut self, seat: &Seat<State>, fingers: u32, time: u32) {
+        let state = seat.user_data().get::<GestureState>().unwrap();
+        if self.has_gesture_binding(fingers, |g| matches!(g, Gesture::Swipe(_))) {
+            state.set(ActiveGesture::Pending {
+                fingers,
+                kind: PendingGesture::Swipe {
+                    delta: Point::default(),
+                },
+            });
+        } else {
+            state.set(ActiveGesture::Forwarded);
+        }
+        seat.get_pointer().unwrap().gesture_swipe_begin(
+            self,
+            &GestureSwipeBeginEvent {
+                serial: SERIAL_COUNTER.next_serial(),
+                time,
+                fingers,
+            },
+        );
+    }
+
+    pub(super) fn gesture_swipe_update(
+        &mut self,
+        seat: &Seat<State>,
+        delta: Point<f64, Logical>,
+        time: u32,
+    ) {
+        let state = seat.user_data().get::<GestureState>().unwrap();
+        match state.get() {
+            Some(ActiveGesture::Pending {
+                fingers,
+                kind: PendingGesture::Swipe { delta: previous },
+            }) => {
+                let total = previous + delta;
+                if total.x.abs().max(total.y.abs()) < SWIPE_THRESHOLD {
+                    state.set(ActiveGesture::Pending {
+                        fingers,
+                        kind: PendingGesture::Swipe { delta: total },
+                    });
+                    seat.get_pointer()
+                        .unwrap()
+                        .gesture_swipe_update(self, &GestureSwipeUpdateEvent { time, delta });
+                    return;
+                }
+
+                let direction = if total.x.abs() > total.y.abs() {
+                    if total.x < 0.0 {
+                        SwipeDirection::Left
+                    } else {
+                        SwipeDirection::Right
+                    }
+                } else if total.y < 0.0 {
+                    SwipeDirection::Up
+                } else {
+                    SwipeDirection::Down
+                };
+
+                let action = self.gesture_binding(fingers, Gesture::Swipe(direction));
+                let pointer = seat.get_pointer().unwrap();
+                if action.is_some() {
+                    // take back what the client got so far
+                    pointer.gesture_swipe_end(
+                        self,
+                        &GestureSwipeEndEvent {
+                            serial: SERIAL_COUNTER.next_serial(),
+                            time,
+                            cancelled: true,
+                        },
+                    );
+                }
+                match action {
+                    Some(action @ (Action::NextWorkspace | Action::PreviousWorkspace)) => {
+                        let output = seat.active_output();
+                        let current = self.common.shell.workspaces.active_num(&output).1;
+                        let idx = if action == Action::NextWorkspace {
+                            current.checked_add(1)
+                        } else {
+                            current.checked_sub(1)
+                        };
+                        let started = idx
+                            .map(|idx| {
+                                self.common
+                                    .shell
+                                    .begin_workspace_swipe(&output, idx)
+                                    .unwrap_or(false)
+                            })
+                            .unwrap_or(false);
+
+                        if started {
+                            let progress = swipe_progress(&output, direction, total);
+                            self.common.shell.update_workspace_swipe(&output, progress);
+                            state.set(ActiveGesture::WorkspaceSwipe {
+                                output,
+                                direction,
+                                progress,
+                            });
+                        } else {
+                            // no workspace in that direction, but the gesture is still bound
+                            state.set(ActiveGesture::Handled);
+                        }
+                    }
+                    Some(action) => {
+                        state.set(ActiveGesture::Handled);
+                        self.trigger_gesture_action(action, seat, time);
+                    }
+                    None => {
+                        state.set(ActiveGesture::Forwarded);
+                        pointer
+                            .gesture_swipe_update(self, &GestureSwipeUpdateEvent { time, delta });
+                    }
+                }
+            }
+            Some(ActiveGesture::WorkspaceSwipe {
+                output,
+                direction,
+                progress,
+            }) => {
+                let progress =
+                    (progress + swipe_progress(&output, direction, delta)).clamp(0.0, 1.0);
+                self.common.shell.update_workspace_swipe(&output, progress);
+                state.set(ActiveGesture::WorkspaceSwipe {
+                    output,
+                    direction,
+                    progress,
+                });
+            }
+            Some(ActiveGesture::Forwarded) => {
+                seat.get_pointer()
+                    .unwrap()
+                    .gesture_swipe_update(self, &GestureSwipeUpdateEvent { time, delta });
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn gesture_swipe_end(&mut self, seat: &Seat<State>, cancelled: bool, time: u32) {
+        let state = seat.user_data().get::<GestureState>().unwrap();
+        match state.take() {
+            Some(ActiveGesture::WorkspaceSwipe { output, .. }) => {
+                self.common.shell.end_workspace_swipe(&output, cancelled);
+            }
+            Some(ActiveGesture::Forwarded | ActiveGesture::Pending { .. }) => {
+                seat.get_pointer().unwrap().gesture_swipe_end(
+                    self,
+                    &GestureSwipeEndEvent {
+                        serial: SERIAL_COUNTER.next_serial(),
+                        time,
+                        cancelled,
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn gesture_pinch_begin(&mut self, seat: &Seat<State>, fingers: u32, time: u32) {
+        let state = seat.user_data().get::<GestureState>().unwrap();
+        if self.has_gesture_binding(fingers, |g| matches!(g, Gesture::Pinch(_))) {
+            state.set(ActiveGesture::Pending {
+                fingers,
+                kind: PendingGesture::Pinch,
+            });
+        } else {
+            state.set(ActiveGesture::Forwarded);
+        }
+        seat.get_pointer().unwrap().gesture_pinch_begin(
+            self,
+            &GesturePinchBeginEvent {
+                serial: SERIAL_COUNTER.next_serial(),
+                time,
+                fingers,
+            },
+        );
+    }
+
+    pub(super) fn gesture_pinch_update(
+        &mut self,
+        seat: &Seat<State>,
+        delta: Point<f64, Logical>,
+        scale: f64,
+        rotation: f64,
+        time: u32,
+    ) {
+        let state = seat.user_data().get::<GestureState>().unwrap();
+        match state.get() {
+            Some(ActiveGesture::Pending {
+                fingers,
+                kind: PendingGesture::Pinch,
+            }) => {
+                // scale is absolute, so there is nothing to accumulate
+                let pointer = seat.get_pointer().unwrap();
+                if (scale - 1.0).abs() < PINCH_THRESHOLD {
+                    pointer.gesture_pinch_update(
+                        self,
+                        &GesturePinchUpdateEvent {
+                            time,
+                            delta,
+                            scale,
+                            rotation,
+                        },
+                    );
+                    return;
+                }
+
+                let direction = if scale < 1.0 {
+                    PinchDirection::In
+                } else {
+                    PinchDirection::Out
+                };
+                match self.gesture_binding(fingers, Gesture::Pinch(direction)) {
+                    Some(action) => {
+                        state.set(ActiveGesture::Handled);
+                        // take back what the client got so far
+                        pointer.gesture_pinch_end(
+                            self,
+                            &GesturePinchEndEvent {
+                                serial: SERIAL_COUNTER.next_serial(),
+                                time,
+                                cancelled: true,
+                            },
+                        );
+                        self.trigger_gesture_action(action, seat, time);
+                    }
+                    None => {
+                        state.set(ActiveGesture::Forwarded);
+                        pointer.gesture_pinch_update(
+                            self,
+                            &GesturePinchUpdateEvent {
+                                time,
+                                delta,
+                                scale,
+                                rotation,
+                            },
+                        );
+                    }
+                }
+            }
+            Some(ActiveGesture::Forwarded) => {
+                seat.get_pointer().unwrap().gesture_pinch_update(
+                    self,
+                    &GesturePinchUpdateEvent {
+                        time,
+                        delta,
+                        scale,
+                        rotation,
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn gesture_pinch_end(&mut self, seat: &Seat<State>, cancelled: bool, time: u32) {
+        let state = seat.user_data().get::<GestureState>().unwrap();
+        if let Some(ActiveGesture::Forwarded | ActiveGesture::Pending { .. }) = state.take() {
+            seat.get_pointer().unwrap().gesture_pinch_end(
+                self,
+                &GesturePinchEndEvent {
+                    serial: SERIAL_COUNTER.next_serial(),
+                    time,
+                    cancelled,
+                },
+            );
+        }
+    }
+
+    pub(super) fn gesture_hold_begin(&mut self, seat: &Seat<State>, fingers: u32, time: u32) {
+        let state = seat.user_data().get::<GestureState>().unwrap();
+        if self.gesture_binding(fingers, Gesture::Hold).is_some() {
+            state.set(ActiveGesture::Pending {
+                fingers,
+                kind: PendingGesture::Hold,
+            });
+        } else {
+            state.set(ActiveGesture::Forwarded);
+        }
+        seat.get_pointer().unwrap().gesture_hold_begin(
+            self,
+            &GestureHoldBeginEvent {
+                serial: SERIAL_COUNTER.next_serial(),
+                time,
+                fingers,
+            },
+        );
+    }
+
+    pub(super) fn gesture_hold_end(&mut self, seat: &Seat<State>, cancelled: bool, time: u32) {
+        let state = seat.user_data().get::<GestureState>().unwrap();
+        // a hold is cancelled, when the fingers start moving
+        let action = match state.take() {
+            Some(ActiveGesture::Pending {
+                fingers,
+                kind: PendingGesture::Hold,
+            }) if !cancelled => self.gesture_binding(fingers, Gesture::Hold),
+            Some(ActiveGesture::Pending { .. } | ActiveGesture::Forwarded) => None,
+            _ => return,
+        };
+
+        seat.get_pointer().unwrap().gesture_hold_end(
+            self,
+            &GestureHoldEndEvent {
+                serial: SERIAL_COUNTER.next_serial(),
+                time,
+                cancelled: cancelled || action.is_some(),
+            },
+        );
+        if let Some(action) = action {
+            self.trigger_gesture_action(action, seat, time);
+        }
+    }
+
+    fn trigger_gesture_action(&mut self, action: Action, seat: &Seat<State>, time: u32) {
+        let modifiers = seat.get_keyboard().unwrap().modifier_state();
+        let mods = KeyModifiers {
+            ctrl: modifiers.ctrl,
+            alt: modifiers.alt,
+            shift: modifiers.shift,
+            logo: modifiers.logo,
+        };
+        self.handle_action(action, seat, SERIAL_COUNTER.next_serial(), time, mods, None);
+    }
+}
+
+/// Progress of a workspace switch caused by moving `delta` in `direction`,
+/// where moving across the whole output is a complete switch.
+fn swipe_progress(output: &Output, direction: SwipeDirection, delta: Point<f64, Logical>) -> f64 {
+    let size = output.geometry().size;
+    match direction {
+        SwipeDirection::Left => -delta.x / size.w as f64,
+        SwipeDirection::Right => delta.x / size.w as f64,
+        SwipeDirection::Up => -delta.y / size.h as f64,
+        SwipeDirection::Down => delta.y / size.h as f64,
+    }
+}
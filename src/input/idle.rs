@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+    cell::Cell,
+    time::{Duration, Instant},
+};
+
+use smithay::{
+    backend::input::{Event, InputBackend, InputEvent},
+    input::Seat,
+    reexports::calloop::{
+        timer::{TimeoutAction, Timer},
+        RegistrationToken,
+    },
+};
+use tracing::{debug, error};
+
+use crate::state::State;
+
+/// Last time a seat received any input
+pub struct SeatActivity(Cell<Instant>);
+
+impl Default for SeatActivity {
+    fn default() -> Self {
+        SeatActivity(Cell::new(Instant::now()))
+    }
+}
+
+impl SeatActivity {
+    pub fn idle_time(&self) -> Duration {
+        self.0.get().elapsed()
+    }
+
+    /// Restarts the idle time without any input, e.g. once an inhibitor is gone
+    pub fn reset(&self) {
+        self.0.set(Instant::now());
+    }
+}
+
+#[derive(Default)]
+pub struct IdleState {
+    timer: Option<(Instant, RegistrationToken)>,
+    dpms_off: bool,
+}
+
+pub fn event_device<B: InputBackend>(event: &InputEvent<B>) -> Option<B::Device> {
+    match event {
+        InputEvent::Keyboard { event } => Some(event.device()),
+        InputEvent::PointerMotion { event } => Some(event.device()),
+        InputEvent::PointerMotionAbsolute { event } => Some(event.device()),
+        InputEvent::PointerButton { event } => Some(event.device()),
+        InputEvent::PointerAxis { event } => Some(event.device()),
+        InputEvent::TouchDown { event } => Some(event.device()),
+        InputEvent::TouchMotion { event } => Some(event.device()),
+        InputEvent::TouchUp { event } => Some(event.device()),
+        InputEvent::TouchCancel { event } => Some(event.device()),
+        InputEvent::TouchFrame { event } => Some(event.device()),
+        InputEvent::TabletToolAxis { event } => Some(event.device()),
+        InputEvent::TabletToolProximity { event } => Some(event.device()),
+        InputEvent::TabletToolTip { event } => Some(event.device()),
+        InputEvent::TabletToolButton { event } => Some(event.device()),
+        InputEvent::GestureSwipeBegin { event } => Some(event.device()),
+        InputEvent::GestureSwipeUpdate { event } => Some(event.device()),
+        InputEvent::GestureSwipeEnd { event } => Some(event.device()),
+        InputEvent::GesturePinchBegin { event } => Some(event.device()),
+        InputEvent::GesturePinchUpdate { event } => Some(event.device()),
+        InputEvent::GesturePinchEnd { event } => Some(event.device()),
+        InputEvent::GestureHoldBegin { event } => Some(event.device()),
+        InputEvent::GestureHoldEnd { event } => Some(event.device()),
+        // adding or removing devices is no user activity
+        _ => None,
+    }
+}
+
+impl State {
+    pub fn notify_activity(&mut self, seat: &Seat<State>) {
+        seat.user_data().get::<SeatActivity>().unwrap().reset();
+        self.common.idle_notifier_state.notify_activity(seat);
+
+        if self.common.idle_state.dpms_off {
+            debug!("Input activity, turning outputs back on");
+            self.common.idle_state.dpms_off = false;
+            self.backend.set_dpms(&self.common.event_loop_handle, true);
+            for output in self.common.shell.outputs().cloned().collect::<Vec<_>>() {
+                self.backend
+                    .schedule_render(&self.common.event_loop_handle, &output, None);
+            }
+        }
+    }
+
+    /// Sends idle notifications and turns outputs off once the configured timeout passed.
+    pub fn refresh_idle(&mut self) {
+        // inhibitors only count, while their surface is actually visible
+        let inhibited = self
+            .common
+            .idle_inhibit_state
+            .inhibiting_surfaces()
+            .any(|surface| {
+                self.common
+                    .shell
+                    .visible_outputs_for_surface(surface)
+                    .next()
+                    .is_some()
+            });
+        // timeouts passed while inhibited must not fire right away, restart them instead
+        if self.common.idle_notifier_state.is_inhibited() && !inhibited {
+            for seat in self.common.seats() {
+                seat.user_data().get::<SeatActivity>().unwrap().reset();
+            }
+        }
+        let notifier = &mut self.common.idle_notifier_state;
+        notifier.set_inhibited(inhibited);
+        let mut next =
+            notifier.refresh(|seat| seat.user_data().get::<SeatActivity>().unwrap().idle_time());
+
+        if let Some(timeout) = self
+            .common
+            .config
+            .static_conf
+            .dpms_timeout
+            .map(|secs| Duration::from_secs(secs as u64))
+            .filter(|_| !inhibited && !self.common.idle_state.dpms_off)
+        {
+            let idle_time = self
+                .common
+                .seats()
+                .map(|seat| seat.user_data().get::<SeatActivity>().unwrap().idle_time())
+                .min()
+                .unwrap_or_default();
+            if idle_time >= timeout {
+                debug!("Idle timeout reached, turning outputs off");
+                self.common.idle_state.dpms_off = true;
+                self.backend.set_dpms(&self.common.event_loop_handle, false);
+            } else {
+                let remaining = timeout - idle_time;
+                next = Some(next.map_or(remaining, |next| next.min(remaining)));
+            }
+        }
+
+        // wake up the loop once the next timeout expires, no input might arrive until then
+        let idle_state = &mut self.common.idle_state;
+        let deadline = next.map(|next| Instant::now() + next);
+        if matches!((&idle_state.timer, deadline), (Some((armed, _)), Some(deadline)) if *armed <= deadline)
+        {
+            return;
+        }
+        if let Some((_, token)) = idle_state.timer.take() {
+            self.common.event_loop_handle.remove(token);
+        }
+        let Some(deadline) = deadline else {
+            return;
+        };
+        match self.common.event_loop_handle.insert_source(
+            Timer::from_deadline(deadline),
+            move |_, _, data| {
+                let idle_state = &mut data.state.common.idle_state;
+                if matches!(idle_state.timer, Some((armed, _)) if armed == deadline) {
+                    idle_state.timer = None;
+                }
+                TimeoutAction::Drop
+            },
+        ) {
+            Ok(token) => idle_state.timer = Some((deadline, token)),
+            Err(err) => error!(?err, "Failed to schedule idle timer."),
+        }
+    }
+}
@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Switching between multiple keyboard layouts.
+//!
+//! Every configured layout is an xkb group of the keymap, so switching layouts only
+//! changes the locked group of the keyboard. The active layout is the effective group,
+//! which also picks up switches done by xkb options (e.g. `grp:alt_shift_toggle`).
+//! Xkb supports at most four groups, so only the first four layouts can be used.
+
+use std::cell::{Cell, RefCell};
+
+use smithay::input::{
+    keyboard::{Keysym, Layout},
+    Seat,
+};
+use xkbcommon::xkb;
+
+use crate::{
+    shell::{focus::target::KeyboardFocusTarget, CosmicSurface},
+    utils::prelude::*,
+};
+
+/// Last focused window of a seat, when remembering layouts per window
+#[derive(Debug, Default)]
+struct LayoutFocus(RefCell<Option<CosmicSurface>>);
+
+/// Layout last used in a window
+struct WindowLayout(Cell<usize>);
+
+/// Keymap of the keyboard of a seat, which can't be accessed while a key is handled
+#[derive(Default)]
+struct SeatKeymap(RefCell<Option<xkb::Keymap>>);
+
+/// Index of the active layout of `seat` into the configured layouts
+pub fn current_layout(seat: &Seat<State>) -> usize {
+    seat.get_keyboard()
+        .map(|keyboard| keyboard.modifier_state().serialized.layout_effective as usize)
+        .unwrap_or(0)
+}
+
+/// Restores the layouts of newly focused windows
+pub fn refresh(state: &mut State) {
+    let xkb = state.common.config.xkb_config();
+    if xkb.layouts().len() <= 1 || !xkb.per_window_layout {
+        return;
+    }
+
+    let seats = state.common.seats().cloned().collect::<Vec<_>>();
+    for seat in seats {
+        let Some(keyboard) = seat.get_keyboard() else {
+            continue;
+        };
+        let window = match keyboard.current_focus() {
+            Some(KeyboardFocusTarget::Element(mapped)) => mapped.active_window(),
+            Some(KeyboardFocusTarget::Fullscreen(window)) => window,
+            // keep the layout for layer shells, popups and the like
+            _ => continue,
+        };
+
+        seat.user_data().insert_if_missing(LayoutFocus::default);
+        let previous = {
+            let mut focus = seat
+                .user_data()
+                .get::<LayoutFocus>()
+                .unwrap()
+                .0
+                .borrow_mut();
+            if focus.as_ref() == Some(&window) {
+                continue;
+            }
+            focus.replace(window.clone())
+        };
+        if let Some(previous) = previous {
+            remember_layout(&previous, current_layout(&seat));
+        }
+        // windows without a remembered layout start out with the first one
+        let restored = window
+            .user_data()
+            .get::<WindowLayout>()
+            .map(|layout| layout.0.get())
+            .unwrap_or(0);
+        state.set_keyboard_layout(&seat, restored);
+    }
+}
+
+/// Remembers the keymap of `seat` for [`base_syms`], the keymap doesn't change at runtime
+pub fn cache_keymap(state: &mut State, seat: &Seat<State>) {
+    seat.user_data().insert_if_missing(SeatKeymap::default);
+    if seat
+        .user_data()
+        .get::<SeatKeymap>()
+        .unwrap()
+        .0
+        .borrow()
+        .is_some()
+    {
+        return;
+    }
+    let Some(keyboard) = seat.get_keyboard() else {
+        return;
+    };
+    let keymap = keyboard.with_xkb_state(state, |context| {
+        // SAFETY: the keymap is only read from
+        unsafe { context.xkb().lock().unwrap().keymap().clone() }
+    });
+    *seat.user_data().get::<SeatKeymap>().unwrap().0.borrow_mut() = Some(keymap);
+}
+
+/// Unmodified syms of the evdev `keycode` in the first configured layout, the first group of
+/// the keymap.
+///
+/// Empty if only a single layout is configured, as the active syms are the same then.
+pub fn base_syms(seat: &Seat<State>, keycode: u32) -> Vec<Keysym> {
+    let Some(cache) = seat.user_data().get::<SeatKeymap>() else {
+        return Vec::new();
+    };
+    match cache.0.borrow().as_ref() {
+        // xkb keycodes are offset by 8 from evdev ones
+        Some(keymap) if keymap.num_layouts() > 1 => {
+            keymap.key_get_syms_by_level(keycode + 8, 0, 0).to_vec()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn remember_layout(window: &CosmicSurface, layout: usize) {
+    window
+        .user_data()
+        .insert_if_missing(|| WindowLayout(Cell::new(layout)));
+    window
+        .user_data()
+        .get::<WindowLayout>()
+        .unwrap()
+        .0
+        .set(layout);
+}
+
+impl State {
+    /// Activates the configured layout at `idx` for `seat`
+    pub fn set_keyboard_layout(&mut self, seat: &Seat<State>, idx: usize) {
+        let xkb = self.common.config.xkb_config();
+        let len = xkb.layouts().len();
+        if len <= 1 {
+            return;
+        }
+        let idx = idx % len;
+        if current_layout(seat) == idx {
+            return;
+        }
+
+        let Some(keyboard) = seat.get_keyboard() else {
+            return;
+        };
+        // locks the group, clients are sent the new modifier state
+        keyboard.with_xkb_state(self, |mut context| context.set_layout(Layout(idx as u32)));
+    }
+
+    pub fn next_keyboard_layout(&mut self, seat: &Seat<State>) {
+        self.set_keyboard_layout(seat, current_layout(seat) + 1);
+    }
+}
@@ -3,45 +3,61 @@
 use crate::{
     config::{Action, Config, KeyModifiers, WorkspaceLayout},
     shell::{
-        focus::{target::PointerFocusTarget, FocusDirection},
+        focus::{
+            target::{KeyboardFocusTarget, PointerFocusTarget},
+            FocusDirection,
+        },
         layout::{
-            floating::SeatMoveGrabState,
-            tiling::{Direction, FocusResult, MoveResult},
+            floating::{SeatMoveGrabState, SnapPreviewIds},
+            tiling::{load_template, save_template, Direction, FocusResult, MoveResult},
         },
-        OverviewMode, Workspace,
+        Expose, OverviewMode, SessionLock, SwitcherScope, WindowSwitcher, Workspace,
     }, // shell::grabs::SeatMoveGrabState
-    state::Common,
+    state::{ClientState, Common},
     utils::prelude::*,
     wayland::{handlers::screencopy::ScreencopySessions, protocols::screencopy::Session},
 };
 use cosmic_protocols::screencopy::v1::server::zcosmic_screencopy_session_v1::InputType;
 use smithay::{
     backend::input::{
-        Axis, AxisSource, Device, DeviceCapability, InputBackend, InputEvent, KeyState,
-        PointerAxisEvent,
+        AbsolutePositionEvent, Axis, AxisSource, ButtonState, Device, DeviceCapability,
+        InputBackend, InputEvent, KeyState, PointerAxisEvent, TouchSlot,
     },
     desktop::{layer_map_for_output, space::SpaceElement, WindowSurfaceType},
     input::{
         keyboard::{keysyms, FilterResult, KeysymHandle, XkbConfig},
-        pointer::{AxisFrame, ButtonEvent, CursorImageStatus, MotionEvent, RelativeMotionEvent},
+        pointer::{
+            AxisFrame, ButtonEvent, CursorImageStatus, GrabStartData as PointerGrabStartData,
+            MotionEvent, RelativeMotionEvent,
+        },
         Seat, SeatState,
     },
     output::Output,
-    reexports::wayland_server::DisplayHandle,
+    reexports::wayland_server::{protocol::wl_surface::WlSurface, DisplayHandle, Resource},
     utils::{Logical, Point, Rectangle, Serial, SERIAL_COUNTER},
     wayland::{
-        keyboard_shortcuts_inhibit::KeyboardShortcutsInhibitorSeat, seat::WaylandFocus,
+        keyboard_shortcuts_inhibit::KeyboardShortcutsInhibitorSeat,
+        seat::WaylandFocus,
         shell::wlr_layer::Layer as WlrLayer,
+        tablet_manager::{TabletDescriptor, TabletSeatTrait},
     },
     xwayland::X11Surface,
 };
 #[cfg(not(feature = "debug"))]
 use tracing::info;
-use tracing::{error, trace, warn};
+use tracing::{debug, error, trace, warn};
 
-use std::{cell::RefCell, collections::HashMap};
+use std::{cell::RefCell, collections::HashMap, sync::atomic::Ordering};
 use xkbcommon::xkb::KEY_XF86Switch_VT_12;
 
+mod gestures;
+pub use self::gestures::GestureState;
+pub mod idle;
+use self::idle::SeatActivity;
+pub mod bindings;
+pub mod constraints;
+pub mod layout;
+
 crate::utils::id_gen!(next_seat_id, SEAT_ID, SEAT_IDS);
 
 #[repr(transparent)]
@@ -51,6 +67,27 @@ pub struct ActiveOutput(pub RefCell<Output>);
 pub struct SupressedKeys(RefCell<Vec<u32>>);
 #[derive(Default)]
 pub struct Devices(RefCell<HashMap<String, Vec<DeviceCapability>>>);
+#[derive(Default)]
+pub struct TouchState(RefCell<TouchStateInner>);
+
+#[derive(Default)]
+struct TouchStateInner {
+    /// Slot currently driving the pointer, because it touched compositor-drawn ui
+    emulated_slot: Option<TouchSlot>,
+    /// Last touch down on a client surface, that may be used to start a grab
+    last_down: Option<TouchDown>,
+}
+
+#[derive(Debug, Clone)]
+struct TouchDown {
+    serial: Serial,
+    slot: TouchSlot,
+    focus: (PointerFocusTarget, Point<i32, Logical>),
+    location: Point<f64, Logical>,
+}
+
+/// Button emulated for touch points driving the pointer
+const BTN_LEFT: u32 = 0x110;
 
 impl Default for SeatId {
     fn default() -> SeatId {
@@ -80,15 +117,48 @@ impl SupressedKeys {
     }
 }
 
+impl TouchState {
+    fn is_emulated(&self, slot: TouchSlot) -> bool {
+        self.0.borrow().emulated_slot == Some(slot)
+    }
+
+    /// Lets the touch point of a touch-down on `surface` with the given serial drive the pointer
+    /// from now on, returning the data necessary to start a pointer grab.
+    pub fn take_grab(
+        &self,
+        surface: &WlSurface,
+        serial: Option<Serial>,
+    ) -> Option<PointerGrabStartData<State>> {
+        let mut inner = self.0.borrow_mut();
+        let down = inner.last_down.as_ref()?;
+        if serial.map(|serial| serial != down.serial).unwrap_or(false)
+            || !down.focus.0.same_client_as(&surface.id())
+        {
+            return None;
+        }
+        let down = inner.last_down.take().unwrap();
+        inner.emulated_slot = Some(down.slot);
+        Some(PointerGrabStartData {
+            focus: Some(down.focus),
+            button: BTN_LEFT,
+            location: down.location,
+        })
+    }
+}
+
 impl Devices {
     fn add_device<D: Device>(&self, device: &D) -> Vec<DeviceCapability> {
         let id = device.id();
         let mut map = self.0.borrow_mut();
-        let caps = [DeviceCapability::Keyboard, DeviceCapability::Pointer]
-            .iter()
-            .cloned()
-            .filter(|c| device.has_capability(*c))
-            .collect::<Vec<_>>();
+        let caps = [
+            DeviceCapability::Keyboard,
+            DeviceCapability::Pointer,
+            DeviceCapability::Touch,
+        ]
+        .iter()
+        .cloned()
+        .filter(|c| device.has_capability(*c))
+        .collect::<Vec<_>>();
         let new_caps = caps
             .iter()
             .cloned()
@@ -125,7 +195,11 @@ pub fn add_seat(
     userdata.insert_if_missing(SeatId::default);
     userdata.insert_if_missing(Devices::default);
     userdata.insert_if_missing(SupressedKeys::default);
+    userdata.insert_if_missing(TouchState::default);
+    userdata.insert_if_missing(GestureState::default);
+    userdata.insert_if_missing(SeatActivity::default);
     userdata.insert_if_missing(SeatMoveGrabState::default);
+    userdata.insert_if_missing(SnapPreviewIds::default);
     userdata.insert_if_missing(|| ActiveOutput(RefCell::new(output.clone())));
     userdata.insert_if_missing(|| RefCell::new(CursorImageStatus::Default));
 
@@ -138,7 +212,8 @@ pub fn add_seat(
     //
     // So instead of doing the right thing (and initialize these capabilities as matching
     // devices appear), we have to surrender to reality and just always expose a keyboard and pointer.
-    let conf = config.xkb_config();
+    // layouts switched at runtime are groups of the keymap, see `layout`
+    let conf = config.xkb_config().keymap_config();
     if let Err(err) = seat.add_keyboard((&conf).into(), 200, 25) {
         warn!(
             ?err,
@@ -149,6 +224,16 @@ pub fn add_seat(
     }
     seat.add_pointer();
 
+    // tablet tools may set their own cursor image, which follows the tool like the pointer does
+    let cursor_seat = seat.clone();
+    seat.tablet_seat().on_cursor_surface(move |_tool, image| {
+        *cursor_seat
+            .user_data()
+            .get::<RefCell<CursorImageStatus>>()
+            .unwrap()
+            .borrow_mut() = image;
+    });
+
     seat
 }
 
@@ -156,33 +241,67 @@ impl State {
     pub fn process_input_event<B: InputBackend>(&mut self, event: InputEvent<B>) {
         use smithay::backend::input::Event;
 
+        if let Some(device) = idle::event_device(&event) {
+            if let Some(seat) = self
+                .common
+                .seats()
+                .find(|seat| {
+                    seat.user_data()
+                        .get::<Devices>()
+                        .unwrap()
+                        .has_device(&device)
+                })
+                .cloned()
+            {
+                self.notify_activity(&seat);
+            }
+        }
+
         match event {
             InputEvent::DeviceAdded { device } => {
-                let seat = &mut self.common.last_active_seat();
+                let mut seat = self.common.last_active_seat().clone();
                 let userdata = seat.user_data();
                 let devices = userdata.get::<Devices>().unwrap();
                 for cap in devices.add_device(&device) {
                     match cap {
-                        // TODO: Handle touch, tablet
+                        DeviceCapability::Touch => {
+                            seat.add_touch();
+                        }
                         _ => {}
                     }
                 }
+                if device.has_capability(DeviceCapability::TabletTool) {
+                    seat.tablet_seat().add_tablet::<State>(
+                        &self.common.display_handle,
+                        &TabletDescriptor::from(&device),
+                    );
+                }
                 #[cfg(feature = "debug")]
                 {
                     self.common.egui.state.handle_device_added(&device);
                 }
             }
             InputEvent::DeviceRemoved { device } => {
-                for seat in &mut self.common.seats() {
+                for mut seat in self.common.seats().cloned().collect::<Vec<_>>().into_iter() {
                     let userdata = seat.user_data();
                     let devices = userdata.get::<Devices>().unwrap();
                     if devices.has_device(&device) {
                         for cap in devices.remove_device(&device) {
                             match cap {
-                                // TODO: Handle touch, tablet
+                                DeviceCapability::Touch => {
+                                    seat.remove_touch();
+                                }
                                 _ => {}
                             }
                         }
+                        if device.has_capability(DeviceCapability::TabletTool) {
+                            let tablet_seat = seat.tablet_seat();
+                            tablet_seat.remove_tablet(&TabletDescriptor::from(&device));
+                            // tools are not bound to a tablet, drop them with the last one
+                            if tablet_seat.count_tablets() == 0 {
+                                tablet_seat.clear_tools();
+                            }
+                        }
                         break;
                     }
                 }
@@ -196,19 +315,7 @@ impl State {
 
                 let device = event.device();
                 for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
-                    let current_output = seat.active_output();
-                    let workspace = self.common.shell.active_space_mut(&current_output);
-                    let shortcuts_inhibited = workspace
-                        .focus_stack
-                        .get(seat)
-                        .last()
-                        .and_then(|window| {
-                            window.wl_surface().and_then(|surface| {
-                                seat.keyboard_shortcuts_inhibitor_for_surface(&surface)
-                            })
-                        })
-                        .map(|inhibitor| inhibitor.is_active())
-                        .unwrap_or(false);
+                    let shortcuts_inhibited = self.shortcuts_inhibited(seat);
 
                     let userdata = seat.user_data();
                     let devices = userdata.get::<Devices>().unwrap();
@@ -219,6 +326,10 @@ impl State {
 
                         let serial = SERIAL_COUNTER.next_serial();
                         let time = Event::time_msec(&event);
+                        let mut switcher_selected = None;
+                        let mut expose_key = None;
+                        let mut release_action = None;
+                        layout::cache_keymap(self, seat);
                         if let Some((action, mods)) = seat
                             .get_keyboard()
                             .unwrap()
@@ -241,12 +352,53 @@ impl State {
                                         }
                                     }
 
+                                    let switcher_released = data
+                                        .common
+                                        .shell
+                                        .switcher
+                                        .as_ref()
+                                        .filter(|switcher| &switcher.seat == seat)
+                                        .map(|switcher| switcher.modifiers_released(modifiers));
+                                    match switcher_released {
+                                        Some(true) => {
+                                            switcher_selected = data
+                                                .common
+                                                .shell
+                                                .switcher
+                                                .take()
+                                                .and_then(|switcher| switcher.selected());
+                                        }
+                                        Some(false)
+                                            if state == KeyState::Pressed
+                                                && handle.modified_sym() == keysyms::KEY_Escape =>
+                                        {
+                                            data.common.shell.switcher = None;
+                                            userdata.get::<SupressedKeys>().unwrap().add(&handle);
+                                            return FilterResult::Intercept(None);
+                                        }
+                                        _ => {}
+                                    }
+
                                     if state == KeyState::Released
                                         && userdata.get::<SupressedKeys>().unwrap().filter(&handle)
                                     {
                                         return FilterResult::Intercept(None);
                                     }
 
+                                    // releases are forwarded, as their presses already were
+                                    if state == KeyState::Released
+                                        && !shortcuts_inhibited
+                                        && data.common.shell.session_lock.is_none()
+                                    {
+                                        release_action = bindings::match_key_release(
+                                            &data.common.config,
+                                            seat,
+                                            modifiers,
+                                            keycode,
+                                            &handle.raw_syms(),
+                                        );
+                                    }
+
                                     #[cfg(feature = "debug")]
                                     {
                                         if data.common.seats().position(|x| x == seat).unwrap() == 0
@@ -282,24 +434,37 @@ impl State {
                                         return FilterResult::Intercept(None);
                                     }
 
-                                    // here we can handle global shortcuts and the like
-                                    if !shortcuts_inhibited {
-                                        for (binding, action) in
-                                            data.common.config.static_conf.key_bindings.iter()
+                                    if state == KeyState::Pressed
+                                        && data.common.shell.expose.is_some()
+                                        && !(modifiers.ctrl || modifiers.alt || modifiers.logo)
+                                        && Expose::handles_key(handle.modified_sym())
+                                    {
+                                        expose_key = Some(handle.modified_sym());
+                                        userdata.get::<SupressedKeys>().unwrap().add(&handle);
+                                        return FilterResult::Intercept(None);
+                                    }
+
+                                    // here we can handle global shortcuts and the like,
+                                    // unless the session is locked
+                                    if state == KeyState::Pressed
+                                        && !shortcuts_inhibited
+                                        && data.common.shell.session_lock.is_none()
+                                    {
+                                        // bindings fall back to the first layout, so they keep
+                                        // working while e.g. a cyrillic layout is active
+                                        if let FilterResult::Intercept(action) =
+                                            bindings::match_key_press(
+                                                &data.common.config,
+                                                seat,
+                                                modifiers,
+                                                keycode,
+                                                &handle.raw_syms(),
+                                                time,
+                                            )
                                         {
-                                            if state == KeyState::Pressed
-                                                && binding.modifiers == *modifiers
-                                                && handle.raw_syms().contains(&binding.key)
-                                            {
-                                                userdata
-                                                    .get::<SupressedKeys>()
-                                                    .unwrap()
-                                                    .add(&handle);
-                                                return FilterResult::Intercept(Some((
-                                                    action.clone(),
-                                                    binding.modifiers.clone(),
-                                                )));
-                                            }
+                                            // the release of the key is never forwarded either
+                                            userdata.get::<SupressedKeys>().unwrap().add(&handle);
+                                            return FilterResult::Intercept(action);
                                         }
                                     }
 
@@ -310,6 +475,15 @@ impl State {
                         {
                             self.handle_action(action, seat, serial, time, mods, None)
                         }
+                        if let Some(window) = switcher_selected {
+                            Shell::activate_window(self, &window, seat);
+                        }
+                        if let Some(keysym) = expose_key {
+                            Expose::handle_key(self, seat, keysym);
+                        }
+                        if let Some((action, mods)) = release_action {
+                            self.handle_action(action, seat, serial, time, mods, None);
+                        }
                         break;
                     }
                 }
@@ -324,8 +498,26 @@ impl State {
                     if devices.has_device(&device) {
                         let current_output = seat.active_output();
 
-                        let mut position = seat.get_pointer().unwrap().current_location();
-                        position += event.delta();
+                        let current = seat.get_pointer().unwrap().current_location();
+                        let constraint = self.pointer_constraint(seat, current);
+                        if let Some(constraint) = constraint.as_ref().filter(|c| c.locked) {
+                            // locked pointers only report relative motion
+                            seat.get_pointer().unwrap().relative_motion(
+                                self,
+                                Some(constraint.focus.clone()),
+                                &RelativeMotionEvent {
+                                    delta: event.delta(),
+                                    delta_unaccel: event.delta_unaccel(),
+                                    utime: event.time(),
+                                },
+                            );
+                            break;
+                        }
+
+                        let mut position = current + event.delta();
+                        if let Some(constraint) = constraint.as_ref() {
+                            position = constraint.confine(current, position);
+                        }
 
                         let output = self
                             .common
@@ -363,6 +555,7 @@ impl State {
                             &output,
                             output_geometry,
                             &self.common.shell.override_redirect_windows,
+                            self.common.shell.session_lock.as_ref(),
                             &workspace,
                         );
 
@@ -421,11 +614,20 @@ impl State {
                     if devices.has_device(&device) {
                         let output = seat.active_output();
                         let geometry = output.geometry();
-                        let position = geometry.loc.to_f64()
+                        let mut position = geometry.loc.to_f64()
                             + smithay::backend::input::AbsolutePositionEvent::position_transformed(
                                 &event,
                                 geometry.size,
                             );
+
+                        let current = seat.get_pointer().unwrap().current_location();
+                        if let Some(constraint) = self.pointer_constraint(seat, current) {
+                            // absolute devices have no relative motion to report to locks
+                            if constraint.locked {
+                                break;
+                            }
+                            position = constraint.confine(current, position);
+                        }
                         let relative_pos = self.common.shell.map_global_to_space(position, &output);
                         let workspace = self.common.shell.active_space(&output);
                         let serial = SERIAL_COUNTER.next_serial();
@@ -435,6 +637,7 @@ impl State {
                             &output,
                             geometry,
                             &self.common.shell.override_redirect_windows,
+                            self.common.shell.session_lock.as_ref(),
                             &workspace,
                         );
 
@@ -477,7 +680,7 @@ impl State {
                 }
             }
             InputEvent::PointerButton { event, .. } => {
-                use smithay::backend::input::{ButtonState, PointerButtonEvent};
+                use smithay::backend::input::PointerButtonEvent;
 
                 let device = event.device();
                 for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
@@ -511,71 +714,32 @@ impl State {
                             {
                                 let output = seat.active_output();
                                 let pos = seat.get_pointer().unwrap().current_location();
-                                let relative_pos =
-                                    self.common.shell.map_global_to_space(pos, &output);
-                                let workspace = self.common.shell.active_space_mut(&output);
-                                let layers = layer_map_for_output(&output);
-                                let mut under = None;
-
-                                if let Some(window) = workspace.get_fullscreen(&output) {
-                                    if let Some(layer) =
-                                        layers.layer_under(WlrLayer::Overlay, relative_pos)
-                                    {
-                                        let layer_loc = layers.layer_geometry(layer).unwrap().loc;
-                                        if layer.can_receive_keyboard_focus()
-                                            && layer
-                                                .surface_under(
-                                                    relative_pos - layer_loc.to_f64(),
-                                                    WindowSurfaceType::ALL,
-                                                )
-                                                .is_some()
-                                        {
-                                            under = Some(layer.clone().into());
-                                        }
-                                    } else {
-                                        under = Some(window.clone().into());
-                                    }
-                                } else {
-                                    if let Some(layer) = layers
-                                        .layer_under(WlrLayer::Overlay, relative_pos)
-                                        .or_else(|| layers.layer_under(WlrLayer::Top, relative_pos))
-                                    {
-                                        let layer_loc = layers.layer_geometry(layer).unwrap().loc;
-                                        if layer.can_receive_keyboard_focus()
-                                            && layer
-                                                .surface_under(
-                                                    relative_pos - layer_loc.to_f64(),
-                                                    WindowSurfaceType::ALL,
-                                                )
-                                                .is_some()
-                                        {
-                                            under = Some(layer.clone().into());
-                                        }
-                                    } else if let Some((window, _)) =
-                                        workspace.element_under(relative_pos)
-                                    {
-                                        under = Some(window.clone().into());
-                                    } else if let Some(layer) = layers
-                                        .layer_under(WlrLayer::Bottom, pos)
-                                        .or_else(|| layers.layer_under(WlrLayer::Background, pos))
-                                    {
-                                        let layer_loc = layers.layer_geometry(layer).unwrap().loc;
-                                        if layer.can_receive_keyboard_focus()
-                                            && layer
-                                                .surface_under(
-                                                    relative_pos - layer_loc.to_f64(),
-                                                    WindowSurfaceType::ALL,
-                                                )
-                                                .is_some()
-                                        {
-                                            under = Some(layer.clone().into());
-                                        }
-                                    };
-                                }
-
+                                let under = self.keyboard_target_under(&output, pos);
                                 Common::set_focus(self, under.as_ref(), seat, Some(serial));
                             }
                         };
+
+                        // bindings act on the window under the pointer, that just got focused
+                        if !seat.get_pointer().unwrap().is_grabbed()
+                            && !self.shortcuts_inhibited(seat)
+                            && self.common.shell.session_lock.is_none()
+                        {
+                            let modifiers = seat.get_keyboard().unwrap().modifier_state();
+                            if let FilterResult::Intercept(action) = bindings::match_button(
+                                &self.common.config,
+                                seat,
+                                &modifiers,
+                                button,
+                                event.state(),
+                            ) {
+                                if let Some((action, mods)) = action {
+                                    let time = event.time_msec();
+                                    self.handle_action(action, seat, serial, time, mods, None);
+                                }
+                                break;
+                            }
+                        }
+
                         seat.get_pointer().unwrap().button(
                             self,
                             &ButtonEvent {
@@ -624,6 +788,27 @@ impl State {
                         let horizontal_amount_discrete = event.amount_discrete(Axis::Horizontal);
                         let vertical_amount_discrete = event.amount_discrete(Axis::Vertical);
 
+                        if !seat.get_pointer().unwrap().is_grabbed()
+                            && !self.shortcuts_inhibited(seat)
+                            && self.common.shell.session_lock.is_none()
+                        {
+                            let modifiers = seat.get_keyboard().unwrap().modifier_state();
+                            if let FilterResult::Intercept(action) = bindings::match_scroll(
+                                &self.common.config,
+                                seat,
+                                &modifiers,
+                                event.source(),
+                                (horizontal_amount, vertical_amount),
+                            ) {
+                                if let Some((action, mods)) = action {
+                                    let serial = SERIAL_COUNTER.next_serial();
+                                    let time = event.time_msec();
+                                    self.handle_action(action, seat, serial, time, mods, None);
+                                }
+                                break;
+                            }
+                        }
+
                         {
                             let mut frame =
                                 AxisFrame::new(event.time_msec()).source(event.source());
@@ -649,11 +834,647 @@ impl State {
                     }
                 }
             }
-            _ => { /* TODO e.g. tablet or touch events */ }
+            InputEvent::TouchDown { event, .. } => {
+                use smithay::backend::input::TouchEvent;
+
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        let (output, region) = self.touch_region(seat, &device);
+                        let position = transform_absolute_position::<B, _>(&output, region, &event);
+                        let relative_pos = self.common.shell.map_global_to_space(position, &output);
+                        let workspace = self.common.shell.active_space(&output);
+                        let serial = SERIAL_COUNTER.next_serial();
+                        let under = State::surface_under(
+                            position,
+                            relative_pos,
+                            &output,
+                            output.geometry(),
+                            &self.common.shell.override_redirect_windows,
+                            self.common.shell.session_lock.as_ref(),
+                            &workspace,
+                        );
+
+                        if !seat.get_pointer().unwrap().is_grabbed()
+                            && !seat.get_keyboard().map(|k| k.is_grabbed()).unwrap_or(false)
+                        {
+                            let target = self.keyboard_target_under(&output, position);
+                            Common::set_focus(self, target.as_ref(), seat, Some(serial));
+                        }
+
+                        let touch_state = userdata.get::<TouchState>().unwrap();
+                        let client_surface = under.as_ref().and_then(|(target, loc)| {
+                            target
+                                .wl_surface_under(*loc, position)
+                                .map(|surface| (target.clone(), *loc, surface))
+                        });
+                        match client_surface {
+                            Some((target, loc, (surface, surface_loc))) => {
+                                if let Some(touch) = seat.get_touch() {
+                                    touch.down(
+                                        serial,
+                                        event.time_msec(),
+                                        &surface,
+                                        surface_loc,
+                                        event.slot(),
+                                        position,
+                                    );
+                                }
+                                touch_state.0.borrow_mut().last_down = Some(TouchDown {
+                                    serial,
+                                    slot: event.slot(),
+                                    focus: (target, loc),
+                                    location: position,
+                                });
+                            }
+                            None => {
+                                // compositor-drawn ui (like headers or tabs) is only pointer aware,
+                                // so let the first touch point drive the pointer instead.
+                                if touch_state.0.borrow().emulated_slot.is_some() {
+                                    break;
+                                }
+                                touch_state.0.borrow_mut().emulated_slot = Some(event.slot());
+                                let ptr = seat.get_pointer().unwrap();
+                                ptr.motion(
+                                    self,
+                                    under,
+                                    &MotionEvent {
+                                        location: position,
+                                        serial,
+                                        time: event.time_msec(),
+                                    },
+                                );
+                                ptr.button(
+                                    self,
+                                    &ButtonEvent {
+                                        button: BTN_LEFT,
+                                        state: ButtonState::Pressed,
+                                        serial,
+                                        time: event.time_msec(),
+                                    },
+                                );
+                            }
+                        }
+                        break;
+                    }
+                }
+            }
+            InputEvent::TouchMotion { event, .. } => {
+                use smithay::backend::input::TouchEvent;
+
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        let (output, region) = self.touch_region(seat, &device);
+                        let position = transform_absolute_position::<B, _>(&output, region, &event);
+                        let touch_state = userdata.get::<TouchState>().unwrap();
+                        if touch_state.is_emulated(event.slot()) {
+                            let relative_pos =
+                                self.common.shell.map_global_to_space(position, &output);
+                            let workspace = self.common.shell.active_space(&output);
+                            let under = State::surface_under(
+                                position,
+                                relative_pos,
+                                &output,
+                                output.geometry(),
+                                &self.common.shell.override_redirect_windows,
+                                self.common.shell.session_lock.as_ref(),
+                                &workspace,
+                            );
+                            seat.get_pointer().unwrap().motion(
+                                self,
+                                under,
+                                &MotionEvent {
+                                    location: position,
+                                    serial: SERIAL_COUNTER.next_serial(),
+                                    time: event.time_msec(),
+                                },
+                            );
+                        } else if let Some(touch) = seat.get_touch() {
+                            touch.motion(event.time_msec(), event.slot(), position);
+                        }
+                        break;
+                    }
+                }
+            }
+            InputEvent::TouchUp { event, .. } => {
+                use smithay::backend::input::TouchEvent;
+
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        let serial = SERIAL_COUNTER.next_serial();
+                        let touch_state = userdata.get::<TouchState>().unwrap();
+                        {
+                            let mut inner = touch_state.0.borrow_mut();
+                            if inner
+                                .last_down
+                                .as_ref()
+                                .map(|down| down.slot == event.slot())
+                                .unwrap_or(false)
+                            {
+                                inner.last_down = None;
+                            }
+                        }
+                        if touch_state.is_emulated(event.slot()) {
+                            touch_state.0.borrow_mut().emulated_slot = None;
+                            seat.get_pointer().unwrap().button(
+                                self,
+                                &ButtonEvent {
+                                    button: BTN_LEFT,
+                                    state: ButtonState::Released,
+                                    serial,
+                                    time: event.time_msec(),
+                                },
+                            );
+                        }
+                        if let Some(touch) = seat.get_touch() {
+                            touch.up(serial, event.time_msec(), event.slot());
+                        }
+                        break;
+                    }
+                }
+            }
+            InputEvent::TouchCancel { event, .. } => {
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        let touch_state = userdata.get::<TouchState>().unwrap();
+                        touch_state.0.borrow_mut().last_down = None;
+                        if touch_state.0.borrow_mut().emulated_slot.take().is_some() {
+                            seat.get_pointer().unwrap().button(
+                                self,
+                                &ButtonEvent {
+                                    button: BTN_LEFT,
+                                    state: ButtonState::Released,
+                                    serial: SERIAL_COUNTER.next_serial(),
+                                    time: event.time_msec(),
+                                },
+                            );
+                        }
+                        if let Some(touch) = seat.get_touch() {
+                            touch.cancel();
+                        }
+                        break;
+                    }
+                }
+            }
+            InputEvent::TouchFrame { event, .. } => {
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        if let Some(touch) = seat.get_touch() {
+                            touch.frame();
+                        }
+                        break;
+                    }
+                }
+            }
+            InputEvent::TabletToolAxis { event, .. } => {
+                use smithay::backend::input::TabletToolEvent;
+
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        let (output, region) = self.mapped_region(&device, || seat.active_output());
+                        let position = transform_absolute_position::<B, _>(&output, region, &event);
+                        let under = self.tablet_motion(seat, &output, position, event.time_msec());
+
+                        let tablet_seat = seat.tablet_seat();
+                        let tablet = tablet_seat.get_tablet(&TabletDescriptor::from(&device));
+                        let tool = tablet_seat.get_tool(&event.tool());
+                        if let (Some(tablet), Some(tool)) = (tablet, tool) {
+                            if event.pressure_has_changed() {
+                                tool.pressure(event.pressure());
+                            }
+                            if event.distance_has_changed() {
+                                tool.distance(event.distance());
+                            }
+                            if event.tilt_has_changed() {
+                                tool.tilt(event.tilt());
+                            }
+                            if event.slider_has_changed() {
+                                tool.slider_position(event.slider_position());
+                            }
+                            if event.rotation_has_changed() {
+                                tool.rotation(event.rotation());
+                            }
+                            if event.wheel_has_changed() {
+                                tool.wheel(event.wheel_delta(), event.wheel_delta_discrete());
+                            }
+                            tool.motion(
+                                position,
+                                under,
+                                &tablet,
+                                SERIAL_COUNTER.next_serial(),
+                                event.time_msec(),
+                            );
+                        }
+                        break;
+                    }
+                }
+            }
+            InputEvent::TabletToolProximity { event, .. } => {
+                use smithay::backend::input::{
+                    ProximityState, TabletToolEvent, TabletToolProximityEvent,
+                };
+
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        let (output, region) = self.mapped_region(&device, || seat.active_output());
+                        let position = transform_absolute_position::<B, _>(&output, region, &event);
+                        let under = self.tablet_motion(seat, &output, position, event.time_msec());
+
+                        let tablet_seat = seat.tablet_seat();
+                        let tool = event.tool();
+                        tablet_seat.add_tool::<State>(&self.common.display_handle, &tool);
+                        let tablet = tablet_seat.get_tablet(&TabletDescriptor::from(&device));
+                        let tool = tablet_seat.get_tool(&tool);
+                        if let (Some(tablet), Some(tool)) = (tablet, tool) {
+                            match event.state() {
+                                ProximityState::In => {
+                                    if let Some(under) = under {
+                                        tool.proximity_in(
+                                            position,
+                                            under,
+                                            &tablet,
+                                            SERIAL_COUNTER.next_serial(),
+                                            event.time_msec(),
+                                        );
+                                    }
+                                }
+                                ProximityState::Out => tool.proximity_out(event.time_msec()),
+                            }
+                        }
+                        break;
+                    }
+                }
+            }
+            InputEvent::TabletToolTip { event, .. } => {
+                use smithay::backend::input::{
+                    TabletToolEvent, TabletToolTipEvent, TabletToolTipState,
+                };
+
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        if let Some(tool) = seat.tablet_seat().get_tool(&event.tool()) {
+                            match event.tip_state() {
+                                TabletToolTipState::Down => {
+                                    let serial = SERIAL_COUNTER.next_serial();
+                                    tool.tip_down(serial, event.time_msec());
+
+                                    if !seat.get_pointer().unwrap().is_grabbed()
+                                        && !seat
+                                            .get_keyboard()
+                                            .map(|k| k.is_grabbed())
+                                            .unwrap_or(false)
+                                    {
+                                        let output = seat.active_output();
+                                        let pos = seat.get_pointer().unwrap().current_location();
+                                        let under = self.keyboard_target_under(&output, pos);
+                                        Common::set_focus(self, under.as_ref(), seat, Some(serial));
+                                    }
+                                }
+                                TabletToolTipState::Up => tool.tip_up(event.time_msec()),
+                            }
+                        }
+                        break;
+                    }
+                }
+            }
+            InputEvent::TabletToolButton { event, .. } => {
+                use smithay::backend::input::{TabletToolButtonEvent, TabletToolEvent};
+
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        if let Some(tool) = seat.tablet_seat().get_tool(&event.tool()) {
+                            tool.button(
+                                event.button(),
+                                event.button_state(),
+                                SERIAL_COUNTER.next_serial(),
+                                event.time_msec(),
+                            );
+                        }
+                        break;
+                    }
+                }
+            }
+            InputEvent::GestureSwipeBegin { event, .. } => {
+                use smithay::backend::input::GestureBeginEvent;
+
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        self.gesture_swipe_begin(seat, event.fingers(), event.time_msec());
+                        break;
+                    }
+                }
+            }
+            InputEvent::GestureSwipeUpdate { event, .. } => {
+                use smithay::backend::input::GestureSwipeUpdateEvent;
+
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        self.gesture_swipe_update(seat, event.delta(), event.time_msec());
+                        break;
+                    }
+                }
+            }
+            InputEvent::GestureSwipeEnd { event, .. } => {
+                use smithay::backend::input::GestureEndEvent;
+
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        self.gesture_swipe_end(seat, event.cancelled(), event.time_msec());
+                        break;
+                    }
+                }
+            }
+            InputEvent::GesturePinchBegin { event, .. } => {
+                use smithay::backend::input::GestureBeginEvent;
+
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        self.gesture_pinch_begin(seat, event.fingers(), event.time_msec());
+                        break;
+                    }
+                }
+            }
+            InputEvent::GesturePinchUpdate { event, .. } => {
+                use smithay::backend::input::GesturePinchUpdateEvent;
+
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        self.gesture_pinch_update(
+                            seat,
+                            event.delta(),
+                            event.scale(),
+                            event.rotation(),
+                            event.time_msec(),
+                        );
+                        break;
+                    }
+                }
+            }
+            InputEvent::GesturePinchEnd { event, .. } => {
+                use smithay::backend::input::GestureEndEvent;
+
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        self.gesture_pinch_end(seat, event.cancelled(), event.time_msec());
+                        break;
+                    }
+                }
+            }
+            InputEvent::GestureHoldBegin { event, .. } => {
+                use smithay::backend::input::GestureBeginEvent;
+
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        self.gesture_hold_begin(seat, event.fingers(), event.time_msec());
+                        break;
+                    }
+                }
+            }
+            InputEvent::GestureHoldEnd { event, .. } => {
+                use smithay::backend::input::GestureEndEvent;
+
+                let device = event.device();
+                for seat in self.common.seats().cloned().collect::<Vec<_>>().iter() {
+                    let userdata = seat.user_data();
+                    let devices = userdata.get::<Devices>().unwrap();
+                    if devices.has_device(&device) {
+                        self.gesture_hold_end(seat, event.cancelled(), event.time_msec());
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Moves the pointer along with a tablet tool, so the cursor follows the pen,
+    /// and returns the client surface under the tool.
+    ///
+    /// Only clients not supporting tablets get pointer events for the tool.
+    fn tablet_motion(
+        &mut self,
+        seat: &Seat<State>,
+        output: &Output,
+        position: Point<f64, Logical>,
+        time: u32,
+    ) -> Option<(WlSurface, Point<i32, Logical>)> {
+        if seat.active_output() != *output {
+            seat.set_active_output(output);
+        }
+        let relative_pos = self.common.shell.map_global_to_space(position, output);
+        let workspace = self.common.shell.active_space(output);
+        let under = State::surface_under(
+            position,
+            relative_pos,
+            output,
+            output.geometry(),
+            &self.common.shell.override_redirect_windows,
+            self.common.shell.session_lock.as_ref(),
+            &workspace,
+        );
+        let surface = under
+            .as_ref()
+            .and_then(|(target, loc)| target.wl_surface_under(*loc, position));
+        let tablet_client = surface
+            .as_ref()
+            .and_then(|(surface, _)| self.common.display_handle.get_client(surface.id()).ok())
+            .and_then(|client| {
+                client
+                    .get_data::<ClientState>()
+                    .map(|state| state.tablet_bound.load(Ordering::SeqCst))
+            })
+            .unwrap_or(false);
+
+        seat.get_pointer().unwrap().motion(
+            self,
+            under.filter(|_| !tablet_client),
+            &MotionEvent {
+                location: position,
+                serial: SERIAL_COUNTER.next_serial(),
+                time,
+            },
+        );
+
+        surface
+    }
+
+    /// Output and area touchscreens are mapped to.
+    ///
+    /// Without a mapping in the inputs config or by udev, touchscreens are assumed
+    /// to be built into the laptop panel, so prefer internal outputs.
+    fn touch_region(
+        &self,
+        seat: &Seat<State>,
+        device: &impl Device,
+    ) -> (Output, Rectangle<i32, Logical>) {
+        self.mapped_region(device, || {
+            self.common
+                .shell
+                .outputs()
+                .find(|output| is_internal_output(output))
+                .cloned()
+                .unwrap_or_else(|| seat.active_output())
+        })
+    }
+
+    /// Output and area of it an absolute input device is mapped to
+    fn mapped_region(
+        &self,
+        device: &impl Device,
+        default_output: impl FnOnce() -> Output,
+    ) -> (Output, Rectangle<i32, Logical>) {
+        let (output_name, area) = self.common.config.device_mapping(&device.name());
+        let output = output_name
+            .and_then(|name| self.common.shell.outputs().find(|o| o.name() == name))
+            .cloned()
+            .unwrap_or_else(default_output);
+        let geometry = output.geometry();
+        let region = match area {
+            Some([x, y, width, height]) => Rectangle::from_loc_and_size(
+                (
+                    geometry.loc.x + (x * geometry.size.w as f32).round() as i32,
+                    geometry.loc.y + (y * geometry.size.h as f32).round() as i32,
+                ),
+                (
+                    (width * geometry.size.w as f32).round() as i32,
+                    (height * geometry.size.h as f32).round() as i32,
+                ),
+            ),
+            None => geometry,
+        };
+        (output, region)
+    }
+
+    /// Whether the focused window of `seat` inhibits the compositors shortcuts
+    fn shortcuts_inhibited(&self, seat: &Seat<State>) -> bool {
+        let output = seat.active_output();
+        self.common
+            .shell
+            .active_space(&output)
+            .focus_stack
+            .get(seat)
+            .last()
+            .and_then(|window| {
+                window
+                    .wl_surface()
+                    .and_then(|surface| seat.keyboard_shortcuts_inhibitor_for_surface(&surface))
+            })
+            .map(|inhibitor| inhibitor.is_active())
+            .unwrap_or(false)
+    }
+
+    /// Returns the keyboard focus target for a click or touch at the given global position
+    fn keyboard_target_under(
+        &self,
+        output: &Output,
+        pos: Point<f64, Logical>,
+    ) -> Option<KeyboardFocusTarget> {
+        if let Some(session_lock) = self.common.shell.session_lock.as_ref() {
+            return session_lock
+                .surfaces
+                .get(output)
+                .map(|surface| KeyboardFocusTarget::LockSurface(surface.wl_surface().clone()));
+        }
+
+        let relative_pos = self.common.shell.map_global_to_space(pos, output);
+        let workspace = self.common.shell.active_space(output);
+        let layers = layer_map_for_output(output);
+        let mut under = None;
+
+        if let Some(window) = workspace.get_fullscreen(output) {
+            if let Some(layer) = layers.layer_under(WlrLayer::Overlay, relative_pos) {
+                let layer_loc = layers.layer_geometry(layer).unwrap().loc;
+                if layer.can_receive_keyboard_focus()
+                    && layer
+                        .surface_under(relative_pos - layer_loc.to_f64(), WindowSurfaceType::ALL)
+                        .is_some()
+                {
+                    under = Some(layer.clone().into());
+                }
+            } else {
+                under = Some(window.clone().into());
+            }
+        } else {
+            if let Some(layer) = layers
+                .layer_under(WlrLayer::Overlay, relative_pos)
+                .or_else(|| layers.layer_under(WlrLayer::Top, relative_pos))
+            {
+                let layer_loc = layers.layer_geometry(layer).unwrap().loc;
+                if layer.can_receive_keyboard_focus()
+                    && layer
+                        .surface_under(relative_pos - layer_loc.to_f64(), WindowSurfaceType::ALL)
+                        .is_some()
+                {
+                    under = Some(layer.clone().into());
+                }
+            } else if let Some((window, _)) = workspace.element_under(relative_pos) {
+                under = Some(window.clone().into());
+            } else if let Some(layer) = layers
+                .layer_under(WlrLayer::Bottom, pos)
+                .or_else(|| layers.layer_under(WlrLayer::Background, pos))
+            {
+                let layer_loc = layers.layer_geometry(layer).unwrap().loc;
+                if layer.can_receive_keyboard_focus()
+                    && layer
+                        .surface_under(relative_pos - layer_loc.to_f64(), WindowSurfaceType::ALL)
+                        .is_some()
+                {
+                    under = Some(layer.clone().into());
+                }
+            };
         }
+
+        under
     }
 
-    fn handle_action(
+    pub fn handle_action(
         &mut self,
         action: Action,
         seat: &Seat<State>,
@@ -662,6 +1483,11 @@ impl State {
         mods: KeyModifiers,
         direction: Option<Direction>,
     ) {
+        if self.common.shell.session_lock.is_some() {
+            debug!(?action, "Ignoring action, session is locked");
+            return;
+        }
+
         match action {
             Action::Terminate => {
                 self.common.should_stop = true;
@@ -697,10 +1523,48 @@ impl State {
                     0 => 9,
                     x => x - 1,
                 };
-                let _ = self
+                // declared workspaces are addressed by their position in the config
+                let declared = self
                     .common
-                    .shell
-                    .activate(&current_output, workspace as usize);
+                    .config
+                    .static_conf
+                    .workspaces
+                    .get(workspace as usize)
+                    .and_then(|definition| {
+                        self.common
+                            .shell
+                            .declared_workspace(&definition.name, &current_output)
+                    })
+                    .map(|(output, idx)| (output.cloned(), idx));
+                match declared {
+                    Some((Some(output), idx)) if output != current_output => {
+                        let _ = self.common.shell.activate(&output, idx);
+                        seat.set_active_output(&output);
+                        if let Some(ptr) = seat.get_pointer() {
+                            let output_geo = output.geometry();
+                            let location = output_geo.loc
+                                + Point::from((output_geo.size.w / 2, output_geo.size.h / 2));
+                            ptr.motion(
+                                self,
+                                None,
+                                &MotionEvent {
+                                    location: location.to_f64(),
+                                    serial,
+                                    time,
+                                },
+                            );
+                        }
+                    }
+                    Some((_, idx)) => {
+                        let _ = self.common.shell.activate(&current_output, idx);
+                    }
+                    None => {
+                        let _ = self
+                            .common
+                            .shell
+                            .activate(&current_output, workspace as usize);
+                    }
+                }
             }
             Action::NextWorkspace => {
                 let current_output = seat.active_output();
@@ -1128,6 +1992,66 @@ impl State {
                 let focus_stack = workspace.focus_stack.get_mut(seat);
                 workspace.tiling_layer.toggle_stacking(seat, focus_stack);
             }
+            Action::TilingLayout(mode) => {
+                let output = seat.active_output();
+                let workspace = self.common.shell.active_space_mut(&output);
+                workspace.set_layout_mode(mode, seat);
+            }
+            Action::SaveLayout(name) => {
+                let output = seat.active_output();
+                let workspace = self.common.shell.active_space(&output);
+                let template = workspace.tiling_layer.save(&self.common.display_handle);
+                if let Err(err) = save_template(&name, &template) {
+                    warn!(?err, "Failed to save layout {:?}.", name);
+                }
+            }
+            Action::LoadLayout(name) => match load_template(&name) {
+                Ok(template) => {
+                    let output = seat.active_output();
+                    let workspace = self.common.shell.active_space_mut(&output);
+                    workspace.tiling_layer.load_template(
+                        template,
+                        &output,
+                        Some(workspace.focus_stack.get_mut(seat)),
+                        &self.common.display_handle,
+                    );
+                }
+                Err(err) => warn!(?err, "Failed to load layout {:?}.", name),
+            },
+            Action::Minimize => {
+                let output = seat.active_output();
+                let workspace = self.common.shell.active_space_mut(&output);
+                let focused = workspace.focus_stack.get(seat).last().cloned();
+                if let Some(mapped) = focused {
+                    workspace.minimize(&mapped);
+                }
+            }
+            Action::Scratchpad(app_id) => {
+                let output = seat.active_output();
+                if let Some(mapped) = self.common.shell.toggle_scratchpad(&app_id, &output) {
+                    Common::set_focus(self, Some(&KeyboardFocusTarget::from(mapped)), seat, None);
+                }
+            }
+            Action::SwitchWindows(scope) => self.switch_windows(seat, scope, mods, false),
+            Action::SwitchWindowsBackwards(scope) => self.switch_windows(seat, scope, mods, true),
+            Action::ToggleExpose => Expose::toggle(self),
+            Action::EnterMode(mode) => {
+                if mode == "default" {
+                    bindings::set_binding_mode(seat, None);
+                } else if self
+                    .common
+                    .config
+                    .static_conf
+                    .binding_modes
+                    .contains_key(&mode)
+                {
+                    bindings::set_binding_mode(seat, Some(mode));
+                } else {
+                    warn!(?mode, "Unknown binding mode");
+                }
+            }
+            Action::NextLayout => self.next_keyboard_layout(seat),
+            Action::SetLayout(idx) => self.set_keyboard_layout(seat, idx as usize),
             Action::ToggleTiling => {
                 let output = seat.active_output();
                 let workspace = self.common.shell.active_space_mut(&output);
@@ -1138,6 +2062,22 @@ impl State {
                 let workspace = self.common.shell.active_space_mut(&output);
                 workspace.toggle_floating_window(seat);
             }
+            Action::ToggleSticky => {
+                let output = seat.active_output();
+                let workspace = self.common.shell.active_space_mut(&output);
+                let focused = workspace.focus_stack.get(seat).last().cloned();
+                if let Some(mapped) = focused {
+                    workspace.set_sticky(&mapped, seat, !mapped.is_sticky());
+                }
+            }
+            Action::ToggleAlwaysOnTop => {
+                let output = seat.active_output();
+                let workspace = self.common.shell.active_space_mut(&output);
+                let focused = workspace.focus_stack.get(seat).last().cloned();
+                if let Some(mapped) = focused {
+                    workspace.set_always_on_top(&mapped, seat, !mapped.is_always_on_top());
+                }
+            }
             Action::Spawn(command) => {
                 let wayland_display = self.common.socket.clone();
 
@@ -1147,6 +2087,11 @@ impl State {
                     .as_ref()
                     .map(|s| format!(":{}", s.display))
                     .unwrap_or_default();
+                let ipc_socket = self
+                    .common
+                    .ipc_state
+                    .socket_path()
+                    .map(|path| path.to_path_buf());
 
                 std::thread::spawn(move || {
                     let mut cmd = std::process::Command::new("/bin/sh");
@@ -1156,6 +2101,9 @@ impl State {
                         .env("WAYLAND_DISPLAY", &wayland_display)
                         .env("DISPLAY", &display)
                         .env_remove("COSMIC_SESSION_SOCK");
+                    if let Some(path) = ipc_socket {
+                        cmd.env(crate::ipc::SOCKET_ENV, path);
+                    }
 
                     match cmd.spawn() {
                         Ok(mut child) => {
@@ -1170,14 +2118,71 @@ impl State {
         }
     }
 
+    /// Opens the window switcher or moves its selection
+    fn switch_windows(
+        &mut self,
+        seat: &Seat<State>,
+        scope: SwitcherScope,
+        mods: KeyModifiers,
+        backwards: bool,
+    ) {
+        if let Some(switcher) = self
+            .common
+            .shell
+            .switcher
+            .as_mut()
+            .filter(|switcher| &switcher.seat == seat)
+        {
+            if backwards {
+                switcher.select_previous();
+            } else {
+                switcher.select_next();
+            }
+            return;
+        }
+
+        let Some(mut switcher) = WindowSwitcher::new(
+            &self.common.shell,
+            seat,
+            scope,
+            mods,
+            self.common.event_loop_handle.clone(),
+        ) else {
+            return;
+        };
+        if backwards {
+            switcher.select_previous();
+        } else {
+            switcher.select_next();
+        }
+
+        if switcher.holds_modifiers() {
+            self.common.shell.switcher = Some(switcher);
+        } else if let Some(window) = switcher.selected() {
+            // nothing to release, switch right away
+            Shell::activate_window(self, &window, seat);
+        }
+    }
+
     pub fn surface_under(
         global_pos: Point<f64, Logical>,
         relative_pos: Point<f64, Logical>,
         output: &Output,
         output_geo: Rectangle<i32, Logical>,
         override_redirect_windows: &[X11Surface],
+        session_lock: Option<&SessionLock>,
         workspace: &Workspace,
     ) -> Option<(PointerFocusTarget, Point<i32, Logical>)> {
+        // while locked, only the lock surfaces may receive input
+        if let Some(session_lock) = session_lock {
+            return session_lock.surfaces.get(output).map(|surface| {
+                (
+                    PointerFocusTarget::LockSurface(surface.wl_surface().clone()),
+                    output_geo.loc,
+                )
+            });
+        }
+
         let layers = layer_map_for_output(output);
         if let Some(window) = workspace.get_fullscreen(output) {
             if let Some(layer) = layers.layer_under(WlrLayer::Overlay, relative_pos) {
@@ -1238,6 +2243,19 @@ impl State {
     }
 }
 
+/// Maps the position of an absolute event, like a touch or a tablet tool motion,
+/// onto the given region of an output, taking its transform into account
+fn transform_absolute_position<B: InputBackend, E: AbsolutePositionEvent<B>>(
+    output: &Output,
+    region: Rectangle<i32, Logical>,
+    event: &E,
+) -> Point<f64, Logical> {
+    let transform = output.current_transform();
+    let size = transform.invert().transform_size(region.size);
+    region.loc.to_f64()
+        + transform.transform_point_in(event.position_transformed(size), &size.to_f64())
+}
+
 fn sessions_for_output(state: &Common, output: &Output) -> impl Iterator<Item = Session> {
     let workspace = state.shell.active_space(&output);
     let maybe_fullscreen = workspace.get_fullscreen(&output);
@@ -0,0 +1,548 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Control socket, that accepts json encoded commands and queries.
+//!
+//! Every line written to the socket is a [`Request`], that is answered with a [`Reply`].
+//! Clients that subscribed to events additionally receive [`Event`]s.
+
+use crate::{
+    config::{Action, KeyModifiers},
+    input::bindings::binding_mode,
+    state::Data,
+    utils::prelude::*,
+};
+use anyhow::{Context, Result};
+use smithay::{
+    reexports::calloop::{
+        generic::Generic, Interest, LoopHandle, Mode, PostAction, RegistrationToken,
+    },
+    utils::SERIAL_COUNTER,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    io::{ErrorKind, Read, Write},
+    net::Shutdown,
+    os::unix::{
+        io::{AsRawFd, RawFd},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tracing::{debug, info, warn};
+
+mod protocol;
+pub use self::protocol::*;
+
+/// Environment variable the socket path is exported as
+pub const SOCKET_ENV: &str = "COSMIC_COMP_SOCK";
+
+/// Amount of data a client may fall behind on, before it is dropped
+const MAX_OUTPUT: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, Default)]
+pub struct IpcState {
+    socket_path: Option<PathBuf>,
+    clients: HashMap<u64, Client>,
+    next_id: u64,
+    snapshot: Option<Snapshot>,
+}
+
+/// Writing side of a connected client
+#[derive(Debug)]
+struct Client {
+    stream: UnixStream,
+    /// Encoded messages the client did not accept yet
+    output: Vec<u8>,
+    /// Source waiting for the client to accept more data, while `output` is not empty
+    writable: Option<RegistrationToken>,
+    /// Subscribed events, `None` unless the client subscribed
+    events: Option<HashSet<EventKind>>,
+}
+
+/// State relevant for events, used to figure out what changed
+#[derive(Debug, Default)]
+struct Snapshot {
+    focus: HashMap<String, Option<u64>>,
+    workspaces: HashMap<String, usize>,
+    windows: HashMap<u64, WindowInfo>,
+    keyboard_layouts: HashMap<String, (usize, String)>,
+    binding_modes: HashMap<String, Option<String>>,
+}
+
+impl IpcState {
+    pub fn socket_path(&self) -> Option<&Path> {
+        self.socket_path.as_deref()
+    }
+}
+
+impl Drop for IpcState {
+    fn drop(&mut self) {
+        if let Some(path) = self.socket_path.take() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Reading side of a connected client
+struct IpcClient {
+    id: u64,
+    stream: UnixStream,
+    buffer: Vec<u8>,
+}
+
+impl AsRawFd for IpcClient {
+    fn as_raw_fd(&self) -> RawFd {
+        self.stream.as_raw_fd()
+    }
+}
+
+pub fn init_socket(handle: LoopHandle<'static, Data>, state: &mut State) -> Result<()> {
+    let socket_name = state.common.socket.to_string_lossy().into_owned();
+    let path = xdg::BaseDirectories::new()
+        .with_context(|| "Failed to find runtime directory")?
+        .place_runtime_file(format!("cosmic-comp-{}.sock", socket_name))
+        .with_context(|| "Failed to create runtime directory")?;
+    // a previous instance on the same wayland socket might have crashed
+    if path.exists() {
+        std::fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+    }
+
+    let listener = UnixListener::bind(&path)
+        .with_context(|| format!("Failed to bind ipc socket {}", path.display()))?;
+    listener
+        .set_nonblocking(true)
+        .with_context(|| "Failed to set ipc socket non-blocking")?;
+
+    handle
+        .insert_source(
+            Generic::new(listener, Interest::READ, Mode::Level),
+            move |_, listener, data: &mut Data| {
+                loop {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            if let Err(err) = add_client(&mut data.state, stream) {
+                                warn!(?err, "Failed to add ipc client");
+                            }
+                        }
+                        Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                        Err(err) => {
+                            warn!(?err, "Failed to accept ipc client");
+                            break;
+                        }
+                    }
+                }
+                Ok(PostAction::Continue)
+            },
+        )
+        .with_context(|| "Failed to init the ipc socket source")?;
+
+    info!("IPC socket listening on {}", path.display());
+    state.common.ipc_state.socket_path = Some(path);
+    Ok(())
+}
+
+fn add_client(state: &mut State, stream: UnixStream) -> Result<()> {
+    // a slow client must never block the compositor
+    stream
+        .set_nonblocking(true)
+        .with_context(|| "Failed to set ipc client non-blocking")?;
+    let output = stream
+        .try_clone()
+        .with_context(|| "Failed to clone ipc client stream")?;
+    let id = state.common.ipc_state.next_id;
+    state.common.ipc_state.next_id += 1;
+
+    state
+        .common
+        .event_loop_handle
+        .insert_source(
+            Generic::new(
+                IpcClient {
+                    id,
+                    stream,
+                    buffer: Vec::new(),
+                },
+                Interest::READ,
+                Mode::Level,
+            ),
+            |_, client, data: &mut Data| {
+                let mut buf = [0u8; 4096];
+                match client.stream.read(&mut buf) {
+                    Ok(0) => {
+                        remove_client(&mut data.state, client.id);
+                        return Ok(PostAction::Remove);
+                    }
+                    Ok(len) => client.buffer.extend_from_slice(&buf[..len]),
+                    Err(err)
+                        if err.kind() == ErrorKind::Interrupted
+                            || err.kind() == ErrorKind::WouldBlock =>
+                    {
+                        return Ok(PostAction::Continue)
+                    }
+                    Err(err) => {
+                        debug!(?err, "Error reading from ipc client");
+                        remove_client(&mut data.state, client.id);
+                        return Ok(PostAction::Remove);
+                    }
+                };
+
+                while let Some(pos) = client.buffer.iter().position(|b| *b == b'\n') {
+                    let line = client.buffer.drain(..=pos).collect::<Vec<_>>();
+                    if line.iter().all(u8::is_ascii_whitespace) {
+                        continue;
+                    }
+                    let reply = data.state.handle_ipc_request(&line, client.id);
+                    if !send(&mut data.state, client.id, &reply) {
+                        return Ok(PostAction::Remove);
+                    }
+                }
+                Ok(PostAction::Continue)
+            },
+        )
+        .with_context(|| "Failed to insert ipc client source")?;
+
+    state.common.ipc_state.clients.insert(
+        id,
+        Client {
+            stream: output,
+            output: Vec::new(),
+            writable: None,
+            events: None,
+        },
+    );
+    Ok(())
+}
+
+/// Drops a client, its reading side is removed once it sees the closed socket
+fn remove_client(state: &mut State, id: u64) {
+    let Some(client) = state.common.ipc_state.clients.remove(&id) else {
+        return;
+    };
+    if let Some(token) = client.writable {
+        state.common.event_loop_handle.remove(token);
+    }
+    let _ = client.stream.shutdown(Shutdown::Both);
+}
+
+/// Queues a message for the client `id`.
+///
+/// Returns `false` if the client is gone, e.g. because it fell too far behind.
+fn send(state: &mut State, id: u64, message: &impl serde::Serialize) -> bool {
+    let mut bytes = match serde_json::to_vec(message) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            warn!(?err, "Failed to encode ipc message");
+            return true;
+        }
+    };
+    bytes.push(b'\n');
+
+    let Some(client) = state.common.ipc_state.clients.get_mut(&id) else {
+        return false;
+    };
+    if client.output.len() + bytes.len() > MAX_OUTPUT {
+        debug!("Dropping ipc client, that doesn't read its messages");
+        remove_client(state, id);
+        return false;
+    }
+    client.output.extend_from_slice(&bytes);
+    if client.writable.is_some() {
+        // already waiting for the client to catch up
+        return true;
+    }
+    if let Err(err) = client.write_output() {
+        debug!(?err, "Failed to write to ipc client");
+        remove_client(state, id);
+        return false;
+    }
+    if client.output.is_empty() {
+        return true;
+    }
+
+    let stream = match client.stream.try_clone() {
+        Ok(stream) => stream,
+        Err(err) => {
+            warn!(?err, "Failed to clone ipc client stream");
+            remove_client(state, id);
+            return false;
+        }
+    };
+    let token = state.common.event_loop_handle.insert_source(
+        Generic::new(stream, Interest::WRITE, Mode::Level),
+        move |_, _, data: &mut Data| {
+            let Some(client) = data.state.common.ipc_state.clients.get_mut(&id) else {
+                return Ok(PostAction::Remove);
+            };
+            match client.write_output() {
+                Ok(()) if client.output.is_empty() => {
+                    client.writable = None;
+                    Ok(PostAction::Remove)
+                }
+                Ok(()) => Ok(PostAction::Continue),
+                Err(err) => {
+                    debug!(?err, "Failed to write to ipc client");
+                    // this source is removed by returning, not by `remove_client`
+                    client.writable = None;
+                    remove_client(&mut data.state, id);
+                    Ok(PostAction::Remove)
+                }
+            }
+        },
+    );
+    match token {
+        Ok(token) => {
+            state
+                .common
+                .ipc_state
+                .clients
+                .get_mut(&id)
+                .unwrap()
+                .writable = Some(token);
+            true
+        }
+        Err(err) => {
+            warn!(?err, "Failed to wait for ipc client");
+            remove_client(state, id);
+            false
+        }
+    }
+}
+
+impl Client {
+    /// Writes as much of the pending output as the client accepts right now
+    fn write_output(&mut self) -> std::io::Result<()> {
+        while !self.output.is_empty() {
+            match self.stream.write(&self.output) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(len) => {
+                    self.output.drain(..len);
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl State {
+    fn handle_ipc_request(&mut self, line: &[u8], id: u64) -> Reply {
+        let request = match serde_json::from_slice::<Request>(line) {
+            Ok(request) => request,
+            Err(err) => {
+                return Reply::Error {
+                    message: format!("Invalid request: {}", err),
+                }
+            }
+        };
+
+        match request {
+            Request::Command { action } => {
+                self.handle_ipc_action(action);
+                Reply::Success
+            }
+            Request::GetWorkspaces => Reply::Workspaces {
+                workspaces: workspace_info(self),
+            },
+            Request::GetTilingTree => Reply::TilingTree {
+                trees: tiling_tree_info(self),
+            },
+            Request::GetOutputs => Reply::Outputs {
+                outputs: output_info(self),
+            },
+            Request::GetSeats => Reply::Seats {
+                seats: seat_info(self),
+            },
+            Request::GetWindows => Reply::Windows {
+                windows: window_info(self),
+            },
+            Request::GetKeyboardLayouts => Reply::KeyboardLayouts {
+                keyboards: keyboard_layout_info(self),
+            },
+            Request::Subscribe { events } => {
+                if self.common.ipc_state.snapshot.is_none() {
+                    self.common.ipc_state.snapshot = Some(Snapshot::new(self));
+                }
+                if let Some(client) = self.common.ipc_state.clients.get_mut(&id) {
+                    client.events = Some(events.into_iter().collect());
+                }
+                Reply::Success
+            }
+        }
+    }
+
+    fn handle_ipc_action(&mut self, action: Action) {
+        let seat = self.common.last_active_seat().clone();
+        let modifiers = seat.get_keyboard().unwrap().modifier_state();
+        let mods = KeyModifiers {
+            ctrl: modifiers.ctrl,
+            alt: modifiers.alt,
+            shift: modifiers.shift,
+            logo: modifiers.logo,
+        };
+        let time = Duration::from(self.common.clock.now()).as_millis() as u32;
+        self.handle_action(
+            action,
+            &seat,
+            SERIAL_COUNTER.next_serial(),
+            time,
+            mods,
+            None,
+        );
+    }
+}
+
+impl Snapshot {
+    fn new(state: &State) -> Snapshot {
+        Snapshot {
+            focus: state
+                .common
+                .seats()
+                .map(|seat| (seat.name().to_string(), focused_window(state, seat)))
+                .collect(),
+            workspaces: state
+                .common
+                .shell
+                .outputs()
+                .map(|output| {
+                    (
+                        output_name(state, output),
+                        state.common.shell.workspaces.active_num(output).1 + 1,
+                    )
+                })
+                .collect(),
+            windows: window_info(state)
+                .into_iter()
+                .map(|info| (info.id, info))
+                .collect(),
+            keyboard_layouts: keyboard_layout_info(state)
+                .into_iter()
+                .map(|info| {
+                    let name = info.layouts.get(info.current).cloned().unwrap_or_default();
+                    (info.seat, (info.current, name))
+                })
+                .collect(),
+            binding_modes: state
+                .common
+                .seats()
+                .map(|seat| (seat.name().to_string(), binding_mode(seat)))
+                .collect(),
+        }
+    }
+
+    fn changes(&self, new: &Snapshot) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        for (output, current) in &new.workspaces {
+            let previous = self.workspaces.get(output).copied();
+            if previous != Some(*current) {
+                events.push(Event::Workspace {
+                    output: output.clone(),
+                    previous,
+                    current: *current,
+                });
+            }
+        }
+
+        let mut ids = self
+            .windows
+            .keys()
+            .chain(new.windows.keys())
+            .copied()
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.dedup();
+        for id in ids {
+            let change = match (self.windows.get(&id), new.windows.get(&id)) {
+                (None, Some(info)) => (WindowChange::New, info),
+                (Some(info), None) => (WindowChange::Close, info),
+                (Some(old), Some(info)) if old.title != info.title => (WindowChange::Title, info),
+                (Some(old), Some(info))
+                    if old.workspace != info.workspace || old.output != info.output =>
+                {
+                    (WindowChange::Move, info)
+                }
+                _ => continue,
+            };
+            events.push(Event::Window {
+                change: change.0,
+                window: change.1.clone(),
+            });
+        }
+
+        for (seat, window) in &new.focus {
+            if self.focus.get(seat) != Some(window) {
+                events.push(Event::Focus {
+                    seat: seat.clone(),
+                    window: *window,
+                });
+            }
+        }
+
+        for (seat, (current, name)) in &new.keyboard_layouts {
+            if self.keyboard_layouts.get(seat).map(|(current, _)| current) != Some(current) {
+                events.push(Event::KeyboardLayout {
+                    seat: seat.clone(),
+                    current: *current,
+                    name: name.clone(),
+                });
+            }
+        }
+
+        for (seat, mode) in &new.binding_modes {
+            if self.binding_modes.get(seat) != Some(mode) {
+                events.push(Event::BindingMode {
+                    seat: seat.clone(),
+                    mode: mode.clone(),
+                });
+            }
+        }
+
+        events
+    }
+}
+
+/// Sends out events for everything that changed since the last call, while clients are subscribed
+pub fn refresh(state: &mut State) {
+    let ipc_state = &mut state.common.ipc_state;
+    if !ipc_state
+        .clients
+        .values()
+        .any(|client| client.events.is_some())
+    {
+        ipc_state.snapshot = None;
+        return;
+    }
+
+    let snapshot = Snapshot::new(state);
+    let ipc_state = &mut state.common.ipc_state;
+    let events = ipc_state
+        .snapshot
+        .replace(snapshot)
+        .map(|old| old.changes(ipc_state.snapshot.as_ref().unwrap()))
+        .unwrap_or_default();
+
+    for event in events {
+        let kind = event.kind();
+        let subscribers = state
+            .common
+            .ipc_state
+            .clients
+            .iter()
+            .filter(|(_, client)| {
+                client
+                    .events
+                    .as_ref()
+                    .map(|events| events.contains(&kind))
+                    .unwrap_or(false)
+            })
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in subscribers {
+            send(state, id, &event);
+        }
+    }
+}
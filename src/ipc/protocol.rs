@@ -0,0 +1,381 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use crate::{
+    config::{Action, WorkspaceMode},
+    shell::{
+        layout::tiling::{LayoutMode, TilingNode},
+        CosmicMapped, CosmicSurface, Workspace,
+    },
+    utils::prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use smithay::{
+    input::Seat,
+    output::Output,
+    utils::{Logical, Rectangle},
+};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Request send by an ipc client, one json object per line
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "request")]
+pub enum Request {
+    Command { action: Action },
+    GetWorkspaces,
+    GetTilingTree,
+    GetOutputs,
+    GetSeats,
+    GetWindows,
+    GetKeyboardLayouts,
+    Subscribe { events: Vec<EventKind> },
+}
+
+/// Reply to a [`Request`], one json object per line
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case", tag = "reply")]
+pub enum Reply {
+    Success,
+    Error { message: String },
+    Workspaces { workspaces: Vec<WorkspaceInfo> },
+    TilingTree { trees: Vec<TilingTreeInfo> },
+    Outputs { outputs: Vec<OutputInfo> },
+    Seats { seats: Vec<SeatInfo> },
+    Windows { windows: Vec<WindowInfo> },
+    KeyboardLayouts { keyboards: Vec<KeyboardLayoutInfo> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Focus,
+    Workspace,
+    Window,
+    KeyboardLayout,
+    BindingMode,
+}
+
+/// Event streamed to subscribed clients
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case", tag = "event")]
+pub enum Event {
+    Focus {
+        seat: String,
+        window: Option<u64>,
+    },
+    Workspace {
+        output: String,
+        previous: Option<usize>,
+        current: usize,
+    },
+    Window {
+        change: WindowChange,
+        window: WindowInfo,
+    },
+    KeyboardLayout {
+        seat: String,
+        current: usize,
+        name: String,
+    },
+    BindingMode {
+        seat: String,
+        mode: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowChange {
+    New,
+    Close,
+    Title,
+    Move,
+}
+
+impl Event {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Event::Focus { .. } => EventKind::Focus,
+            Event::Workspace { .. } => EventKind::Workspace,
+            Event::Window { .. } => EventKind::Window,
+            Event::KeyboardLayout { .. } => EventKind::KeyboardLayout,
+            Event::BindingMode { .. } => EventKind::BindingMode,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Geometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl From<Rectangle<i32, Logical>> for Geometry {
+    fn from(rect: Rectangle<i32, Logical>) -> Geometry {
+        Geometry {
+            x: rect.loc.x,
+            y: rect.loc.y,
+            width: rect.size.w,
+            height: rect.size.h,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorkspaceInfo {
+    /// Number as used by `Workspace(u8)` actions
+    pub num: usize,
+    pub output: String,
+    pub active: bool,
+    pub tiling: bool,
+    pub layout: LayoutMode,
+    pub windows: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TilingTreeInfo {
+    pub output: String,
+    pub workspace: usize,
+    pub root: Option<TilingNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutputInfo {
+    pub name: String,
+    pub make: String,
+    pub model: String,
+    pub geometry: Geometry,
+    pub scale: f64,
+    pub refresh: Option<i32>,
+    pub active_workspace: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SeatInfo {
+    pub name: String,
+    pub active_output: String,
+    pub focused_window: Option<u64>,
+    pub pointer: Option<(f64, f64)>,
+    /// Active binding mode, `None` for the default bindings
+    pub binding_mode: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WindowInfo {
+    pub id: u64,
+    pub app_id: String,
+    pub title: String,
+    pub output: Option<String>,
+    pub workspace: usize,
+    pub geometry: Option<Geometry>,
+    pub floating: bool,
+    pub fullscreen: bool,
+    pub maximized: bool,
+    pub activated: bool,
+    pub minimized: bool,
+    pub sticky: bool,
+    pub always_on_top: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyboardLayoutInfo {
+    pub seat: String,
+    /// Configured layouts, formatted like `layout(variant)`
+    pub layouts: Vec<String>,
+    /// Index into `layouts`
+    pub current: usize,
+}
+
+struct WindowId(u64);
+
+/// Stable identifier of a window, used to reference it via ipc
+pub fn window_id(window: &CosmicSurface) -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    window
+        .user_data()
+        .insert_if_missing(|| WindowId(NEXT_ID.fetch_add(1, Ordering::SeqCst)));
+    window.user_data().get::<WindowId>().unwrap().0
+}
+
+/// All workspaces together with the output they are listed for and their index
+fn workspaces(state: &State) -> Vec<(Output, usize, &Workspace)> {
+    let shell = &state.common.shell;
+    match state.common.config.static_conf.workspace_mode {
+        WorkspaceMode::Global => {
+            let output = match shell.outputs().next() {
+                Some(output) => output,
+                None => return Vec::new(),
+            };
+            shell
+                .workspaces
+                .spaces()
+                .enumerate()
+                .map(|(idx, space)| (output.clone(), idx, space))
+                .collect()
+        }
+        WorkspaceMode::OutputBound => shell
+            .outputs()
+            .flat_map(|output| {
+                shell
+                    .workspaces
+                    .spaces_for_output(output)
+                    .enumerate()
+                    .map(move |(idx, space)| (output.clone(), idx, space))
+            })
+            .collect(),
+    }
+}
+
+pub fn output_name(state: &State, output: &Output) -> String {
+    match state.common.config.static_conf.workspace_mode {
+        // global workspaces span all outputs
+        WorkspaceMode::Global => String::from("*"),
+        WorkspaceMode::OutputBound => output.name(),
+    }
+}
+
+pub fn workspace_info(state: &State) -> Vec<WorkspaceInfo> {
+    workspaces(state)
+        .into_iter()
+        .map(|(output, idx, space)| WorkspaceInfo {
+            num: idx + 1,
+            output: output_name(state, &output),
+            active: state.common.shell.workspaces.active_num(&output).1 == idx,
+            tiling: space.tiling_enabled,
+            layout: space.tiling_layer.layout_mode(),
+            windows: space.windows().map(|w| window_id(&w)).collect(),
+        })
+        .collect()
+}
+
+pub fn tiling_tree_info(state: &State) -> Vec<TilingTreeInfo> {
+    workspaces(state)
+        .into_iter()
+        .flat_map(|(_, idx, space)| {
+            space.outputs().map(move |output| TilingTreeInfo {
+                output: output.name(),
+                workspace: idx + 1,
+                root: space.tiling_layer.tree_info(output),
+            })
+        })
+        .collect()
+}
+
+pub fn output_info(state: &State) -> Vec<OutputInfo> {
+    state
+        .common
+        .shell
+        .outputs()
+        .map(|output| {
+            let physical = output.physical_properties();
+            OutputInfo {
+                name: output.name(),
+                make: physical.make,
+                model: physical.model,
+                geometry: output.geometry().into(),
+                scale: output.current_scale().fractional_scale(),
+                refresh: output.current_mode().map(|mode| mode.refresh),
+                active_workspace: state.common.shell.workspaces.active_num(output).1 + 1,
+            }
+        })
+        .collect()
+}
+
+pub fn seat_info(state: &State) -> Vec<SeatInfo> {
+    state
+        .common
+        .seats()
+        .map(|seat| SeatInfo {
+            name: seat.name().to_string(),
+            active_output: seat.active_output().name(),
+            focused_window: focused_window(state, seat),
+            pointer: seat
+                .get_pointer()
+                .map(|ptr| ptr.current_location())
+                .map(|loc| (loc.x, loc.y)),
+            binding_mode: crate::input::bindings::binding_mode(seat),
+        })
+        .collect()
+}
+
+pub fn focused_window(state: &State, seat: &Seat<State>) -> Option<u64> {
+    let output = seat.active_output();
+    let workspace = state.common.shell.active_space(&output);
+    if let Some(fullscreen) = workspace.get_fullscreen(&output) {
+        return Some(window_id(fullscreen));
+    }
+    workspace
+        .focus_stack
+        .get(seat)
+        .last()
+        .map(|mapped| window_id(&mapped.active_window()))
+}
+
+pub fn window_info(state: &State) -> Vec<WindowInfo> {
+    workspaces(state)
+        .into_iter()
+        .flat_map(|(_, idx, space)| {
+            space
+                .mapped()
+                .flat_map(move |mapped| mapped_info(space, idx, mapped))
+                .chain(space.minimized.iter().flat_map(move |minimized| {
+                    let mut infos = mapped_info(space, idx, &minimized.window);
+                    for info in infos.iter_mut() {
+                        info.output = Some(minimized.output.name());
+                    }
+                    infos
+                }))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn mapped_info(space: &Workspace, idx: usize, mapped: &CosmicMapped) -> Vec<WindowInfo> {
+    let output = space.outputs_for_element(mapped).next().map(|o| o.name());
+    let geometry = space.element_geometry(mapped);
+    mapped
+        .windows()
+        .map(|(window, _)| WindowInfo {
+            id: window_id(&window),
+            app_id: window.app_id(),
+            title: window.title(),
+            output: output.clone(),
+            workspace: idx + 1,
+            geometry: geometry.map(Into::into),
+            floating: space.is_floating(mapped),
+            fullscreen: window.is_fullscreen(false),
+            maximized: window.is_maximized(false),
+            activated: window.is_activated(false),
+            minimized: window.is_minimized(),
+            sticky: mapped.is_sticky(),
+            always_on_top: mapped.is_always_on_top(),
+        })
+        .collect()
+}
+
+pub fn keyboard_layout_info(state: &State) -> Vec<KeyboardLayoutInfo> {
+    let layouts = state
+        .common
+        .config
+        .xkb_config()
+        .layouts()
+        .into_iter()
+        .map(|layout| {
+            if layout.variant.is_empty() {
+                layout.layout
+            } else {
+                format!("{}({})", layout.layout, layout.variant)
+            }
+        })
+        .collect::<Vec<_>>();
+    state
+        .common
+        .seats()
+        .map(|seat| KeyboardLayoutInfo {
+            seat: seat.name().to_string(),
+            layouts: layouts.clone(),
+            current: crate::input::layout::current_layout(seat),
+        })
+        .collect()
+}
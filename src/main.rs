@@ -17,12 +17,15 @@ pub mod config;
 #[cfg(feature = "debug")]
 pub mod debug;
 pub mod input;
+pub mod ipc;
 mod logger;
 pub mod session;
 pub mod shell;
 pub mod state;
 #[cfg(feature = "systemd")]
 pub mod systemd;
+#[cfg(test)]
+mod tests;
 pub mod utils;
 pub mod wayland;
 pub mod xwayland;
@@ -43,6 +46,7 @@ fn main() -> Result<()> {
         socket,
         event_loop.handle(),
         event_loop.get_signal(),
+        config::Config::load(),
     );
     // init backend
     backend::init_backend_auto(&display.handle(), &mut event_loop, &mut state)?;
@@ -51,8 +55,19 @@ fn main() -> Result<()> {
     if let state::BackendData::Kms(_) = &state.backend {
         systemd::ready(&state);
     }
+    // setup the ipc socket, before the session learns about our environment
+    if let Err(err) = ipc::init_socket(event_loop.handle(), &mut state) {
+        warn!(?err, "Failed to setup ipc socket");
+    }
     // potentially tell the session we are setup now
     session::setup_socket(event_loop.handle(), &state)?;
+    // reload the config on changes
+    if let Err(err) = config::watch_static_config(event_loop.handle()) {
+        warn!(
+            ?err,
+            "Failed to watch config files, changes will require a restart."
+        );
+    }
 
     let mut data = state::Data { display, state };
     // run the event loop
@@ -60,18 +75,17 @@ fn main() -> Result<()> {
         // shall we shut down?
         if data.state.common.shell.outputs().next().is_none() || data.state.common.should_stop {
             info!("Shutting down");
+            // without outputs there would be no layouts left to save
+            if data.state.common.shell.outputs().next().is_some() {
+                data.state.common.flush_layouts();
+            }
             data.state.common.event_loop_signal.stop();
             data.state.common.event_loop_signal.wakeup();
             return;
         }
 
         // trigger routines
-        data.state
-            .common
-            .shell
-            .update_animations(&data.state.common.event_loop_handle);
-        data.state.common.shell.refresh();
-        state::Common::refresh_focus(&mut data.state);
+        data.state.refresh_loop_iteration();
 
         // send out events
         let _ = data.display.flush_clients();
@@ -81,6 +81,12 @@ pub fn setup_socket(handle: LoopHandle<Data>, state: &State) -> Result<()> {
             if let Some(display) = state.common.xwayland_state.as_ref().map(|s| s.display) {
                 env.insert(String::from("DISPLAY"), format!(":{}", display));
             }
+            if let Some(path) = state.common.ipc_state.socket_path() {
+                env.insert(
+                    String::from(crate::ipc::SOCKET_ENV),
+                    path.to_string_lossy().into_owned(),
+                );
+            }
             let message = serde_json::to_string(&Message::SetEnv { variables: env })
                 .with_context(|| "Failed to encode environment variables into json")?;
             let bytes = message.into_bytes();
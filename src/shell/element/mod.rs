@@ -24,7 +24,12 @@ use smithay::{
     desktop::{space::SpaceElement, PopupManager, WindowSurfaceType},
     input::{
         keyboard::{KeyboardTarget, KeysymHandle, ModifiersState},
-        pointer::{AxisFrame, ButtonEvent, MotionEvent, PointerTarget, RelativeMotionEvent},
+        pointer::{
+            AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent,
+            GesturePinchBeginEvent, GesturePinchEndEvent, GesturePinchUpdateEvent,
+            GestureSwipeBeginEvent, GestureSwipeEndEvent, GestureSwipeUpdateEvent, MotionEvent,
+            PointerTarget, RelativeMotionEvent,
+        },
         Seat,
     },
     output::Output,
@@ -44,7 +49,10 @@ use std::{
     collections::HashMap,
     fmt,
     hash::Hash,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
 };
 
 pub mod surface;
@@ -86,6 +94,10 @@ pub struct CosmicMapped {
     //floating
     pub(super) last_geometry: Arc<Mutex<Option<Rectangle<i32, Logical>>>>,
     pub(super) resize_state: Arc<Mutex<Option<ResizeState>>>,
+    /// Size before being snapped to an output edge
+    pub(super) pre_snap_size: Arc<Mutex<Option<Size<i32, Logical>>>>,
+    sticky: Arc<AtomicBool>,
+    always_on_top: Arc<AtomicBool>,
 
     #[cfg(feature = "debug")]
     debug: Arc<Mutex<Option<smithay_egui::EguiState>>>,
@@ -98,6 +110,8 @@ impl fmt::Debug for CosmicMapped {
             .field("last_cursor_position", &self.last_cursor_position)
             .field("tiling_node_id", &self.tiling_node_id)
             .field("resize_state", &self.resize_state)
+            .field("sticky", &self.sticky)
+            .field("always_on_top", &self.always_on_top)
             .finish()
     }
 }
@@ -333,6 +347,24 @@ impl CosmicMapped {
         window.is_maximized(pending)
     }
 
+    /// Sticky windows follow workspace switches of their output
+    pub fn set_sticky(&self, sticky: bool) {
+        self.sticky.store(sticky, Ordering::SeqCst);
+    }
+
+    pub fn is_sticky(&self) -> bool {
+        self.sticky.load(Ordering::SeqCst)
+    }
+
+    /// Always-on-top windows are stacked above all other windows of their workspace
+    pub fn set_always_on_top(&self, always_on_top: bool) {
+        self.always_on_top.store(always_on_top, Ordering::SeqCst);
+    }
+
+    pub fn is_always_on_top(&self) -> bool {
+        self.always_on_top.load(Ordering::SeqCst)
+    }
+
     pub fn set_activated(&self, activated: bool) {
         match &self.element {
             CosmicMappedInternal::Stack(s) => s.set_activate(activated),
@@ -704,6 +736,135 @@ impl PointerTarget<State> for CosmicMapped {
             _ => {}
         }
     }
+
+    fn gesture_swipe_begin(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GestureSwipeBeginEvent,
+    ) {
+        match &self.element {
+            CosmicMappedInternal::Stack(s) => {
+                PointerTarget::gesture_swipe_begin(s, seat, data, event)
+            }
+            CosmicMappedInternal::Window(w) => {
+                PointerTarget::gesture_swipe_begin(w, seat, data, event)
+            }
+            _ => {}
+        }
+    }
+
+    fn gesture_swipe_update(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GestureSwipeUpdateEvent,
+    ) {
+        match &self.element {
+            CosmicMappedInternal::Stack(s) => {
+                PointerTarget::gesture_swipe_update(s, seat, data, event)
+            }
+            CosmicMappedInternal::Window(w) => {
+                PointerTarget::gesture_swipe_update(w, seat, data, event)
+            }
+            _ => {}
+        }
+    }
+
+    fn gesture_swipe_end(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GestureSwipeEndEvent,
+    ) {
+        match &self.element {
+            CosmicMappedInternal::Stack(s) => {
+                PointerTarget::gesture_swipe_end(s, seat, data, event)
+            }
+            CosmicMappedInternal::Window(w) => {
+                PointerTarget::gesture_swipe_end(w, seat, data, event)
+            }
+            _ => {}
+        }
+    }
+
+    fn gesture_pinch_begin(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GesturePinchBeginEvent,
+    ) {
+        match &self.element {
+            CosmicMappedInternal::Stack(s) => {
+                PointerTarget::gesture_pinch_begin(s, seat, data, event)
+            }
+            CosmicMappedInternal::Window(w) => {
+                PointerTarget::gesture_pinch_begin(w, seat, data, event)
+            }
+            _ => {}
+        }
+    }
+
+    fn gesture_pinch_update(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GesturePinchUpdateEvent,
+    ) {
+        match &self.element {
+            CosmicMappedInternal::Stack(s) => {
+                PointerTarget::gesture_pinch_update(s, seat, data, event)
+            }
+            CosmicMappedInternal::Window(w) => {
+                PointerTarget::gesture_pinch_update(w, seat, data, event)
+            }
+            _ => {}
+        }
+    }
+
+    fn gesture_pinch_end(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GesturePinchEndEvent,
+    ) {
+        match &self.element {
+            CosmicMappedInternal::Stack(s) => {
+                PointerTarget::gesture_pinch_end(s, seat, data, event)
+            }
+            CosmicMappedInternal::Window(w) => {
+                PointerTarget::gesture_pinch_end(w, seat, data, event)
+            }
+            _ => {}
+        }
+    }
+
+    fn gesture_hold_begin(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GestureHoldBeginEvent,
+    ) {
+        match &self.element {
+            CosmicMappedInternal::Stack(s) => {
+                PointerTarget::gesture_hold_begin(s, seat, data, event)
+            }
+            CosmicMappedInternal::Window(w) => {
+                PointerTarget::gesture_hold_begin(w, seat, data, event)
+            }
+            _ => {}
+        }
+    }
+
+    fn gesture_hold_end(&self, seat: &Seat<State>, data: &mut State, event: &GestureHoldEndEvent) {
+        match &self.element {
+            CosmicMappedInternal::Stack(s) => PointerTarget::gesture_hold_end(s, seat, data, event),
+            CosmicMappedInternal::Window(w) => {
+                PointerTarget::gesture_hold_end(w, seat, data, event)
+            }
+            _ => {}
+        }
+    }
 }
 
 impl WaylandFocus for CosmicMapped {
@@ -732,6 +893,9 @@ impl From<CosmicWindow> for CosmicMapped {
             tiling_node_id: Arc::new(Mutex::new(None)),
             last_geometry: Arc::new(Mutex::new(None)),
             resize_state: Arc::new(Mutex::new(None)),
+            pre_snap_size: Arc::new(Mutex::new(None)),
+            sticky: Arc::new(AtomicBool::new(false)),
+            always_on_top: Arc::new(AtomicBool::new(false)),
             #[cfg(feature = "debug")]
             debug: Arc::new(Mutex::new(None)),
         }
@@ -746,6 +910,9 @@ impl From<CosmicStack> for CosmicMapped {
             tiling_node_id: Arc::new(Mutex::new(None)),
             last_geometry: Arc::new(Mutex::new(None)),
             resize_state: Arc::new(Mutex::new(None)),
+            pre_snap_size: Arc::new(Mutex::new(None)),
+            sticky: Arc::new(AtomicBool::new(false)),
+            always_on_top: Arc::new(AtomicBool::new(false)),
             #[cfg(feature = "debug")]
             debug: Arc::new(Mutex::new(None)),
         }
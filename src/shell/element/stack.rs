@@ -31,7 +31,12 @@ use smithay::{
     desktop::space::SpaceElement,
     input::{
         keyboard::{KeyboardTarget, KeysymHandle, ModifiersState},
-        pointer::{AxisFrame, ButtonEvent, MotionEvent, PointerTarget, RelativeMotionEvent},
+        pointer::{
+            AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent,
+            GesturePinchBeginEvent, GesturePinchEndEvent, GesturePinchUpdateEvent,
+            GestureSwipeBeginEvent, GestureSwipeEndEvent, GestureSwipeUpdateEvent, MotionEvent,
+            PointerTarget, RelativeMotionEvent,
+        },
         Seat,
     },
     output::Output,
@@ -1053,6 +1058,113 @@ impl PointerTarget<State> for CosmicStack {
             _ => {}
         }
     }
+
+    fn gesture_swipe_begin(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GestureSwipeBeginEvent,
+    ) {
+        self.0.with_program(|p| {
+            if p.current_focus() == Focus::Window {
+                let window = &p.windows.lock().unwrap()[p.active.load(Ordering::SeqCst)];
+                PointerTarget::gesture_swipe_begin(window, seat, data, event)
+            }
+        })
+    }
+
+    fn gesture_swipe_update(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GestureSwipeUpdateEvent,
+    ) {
+        self.0.with_program(|p| {
+            if p.current_focus() == Focus::Window {
+                let window = &p.windows.lock().unwrap()[p.active.load(Ordering::SeqCst)];
+                PointerTarget::gesture_swipe_update(window, seat, data, event)
+            }
+        })
+    }
+
+    fn gesture_swipe_end(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GestureSwipeEndEvent,
+    ) {
+        self.0.with_program(|p| {
+            if p.current_focus() == Focus::Window {
+                let window = &p.windows.lock().unwrap()[p.active.load(Ordering::SeqCst)];
+                PointerTarget::gesture_swipe_end(window, seat, data, event)
+            }
+        })
+    }
+
+    fn gesture_pinch_begin(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GesturePinchBeginEvent,
+    ) {
+        self.0.with_program(|p| {
+            if p.current_focus() == Focus::Window {
+                let window = &p.windows.lock().unwrap()[p.active.load(Ordering::SeqCst)];
+                PointerTarget::gesture_pinch_begin(window, seat, data, event)
+            }
+        })
+    }
+
+    fn gesture_pinch_update(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GesturePinchUpdateEvent,
+    ) {
+        self.0.with_program(|p| {
+            if p.current_focus() == Focus::Window {
+                let window = &p.windows.lock().unwrap()[p.active.load(Ordering::SeqCst)];
+                PointerTarget::gesture_pinch_update(window, seat, data, event)
+            }
+        })
+    }
+
+    fn gesture_pinch_end(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GesturePinchEndEvent,
+    ) {
+        self.0.with_program(|p| {
+            if p.current_focus() == Focus::Window {
+                let window = &p.windows.lock().unwrap()[p.active.load(Ordering::SeqCst)];
+                PointerTarget::gesture_pinch_end(window, seat, data, event)
+            }
+        })
+    }
+
+    fn gesture_hold_begin(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GestureHoldBeginEvent,
+    ) {
+        self.0.with_program(|p| {
+            if p.current_focus() == Focus::Window {
+                let window = &p.windows.lock().unwrap()[p.active.load(Ordering::SeqCst)];
+                PointerTarget::gesture_hold_begin(window, seat, data, event)
+            }
+        })
+    }
+
+    fn gesture_hold_end(&self, seat: &Seat<State>, data: &mut State, event: &GestureHoldEndEvent) {
+        self.0.with_program(|p| {
+            if p.current_focus() == Focus::Window {
+                let window = &p.windows.lock().unwrap()[p.active.load(Ordering::SeqCst)];
+                PointerTarget::gesture_hold_end(window, seat, data, event)
+            }
+        })
+    }
 }
 
 render_elements! {
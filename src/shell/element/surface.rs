@@ -1,4 +1,7 @@
-use std::time::Duration;
+use std::{
+    cell::{Cell, RefCell},
+    time::Duration,
+};
 
 use smithay::{
     backend::renderer::{
@@ -14,7 +17,7 @@ use smithay::{
             take_presentation_feedback_surface_tree, with_surfaces_surface_tree,
             OutputPresentationFeedback,
         },
-        Window,
+        Window, WindowSurfaceType,
     },
     input::{keyboard::KeyboardTarget, pointer::PointerTarget},
     output::Output,
@@ -29,7 +32,7 @@ use smithay::{
         wayland_server::protocol::wl_surface::WlSurface,
     },
     space_elements,
-    utils::{user_data::UserDataMap, Logical, Rectangle, Serial, Size},
+    utils::{user_data::UserDataMap, Logical, Point, Rectangle, Serial, Size},
     wayland::{
         compositor::{with_states, SurfaceData},
         seat::WaylandFocus,
@@ -38,7 +41,11 @@ use smithay::{
     xwayland::{xwm::X11Relatable, X11Surface},
 };
 
-use crate::{state::SurfaceDmabufFeedback, wayland::handlers::decoration::PreferredDecorationMode};
+use crate::{
+    config::{DecorationPreference, WindowRule, WindowRuleActions},
+    state::SurfaceDmabufFeedback,
+    wayland::handlers::decoration::PreferredDecorationMode,
+};
 
 space_elements! {
     #[derive(Debug, Clone, PartialEq)]
@@ -67,6 +74,26 @@ impl From<X11Surface> for CosmicSurface {
 
 pub const SSD_HEIGHT: i32 = 48;
 
+/// Window rules last applied to a surface and the (app_id, title) they were evaluated for
+#[derive(Default)]
+struct WindowRulesState {
+    identity: Option<(String, String)>,
+    actions: WindowRuleActions,
+}
+
+/// Set while the window is hidden in the minimized set of its workspace
+#[derive(Default)]
+struct MinimizedState(Cell<bool>);
+
+impl From<DecorationPreference> for DecorationMode {
+    fn from(pref: DecorationPreference) -> Self {
+        match pref {
+            DecorationPreference::ServerSide => DecorationMode::ServerSide,
+            DecorationPreference::ClientSide => DecorationMode::ClientSide,
+        }
+    }
+}
+
 impl CosmicSurface {
     pub fn title(&self) -> String {
         match self {
@@ -108,6 +135,20 @@ impl CosmicSurface {
         }
     }
 
+    /// Returns the (sub-)surface at the given point relative to the window and its location
+    pub fn wl_surface_under(
+        &self,
+        point: Point<f64, Logical>,
+    ) -> Option<(WlSurface, Point<i32, Logical>)> {
+        match self {
+            CosmicSurface::Wayland(window) => window.surface_under(point, WindowSurfaceType::ALL),
+            CosmicSurface::X11(surface) => surface
+                .wl_surface()
+                .map(|surface| (surface, Point::default())),
+            _ => unreachable!(),
+        }
+    }
+
     pub fn set_geometry(&self, geo: Rectangle<i32, Logical>) {
         match self {
             CosmicSurface::Wayland(window) => window
@@ -184,7 +225,10 @@ impl CosmicSurface {
                         .unwrap_or(true)
                 }
             }
-            CosmicSurface::X11(surface) => surface.is_decorated(),
+            CosmicSurface::X11(surface) => self
+                .forced_decorations()
+                .map(|pref| pref == DecorationPreference::ClientSide)
+                .unwrap_or_else(|| surface.is_decorated()),
             _ => unreachable!(),
         }
     }
@@ -202,7 +246,10 @@ impl CosmicSurface {
                         pending.decoration_mode = Some(DecorationMode::ServerSide);
                     });
                 } else {
-                    let previous_mode = PreferredDecorationMode::mode(window);
+                    let previous_mode = self
+                        .forced_decorations()
+                        .map(Into::into)
+                        .or_else(|| PreferredDecorationMode::mode(window));
                     window.toplevel().with_pending_state(|pending| {
                         pending.decoration_mode = previous_mode;
                     });
@@ -591,6 +638,81 @@ impl CosmicSurface {
             _ => unreachable!(),
         }
     }
+
+    pub fn is_minimized(&self) -> bool {
+        self.user_data()
+            .get::<MinimizedState>()
+            .map(|state| state.0.get())
+            .unwrap_or(false)
+    }
+
+    pub fn set_minimized(&self, minimized: bool) {
+        let user_data = self.user_data();
+        user_data.insert_if_missing(MinimizedState::default);
+        user_data.get::<MinimizedState>().unwrap().0.set(minimized);
+    }
+
+    /// Re-evaluates the given window rules, if the app_id or title changed since they were last applied.
+    ///
+    /// Returns the previously applied and the resulting actions, if they differ.
+    pub fn update_window_rules(
+        &self,
+        rules: &[WindowRule],
+    ) -> Option<(WindowRuleActions, WindowRuleActions)> {
+        let user_data = self.user_data();
+        user_data.insert_if_missing(|| RefCell::new(WindowRulesState::default()));
+        let mut state = user_data
+            .get::<RefCell<WindowRulesState>>()
+            .unwrap()
+            .borrow_mut();
+
+        let identity = (self.app_id(), self.title());
+        if state.identity.as_ref() == Some(&identity) {
+            return None;
+        }
+        state.identity = Some(identity);
+
+        let actions = WindowRuleActions::for_window(rules, self);
+        if state.actions == actions {
+            return None;
+        }
+        let previous = std::mem::replace(&mut state.actions, actions.clone());
+        Some((previous, actions))
+    }
+
+    /// Forces the next call to `update_window_rules` to re-evaluate the rules
+    pub fn reset_window_rules(&self) {
+        if let Some(state) = self.user_data().get::<RefCell<WindowRulesState>>() {
+            state.borrow_mut().identity = None;
+        }
+    }
+
+    pub fn forced_decorations(&self) -> Option<DecorationPreference> {
+        self.user_data()
+            .get::<RefCell<WindowRulesState>>()
+            .and_then(|state| state.borrow().actions.decorations)
+    }
+
+    /// Applies the decoration mode enforced by a window rule, if any.
+    pub fn apply_forced_decorations(&self) {
+        if let Some(mode) = self.forced_decorations() {
+            if let CosmicSurface::Wayland(window) = self {
+                window.toplevel().with_pending_state(|pending| {
+                    pending.decoration_mode = Some(mode.into());
+                });
+            }
+        }
+    }
+
+    /// Goes back to the decoration mode requested by the client, once no rule enforces one anymore.
+    pub fn restore_requested_decorations(&self) {
+        if let CosmicSurface::Wayland(window) = self {
+            let mode = PreferredDecorationMode::mode(window);
+            window.toplevel().with_pending_state(|pending| {
+                pending.decoration_mode = mode;
+            });
+        }
+    }
 }
 
 impl KeyboardTarget<crate::state::State> for CosmicSurface {
@@ -749,6 +871,142 @@ impl PointerTarget<crate::state::State> for CosmicSurface {
             _ => unreachable!(),
         }
     }
+
+    fn gesture_swipe_begin(
+        &self,
+        seat: &smithay::input::Seat<crate::state::State>,
+        data: &mut crate::state::State,
+        event: &smithay::input::pointer::GestureSwipeBeginEvent,
+    ) {
+        match self {
+            CosmicSurface::Wayland(window) => {
+                PointerTarget::gesture_swipe_begin(window, seat, data, event)
+            }
+            CosmicSurface::X11(surface) => {
+                PointerTarget::gesture_swipe_begin(surface, seat, data, event)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn gesture_swipe_update(
+        &self,
+        seat: &smithay::input::Seat<crate::state::State>,
+        data: &mut crate::state::State,
+        event: &smithay::input::pointer::GestureSwipeUpdateEvent,
+    ) {
+        match self {
+            CosmicSurface::Wayland(window) => {
+                PointerTarget::gesture_swipe_update(window, seat, data, event)
+            }
+            CosmicSurface::X11(surface) => {
+                PointerTarget::gesture_swipe_update(surface, seat, data, event)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn gesture_swipe_end(
+        &self,
+        seat: &smithay::input::Seat<crate::state::State>,
+        data: &mut crate::state::State,
+        event: &smithay::input::pointer::GestureSwipeEndEvent,
+    ) {
+        match self {
+            CosmicSurface::Wayland(window) => {
+                PointerTarget::gesture_swipe_end(window, seat, data, event)
+            }
+            CosmicSurface::X11(surface) => {
+                PointerTarget::gesture_swipe_end(surface, seat, data, event)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn gesture_pinch_begin(
+        &self,
+        seat: &smithay::input::Seat<crate::state::State>,
+        data: &mut crate::state::State,
+        event: &smithay::input::pointer::GesturePinchBeginEvent,
+    ) {
+        match self {
+            CosmicSurface::Wayland(window) => {
+                PointerTarget::gesture_pinch_begin(window, seat, data, event)
+            }
+            CosmicSurface::X11(surface) => {
+                PointerTarget::gesture_pinch_begin(surface, seat, data, event)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn gesture_pinch_update(
+        &self,
+        seat: &smithay::input::Seat<crate::state::State>,
+        data: &mut crate::state::State,
+        event: &smithay::input::pointer::GesturePinchUpdateEvent,
+    ) {
+        match self {
+            CosmicSurface::Wayland(window) => {
+                PointerTarget::gesture_pinch_update(window, seat, data, event)
+            }
+            CosmicSurface::X11(surface) => {
+                PointerTarget::gesture_pinch_update(surface, seat, data, event)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn gesture_pinch_end(
+        &self,
+        seat: &smithay::input::Seat<crate::state::State>,
+        data: &mut crate::state::State,
+        event: &smithay::input::pointer::GesturePinchEndEvent,
+    ) {
+        match self {
+            CosmicSurface::Wayland(window) => {
+                PointerTarget::gesture_pinch_end(window, seat, data, event)
+            }
+            CosmicSurface::X11(surface) => {
+                PointerTarget::gesture_pinch_end(surface, seat, data, event)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn gesture_hold_begin(
+        &self,
+        seat: &smithay::input::Seat<crate::state::State>,
+        data: &mut crate::state::State,
+        event: &smithay::input::pointer::GestureHoldBeginEvent,
+    ) {
+        match self {
+            CosmicSurface::Wayland(window) => {
+                PointerTarget::gesture_hold_begin(window, seat, data, event)
+            }
+            CosmicSurface::X11(surface) => {
+                PointerTarget::gesture_hold_begin(surface, seat, data, event)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn gesture_hold_end(
+        &self,
+        seat: &smithay::input::Seat<crate::state::State>,
+        data: &mut crate::state::State,
+        event: &smithay::input::pointer::GestureHoldEndEvent,
+    ) {
+        match self {
+            CosmicSurface::Wayland(window) => {
+                PointerTarget::gesture_hold_end(window, seat, data, event)
+            }
+            CosmicSurface::X11(surface) => {
+                PointerTarget::gesture_hold_end(surface, seat, data, event)
+            }
+            _ => unreachable!(),
+        }
+    }
 }
 
 impl WaylandFocus for CosmicSurface {
@@ -0,0 +1,732 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{cell::RefCell, collections::HashMap, sync::Arc, time::Duration};
+
+use smithay::{
+    backend::{
+        input::ButtonState,
+        renderer::{
+            element::{
+                utils::{CropRenderElement, Relocate, RelocateRenderElement, RescaleRenderElement},
+                AsRenderElements, Id, RenderElement,
+            },
+            ImportAll, ImportMem, Renderer,
+        },
+    },
+    desktop::{layer_map_for_output, space::SpaceElement},
+    input::{
+        keyboard::keysyms,
+        pointer::{
+            AxisFrame, ButtonEvent, Focus, GrabStartData as PointerGrabStartData, MotionEvent,
+            PointerGrab, PointerInnerHandle, RelativeMotionEvent,
+        },
+        Seat,
+    },
+    output::Output,
+    utils::{IsAlive, Logical, Point, Rectangle, Scale, Size, SERIAL_COUNTER},
+};
+
+use super::{
+    focus::{target::PointerFocusTarget, FocusDirection},
+    CosmicMapped, CosmicMappedRenderElement, Shell, Workspace,
+};
+use crate::{
+    backend::render::{
+        element::AsGlowRenderer, BackdropShader, IndicatorShader, Key, FOCUS_INDICATOR_COLOR,
+    },
+    utils::prelude::*,
+    wayland::protocols::workspace::WorkspaceHandle,
+};
+
+/// Gap between windows and workspaces in the overview
+const GAP: i32 = 32;
+/// Share of the output height taken by the workspace strip
+const STRIP_RATIO: f64 = 0.15;
+/// Distance the pointer has to travel, before a pressed window is dragged
+const DRAG_THRESHOLD: f64 = 8.0;
+
+/// Exposé-like overview of the windows of every output and their workspaces
+#[derive(Debug)]
+pub struct Expose {
+    /// Window under the pointer or selected via keyboard
+    pub selected: Option<CosmicMapped>,
+    drag: Option<ExposeDrag>,
+    /// Seats, whose pointer is grabbed by the overview
+    grabs: Vec<Seat<State>>,
+    backdrop_id: Id,
+    /// Render cache keys of the indicator and backdrop of every workspace thumbnail,
+    /// separate from the ones of the workspace itself and dropped with the overview
+    thumbnail_keys: RefCell<HashMap<WorkspaceHandle, (Arc<()>, Arc<()>)>>,
+}
+
+#[derive(Debug)]
+struct ExposeDrag {
+    window: CosmicMapped,
+    output: Output,
+    /// Size of the window in the overview
+    size: Size<i32, Logical>,
+    /// Pointer position relative to the window in the overview
+    offset: Point<f64, Logical>,
+    start: Point<f64, Logical>,
+    position: Point<f64, Logical>,
+    moved: bool,
+}
+
+/// Output-local placement of the overview on a single output
+#[derive(Debug)]
+pub struct ExposeLayout {
+    /// Windows of the active workspace, scaled down into a grid
+    pub windows: Vec<(CosmicMapped, Rectangle<i32, Logical>)>,
+    /// Thumbnails of all workspaces of the output, by index
+    pub workspaces: Vec<Rectangle<i32, Logical>>,
+}
+
+impl ExposeLayout {
+    pub fn new(shell: &Shell, output: &Output) -> ExposeLayout {
+        let zone = layer_map_for_output(output).non_exclusive_zone();
+        let output_size = output.geometry().size;
+        let count = shell.workspaces.spaces_for_output(output).count() as i32;
+
+        // workspace strip along the bottom, shrunk if it doesn't fit
+        let mut scale = zone.size.h as f64 * STRIP_RATIO / output_size.h as f64;
+        let max_width = zone.size.w - 2 * GAP - (count - 1) * GAP;
+        if (output_size.w as f64 * scale) as i32 * count > max_width {
+            scale = max_width as f64 / count as f64 / output_size.w as f64;
+        }
+        let thumbnail = Size::<i32, Logical>::from((
+            (output_size.w as f64 * scale).round() as i32,
+            (output_size.h as f64 * scale).round() as i32,
+        ));
+        let strip_width = count * thumbnail.w + (count - 1) * GAP;
+        let strip_loc = Point::<i32, Logical>::from((
+            zone.loc.x + (zone.size.w - strip_width) / 2,
+            zone.loc.y + zone.size.h - GAP - thumbnail.h,
+        ));
+        let workspaces = (0..count)
+            .map(|i| {
+                Rectangle::from_loc_and_size(
+                    strip_loc + Point::from((i * (thumbnail.w + GAP), 0)),
+                    thumbnail,
+                )
+            })
+            .collect();
+
+        let area = Rectangle::from_loc_and_size(
+            (zone.loc.x + GAP, zone.loc.y + GAP),
+            (zone.size.w - 2 * GAP, strip_loc.y - zone.loc.y - 2 * GAP),
+        );
+        let workspace = shell.active_space(output);
+        let mapped = workspace
+            .mapped()
+            .filter(|mapped| workspace.outputs_for_element(mapped).any(|o| &o == output))
+            .cloned()
+            .collect::<Vec<_>>();
+        let sizes = mapped
+            .iter()
+            .map(|mapped| mapped.geometry().size)
+            .collect::<Vec<_>>();
+        let windows = mapped.into_iter().zip(grid(&sizes, area)).collect();
+
+        ExposeLayout {
+            windows,
+            workspaces,
+        }
+    }
+
+    pub fn window_under(
+        &self,
+        location: Point<f64, Logical>,
+    ) -> Option<&(CosmicMapped, Rectangle<i32, Logical>)> {
+        self.windows
+            .iter()
+            .find(|(_, geo)| geo.to_f64().contains(location))
+    }
+
+    pub fn workspace_under(&self, location: Point<f64, Logical>) -> Option<usize> {
+        self.workspaces
+            .iter()
+            .position(|geo| geo.to_f64().contains(location))
+    }
+
+    /// Closest window in `direction` of `from`, or the first window without a selection
+    fn neighbor(
+        &self,
+        from: Option<&CosmicMapped>,
+        direction: FocusDirection,
+    ) -> Option<CosmicMapped> {
+        let current = from.and_then(|from| self.windows.iter().find(|(m, _)| m == from));
+        let Some((current, geo)) = current else {
+            return self.windows.first().map(|(mapped, _)| mapped.clone());
+        };
+
+        let center = |geo: &Rectangle<i32, Logical>| {
+            Point::<i32, Logical>::from((geo.loc.x + geo.size.w / 2, geo.loc.y + geo.size.h / 2))
+        };
+        let origin = center(geo);
+        self.windows
+            .iter()
+            .filter(|(mapped, _)| mapped != current)
+            .filter_map(|(mapped, geo)| {
+                let offset = center(geo) - origin;
+                let (primary, secondary) = match direction {
+                    FocusDirection::Left => (-offset.x, offset.y),
+                    FocusDirection::Right => (offset.x, offset.y),
+                    FocusDirection::Up => (-offset.y, offset.x),
+                    FocusDirection::Down => (offset.y, offset.x),
+                    _ => return None,
+                };
+                (primary > 0).then_some((primary + 2 * secondary.abs(), mapped))
+            })
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, mapped)| mapped.clone())
+            .or_else(|| Some(current.clone()))
+    }
+}
+
+/// Fits elements of the given sizes into a grid on `area`, keeping their aspect ratios
+fn grid(
+    sizes: &[Size<i32, Logical>],
+    area: Rectangle<i32, Logical>,
+) -> Vec<Rectangle<i32, Logical>> {
+    let count = sizes.len() as i32;
+    if count == 0 {
+        return Vec::new();
+    }
+
+    let columns = (count as f64).sqrt().ceil() as i32;
+    let rows = (count + columns - 1) / columns;
+    let cell = Size::<i32, Logical>::from((
+        ((area.size.w - (columns - 1) * GAP) / columns).max(1),
+        ((area.size.h - (rows - 1) * GAP) / rows).max(1),
+    ));
+
+    sizes
+        .iter()
+        .enumerate()
+        .map(|(i, size)| {
+            let (row, column) = (i as i32 / columns, i as i32 % columns);
+            // the last row might not be full, center it
+            let in_row = (count - row * columns).min(columns);
+            let row_offset = (columns - in_row) * (cell.w + GAP) / 2;
+
+            let scale = f64::min(
+                cell.w as f64 / size.w.max(1) as f64,
+                cell.h as f64 / size.h.max(1) as f64,
+            )
+            .min(1.0);
+            let scaled = Size::<i32, Logical>::from((
+                (size.w as f64 * scale).round() as i32,
+                (size.h as f64 * scale).round() as i32,
+            ));
+            let cell_loc = area.loc
+                + Point::from((row_offset + column * (cell.w + GAP), row * (cell.h + GAP)));
+            Rectangle::from_loc_and_size(
+                cell_loc + Point::from(((cell.w - scaled.w) / 2, (cell.h - scaled.h) / 2)),
+                scaled,
+            )
+        })
+        .collect()
+}
+
+impl Expose {
+    /// Opens the overview on all outputs
+    pub fn open(state: &mut State) {
+        if state.common.shell.expose.is_some() {
+            return;
+        }
+
+        let seat = state.common.last_active_seat().clone();
+        let selected = state
+            .common
+            .shell
+            .active_space(&seat.active_output())
+            .focus_stack
+            .get(&seat)
+            .last()
+            .cloned();
+
+        let serial = SERIAL_COUNTER.next_serial();
+        let mut grabs = Vec::new();
+        for seat in state.common.seats().cloned().collect::<Vec<_>>() {
+            let Some(pointer) = seat.get_pointer() else { continue };
+            if pointer.is_grabbed() {
+                continue;
+            }
+            let grab = ExposeGrab {
+                start_data: PointerGrabStartData {
+                    focus: None,
+                    button: 0,
+                    location: pointer.current_location(),
+                },
+                seat: seat.clone(),
+            };
+            pointer.set_grab(state, grab, serial, Focus::Clear);
+            grabs.push(seat);
+        }
+
+        state.common.shell.expose = Some(Expose {
+            selected,
+            drag: None,
+            grabs,
+            backdrop_id: state.common.shell.expose_backdrop_id.clone(),
+            thumbnail_keys: RefCell::new(HashMap::new()),
+        });
+    }
+
+    /// Closes the overview, must not be called from within a pointer grab
+    pub fn close(state: &mut State) {
+        let Some(expose) = state.common.shell.expose.take() else { return };
+
+        let serial = SERIAL_COUNTER.next_serial();
+        let time = Duration::from(state.common.clock.now()).as_millis() as u32;
+        for seat in expose.grabs {
+            if let Some(pointer) = seat.get_pointer().filter(|pointer| pointer.is_grabbed()) {
+                pointer.unset_grab(state, serial, time);
+            }
+        }
+    }
+
+    pub fn toggle(state: &mut State) {
+        if state.common.shell.expose.is_some() {
+            Expose::close(state);
+        } else {
+            Expose::open(state);
+        }
+    }
+
+    /// Whether the overview reacts to the given key
+    pub fn handles_key(keysym: u32) -> bool {
+        matches!(
+            keysym,
+            keysyms::KEY_Escape
+                | keysyms::KEY_Return
+                | keysyms::KEY_KP_Enter
+                | keysyms::KEY_Left
+                | keysyms::KEY_Right
+                | keysyms::KEY_Up
+                | keysyms::KEY_Down
+                | keysyms::KEY_1..=keysyms::KEY_9
+        )
+    }
+
+    /// Handles a key press while the overview is open.
+    ///
+    /// Arrows select a window on the active output, return focuses it and
+    /// number keys send it to the workspace of that number.
+    pub fn handle_key(state: &mut State, seat: &Seat<State>, keysym: u32) {
+        let output = seat.active_output();
+        let Some(selected) = state.common.shell.expose.as_ref().map(|e| e.selected.clone()) else {
+            return;
+        };
+        let selected = selected.filter(|mapped| {
+            mapped.alive()
+                && state
+                    .common
+                    .shell
+                    .active_space(&output)
+                    .mapped()
+                    .any(|m| m == mapped)
+        });
+
+        let direction = match keysym {
+            keysyms::KEY_Escape => return Expose::close(state),
+            keysyms::KEY_Return | keysyms::KEY_KP_Enter => {
+                Expose::close(state);
+                if let Some(mapped) = selected {
+                    Shell::activate_window(state, &mapped.active_window(), seat);
+                }
+                return;
+            }
+            keysyms::KEY_1..=keysyms::KEY_9 => {
+                if let Some(mapped) = selected {
+                    let idx = (keysym - keysyms::KEY_1) as usize;
+                    let _ = Shell::move_window(
+                        state,
+                        seat,
+                        &mapped,
+                        &output,
+                        (&output, Some(idx)),
+                        false,
+                        None,
+                    );
+                }
+                return;
+            }
+            keysyms::KEY_Left => FocusDirection::Left,
+            keysyms::KEY_Right => FocusDirection::Right,
+            keysyms::KEY_Up => FocusDirection::Up,
+            keysyms::KEY_Down => FocusDirection::Down,
+            _ => return,
+        };
+
+        let next =
+            ExposeLayout::new(&state.common.shell, &output).neighbor(selected.as_ref(), direction);
+        if let Some(expose) = state.common.shell.expose.as_mut() {
+            expose.selected = next;
+        }
+    }
+
+    fn pointer_motion(state: &mut State, location: Point<f64, Logical>) {
+        let Some(output) = output_at(&state.common.shell, location) else { return };
+        let local = location - output.geometry().loc.to_f64();
+        let hovered = ExposeLayout::new(&state.common.shell, &output)
+            .window_under(local)
+            .map(|(mapped, _)| mapped.clone());
+
+        let Some(expose) = state.common.shell.expose.as_mut() else { return };
+        match expose.drag.as_mut() {
+            Some(drag) => {
+                drag.position = location;
+                let distance = location - drag.start;
+                if distance.x.hypot(distance.y) > DRAG_THRESHOLD {
+                    drag.moved = true;
+                }
+            }
+            None => {
+                if hovered.is_some() {
+                    expose.selected = hovered;
+                }
+            }
+        }
+    }
+
+    /// Handles a button, returns whether the overview should be closed
+    fn button(
+        state: &mut State,
+        seat: &Seat<State>,
+        location: Point<f64, Logical>,
+        pressed: bool,
+    ) -> bool {
+        let Some(output) = output_at(&state.common.shell, location) else { return false };
+        let local = location - output.geometry().loc.to_f64();
+        let layout = ExposeLayout::new(&state.common.shell, &output);
+
+        if pressed {
+            if let Some((mapped, geo)) = layout.window_under(local) {
+                let drag = ExposeDrag {
+                    window: mapped.clone(),
+                    output: output.clone(),
+                    size: geo.size,
+                    offset: local - geo.loc.to_f64(),
+                    start: location,
+                    position: location,
+                    moved: false,
+                };
+                if let Some(expose) = state.common.shell.expose.as_mut() {
+                    expose.selected = Some(mapped.clone());
+                    expose.drag = Some(drag);
+                }
+                false
+            } else if let Some(idx) = layout.workspace_under(local) {
+                let _ = state.common.shell.activate(&output, idx);
+                false
+            } else {
+                // clicking the background leaves the overview
+                true
+            }
+        } else {
+            let Some(drag) = state.common.shell.expose.as_mut().and_then(|e| e.drag.take()) else {
+                return false;
+            };
+            if !drag.window.alive() {
+                return false;
+            }
+
+            if !drag.moved {
+                Shell::activate_window(state, &drag.window.active_window(), seat);
+                return true;
+            }
+            if let Some(idx) = layout.workspace_under(local) {
+                let _ = Shell::move_window(
+                    state,
+                    seat,
+                    &drag.window,
+                    &drag.output,
+                    (&output, Some(idx)),
+                    false,
+                    None,
+                );
+            }
+            false
+        }
+    }
+
+    pub fn render_elements<R>(
+        &self,
+        shell: &Shell,
+        renderer: &mut R,
+        output: &Output,
+        indicator_thickness: u8,
+    ) -> Vec<CosmicMappedRenderElement<R>>
+    where
+        R: Renderer + ImportAll + ImportMem + AsGlowRenderer,
+        <R as Renderer>::TextureId: 'static,
+        CosmicMappedRenderElement<R>: RenderElement<R>,
+    {
+        let output_geo = output.geometry();
+        let output_scale = output.current_scale().fractional_scale();
+        let layout = ExposeLayout::new(shell, output);
+        let mut elements = Vec::new();
+
+        // the dragged window follows the pointer
+        let drag = self.drag.as_ref().filter(|drag| drag.moved);
+        if let Some(drag) = drag.filter(|drag| output_geo.to_f64().contains(drag.position)) {
+            let loc = (drag.position - drag.offset - output_geo.loc.to_f64()).to_i32_round();
+            elements.extend(render_scaled(
+                renderer,
+                &drag.window,
+                Rectangle::from_loc_and_size(loc, drag.size),
+                output_scale,
+            ));
+        }
+
+        let drop_target = drag
+            .and_then(|drag| output_geo.to_f64().contains(drag.position).then_some(drag))
+            .and_then(|drag| layout.workspace_under(drag.position - output_geo.loc.to_f64()));
+
+        for (mapped, geo) in layout.windows.iter() {
+            if drag.map(|drag| &drag.window == mapped).unwrap_or(false) {
+                continue;
+            }
+            if self.selected.as_ref() == Some(mapped) && indicator_thickness > 0 {
+                elements.push(CosmicMappedRenderElement::from(
+                    IndicatorShader::focus_element(
+                        renderer,
+                        mapped.clone(),
+                        *geo,
+                        indicator_thickness,
+                        1.0,
+                    ),
+                ));
+            }
+            elements.extend(render_scaled(renderer, mapped, *geo, output_scale));
+        }
+
+        let active = shell.workspaces.active_num(output).1;
+        for ((idx, workspace), geo) in shell
+            .workspaces
+            .spaces_for_output(output)
+            .enumerate()
+            .zip(layout.workspaces.iter())
+        {
+            let (indicator_key, backdrop_key) = self.thumbnail_keys(workspace);
+            if idx == active || drop_target == Some(idx) {
+                elements.push(CosmicMappedRenderElement::from(IndicatorShader::element(
+                    renderer,
+                    indicator_key,
+                    *geo,
+                    indicator_thickness.max(1),
+                    indicator_thickness.max(1) * 2,
+                    1.0,
+                    FOCUS_INDICATOR_COLOR,
+                )));
+            }
+            elements.extend(render_workspace(
+                renderer,
+                workspace,
+                output,
+                *geo,
+                output_scale,
+            ));
+            elements.push(CosmicMappedRenderElement::from(BackdropShader::element(
+                renderer,
+                backdrop_key,
+                *geo,
+                (indicator_thickness.max(1) * 2) as f32,
+                0.5,
+                [0.0, 0.0, 0.0],
+            )));
+        }
+
+        // dim the background layers
+        elements.push(CosmicMappedRenderElement::from(BackdropShader::element(
+            renderer,
+            Key::Static(self.backdrop_id.clone()),
+            Rectangle::from_loc_and_size((0, 0), output_geo.size),
+            0.,
+            0.6,
+            [0.0, 0.0, 0.0],
+        )));
+
+        elements
+    }
+
+    fn thumbnail_keys(&self, workspace: &Workspace) -> (Key, Key) {
+        let mut keys = self.thumbnail_keys.borrow_mut();
+        let (indicator, backdrop) = keys
+            .entry(workspace.handle)
+            .or_insert_with(|| (Arc::new(()), Arc::new(())));
+        (
+            Key::Group(Arc::downgrade(indicator)),
+            Key::Group(Arc::downgrade(backdrop)),
+        )
+    }
+}
+
+fn output_at(shell: &Shell, location: Point<f64, Logical>) -> Option<Output> {
+    shell
+        .outputs()
+        .find(|output| output.geometry().to_f64().contains(location))
+        .cloned()
+}
+
+/// Renders the windows of `workspace` on `output` shrunk into `geo`
+fn render_workspace<R>(
+    renderer: &mut R,
+    workspace: &Workspace,
+    output: &Output,
+    geo: Rectangle<i32, Logical>,
+    output_scale: f64,
+) -> Vec<CosmicMappedRenderElement<R>>
+where
+    R: Renderer + ImportAll + ImportMem + AsGlowRenderer,
+    <R as Renderer>::TextureId: 'static,
+    CosmicMappedRenderElement<R>: RenderElement<R>,
+{
+    let Some(space_geo) = workspace.floating_layer.space.output_geometry(output) else {
+        return Vec::new();
+    };
+    let scale = geo.size.w as f64 / output.geometry().size.w as f64;
+
+    // floating windows are above tiled ones
+    workspace
+        .mapped()
+        .filter(|mapped| !workspace.is_fullscreen(mapped))
+        .filter_map(|mapped| {
+            let element_geo = workspace
+                .floating_layer
+                .space
+                .element_geometry(mapped)
+                .or_else(|| workspace.tiling_layer.element_geometry(mapped))?;
+            let loc = element_geo.loc - space_geo.loc;
+            let thumbnail = Rectangle::from_loc_and_size(
+                geo.loc
+                    + Point::from((
+                        (loc.x as f64 * scale).round() as i32,
+                        (loc.y as f64 * scale).round() as i32,
+                    )),
+                (
+                    (element_geo.size.w as f64 * scale).round() as i32,
+                    (element_geo.size.h as f64 * scale).round() as i32,
+                ),
+            );
+            geo.overlaps(thumbnail).then_some((mapped, thumbnail))
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .flat_map(|(mapped, thumbnail)| render_scaled(renderer, mapped, thumbnail, output_scale))
+        .collect()
+}
+
+/// Renders `mapped` shrunk into the output-local `geo`
+fn render_scaled<R>(
+    renderer: &mut R,
+    mapped: &CosmicMapped,
+    geo: Rectangle<i32, Logical>,
+    output_scale: f64,
+) -> Vec<CosmicMappedRenderElement<R>>
+where
+    R: Renderer + ImportAll + ImportMem + AsGlowRenderer,
+    <R as Renderer>::TextureId: 'static,
+    CosmicMappedRenderElement<R>: RenderElement<R>,
+{
+    let mapped_geo = mapped.geometry();
+    if mapped_geo.size.w <= 0 {
+        return Vec::new();
+    }
+    let scale = geo.size.w as f64 / mapped_geo.size.w as f64;
+    let location = geo.loc.to_physical_precise_round(output_scale);
+    let crop = Rectangle::from_loc_and_size(geo.loc, mapped_geo.size)
+        .to_physical_precise_round(output_scale);
+
+    AsRenderElements::<R>::render_elements::<CosmicMappedRenderElement<R>>(
+        mapped,
+        renderer,
+        location - mapped_geo.loc.to_physical_precise_round(output_scale),
+        Scale::from(output_scale),
+        1.0,
+    )
+    .into_iter()
+    .filter_map(|element| match element {
+        CosmicMappedRenderElement::Stack(elem) => Some(CosmicMappedRenderElement::TiledStack({
+            let cropped = CropRenderElement::from_element(elem, output_scale, crop)?;
+            let rescaled = RescaleRenderElement::from_element(cropped, location, scale);
+            RelocateRenderElement::from_element(rescaled, (0, 0), Relocate::Relative)
+        })),
+        CosmicMappedRenderElement::Window(elem) => Some(CosmicMappedRenderElement::TiledWindow({
+            let cropped = CropRenderElement::from_element(elem, output_scale, crop)?;
+            let rescaled = RescaleRenderElement::from_element(cropped, location, scale);
+            RelocateRenderElement::from_element(rescaled, (0, 0), Relocate::Relative)
+        })),
+        _ => None,
+    })
+    .collect()
+}
+
+/// Pointer grab of the overview, keeping clients from receiving pointer events
+pub struct ExposeGrab {
+    start_data: PointerGrabStartData<State>,
+    seat: Seat<State>,
+}
+
+impl PointerGrab<State> for ExposeGrab {
+    fn motion(
+        &mut self,
+        state: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        _focus: Option<(PointerFocusTarget, Point<i32, Logical>)>,
+        event: &MotionEvent,
+    ) {
+        handle.motion(state, None, event);
+        if state.common.shell.expose.is_none() {
+            handle.unset_grab(state, event.serial, event.time);
+            return;
+        }
+        Expose::pointer_motion(state, event.location);
+    }
+
+    fn relative_motion(
+        &mut self,
+        state: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        _focus: Option<(PointerFocusTarget, Point<i32, Logical>)>,
+        event: &RelativeMotionEvent,
+    ) {
+        handle.relative_motion(state, None, event);
+    }
+
+    fn button(
+        &mut self,
+        state: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        event: &ButtonEvent,
+    ) {
+        handle.button(state, event);
+        if state.common.shell.expose.is_none() {
+            handle.unset_grab(state, event.serial, event.time);
+            return;
+        }
+
+        let pressed = event.state == ButtonState::Pressed;
+        if Expose::button(state, &self.seat, handle.current_location(), pressed) {
+            // releases the grabs of all seats, which can't happen from within this one
+            state
+                .common
+                .event_loop_handle
+                .insert_idle(|data| Expose::close(&mut data.state));
+        }
+    }
+
+    fn axis(
+        &mut self,
+        state: &mut State,
+        handle: &mut PointerInnerHandle<'_, State>,
+        details: AxisFrame,
+    ) {
+        handle.axis(state, details);
+    }
+
+    fn start_data(&self) -> &PointerGrabStartData<State> {
+        &self.start_data
+    }
+}
@@ -96,6 +96,13 @@ impl Shell {
         active_seat: &Seat<State>,
         serial: Option<Serial>,
     ) {
+        // nothing but the lock surfaces may receive focus while the session is locked
+        if state.common.shell.session_lock.is_some()
+            && !matches!(target, Some(KeyboardFocusTarget::LockSurface(_)))
+        {
+            return;
+        }
+
         // update FocusStack and notify layouts about new focus (if any window)
         if let Some(KeyboardFocusTarget::Element(mapped)) = target {
             if let Some(workspace) = state.common.shell.space_for_mut(mapped) {
@@ -162,8 +169,11 @@ impl Shell {
                 }
                 if workspace.floating_layer.mapped().any(|m| m == focused) {
                     workspace.floating_layer.space.raise_element(focused, true);
+                } else {
+                    workspace.tiling_layer.scroll_to(focused);
                 }
             }
+            workspace.floating_layer.raise_always_on_top();
             for window in workspace.mapped() {
                 window.set_activated(focused_windows.contains(&window));
                 window.configure();
@@ -197,6 +207,23 @@ impl Common {
             }
             let last_known_focus = ActiveFocus::get(&seat);
 
+            if let Some(session_lock) = state.common.shell.session_lock.as_ref() {
+                // prefer the lock surface of the active output, but any will do
+                let target = session_lock
+                    .surfaces
+                    .get(&output)
+                    .or_else(|| session_lock.surfaces.values().next())
+                    .map(|surface| KeyboardFocusTarget::LockSurface(surface.wl_surface().clone()));
+                if last_known_focus != target {
+                    if let Some(keyboard) = seat.get_keyboard() {
+                        debug!("Focusing lock surface {:?}", target.as_ref());
+                        keyboard.set_focus(state, target.clone(), SERIAL_COUNTER.next_serial());
+                        ActiveFocus::set(&seat, target);
+                    }
+                }
+                continue;
+            }
+
             if let Some(target) = last_known_focus {
                 if target.alive() {
                     match target {
@@ -242,6 +269,9 @@ impl Common {
                         KeyboardFocusTarget::Popup(_) => {
                             continue; // Focus is valid
                         }
+                        KeyboardFocusTarget::LockSurface(_) => {
+                            trace!("Session unlocked, focus fixup");
+                        }
                     };
                 } else {
                     trace!("Surface dead, focus fixup");
@@ -7,15 +7,20 @@ use crate::{
 use id_tree::NodeId;
 use smithay::{
     backend::input::KeyState,
-    desktop::{LayerSurface, PopupKind},
+    desktop::{LayerSurface, PopupKind, WindowSurfaceType},
     input::{
         keyboard::{KeyboardTarget, KeysymHandle, ModifiersState},
-        pointer::{AxisFrame, ButtonEvent, MotionEvent, PointerTarget, RelativeMotionEvent},
+        pointer::{
+            AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent,
+            GesturePinchBeginEvent, GesturePinchEndEvent, GesturePinchUpdateEvent,
+            GestureSwipeBeginEvent, GestureSwipeEndEvent, GestureSwipeUpdateEvent, MotionEvent,
+            PointerTarget, RelativeMotionEvent,
+        },
         Seat,
     },
     output::WeakOutput,
     reexports::wayland_server::{backend::ObjectId, protocol::wl_surface::WlSurface, Resource},
-    utils::{IsAlive, Serial},
+    utils::{IsAlive, Logical, Point, Serial},
     wayland::seat::WaylandFocus,
     xwayland::X11Surface,
 };
@@ -27,6 +32,7 @@ pub enum PointerFocusTarget {
     LayerSurface(LayerSurface),
     Popup(PopupKind),
     OverrideRedirect(X11Surface),
+    LockSurface(WlSurface),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -36,6 +42,7 @@ pub enum KeyboardFocusTarget {
     Group(WindowGroup),
     LayerSurface(LayerSurface),
     Popup(PopupKind),
+    LockSurface(WlSurface),
 }
 
 impl From<KeyboardFocusTarget> for PointerFocusTarget {
@@ -45,6 +52,7 @@ impl From<KeyboardFocusTarget> for PointerFocusTarget {
             KeyboardFocusTarget::Fullscreen(elem) => PointerFocusTarget::Fullscreen(elem),
             KeyboardFocusTarget::LayerSurface(layer) => PointerFocusTarget::LayerSurface(layer),
             KeyboardFocusTarget::Popup(popup) => PointerFocusTarget::Popup(popup),
+            KeyboardFocusTarget::LockSurface(surface) => PointerFocusTarget::LockSurface(surface),
             _ => unreachable!("A window grab cannot start a popup grab"),
         }
     }
@@ -66,6 +74,43 @@ impl PartialEq for WindowGroup {
     }
 }
 
+impl PointerFocusTarget {
+    /// Returns the client surface at `position` (in global coordinates) and its global location,
+    /// given this target is located at `location`.
+    ///
+    /// Returns `None` for compositor-drawn parts of an element, like server-side decorations or stack tabs.
+    pub fn wl_surface_under(
+        &self,
+        location: Point<i32, Logical>,
+        position: Point<f64, Logical>,
+    ) -> Option<(WlSurface, Point<i32, Logical>)> {
+        let relative = position - location.to_f64();
+        match self {
+            PointerFocusTarget::Element(mapped) => {
+                if !mapped.active_window_geometry().to_f64().contains(relative) {
+                    return None;
+                }
+                let offset = mapped.active_window_offset();
+                mapped
+                    .active_window()
+                    .wl_surface_under(relative - offset.to_f64())
+                    .map(|(surface, surface_loc)| (surface, location + offset + surface_loc))
+            }
+            PointerFocusTarget::Fullscreen(window) => window
+                .wl_surface_under(relative)
+                .map(|(surface, surface_loc)| (surface, location + surface_loc)),
+            PointerFocusTarget::LayerSurface(layer) => layer
+                .surface_under(relative, WindowSurfaceType::ALL)
+                .map(|(surface, surface_loc)| (surface, location + surface_loc)),
+            PointerFocusTarget::Popup(popup) => Some((popup.wl_surface().clone(), location)),
+            PointerFocusTarget::LockSurface(surface) => Some((surface.clone(), location)),
+            PointerFocusTarget::OverrideRedirect(surface) => {
+                surface.wl_surface().map(|surface| (surface, location))
+            }
+        }
+    }
+}
+
 impl IsAlive for PointerFocusTarget {
     fn alive(&self) -> bool {
         match self {
@@ -73,6 +118,7 @@ impl IsAlive for PointerFocusTarget {
             PointerFocusTarget::Fullscreen(f) => f.alive(),
             PointerFocusTarget::LayerSurface(l) => l.alive(),
             PointerFocusTarget::Popup(p) => p.alive(),
+            PointerFocusTarget::LockSurface(s) => s.alive(),
             PointerFocusTarget::OverrideRedirect(s) => s.alive(),
         }
     }
@@ -86,6 +132,7 @@ impl IsAlive for KeyboardFocusTarget {
             KeyboardFocusTarget::Group(g) => g.alive.upgrade().is_some(),
             KeyboardFocusTarget::LayerSurface(l) => l.alive(),
             KeyboardFocusTarget::Popup(p) => p.alive(),
+            KeyboardFocusTarget::LockSurface(s) => s.alive(),
         }
     }
 }
@@ -97,6 +144,7 @@ impl PointerTarget<State> for PointerFocusTarget {
             PointerFocusTarget::Fullscreen(w) => PointerTarget::enter(w, seat, data, event),
             PointerFocusTarget::LayerSurface(l) => PointerTarget::enter(l, seat, data, event),
             PointerFocusTarget::Popup(p) => PointerTarget::enter(p.wl_surface(), seat, data, event),
+            PointerFocusTarget::LockSurface(s) => PointerTarget::enter(s, seat, data, event),
             PointerFocusTarget::OverrideRedirect(s) => PointerTarget::enter(s, seat, data, event),
         }
     }
@@ -108,6 +156,7 @@ impl PointerTarget<State> for PointerFocusTarget {
             PointerFocusTarget::Popup(p) => {
                 PointerTarget::motion(p.wl_surface(), seat, data, event)
             }
+            PointerFocusTarget::LockSurface(s) => PointerTarget::motion(s, seat, data, event),
             PointerFocusTarget::OverrideRedirect(s) => PointerTarget::motion(s, seat, data, event),
         }
     }
@@ -123,6 +172,9 @@ impl PointerTarget<State> for PointerFocusTarget {
             PointerFocusTarget::Popup(p) => {
                 PointerTarget::relative_motion(p.wl_surface(), seat, data, event)
             }
+            PointerFocusTarget::LockSurface(s) => {
+                PointerTarget::relative_motion(s, seat, data, event)
+            }
             PointerFocusTarget::OverrideRedirect(s) => {
                 PointerTarget::relative_motion(s, seat, data, event)
             }
@@ -136,6 +188,7 @@ impl PointerTarget<State> for PointerFocusTarget {
             PointerFocusTarget::Popup(p) => {
                 PointerTarget::button(p.wl_surface(), seat, data, event)
             }
+            PointerFocusTarget::LockSurface(s) => PointerTarget::button(s, seat, data, event),
             PointerFocusTarget::OverrideRedirect(s) => PointerTarget::button(s, seat, data, event),
         }
     }
@@ -145,6 +198,7 @@ impl PointerTarget<State> for PointerFocusTarget {
             PointerFocusTarget::Fullscreen(w) => PointerTarget::axis(w, seat, data, frame),
             PointerFocusTarget::LayerSurface(l) => PointerTarget::axis(l, seat, data, frame),
             PointerFocusTarget::Popup(p) => PointerTarget::axis(p.wl_surface(), seat, data, frame),
+            PointerFocusTarget::LockSurface(s) => PointerTarget::axis(s, seat, data, frame),
             PointerFocusTarget::OverrideRedirect(s) => PointerTarget::axis(s, seat, data, frame),
         }
     }
@@ -158,11 +212,229 @@ impl PointerTarget<State> for PointerFocusTarget {
             PointerFocusTarget::Popup(p) => {
                 PointerTarget::leave(p.wl_surface(), seat, data, serial, time)
             }
+            PointerFocusTarget::LockSurface(s) => PointerTarget::leave(s, seat, data, serial, time),
             PointerFocusTarget::OverrideRedirect(s) => {
                 PointerTarget::leave(s, seat, data, serial, time)
             }
         }
     }
+
+    fn gesture_swipe_begin(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GestureSwipeBeginEvent,
+    ) {
+        match self {
+            PointerFocusTarget::Element(w) => {
+                PointerTarget::gesture_swipe_begin(w, seat, data, event)
+            }
+            PointerFocusTarget::Fullscreen(w) => {
+                PointerTarget::gesture_swipe_begin(w, seat, data, event)
+            }
+            PointerFocusTarget::LayerSurface(l) => {
+                PointerTarget::gesture_swipe_begin(l, seat, data, event)
+            }
+            PointerFocusTarget::Popup(p) => {
+                PointerTarget::gesture_swipe_begin(p.wl_surface(), seat, data, event)
+            }
+            PointerFocusTarget::LockSurface(s) => {
+                PointerTarget::gesture_swipe_begin(s, seat, data, event)
+            }
+            PointerFocusTarget::OverrideRedirect(s) => {
+                PointerTarget::gesture_swipe_begin(s, seat, data, event)
+            }
+        }
+    }
+
+    fn gesture_swipe_update(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GestureSwipeUpdateEvent,
+    ) {
+        match self {
+            PointerFocusTarget::Element(w) => {
+                PointerTarget::gesture_swipe_update(w, seat, data, event)
+            }
+            PointerFocusTarget::Fullscreen(w) => {
+                PointerTarget::gesture_swipe_update(w, seat, data, event)
+            }
+            PointerFocusTarget::LayerSurface(l) => {
+                PointerTarget::gesture_swipe_update(l, seat, data, event)
+            }
+            PointerFocusTarget::Popup(p) => {
+                PointerTarget::gesture_swipe_update(p.wl_surface(), seat, data, event)
+            }
+            PointerFocusTarget::LockSurface(s) => {
+                PointerTarget::gesture_swipe_update(s, seat, data, event)
+            }
+            PointerFocusTarget::OverrideRedirect(s) => {
+                PointerTarget::gesture_swipe_update(s, seat, data, event)
+            }
+        }
+    }
+
+    fn gesture_swipe_end(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GestureSwipeEndEvent,
+    ) {
+        match self {
+            PointerFocusTarget::Element(w) => {
+                PointerTarget::gesture_swipe_end(w, seat, data, event)
+            }
+            PointerFocusTarget::Fullscreen(w) => {
+                PointerTarget::gesture_swipe_end(w, seat, data, event)
+            }
+            PointerFocusTarget::LayerSurface(l) => {
+                PointerTarget::gesture_swipe_end(l, seat, data, event)
+            }
+            PointerFocusTarget::Popup(p) => {
+                PointerTarget::gesture_swipe_end(p.wl_surface(), seat, data, event)
+            }
+            PointerFocusTarget::LockSurface(s) => {
+                PointerTarget::gesture_swipe_end(s, seat, data, event)
+            }
+            PointerFocusTarget::OverrideRedirect(s) => {
+                PointerTarget::gesture_swipe_end(s, seat, data, event)
+            }
+        }
+    }
+
+    fn gesture_pinch_begin(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GesturePinchBeginEvent,
+    ) {
+        match self {
+            PointerFocusTarget::Element(w) => {
+                PointerTarget::gesture_pinch_begin(w, seat, data, event)
+            }
+            PointerFocusTarget::Fullscreen(w) => {
+                PointerTarget::gesture_pinch_begin(w, seat, data, event)
+            }
+            PointerFocusTarget::LayerSurface(l) => {
+                PointerTarget::gesture_pinch_begin(l, seat, data, event)
+            }
+            PointerFocusTarget::Popup(p) => {
+                PointerTarget::gesture_pinch_begin(p.wl_surface(), seat, data, event)
+            }
+            PointerFocusTarget::LockSurface(s) => {
+                PointerTarget::gesture_pinch_begin(s, seat, data, event)
+            }
+            PointerFocusTarget::OverrideRedirect(s) => {
+                PointerTarget::gesture_pinch_begin(s, seat, data, event)
+            }
+        }
+    }
+
+    fn gesture_pinch_update(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GesturePinchUpdateEvent,
+    ) {
+        match self {
+            PointerFocusTarget::Element(w) => {
+                PointerTarget::gesture_pinch_update(w, seat, data, event)
+            }
+            PointerFocusTarget::Fullscreen(w) => {
+                PointerTarget::gesture_pinch_update(w, seat, data, event)
+            }
+            PointerFocusTarget::LayerSurface(l) => {
+                PointerTarget::gesture_pinch_update(l, seat, data, event)
+            }
+            PointerFocusTarget::Popup(p) => {
+                PointerTarget::gesture_pinch_update(p.wl_surface(), seat, data, event)
+            }
+            PointerFocusTarget::LockSurface(s) => {
+                PointerTarget::gesture_pinch_update(s, seat, data, event)
+            }
+            PointerFocusTarget::OverrideRedirect(s) => {
+                PointerTarget::gesture_pinch_update(s, seat, data, event)
+            }
+        }
+    }
+
+    fn gesture_pinch_end(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GesturePinchEndEvent,
+    ) {
+        match self {
+            PointerFocusTarget::Element(w) => {
+                PointerTarget::gesture_pinch_end(w, seat, data, event)
+            }
+            PointerFocusTarget::Fullscreen(w) => {
+                PointerTarget::gesture_pinch_end(w, seat, data, event)
+            }
+            PointerFocusTarget::LayerSurface(l) => {
+                PointerTarget::gesture_pinch_end(l, seat, data, event)
+            }
+            PointerFocusTarget::Popup(p) => {
+                PointerTarget::gesture_pinch_end(p.wl_surface(), seat, data, event)
+            }
+            PointerFocusTarget::LockSurface(s) => {
+                PointerTarget::gesture_pinch_end(s, seat, data, event)
+            }
+            PointerFocusTarget::OverrideRedirect(s) => {
+                PointerTarget::gesture_pinch_end(s, seat, data, event)
+            }
+        }
+    }
+
+    fn gesture_hold_begin(
+        &self,
+        seat: &Seat<State>,
+        data: &mut State,
+        event: &GestureHoldBeginEvent,
+    ) {
+        match self {
+            PointerFocusTarget::Element(w) => {
+                PointerTarget::gesture_hold_begin(w, seat, data, event)
+            }
+            PointerFocusTarget::Fullscreen(w) => {
+                PointerTarget::gesture_hold_begin(w, seat, data, event)
+            }
+            PointerFocusTarget::LayerSurface(l) => {
+                PointerTarget::gesture_hold_begin(l, seat, data, event)
+            }
+            PointerFocusTarget::Popup(p) => {
+                PointerTarget::gesture_hold_begin(p.wl_surface(), seat, data, event)
+            }
+            PointerFocusTarget::LockSurface(s) => {
+                PointerTarget::gesture_hold_begin(s, seat, data, event)
+            }
+            PointerFocusTarget::OverrideRedirect(s) => {
+                PointerTarget::gesture_hold_begin(s, seat, data, event)
+            }
+        }
+    }
+
+    fn gesture_hold_end(&self, seat: &Seat<State>, data: &mut State, event: &GestureHoldEndEvent) {
+        match self {
+            PointerFocusTarget::Element(w) => PointerTarget::gesture_hold_end(w, seat, data, event),
+            PointerFocusTarget::Fullscreen(w) => {
+                PointerTarget::gesture_hold_end(w, seat, data, event)
+            }
+            PointerFocusTarget::LayerSurface(l) => {
+                PointerTarget::gesture_hold_end(l, seat, data, event)
+            }
+            PointerFocusTarget::Popup(p) => {
+                PointerTarget::gesture_hold_end(p.wl_surface(), seat, data, event)
+            }
+            PointerFocusTarget::LockSurface(s) => {
+                PointerTarget::gesture_hold_end(s, seat, data, event)
+            }
+            PointerFocusTarget::OverrideRedirect(s) => {
+                PointerTarget::gesture_hold_end(s, seat, data, event)
+            }
+        }
+    }
 }
 
 impl KeyboardTarget<State> for KeyboardFocusTarget {
@@ -185,6 +457,9 @@ impl KeyboardTarget<State> for KeyboardFocusTarget {
             KeyboardFocusTarget::Popup(p) => {
                 KeyboardTarget::enter(p.wl_surface(), seat, data, keys, serial)
             }
+            KeyboardFocusTarget::LockSurface(s) => {
+                KeyboardTarget::enter(s, seat, data, keys, serial)
+            }
         }
     }
     fn leave(&self, seat: &Seat<State>, data: &mut State, serial: Serial) {
@@ -196,6 +471,7 @@ impl KeyboardTarget<State> for KeyboardFocusTarget {
             KeyboardFocusTarget::Popup(p) => {
                 KeyboardTarget::leave(p.wl_surface(), seat, data, serial)
             }
+            KeyboardFocusTarget::LockSurface(s) => KeyboardTarget::leave(s, seat, data, serial),
         }
     }
     fn key(
@@ -221,6 +497,9 @@ impl KeyboardTarget<State> for KeyboardFocusTarget {
             KeyboardFocusTarget::Popup(p) => {
                 KeyboardTarget::key(p.wl_surface(), seat, data, key, state, serial, time)
             }
+            KeyboardFocusTarget::LockSurface(s) => {
+                KeyboardTarget::key(s, seat, data, key, state, serial, time)
+            }
         }
     }
     fn modifiers(
@@ -244,6 +523,9 @@ impl KeyboardTarget<State> for KeyboardFocusTarget {
             KeyboardFocusTarget::Popup(p) => {
                 KeyboardTarget::modifiers(p.wl_surface(), seat, data, modifiers, serial)
             }
+            KeyboardFocusTarget::LockSurface(s) => {
+                KeyboardTarget::modifiers(s, seat, data, modifiers, serial)
+            }
         }
     }
 }
@@ -256,6 +538,7 @@ impl WaylandFocus for KeyboardFocusTarget {
             KeyboardFocusTarget::Group(_) => None,
             KeyboardFocusTarget::LayerSurface(l) => Some(l.wl_surface().clone()),
             KeyboardFocusTarget::Popup(p) => Some(p.wl_surface().clone()),
+            KeyboardFocusTarget::LockSurface(s) => Some(s.clone()),
         }
     }
     fn same_client_as(&self, object_id: &ObjectId) -> bool {
@@ -265,6 +548,7 @@ impl WaylandFocus for KeyboardFocusTarget {
             KeyboardFocusTarget::Group(_) => false,
             KeyboardFocusTarget::LayerSurface(l) => l.wl_surface().id().same_client_as(object_id),
             KeyboardFocusTarget::Popup(p) => p.wl_surface().id().same_client_as(object_id),
+            KeyboardFocusTarget::LockSurface(s) => s.id().same_client_as(object_id),
         }
     }
 }
@@ -276,6 +560,7 @@ impl WaylandFocus for PointerFocusTarget {
             PointerFocusTarget::Fullscreen(w) => WaylandFocus::wl_surface(w)?,
             PointerFocusTarget::LayerSurface(l) => l.wl_surface().clone(),
             PointerFocusTarget::Popup(p) => p.wl_surface().clone(),
+            PointerFocusTarget::LockSurface(s) => s.clone(),
             PointerFocusTarget::OverrideRedirect(s) => {
                 return s.wl_surface();
             }
@@ -287,6 +572,7 @@ impl WaylandFocus for PointerFocusTarget {
             PointerFocusTarget::Fullscreen(w) => WaylandFocus::same_client_as(w, object_id),
             PointerFocusTarget::LayerSurface(l) => l.wl_surface().id().same_client_as(object_id),
             PointerFocusTarget::Popup(p) => p.wl_surface().id().same_client_as(object_id),
+            PointerFocusTarget::LockSurface(s) => s.id().same_client_as(object_id),
             PointerFocusTarget::OverrideRedirect(s) => WaylandFocus::same_client_as(s, object_id),
         }
     }
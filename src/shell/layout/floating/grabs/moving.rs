@@ -1,10 +1,13 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use crate::{
-    backend::render::{element::AsGlowRenderer, IndicatorShader},
+    backend::render::{
+        element::AsGlowRenderer, BackdropShader, IndicatorShader, Key, FOCUS_INDICATOR_COLOR,
+    },
     shell::{
         element::CosmicMappedRenderElement,
         focus::target::{KeyboardFocusTarget, PointerFocusTarget},
+        layout::floating::SnapZone,
         CosmicMapped, CosmicSurface,
     },
     utils::prelude::*,
@@ -12,7 +15,7 @@ use crate::{
 
 use smithay::{
     backend::renderer::{
-        element::{AsRenderElements, RenderElement},
+        element::{AsRenderElements, Id, RenderElement},
         ImportAll, ImportMem, Renderer,
     },
     desktop::space::SpaceElement,
@@ -32,10 +35,29 @@ use std::{cell::RefCell, collections::HashSet, time::Duration};
 
 pub type SeatMoveGrabState = RefCell<Option<MoveGrabState>>;
 
+/// Render ids of the snap preview, kept per seat so grabs reuse the same shader cache entries
+pub struct SnapPreviewIds {
+    indicator: Id,
+    backdrop: Id,
+}
+
+impl Default for SnapPreviewIds {
+    fn default() -> Self {
+        SnapPreviewIds {
+            indicator: Id::new(),
+            backdrop: Id::new(),
+        }
+    }
+}
+
 pub struct MoveGrabState {
     window: CosmicMapped,
     window_offset: Point<i32, Logical>,
     indicator_thickness: u8,
+    /// Zone the window snaps to, if released now
+    snap: Option<(Output, SnapZone)>,
+    preview_indicator_id: Id,
+    preview_backdrop_id: Id,
 }
 
 impl MoveGrabState {
@@ -80,6 +102,38 @@ impl MoveGrabState {
             scale,
             1.0,
         ));
+
+        // preview of the snapped window, below the window itself
+        if let Some((_, zone)) = self.snap.as_ref().filter(|(o, _)| o == output) {
+            let mut geo = zone.geometry(output);
+            geo.loc -= output.geometry().loc;
+            if self.indicator_thickness > 0 {
+                elements.push(
+                    CosmicMappedRenderElement::from(IndicatorShader::element(
+                        renderer,
+                        Key::Static(self.preview_indicator_id.clone()),
+                        geo,
+                        self.indicator_thickness,
+                        self.indicator_thickness * 2,
+                        1.0,
+                        FOCUS_INDICATOR_COLOR,
+                    ))
+                    .into(),
+                );
+            }
+            elements.push(
+                CosmicMappedRenderElement::from(BackdropShader::element(
+                    renderer,
+                    Key::Static(self.preview_backdrop_id.clone()),
+                    geo,
+                    (self.indicator_thickness * 2) as f32,
+                    0.3,
+                    FOCUS_INDICATOR_COLOR,
+                ))
+                .into(),
+            );
+        }
+
         elements
     }
 
@@ -115,12 +169,20 @@ impl PointerGrab<State> for MoveSurfaceGrab {
         _focus: Option<(PointerFocusTarget, Point<i32, Logical>)>,
         event: &MotionEvent,
     ) {
-        let borrow = self
+        let mut borrow = self
             .seat
             .user_data()
             .get::<SeatMoveGrabState>()
-            .map(|s| s.borrow());
-        if let Some(grab_state) = borrow.as_ref().and_then(|s| s.as_ref()) {
+            .map(|s| s.borrow_mut());
+        if let Some(grab_state) = borrow.as_mut().and_then(|s| s.as_mut()) {
+            let outputs = state.common.shell.outputs().cloned().collect::<Vec<_>>();
+            grab_state.snap = outputs
+                .iter()
+                .find(|o| o.geometry().to_f64().contains(event.location))
+                .and_then(|o| {
+                    SnapZone::at(o, &outputs, event.location).map(|zone| (o.clone(), zone))
+                });
+
             let mut window_geo = self.window.geometry();
             window_geo.loc += event.location.to_i32_round() + grab_state.window_offset;
             for output in state.common.shell.outputs() {
@@ -192,12 +254,16 @@ impl MoveSurfaceGrab {
         let mut outputs = HashSet::new();
         outputs.insert(output.clone());
         window.output_enter(&output, window.geometry()); // not accurate but...
+        let preview_ids = seat.user_data().get::<SnapPreviewIds>().unwrap();
 
         let grab_state = MoveGrabState {
             window: window.clone(),
             window_offset: dbg!(initial_window_location)
                 - dbg!(initial_cursor_location.to_i32_round()),
             indicator_thickness,
+            snap: None,
+            preview_indicator_id: preview_ids.indicator.clone(),
+            preview_backdrop_id: preview_ids.backdrop.clone(),
         };
 
         *seat
@@ -222,7 +288,7 @@ impl MoveSurfaceGrab {
         time: u32,
     ) {
         // No more buttons are pressed, release the grab.
-        let output = self.seat.active_output();
+        let mut output = self.seat.active_output();
 
         let position = if let Some(grab_state) = self
             .seat
@@ -230,7 +296,41 @@ impl MoveSurfaceGrab {
             .get::<SeatMoveGrabState>()
             .and_then(|s| s.borrow_mut().take())
         {
-            if grab_state.window.alive() {
+            if let Some((snap_output, zone)) = grab_state
+                .snap
+                .clone()
+                .filter(|_| grab_state.window.alive())
+            {
+                output = snap_output;
+                let geometry = zone.geometry(&output);
+                let mut pre_snap_size = grab_state.window.pre_snap_size.lock().unwrap();
+                if pre_snap_size.is_none() {
+                    *pre_snap_size = Some(grab_state.window.geometry().size);
+                }
+                std::mem::drop(pre_snap_size);
+
+                let workspace_handle = state.common.shell.active_space(&output).handle;
+                for (window, _) in grab_state.window.windows() {
+                    state
+                        .common
+                        .shell
+                        .toplevel_info_state
+                        .toplevel_enter_workspace(&window, &workspace_handle);
+                    state
+                        .common
+                        .shell
+                        .toplevel_info_state
+                        .toplevel_enter_output(&window, &output);
+                }
+
+                state
+                    .common
+                    .shell
+                    .active_space_mut(&output)
+                    .floating_layer
+                    .map_snapped(grab_state.window, &output, geometry);
+                Some(geometry.loc)
+            } else if grab_state.window.alive() {
                 let window_location = handle.current_location().to_i32_round()
                     - output.geometry().loc
                     + grab_state.window_offset;
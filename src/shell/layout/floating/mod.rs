@@ -14,6 +14,7 @@ use std::collections::HashMap;
 
 use crate::{
     backend::render::{element::AsGlowRenderer, IndicatorShader},
+    config::OutputInfo,
     shell::{
         element::{
             stack::CosmicStackRenderElement, window::CosmicWindowRenderElement, CosmicMapped,
@@ -29,10 +30,14 @@ use crate::{
 
 mod grabs;
 pub use self::grabs::*;
+mod snap;
+pub use self::snap::*;
 
 #[derive(Debug, Default)]
 pub struct FloatingLayout {
     pub(in crate::shell) space: Space<CosmicMapped>,
+    /// Windows relocated from a disconnected output and their position relative to it
+    origins: HashMap<CosmicMapped, (OutputInfo, Point<i32, Logical>)>,
 }
 
 impl FloatingLayout {
@@ -41,7 +46,30 @@ impl FloatingLayout {
     }
 
     pub fn map_output(&mut self, output: &Output, location: Point<i32, Logical>) {
-        self.space.map_output(output, location)
+        self.space.map_output(output, location);
+
+        // move windows back, that were relocated when this output got disconnected
+        let origin = OutputInfo::from(output.clone());
+        let restored = self
+            .origins
+            .iter()
+            .filter(|(_, (info, _))| info == &origin)
+            .map(|(mapped, (_, relative))| (mapped.clone(), *relative))
+            .collect::<Vec<_>>();
+        for (mapped, relative) in restored {
+            self.origins.remove(&mapped);
+            let Some(mut geometry) = self.space.element_geometry(&mapped) else {
+                continue;
+            };
+            geometry.loc = location + relative;
+            let offset = output.geometry().loc - location;
+            mapped.set_geometry(Rectangle::from_loc_and_size(
+                geometry.loc + offset,
+                geometry.size,
+            ));
+            mapped.configure();
+            self.space.map_element(mapped, geometry.loc, false);
+        }
     }
 
     pub fn unmap_output(
@@ -59,7 +87,22 @@ impl FloatingLayout {
                 toplevel_info.toplevel_leave_output(&toplevel, output);
             }
         }
+        let output_geometry = self.space.output_geometry(output);
         self.space.unmap_output(output);
+        if let Some(output_geometry) = output_geometry {
+            // remember windows, that are about to be relocated, unless they were moved before
+            let origin = OutputInfo::from(output.clone());
+            for window in &windows {
+                if !self.space.outputs_for_element(window).is_empty() {
+                    continue;
+                }
+                if let Some(location) = self.space.element_location(window) {
+                    self.origins
+                        .entry(window.clone())
+                        .or_insert_with(|| (origin.clone(), location - output_geometry.loc));
+                }
+            }
+        }
         self.refresh();
         for window in &windows {
             for output in self.space.outputs_for_element(&window) {
@@ -159,6 +202,28 @@ impl FloatingLayout {
         self.space.map_element(mapped, position, false);
     }
 
+    /// Maps a window with exactly the given geometry, in global coordinates
+    pub(in crate::shell) fn map_snapped(
+        &mut self,
+        mapped: CosmicMapped,
+        output: &Output,
+        geometry: Rectangle<i32, Logical>,
+    ) {
+        let zone = layer_map_for_output(output).non_exclusive_zone();
+        mapped.set_bounds(zone.size);
+
+        let position = geometry.loc - output.geometry().loc
+            + self
+                .space
+                .output_geometry(output)
+                .map(|g| g.loc)
+                .unwrap_or_default();
+        mapped.set_tiled(false);
+        mapped.set_geometry(geometry);
+        mapped.configure();
+        self.space.map_element(mapped, position, false);
+    }
+
     pub fn unmap(&mut self, window: &CosmicMapped) -> bool {
         #[allow(irrefutable_let_patterns)]
         let is_maximized = window.is_maximized(true);
@@ -174,6 +239,7 @@ impl FloatingLayout {
 
         let was_unmaped = self.space.elements().any(|e| e == window);
         self.space.unmap_elem(&window);
+        self.origins.remove(window);
         was_unmaped
     }
 
@@ -278,6 +344,21 @@ impl FloatingLayout {
             let output = self.space.outputs().next().unwrap().clone();
             self.map_internal(element, &output, None);
         }
+
+        self.raise_always_on_top();
+    }
+
+    /// Restores the stacking order after other windows got raised
+    pub fn raise_always_on_top(&mut self) {
+        for element in self
+            .space
+            .elements()
+            .filter(|e| e.is_always_on_top())
+            .cloned()
+            .collect::<Vec<_>>()
+        {
+            self.space.raise_element(&element, false);
+        }
     }
 
     pub fn most_overlapped_output_for_element(&self, elem: &CosmicMapped) -> Option<Output> {
@@ -345,6 +426,7 @@ impl FloatingLayout {
             ));
             self.space.map_element(element.clone(), elem_geo.loc, false);
         }
+        self.origins.extend(other.origins);
         self.refresh(); //fixup any out of bounds elements
     }
 
@@ -355,6 +437,7 @@ impl FloatingLayout {
         focused: Option<&CosmicMapped>,
         indicator_thickness: u8,
         alpha: f32,
+        always_on_top: bool,
     ) -> Vec<CosmicMappedRenderElement<R>>
     where
         R: Renderer + ImportAll + ImportMem + AsGlowRenderer,
@@ -372,6 +455,7 @@ impl FloatingLayout {
         self.space
             .elements_for_output(output)
             .rev()
+            .filter(|elem| elem.is_always_on_top() == always_on_top)
             .flat_map(|elem| {
                 let render_location =
                     self.space.element_location(elem).unwrap() - output_loc - elem.geometry().loc;
@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use smithay::{
+    desktop::layer_map_for_output,
+    output::Output,
+    utils::{Logical, Point, Rectangle},
+};
+
+use crate::utils::prelude::*;
+
+/// Distance to an output edge, that snaps a dragged window
+const EDGE_THRESHOLD: f64 = 8.0;
+/// Distance to a corner along an edge, that snaps to a quarter instead of a half
+const CORNER_THRESHOLD: f64 = 128.0;
+
+/// Part of an output a dragged floating window snaps to, when released
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapZone {
+    Left,
+    Right,
+    Top,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl SnapZone {
+    /// Zone triggered by the pointer at the given global position on `output`.
+    ///
+    /// Edges shared with one of the other `outputs` don't snap, so windows can be dragged across.
+    pub fn at(
+        output: &Output,
+        outputs: &[Output],
+        pointer: Point<f64, Logical>,
+    ) -> Option<SnapZone> {
+        let geo = output.geometry().to_f64();
+        let pos = pointer - geo.loc;
+        let open = |x: f64, y: f64| {
+            !outputs
+                .iter()
+                .any(|other| other != output && other.geometry().to_f64().contains((x, y)))
+        };
+
+        let left = pos.x < EDGE_THRESHOLD && open(geo.loc.x - 1.0, pointer.y);
+        let right = pos.x > geo.size.w - EDGE_THRESHOLD && open(geo.loc.x + geo.size.w, pointer.y);
+        let top = pos.y < EDGE_THRESHOLD && open(pointer.x, geo.loc.y - 1.0);
+        let bottom = pos.y > geo.size.h - EDGE_THRESHOLD && open(pointer.x, geo.loc.y + geo.size.h);
+        let near_left = pos.x < CORNER_THRESHOLD;
+        let near_right = pos.x > geo.size.w - CORNER_THRESHOLD;
+        let near_top = pos.y < CORNER_THRESHOLD;
+        let near_bottom = pos.y > geo.size.h - CORNER_THRESHOLD;
+
+        if (left && near_top) || (top && near_left) {
+            Some(SnapZone::TopLeft)
+        } else if (right && near_top) || (top && near_right) {
+            Some(SnapZone::TopRight)
+        } else if (left && near_bottom) || (bottom && near_left) {
+            Some(SnapZone::BottomLeft)
+        } else if (right && near_bottom) || (bottom && near_right) {
+            Some(SnapZone::BottomRight)
+        } else if left {
+            Some(SnapZone::Left)
+        } else if right {
+            Some(SnapZone::Right)
+        } else if top {
+            Some(SnapZone::Top)
+        } else {
+            None
+        }
+    }
+
+    /// Global geometry of the zone, leaving out space reserved by layer-shell surfaces
+    pub fn geometry(&self, output: &Output) -> Rectangle<i32, Logical> {
+        let mut zone = layer_map_for_output(output).non_exclusive_zone();
+        zone.loc += output.geometry().loc;
+
+        let (w, h) = (zone.size.w, zone.size.h);
+        let (half_w, half_h) = (w / 2, h / 2);
+        let (x, y, width, height) = match self {
+            SnapZone::Left => (0, 0, half_w, h),
+            SnapZone::Right => (half_w, 0, w - half_w, h),
+            SnapZone::Top => (0, 0, w, h),
+            SnapZone::TopLeft => (0, 0, half_w, half_h),
+            SnapZone::TopRight => (half_w, 0, w - half_w, half_h),
+            SnapZone::BottomLeft => (0, half_h, half_w, h - half_h),
+            SnapZone::BottomRight => (half_w, half_h, w - half_w, h - half_h),
+        };
+
+        Rectangle::from_loc_and_size(zone.loc + Point::from((x, y)), (width, height))
+    }
+}
@@ -1,6 +1,5 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use regex::RegexSet;
 use smithay::{
     wayland::{compositor::with_states, shell::xdg::XdgToplevelSurfaceData},
     xwayland::xwm::WmWindowType,
@@ -11,7 +10,7 @@ use super::CosmicSurface;
 pub mod floating;
 pub mod tiling;
 
-#[derive(Debug, serde::Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Orientation {
     Horizontal,
     Vertical,
@@ -27,77 +26,6 @@ impl std::ops::Not for Orientation {
     }
 }
 
-lazy_static::lazy_static! {
-    static ref EXCEPTIONS_APPID: RegexSet = RegexSet::new(&[
-        r"Authy Desktop",
-        r"Com.github.amezin.ddterm",
-        r"Com.github.donadigo.eddy",
-        r".*",
-        r"Enpass",
-        r"Gjs",
-        r"Gnome-initial-setup",
-        r"Gnome-terminal",
-        r"Guake",
-        r"Io.elementary.sideload",
-        r"KotatogramDesktop",
-        r"Mozilla VPN",
-        r"update-manager",
-        r"Solaar",
-        r"Steam",
-        r"",
-        r"TelegramDesktop",
-        r"Zotero",
-        r"gjs",
-        r"gnome-screenshot",
-        r"ibus-.*",
-        r"jetbrains-toolbox",
-        r"jetbrains-webstorm",
-        r"jetbrains-webstorm",
-        r"jetbrains-webstorm",
-        r"krunner",
-        r"pritunl",
-        r"re.sonny.Junction",
-        r"system76-driver",
-        r"tilda",
-        r"zoom",
-        r"^.*?action=join.*$",
-    ]).unwrap();
-    static ref EXCEPTIONS_TITLE: RegexSet = RegexSet::new(&[
-        r".*",
-        r".*",
-        r".*",
-        r"Discord Updater",
-        r"Enpass Assistant",
-        r"Settings",
-        r".*",
-        r"Preferences – General",
-        r".*",
-        r".*",
-        r"Media viewer",
-        r".*",
-        r"Software Updater",
-        r".*",
-        r"^.*?(Guard|Login).*",
-        r"Steam",
-        r"Media viewer",
-        r"Quick Format Citation",
-        r".*",
-        r".*",
-        r".*",
-        r".*",
-        r"Customize WebStorm",
-        r"License Activation",
-        r"Welcome to WebStorm",
-        r".*",
-        r".*",
-        r".*",
-        r".*",
-        r".*",
-        r".*",
-        r".*",
-    ]).unwrap();
-}
-
 pub fn should_be_floating(window: &CosmicSurface) -> bool {
     // Check "window type"
     match window {
@@ -136,14 +64,6 @@ pub fn should_be_floating(window: &CosmicSurface) -> bool {
         return true;
     }
 
-    // else take a look at our exceptions
-    let appid_matches = EXCEPTIONS_APPID.matches(&window.app_id());
-    let title_matches = EXCEPTIONS_TITLE.matches(&window.title());
-    for idx in appid_matches.into_iter() {
-        if title_matches.matched(idx) {
-            return true;
-        }
-    }
-
+    // everything else is up to the configured window rules
     false
 }
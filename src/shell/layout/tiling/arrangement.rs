@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::sync::Arc;
+
+use id_tree::{InsertBehavior, MoveBehavior, Node, NodeId, Tree};
+use smithay::utils::{Logical, Rectangle};
+
+use super::Data;
+use crate::shell::layout::Orientation;
+
+/// Share of the available width taken by the master window
+const MASTER_RATIO: f64 = 0.55;
+/// Columns of the columns layout fitting side by side, further columns are scrolled to
+pub const VISIBLE_COLUMNS: i32 = 2;
+
+/// How the windows of a tiling layer are arranged
+#[derive(
+    Debug, Default, serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Eq, Hash,
+)]
+pub enum LayoutMode {
+    /// Windows are grouped and placed by the user
+    #[default]
+    Manual,
+    /// One large window on the left, all others in a column on the right
+    MasterStack,
+    /// All windows side by side on a horizontally scrolling strip, following the focus
+    Columns,
+    /// All windows in one stack, showing a single window at a time
+    Monocle,
+}
+
+impl LayoutMode {
+    /// Automatic arrangement of this mode, `None` for manual tiling
+    pub fn arrangement(&self) -> Option<&'static dyn Arrangement> {
+        match self {
+            LayoutMode::Manual => None,
+            LayoutMode::MasterStack => Some(&MasterStack),
+            LayoutMode::Columns => Some(&Columns),
+            LayoutMode::Monocle => Some(&Monocle),
+        }
+    }
+}
+
+/// An automatic layout, rebuilding the tree whenever windows come or go.
+pub trait Arrangement {
+    /// Places `windows` below the empty group `root` in the given order.
+    ///
+    /// The windows are still part of the previous tree, groups left empty are removed afterwards.
+    fn arrange(
+        &self,
+        tree: &mut Tree<Data>,
+        root: &NodeId,
+        windows: &[NodeId],
+        area: Rectangle<i32, Logical>,
+    );
+
+    /// Whether all windows are combined into a single stack before arranging
+    fn stacked(&self) -> bool {
+        false
+    }
+}
+
+pub struct MasterStack;
+
+impl Arrangement for MasterStack {
+    fn arrange(
+        &self,
+        tree: &mut Tree<Data>,
+        root: &NodeId,
+        windows: &[NodeId],
+        area: Rectangle<i32, Logical>,
+    ) {
+        match windows {
+            [] => {}
+            [window] => move_to(tree, window, root),
+            [master, stack] => {
+                let group = insert_group(
+                    tree,
+                    root,
+                    Orientation::Vertical,
+                    area,
+                    &[MASTER_RATIO, 1.0 - MASTER_RATIO],
+                );
+                move_to(tree, master, &group);
+                move_to(tree, stack, &group);
+            }
+            [master, stack @ ..] => {
+                let group = insert_group(
+                    tree,
+                    root,
+                    Orientation::Vertical,
+                    area,
+                    &[MASTER_RATIO, 1.0 - MASTER_RATIO],
+                );
+                move_to(tree, master, &group);
+                let column = insert_group(
+                    tree,
+                    &group,
+                    Orientation::Horizontal,
+                    area,
+                    &vec![1.0; stack.len()],
+                );
+                for window in stack {
+                    move_to(tree, window, &column);
+                }
+            }
+        }
+    }
+}
+
+pub struct Columns;
+
+impl Arrangement for Columns {
+    fn arrange(
+        &self,
+        tree: &mut Tree<Data>,
+        root: &NodeId,
+        windows: &[NodeId],
+        area: Rectangle<i32, Logical>,
+    ) {
+        match windows {
+            [] => {}
+            [window] => move_to(tree, window, root),
+            windows => {
+                let group = insert_group(
+                    tree,
+                    root,
+                    Orientation::Vertical,
+                    area,
+                    &vec![1.0; windows.len()],
+                );
+                for window in windows {
+                    move_to(tree, window, &group);
+                }
+            }
+        }
+    }
+}
+
+pub struct Monocle;
+
+impl Arrangement for Monocle {
+    fn arrange(
+        &self,
+        tree: &mut Tree<Data>,
+        root: &NodeId,
+        windows: &[NodeId],
+        area: Rectangle<i32, Logical>,
+    ) {
+        // there is only the stack left, unless stacking failed
+        Columns.arrange(tree, root, windows, area)
+    }
+
+    fn stacked(&self) -> bool {
+        true
+    }
+}
+
+/// Adds a group below `parent`, splitting the space of `area` by `weights`
+pub(super) fn insert_group(
+    tree: &mut Tree<Data>,
+    parent: &NodeId,
+    orientation: Orientation,
+    area: Rectangle<i32, Logical>,
+    weights: &[f64],
+) -> NodeId {
+    let length = match orientation {
+        Orientation::Horizontal => area.size.h,
+        Orientation::Vertical => area.size.w,
+    };
+    let total: f64 = weights.iter().sum();
+    let mut sizes = weights
+        .iter()
+        .map(|weight| (weight / total * length as f64).round() as i32)
+        .collect::<Vec<_>>();
+    let used: i32 = sizes.iter().sum();
+    *sizes.last_mut().unwrap() += length - used;
+
+    tree.insert(
+        Node::new(Data::Group {
+            orientation,
+            sizes,
+            last_geometry: area,
+            alive: Arc::new(()),
+        }),
+        InsertBehavior::UnderNode(parent),
+    )
+    .unwrap()
+}
+
+pub(super) fn move_to(tree: &mut Tree<Data>, window: &NodeId, parent: &NodeId) {
+    tree.move_node(window, MoveBehavior::ToParent(parent))
+        .unwrap();
+}
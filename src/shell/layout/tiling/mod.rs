@@ -36,6 +36,7 @@ use smithay::{
     desktop::{layer_map_for_output, space::SpaceElement, PopupKind},
     input::{pointer::GrabStartData as PointerGrabStartData, Seat},
     output::Output,
+    reexports::wayland_server::DisplayHandle,
     utils::{IsAlive, Logical, Point, Rectangle, Scale},
     wayland::{compositor::add_blocker, seat::WaylandFocus},
 };
@@ -48,10 +49,14 @@ use std::{
 };
 use tracing::trace;
 
+mod arrangement;
 mod blocker;
 mod grabs;
+mod saved;
+pub use self::arrangement::*;
 pub use self::blocker::*;
 pub use self::grabs::*;
+pub use self::saved::*;
 
 pub const ANIMATION_DURATION: Duration = Duration::from_millis(200);
 
@@ -125,17 +130,28 @@ pub enum MoveResult {
 struct TreeQueue {
     trees: VecDeque<(Tree<Data>, Option<TilingBlocker>)>,
     animation_start: Option<Instant>,
+    /// Whether a tree was pushed since the layout was last saved
+    changed: bool,
 }
 
 impl TreeQueue {
     pub fn push_tree(&mut self, tree: Tree<Data>, blocker: Option<TilingBlocker>) {
-        self.trees.push_back((tree, blocker))
+        self.trees.push_back((tree, blocker));
+        self.changed = true;
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct TilingLayout {
     gaps: (i32, i32),
+    default_orientation: Option<Orientation>,
+    mode: LayoutMode,
+    /// Saved layout, that is still missing some of its windows
+    template: Vec<SavedTree>,
+    /// Whether outputs or the template changed since the layout was last saved
+    changed: bool,
+    /// Windows in all trees, as stacks gain or lose windows without changing the tree
+    window_count: usize,
     queues: HashMap<OutputData, TreeQueue>,
     standby_tree: Option<Tree<Data>>,
     pending_blockers: Vec<TilingBlocker>,
@@ -307,6 +323,23 @@ impl Data {
     }
 }
 
+/// Serializable snapshot of a tiling tree
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum TilingNode {
+    Group {
+        orientation: Orientation,
+        sizes: Vec<i32>,
+        geometry: crate::ipc::Geometry,
+        children: Vec<TilingNode>,
+    },
+    Window {
+        windows: Vec<u64>,
+        stack: bool,
+        geometry: crate::ipc::Geometry,
+    },
+}
+
 #[derive(Debug, Clone)]
 enum FocusedNodeData {
     Group(Vec<NodeId>, Weak<()>),
@@ -317,6 +350,11 @@ impl TilingLayout {
     pub fn new(gaps: (u8, u8)) -> TilingLayout {
         TilingLayout {
             gaps: (gaps.0 as i32, gaps.1 as i32),
+            default_orientation: None,
+            mode: LayoutMode::Manual,
+            template: Vec::new(),
+            changed: false,
+            window_count: 0,
             queues: HashMap::new(),
             standby_tree: None,
             pending_blockers: Vec::new(),
@@ -324,6 +362,7 @@ impl TilingLayout {
     }
 
     pub fn map_output(&mut self, output: &Output, location: Point<i32, Logical>) {
+        self.changed = true;
         if !self.queues.contains_key(output) {
             self.queues.insert(
                 OutputData {
@@ -337,6 +376,7 @@ impl TilingLayout {
                         queue
                     },
                     animation_start: None,
+                    changed: false,
                 },
             );
         } else {
@@ -356,6 +396,7 @@ impl TilingLayout {
         output: &Output,
         toplevel_info: &mut ToplevelInfoState<State, CosmicSurface>,
     ) {
+        self.changed = true;
         if let Some(mut src) = self.queues.remove(output) {
             // Operate on last pending tree & unblock queue
             for blocker in src.trees.iter_mut().flat_map(|(_, blocker)| blocker.take()) {
@@ -393,8 +434,9 @@ impl TilingLayout {
                 }
             }
             TilingLayout::merge_trees(src, &mut dst, orientation);
+            TilingLayout::rearrange(&mut dst, &new_output.output, self.mode, self.gaps);
 
-            let blocker = TilingLayout::update_positions(output, &mut dst, self.gaps);
+            let blocker = TilingLayout::update_positions(output, &mut dst, self.gaps, self.mode);
             dst_queue.push_tree(dst, blocker);
         }
     }
@@ -407,9 +449,19 @@ impl TilingLayout {
         direction: Option<Direction>,
     ) {
         let output = seat.active_output();
-        window.output_enter(&output, window.bbox());
+        self.map_to_output(window, &output, focus_stack, direction)
+    }
+
+    pub fn map_to_output<'a>(
+        &mut self,
+        window: CosmicMapped,
+        output: &Output,
+        focus_stack: impl Iterator<Item = &'a CosmicMapped> + 'a,
+        direction: Option<Direction>,
+    ) {
+        window.output_enter(output, window.bbox());
         window.set_bounds(output.geometry().size);
-        self.map_internal(window, &output, Some(focus_stack), direction);
+        self.map_internal(window, output, Some(focus_stack), direction);
     }
 
     fn map_internal<'a>(
@@ -422,9 +474,17 @@ impl TilingLayout {
         let queue = self.queues.get_mut(output).expect("Output not mapped?");
         let mut tree = queue.trees.back().unwrap().0.copy_clone();
 
-        TilingLayout::map_to_tree(&mut tree, window, output, focus_stack, direction);
-
-        let blocker = TilingLayout::update_positions(output, &mut tree, self.gaps);
+        TilingLayout::map_to_tree(
+            &mut tree,
+            window,
+            output,
+            focus_stack,
+            direction,
+            self.default_orientation,
+        );
+        TilingLayout::rearrange(&mut tree, output, self.mode, self.gaps);
+
+        let blocker = TilingLayout::update_positions(output, &mut tree, self.gaps, self.mode);
         queue.push_tree(tree, blocker);
     }
 
@@ -434,6 +494,7 @@ impl TilingLayout {
         output: &Output,
         focus_stack: Option<impl Iterator<Item = &'a CosmicMapped> + 'a>,
         direction: Option<Direction>,
+        default_orientation: Option<Orientation>,
     ) {
         let window = window.into();
         let new_window = Node::new(Data::Mapped {
@@ -476,28 +537,28 @@ impl TilingLayout {
                     return;
                 }
 
-                let orientation = {
+                let orientation = default_orientation.unwrap_or_else(|| {
                     let window_size = tree.get(node_id).unwrap().data().geometry().size;
                     if window_size.w > window_size.h {
                         Orientation::Vertical
                     } else {
                         Orientation::Horizontal
                     }
-                };
+                });
                 let new_id = tree.insert(new_window, InsertBehavior::AsRoot).unwrap();
                 TilingLayout::new_group(&mut tree, &node_id, &new_id, orientation).unwrap();
                 new_id
             } else {
                 // nothing? then we add to the root
                 if let Some(root_id) = tree.root_node_id().cloned() {
-                    let orientation = {
+                    let orientation = default_orientation.unwrap_or_else(|| {
                         let output_size = output.geometry().size;
                         if output_size.w > output_size.h {
                             Orientation::Vertical
                         } else {
                             Orientation::Horizontal
                         }
-                    };
+                    });
                     let new_id = tree.insert(new_window, InsertBehavior::AsRoot).unwrap();
                     TilingLayout::new_group(&mut tree, &root_id, &new_id, orientation).unwrap();
                     new_id
@@ -599,8 +660,10 @@ impl TilingLayout {
                     }
                     None => {} // root
                 }
+                TilingLayout::rearrange(&mut tree, &output.output, self.mode, self.gaps);
 
-                let blocker = TilingLayout::update_positions(&output.output, &mut tree, self.gaps);
+                let blocker =
+                    TilingLayout::update_positions(&output.output, &mut tree, self.gaps, self.mode);
                 queue.push_tree(tree, blocker);
             }
         }
@@ -638,6 +701,15 @@ impl TilingLayout {
         let Some((node_id, data)) = TilingLayout::currently_focused_node(&mut tree, seat) else {
             return MoveResult::Done
         };
+        // the stack is all there is in a monocle layout
+        if self
+            .mode
+            .arrangement()
+            .map(|a| a.stacked())
+            .unwrap_or(false)
+        {
+            return MoveResult::Done;
+        }
 
         // stacks may handle movement internally
         if let FocusedNodeData::Window(window) = data.clone() {
@@ -666,8 +738,10 @@ impl TilingLayout {
                     )
                     .unwrap();
                     *mapped.tiling_node_id.lock().unwrap() = Some(new_id);
+                    TilingLayout::rearrange(&mut tree, &output, self.mode, self.gaps);
 
-                    let blocker = TilingLayout::update_positions(&output, &mut tree, self.gaps);
+                    let blocker =
+                        TilingLayout::update_positions(&output, &mut tree, self.gaps, self.mode);
                     queue.push_tree(tree, blocker);
                     return MoveResult::ShiftFocus(mapped.into());
                 }
@@ -695,6 +769,31 @@ impl TilingLayout {
             .unwrap();
         let mut maybe_parent = Some(og_parent.clone());
 
+        // automatic layouts only reorder their windows
+        if self.mode.arrangement().is_some() {
+            let windows = tree
+                .traverse_pre_order_ids(tree.root_node_id().unwrap())
+                .unwrap()
+                .filter(|id| tree.get(id).unwrap().data().is_mapped(None))
+                .collect::<Vec<_>>();
+            let other = windows
+                .iter()
+                .position(|id| id == &node_id)
+                .and_then(|idx| match direction {
+                    Direction::Left | Direction::Up => idx.checked_sub(1),
+                    Direction::Right | Direction::Down => Some(idx + 1),
+                })
+                .and_then(|idx| windows.get(idx));
+            if let Some(other) = other {
+                TilingLayout::swap_nodes(&mut tree, &node_id, other);
+                let blocker =
+                    TilingLayout::update_positions(&output, &mut tree, self.gaps, self.mode);
+                queue.push_tree(tree, blocker);
+                return MoveResult::Done;
+            }
+            maybe_parent = None;
+        }
+
         while let Some(parent) = maybe_parent {
             let parent_data = tree.get(&parent).unwrap().data();
             let orientation = parent_data.orientation();
@@ -741,7 +840,8 @@ impl TilingLayout {
                     .data_mut()
                     .remove_window(og_idx);
 
-                let blocker = TilingLayout::update_positions(&output, &mut tree, self.gaps);
+                let blocker =
+                    TilingLayout::update_positions(&output, &mut tree, self.gaps, self.mode);
                 queue.push_tree(tree, blocker);
                 return MoveResult::Done;
             }
@@ -767,7 +867,8 @@ impl TilingLayout {
                     .data_mut()
                     .remove_window(og_idx);
 
-                let blocker = TilingLayout::update_positions(&output, &mut tree, self.gaps);
+                let blocker =
+                    TilingLayout::update_positions(&output, &mut tree, self.gaps, self.mode);
                 queue.push_tree(tree, blocker);
                 return MoveResult::Done;
             }
@@ -923,7 +1024,8 @@ impl TilingLayout {
                     MoveResult::Done
                 };
 
-                let blocker = TilingLayout::update_positions(&output, &mut tree, self.gaps);
+                let blocker =
+                    TilingLayout::update_positions(&output, &mut tree, self.gaps, self.mode);
                 queue.push_tree(tree, blocker);
                 return result;
             }
@@ -1142,6 +1244,10 @@ impl TilingLayout {
         new_orientation: Option<Orientation>,
         seat: &Seat<State>,
     ) {
+        // groups are managed by automatic layouts
+        if self.mode.arrangement().is_some() {
+            return;
+        }
         let output = seat.active_output();
         let Some(queue) = self.queues.get_mut(&output) else { return };
         let mut tree = queue.trees.back().unwrap().0.copy_clone();
@@ -1176,7 +1282,8 @@ impl TilingLayout {
 
                     *orientation = new_orientation;
 
-                    let blocker = TilingLayout::update_positions(&output, &mut tree, self.gaps);
+                    let blocker =
+                        TilingLayout::update_positions(&output, &mut tree, self.gaps, self.mode);
                     queue.push_tree(tree, blocker);
                 }
             }
@@ -1184,6 +1291,14 @@ impl TilingLayout {
     }
 
     pub fn toggle_stacking<'a>(&mut self, seat: &Seat<State>, mut focus_stack: FocusStackMut) {
+        if self
+            .mode
+            .arrangement()
+            .map(|a| a.stacked())
+            .unwrap_or(false)
+        {
+            return;
+        }
         let output = seat.active_output();
         let Some(queue) = self.queues.get_mut(&output) else { return };
         let mut tree = queue.trees.back().unwrap().0.copy_clone();
@@ -1235,6 +1350,7 @@ impl TilingLayout {
                                 &output,
                                 Some(focus_stack.iter()),
                                 None,
+                                self.default_orientation,
                             )
                         }
 
@@ -1293,16 +1409,189 @@ impl TilingLayout {
                     };
                 }
             }
+            TilingLayout::rearrange(&mut tree, &output, self.mode, self.gaps);
+
+            let blocker = TilingLayout::update_positions(&output, &mut tree, self.gaps, self.mode);
+            queue.push_tree(tree, blocker);
+        }
+    }
+
+    /// Orientation of newly created groups, derived from the available space if unset
+    pub fn set_default_orientation(&mut self, orientation: Option<Orientation>) {
+        self.default_orientation = orientation;
+    }
+
+    pub fn layout_mode(&self) -> LayoutMode {
+        self.mode
+    }
+
+    /// Switches to another arrangement of the windows, keeping all of them mapped
+    pub fn set_layout_mode(&mut self, mode: LayoutMode, mut focus_stack: Option<FocusStackMut>) {
+        if self.mode == mode {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.mode, mode);
+        self.template.clear();
+        self.changed = true;
+        let was_stacked = previous.arrangement().map(|a| a.stacked()).unwrap_or(false);
+
+        for (output_data, queue) in self.queues.iter_mut() {
+            let output = &output_data.output;
+            let mut tree = queue.trees.back().unwrap().0.copy_clone();
+
+            // elements converted from or into stacks need to replace their previous focus
+            let mut converted = Vec::new();
+            if was_stacked {
+                converted =
+                    TilingLayout::unstack_windows(&mut tree, output, self.default_orientation);
+            }
+            TilingLayout::rearrange(&mut tree, output, mode, self.gaps);
+            if mode.arrangement().map(|a| a.stacked()).unwrap_or(false) {
+                if let Some(root) = tree.root_node_id() {
+                    converted.extend(tree.traverse_pre_order(root).unwrap().filter_map(|node| {
+                        match node.data() {
+                            Data::Mapped { mapped, .. } if mapped.is_stack() => {
+                                Some(mapped.clone())
+                            }
+                            _ => None,
+                        }
+                    }));
+                }
+            }
+            if let Some(focus_stack) = focus_stack.as_mut() {
+                for mapped in &converted {
+                    focus_stack.append(mapped);
+                }
+            }
 
-            let blocker = TilingLayout::update_positions(&output, &mut tree, self.gaps);
+            let blocker = TilingLayout::update_positions(output, &mut tree, self.gaps, self.mode);
             queue.push_tree(tree, blocker);
         }
     }
 
+    /// Whether the trees changed since the last call, e.g. by mapping, moving or resizing windows.
+    ///
+    /// Titles are not part of this, so they don't cause the layout to be saved again.
+    pub fn take_changed(&mut self) -> bool {
+        let mut changed = std::mem::take(&mut self.changed);
+        for queue in self.queues.values_mut() {
+            changed |= std::mem::take(&mut queue.changed);
+        }
+        changed
+    }
+
+    /// Forces the layout to be saved again, e.g. as the workspace holding it moved
+    pub fn mark_changed(&mut self) {
+        self.changed = true;
+    }
+
+    /// Snapshot of the trees of all outputs, or the pending template, if not all of its windows appeared yet
+    pub fn save(&self, dh: &DisplayHandle) -> Vec<SavedTree> {
+        if !self.template.is_empty() {
+            return self.template.clone();
+        }
+
+        self.queues
+            .iter()
+            .filter_map(|(output_data, queue)| {
+                let tree = &queue.trees.back().unwrap().0;
+                tree.root_node_id().map(|root| SavedTree {
+                    output: output_data.output.name(),
+                    root: SavedNode::from_tree(tree, root, dh),
+                })
+            })
+            .collect()
+    }
+
+    /// Rearranges the windows following `template`, switching to manual tiling.
+    ///
+    /// Windows matching the template, that are mapped later, are put into their slots as well.
+    pub fn load_template(
+        &mut self,
+        template: Vec<SavedTree>,
+        output: &Output,
+        focus_stack: Option<FocusStackMut>,
+        dh: &DisplayHandle,
+    ) {
+        self.set_layout_mode(LayoutMode::Manual, focus_stack);
+        self.restore(template, output, dh);
+    }
+
+    /// Like [`TilingLayout::load_template`], but keeps automatic layouts, only tracking the missing windows
+    pub fn restore(&mut self, mut template: Vec<SavedTree>, output: &Output, dh: &DisplayHandle) {
+        // a single tree may be used on any output
+        if let [tree] = template.as_mut_slice() {
+            tree.output = output.name();
+        }
+        self.template = template;
+        self.changed = true;
+        self.apply_template(dh);
+    }
+
+    /// Whether the pending template has an empty slot for `window` on `output`
+    pub fn has_slot_for(
+        &self,
+        window: &CosmicSurface,
+        output: &Output,
+        dh: &DisplayHandle,
+    ) -> bool {
+        let Some(saved) = self.template.iter().find(|saved| saved.output == output.name()) else {
+            return false;
+        };
+        let Some(queue) = self.queues.get(output) else { return false };
+        saved.has_slot_for(
+            &queue.trees.back().unwrap().0,
+            &WindowMatcher::for_window(window, dh),
+            dh,
+        )
+    }
+
+    /// Puts all windows matching the pending template into their slots
+    pub fn apply_template(&mut self, dh: &DisplayHandle) {
+        if self.template.is_empty() {
+            return;
+        }
+
+        let mut complete = true;
+        for (output_data, queue) in self.queues.iter_mut() {
+            let output = &output_data.output;
+            let Some(saved) = self.template.iter().find(|saved| saved.output == output.name()) else {
+                continue;
+            };
+
+            if self.mode != LayoutMode::Manual {
+                complete &= saved.is_complete(&queue.trees.back().unwrap().0, dh);
+                continue;
+            }
+            let mut tree = queue.trees.back().unwrap().0.copy_clone();
+            complete &= saved.apply(&mut tree, output, dh);
+
+            let blocker = TilingLayout::update_positions(output, &mut tree, self.gaps, self.mode);
+            queue.push_tree(tree, blocker);
+        }
+
+        if complete {
+            self.template.clear();
+            self.changed = true;
+        }
+    }
+
+    pub fn set_gaps(&mut self, gaps: (u8, u8)) {
+        self.gaps = (gaps.0 as i32, gaps.1 as i32);
+        let outputs = self
+            .queues
+            .keys()
+            .map(|data| data.output.clone())
+            .collect::<Vec<_>>();
+        for output in outputs {
+            self.recalculate(&output);
+        }
+    }
+
     pub fn recalculate(&mut self, output: &Output) {
         let Some(queue) = self.queues.get_mut(output) else { return };
         let mut tree = queue.trees.back().unwrap().0.copy_clone();
-        let blocker = TilingLayout::update_positions(&output, &mut tree, self.gaps);
+        let blocker = TilingLayout::update_positions(&output, &mut tree, self.gaps, self.mode);
         queue.push_tree(tree, blocker);
     }
 
@@ -1319,8 +1608,14 @@ impl TilingLayout {
             self.unmap_window_internal(&dead_window);
         }
 
+        let mut window_count = 0;
         for (_, mapped, _) in self.mapped() {
             mapped.refresh();
+            window_count += mapped.windows().count();
+        }
+        if window_count != self.window_count {
+            self.window_count = window_count;
+            self.changed = true;
         }
     }
 
@@ -1522,10 +1817,163 @@ impl TilingLayout {
         Ok(group_id)
     }
 
+    /// Rebuilds the tree following the arrangement of `mode`, keeping the nodes of all windows.
+    fn rearrange(tree: &mut Tree<Data>, output: &Output, mode: LayoutMode, gaps: (i32, i32)) {
+        let Some(arrangement) = mode.arrangement() else { return };
+        let Some(old_root) = tree.root_node_id().cloned() else { return };
+        // the columns layout keeps scrolled to where it was
+        let previous_loc = match tree.get(&old_root).unwrap().data() {
+            Data::Group { last_geometry, .. } if mode == LayoutMode::Columns => {
+                Some(last_geometry.loc)
+            }
+            _ => None,
+        };
+
+        let mut windows = tree
+            .traverse_pre_order_ids(&old_root)
+            .unwrap()
+            .filter(|id| tree.get(id).unwrap().data().is_mapped(None))
+            .collect::<Vec<_>>();
+        if arrangement.stacked() && windows.len() > 1 {
+            windows = vec![TilingLayout::stack_windows(tree, output, windows)];
+        }
+
+        let mut area = TilingLayout::tiling_zone(output, gaps);
+        let root = tree
+            .insert(
+                Node::new(Data::Group {
+                    orientation: Orientation::Vertical,
+                    sizes: vec![area.size.w],
+                    last_geometry: area,
+                    alive: Arc::new(()),
+                }),
+                InsertBehavior::AsRoot,
+            )
+            .unwrap();
+        if let Some(loc) = previous_loc {
+            area.loc = loc;
+        }
+        arrangement.arrange(tree, &root, &windows, area);
+
+        // drop what is left of the previous groups, `update_positions` flattens the new root
+        if tree
+            .get(&old_root)
+            .map(|node| node.data().is_group())
+            .unwrap_or(false)
+        {
+            tree.remove_node(old_root, RemoveBehavior::DropChildren)
+                .unwrap();
+        }
+    }
+
+    /// Moves all windows into the first stack among `windows`, returning its node
+    fn stack_windows(tree: &mut Tree<Data>, output: &Output, windows: Vec<NodeId>) -> NodeId {
+        let target = windows
+            .iter()
+            .find(|id| tree.get(id).unwrap().data().is_stack())
+            .unwrap_or(&windows[0])
+            .clone();
+        let mut stack = match tree.get_mut(&target).unwrap().data_mut() {
+            Data::Mapped { mapped, .. } => {
+                if mapped.is_window() {
+                    mapped.convert_to_stack(std::iter::once((output, mapped.bbox())));
+                }
+                mapped.clone()
+            }
+            _ => unreachable!(),
+        };
+
+        for id in windows.into_iter().filter(|id| id != &target) {
+            let node = tree.remove_node(id, RemoveBehavior::DropChildren).unwrap();
+            if let Data::Mapped { mapped, .. } = node.data() {
+                for (surface, _) in mapped.windows() {
+                    stack.stack_ref_mut().unwrap().add_window(surface, None);
+                }
+            }
+        }
+
+        target
+    }
+
+    /// Splits all stacks back into single windows, returning the elements of the former stacks
+    fn unstack_windows(
+        tree: &mut Tree<Data>,
+        output: &Output,
+        default_orientation: Option<Orientation>,
+    ) -> Vec<CosmicMapped> {
+        let Some(root) = tree.root_node_id().cloned() else { return Vec::new() };
+        let stacks = tree
+            .traverse_pre_order_ids(&root)
+            .unwrap()
+            .filter(|id| tree.get(id).unwrap().data().is_stack())
+            .collect::<Vec<_>>();
+
+        let mut converted = Vec::new();
+        for id in stacks {
+            let (handle, surfaces) = match tree.get_mut(&id).unwrap().data_mut() {
+                Data::Mapped { mapped, .. } => {
+                    let handle = mapped.loop_handle();
+                    let mut surfaces = mapped.windows().map(|(s, _)| s).collect::<Vec<_>>();
+                    let first = surfaces.remove(0);
+                    mapped.convert_to_surface(first, std::iter::once((output, mapped.bbox())));
+                    converted.push(mapped.clone());
+                    (handle, surfaces)
+                }
+                _ => unreachable!(),
+            };
+
+            for surface in surfaces {
+                surface.try_force_undecorated(false);
+                surface.set_tiled(false);
+                let window = CosmicMapped::from(CosmicWindow::new(surface, handle.clone()));
+                window.output_enter(output, window.bbox());
+                window.set_bounds(output.geometry().size);
+
+                TilingLayout::map_to_tree(
+                    tree,
+                    window,
+                    output,
+                    None::<std::iter::Empty<&CosmicMapped>>,
+                    None,
+                    default_orientation,
+                );
+            }
+        }
+
+        converted
+    }
+
+    /// Exchanges the position of two nodes, that both have a parent
+    fn swap_nodes(tree: &mut Tree<Data>, a: &NodeId, b: &NodeId) {
+        let position = |tree: &Tree<Data>, id: &NodeId| {
+            let parent = tree.get(id).unwrap().parent().cloned().unwrap();
+            let idx = tree
+                .children_ids(&parent)
+                .unwrap()
+                .position(|child| child == id)
+                .unwrap();
+            (parent, idx)
+        };
+        let (parent_a, idx_a) = position(tree, a);
+        let (parent_b, idx_b) = position(tree, b);
+
+        if parent_a != parent_b {
+            tree.move_node(a, MoveBehavior::ToParent(&parent_b))
+                .unwrap();
+            tree.make_nth_sibling(a, idx_b).unwrap();
+            tree.move_node(b, MoveBehavior::ToParent(&parent_a))
+                .unwrap();
+        } else {
+            tree.make_nth_sibling(a, idx_b).unwrap();
+        }
+        tree.make_nth_sibling(b, idx_a).unwrap();
+    }
+
     fn update_positions(
         output: &Output,
         tree: &mut Tree<Data>,
         gaps: (i32, i32),
+        mode: LayoutMode,
     ) -> Option<TilingBlocker> {
         #[cfg(feature = "debug")]
         puffin::profile_function!();
@@ -1533,12 +1981,11 @@ impl TilingLayout {
         if let Some(root_id) = tree.root_node_id() {
             let mut configures = Vec::new();
 
-            let (outer, inner) = gaps;
-            let mut geo = layer_map_for_output(&output).non_exclusive_zone();
-            geo.loc.x += outer;
-            geo.loc.y += outer;
-            geo.size.w -= outer * 2;
-            geo.size.h -= outer * 2;
+            let (_, inner) = gaps;
+            let mut geo = TilingLayout::tiling_zone(output, gaps);
+            if mode == LayoutMode::Columns {
+                geo = TilingLayout::columns_strip(tree, geo);
+            }
             let mut stack = vec![geo];
 
             for node_id in tree
@@ -1646,6 +2093,119 @@ impl TilingLayout {
         None
     }
 
+    /// Usable area of `output` for tiled windows
+    fn tiling_zone(output: &Output, gaps: (i32, i32)) -> Rectangle<i32, Logical> {
+        let (outer, _) = gaps;
+        let mut geo = layer_map_for_output(output).non_exclusive_zone();
+        geo.loc.x += outer;
+        geo.loc.y += outer;
+        geo.size.w -= outer * 2;
+        geo.size.h -= outer * 2;
+        geo
+    }
+
+    /// Geometry of the strip of the columns layout, which gets wider than the `viewport`
+    /// once there are more columns than fit side by side. The strip keeps its previous offset.
+    fn columns_strip(
+        tree: &Tree<Data>,
+        viewport: Rectangle<i32, Logical>,
+    ) -> Rectangle<i32, Logical> {
+        let Some(mut node_id) = tree.root_node_id().cloned() else { return viewport };
+        // groups with a single child are about to be flattened
+        while let Data::Group { sizes, .. } = tree.get(&node_id).unwrap().data() {
+            if sizes.len() != 1 {
+                break;
+            }
+            node_id = tree.children_ids(&node_id).unwrap().next().unwrap().clone();
+        }
+
+        match tree.get(&node_id).unwrap().data() {
+            Data::Group {
+                orientation: Orientation::Vertical,
+                sizes,
+                last_geometry,
+                ..
+            } => {
+                let width = sizes.len() as i32 * viewport.size.w / VISIBLE_COLUMNS;
+                if width <= viewport.size.w {
+                    return viewport;
+                }
+                let offset =
+                    (viewport.loc.x - last_geometry.loc.x).clamp(0, width - viewport.size.w);
+                Rectangle::from_loc_and_size(
+                    (viewport.loc.x - offset, viewport.loc.y),
+                    (width, viewport.size.h),
+                )
+            }
+            _ => viewport,
+        }
+    }
+
+    /// Scrolls the strip of the columns layout, until the column containing `focused` is visible
+    pub fn scroll_to(&mut self, focused: &CosmicMapped) {
+        if self.mode != LayoutMode::Columns {
+            return;
+        }
+        let Some(node_id) = focused.tiling_node_id.lock().unwrap().clone() else { return };
+        let Some((output_data, queue)) = self.queues.iter_mut().find(|(_, queue)| {
+            queue
+                .trees
+                .back()
+                .unwrap()
+                .0
+                .get(&node_id)
+                .map(|node| node.data().is_mapped(Some(focused)))
+                .unwrap_or(false)
+        }) else {
+            return;
+        };
+        let output = &output_data.output;
+        let tree = &queue.trees.back().unwrap().0;
+        let Some(root_id) = tree.root_node_id().cloned() else { return };
+        let Data::Group {
+            orientation: Orientation::Vertical,
+            sizes,
+            last_geometry: strip,
+            ..
+        } = tree.get(&root_id).unwrap().data() else {
+            return;
+        };
+
+        let viewport = TilingLayout::tiling_zone(output, self.gaps);
+        if strip.size.w <= viewport.size.w {
+            return;
+        }
+
+        let Some(column) = std::iter::once(&node_id)
+            .chain(tree.ancestor_ids(&node_id).unwrap())
+            .find(|id| tree.get(id).unwrap().parent() == Some(&root_id))
+        else {
+            return;
+        };
+        let idx = tree
+            .children_ids(&root_id)
+            .unwrap()
+            .position(|id| id == column)
+            .unwrap();
+        let offset = viewport.loc.x - strip.loc.x;
+        let start = sizes[..idx].iter().sum::<i32>();
+        let end = start + sizes[idx];
+        let new_offset = if start < offset {
+            start
+        } else if end > offset + viewport.size.w {
+            end - viewport.size.w
+        } else {
+            return;
+        };
+
+        let mut tree = tree.copy_clone();
+        if let Data::Group { last_geometry, .. } = tree.get_mut(&root_id).unwrap().data_mut() {
+            last_geometry.loc.x = viewport.loc.x - new_offset;
+        }
+        let blocker = TilingLayout::update_positions(output, &mut tree, self.gaps, self.mode);
+        queue.push_tree(tree, blocker);
+    }
+
     pub fn mapped(
         &self,
     ) -> impl Iterator<Item = (&Output, &CosmicMapped, Rectangle<i32, Logical>)> {
@@ -1703,6 +2263,44 @@ impl TilingLayout {
             .flatten()
     }
 
+    pub fn tree_info(&self, output: &Output) -> Option<TilingNode> {
+        fn node_info(tree: &Tree<Data>, id: &NodeId) -> TilingNode {
+            let node = tree.get(id).unwrap();
+            match node.data() {
+                Data::Group {
+                    orientation,
+                    sizes,
+                    last_geometry,
+                    ..
+                } => TilingNode::Group {
+                    orientation: *orientation,
+                    sizes: sizes.clone(),
+                    geometry: (*last_geometry).into(),
+                    children: node
+                        .children()
+                        .iter()
+                        .map(|child| node_info(tree, child))
+                        .collect(),
+                },
+                Data::Mapped {
+                    mapped,
+                    last_geometry,
+                } => TilingNode::Window {
+                    windows: mapped
+                        .windows()
+                        .map(|(w, _)| crate::ipc::window_id(&w))
+                        .collect(),
+                    stack: mapped.is_stack(),
+                    geometry: (*last_geometry).into(),
+                },
+            }
+        }
+
+        let queue = self.queues.get(output)?;
+        let tree = &queue.trees.back().unwrap().0;
+        tree.root_node_id().map(|root| node_info(tree, root))
+    }
+
     pub fn windows(
         &self,
     ) -> impl Iterator<Item = (Output, CosmicSurface, Rectangle<i32, Logical>)> + '_ {
@@ -1729,8 +2327,10 @@ impl TilingLayout {
                 _ => Orientation::Horizontal,
             };
             TilingLayout::merge_trees(src, &mut dst, orientation);
+            TilingLayout::rearrange(&mut dst, &output_data.output, self.mode, self.gaps);
 
-            let blocker = TilingLayout::update_positions(&output_data.output, &mut dst, self.gaps);
+            let blocker =
+                TilingLayout::update_positions(&output_data.output, &mut dst, self.gaps, self.mode);
             dst_queue.push_tree(dst, blocker);
         }
     }
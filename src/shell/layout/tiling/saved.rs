@@ -0,0 +1,378 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{fs::File, path::PathBuf, sync::Arc};
+
+use anyhow::Context;
+use id_tree::{InsertBehavior, Node, NodeId, RemoveBehavior, Tree};
+use serde::{Deserialize, Serialize};
+use smithay::{
+    desktop::layer_map_for_output,
+    output::Output,
+    reexports::wayland_server::{DisplayHandle, Resource},
+    utils::{Logical, Rectangle},
+};
+
+use super::{
+    arrangement::{insert_group, move_to},
+    Data, TilingLayout,
+};
+use crate::shell::{layout::Orientation, CosmicSurface};
+
+/// Identifies the window supposed to fill a slot of a saved layout
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WindowMatcher {
+    pub app_id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub pid: Option<i32>,
+}
+
+impl WindowMatcher {
+    pub fn for_window(window: &CosmicSurface, dh: &DisplayHandle) -> WindowMatcher {
+        let pid = match window {
+            CosmicSurface::Wayland(window) => window
+                .toplevel()
+                .wl_surface()
+                .client()
+                .and_then(|client| client.get_credentials(dh).ok())
+                .map(|credentials| credentials.pid),
+            _ => None,
+        };
+
+        WindowMatcher {
+            app_id: window.app_id(),
+            title: Some(window.title()),
+            pid,
+        }
+    }
+
+    /// How well `window` fits this slot, `None` if it doesn't fit at all.
+    ///
+    /// Only the app_id has to match, title and pid rarely survive a restart and just break ties.
+    fn score(&self, window: &WindowMatcher) -> Option<u8> {
+        if self.app_id != window.app_id {
+            return None;
+        }
+        let title = self.title.is_some() && self.title == window.title;
+        let pid = self.pid.is_some() && self.pid == window.pid;
+        Some(title as u8 + pid as u8)
+    }
+}
+
+/// A node of a saved tiling tree
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum SavedNode {
+    Group {
+        orientation: Orientation,
+        /// Share of each child, missing entries are split equally
+        #[serde(default)]
+        ratios: Vec<f64>,
+        children: Vec<SavedNode>,
+    },
+    Window(WindowMatcher),
+    Stack(Vec<WindowMatcher>),
+}
+
+/// The tiling tree of a workspace on a single output
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SavedTree {
+    /// Connector name of the output
+    pub output: String,
+    pub root: SavedNode,
+}
+
+impl SavedNode {
+    pub(super) fn from_tree(tree: &Tree<Data>, id: &NodeId, dh: &DisplayHandle) -> SavedNode {
+        let node = tree.get(id).unwrap();
+        match node.data() {
+            Data::Group {
+                orientation, sizes, ..
+            } => {
+                let total = sizes.iter().sum::<i32>().max(1) as f64;
+                SavedNode::Group {
+                    orientation: *orientation,
+                    ratios: sizes.iter().map(|size| *size as f64 / total).collect(),
+                    children: node
+                        .children()
+                        .iter()
+                        .map(|child| SavedNode::from_tree(tree, child, dh))
+                        .collect(),
+                }
+            }
+            Data::Mapped { mapped, .. } if mapped.is_stack() => SavedNode::Stack(
+                mapped
+                    .windows()
+                    .map(|(window, _)| WindowMatcher::for_window(&window, dh))
+                    .collect(),
+            ),
+            Data::Mapped { mapped, .. } => {
+                SavedNode::Window(WindowMatcher::for_window(&mapped.active_window(), dh))
+            }
+        }
+    }
+
+    fn slots<'a>(&'a self, slots: &mut Vec<&'a WindowMatcher>) {
+        match self {
+            SavedNode::Group { children, .. } => {
+                children.iter().for_each(|child| child.slots(slots))
+            }
+            SavedNode::Window(matcher) => slots.push(matcher),
+            SavedNode::Stack(matchers) => slots.extend(matchers),
+        }
+    }
+}
+
+impl SavedTree {
+    /// Whether any slot of this tree fits `window`
+    pub fn fits(&self, window: &WindowMatcher) -> bool {
+        let mut slots = Vec::new();
+        self.root.slots(&mut slots);
+        slots.iter().any(|slot| slot.score(window).is_some())
+    }
+
+    /// Whether `window` would get a slot, that is not already taken by the windows of `tree`
+    pub(super) fn has_slot_for(
+        &self,
+        tree: &Tree<Data>,
+        window: &WindowMatcher,
+        dh: &DisplayHandle,
+    ) -> bool {
+        let mut slots = Vec::new();
+        self.root.slots(&mut slots);
+        let mut windows = leaves(tree, dh)
+            .into_iter()
+            .map(|(_, matcher)| matcher)
+            .collect::<Vec<_>>();
+        windows.push(window.clone());
+        assign(&slots, &windows).contains(&Some(windows.len() - 1))
+    }
+
+    pub(super) fn is_complete(&self, tree: &Tree<Data>, dh: &DisplayHandle) -> bool {
+        let mut slots = Vec::new();
+        self.root.slots(&mut slots);
+        let windows = leaves(tree, dh)
+            .into_iter()
+            .map(|(_, matcher)| matcher)
+            .collect::<Vec<_>>();
+        assign(&slots, &windows).iter().all(Option::is_some)
+    }
+
+    /// Rebuilds `tree` following this layout, keeping the nodes of all windows.
+    /// Windows without a slot are placed next to the restored groups.
+    ///
+    /// Returns `true` once all slots are filled.
+    pub(super) fn apply(&self, tree: &mut Tree<Data>, output: &Output, dh: &DisplayHandle) -> bool {
+        let Some(old_root) = tree.root_node_id().cloned() else { return false };
+
+        let mut slots = Vec::new();
+        self.root.slots(&mut slots);
+        let leaves = leaves(tree, dh);
+        let assigned = assign(
+            &slots,
+            &leaves
+                .iter()
+                .map(|(_, matcher)| matcher.clone())
+                .collect::<Vec<_>>(),
+        );
+        let complete = assigned.iter().all(Option::is_some);
+
+        let mut placed = Vec::new();
+        let mut assigned = assigned
+            .into_iter()
+            .map(|idx| idx.map(|idx| leaves[idx].0.clone()));
+        let Some(filled) = Filled::new(&self.root, tree, output, &mut assigned, &mut placed) else {
+            return false;
+        };
+
+        let area = layer_map_for_output(output).non_exclusive_zone();
+        let root = tree
+            .insert(
+                Node::new(Data::Group {
+                    orientation: Orientation::Vertical,
+                    sizes: vec![area.size.w],
+                    last_geometry: area,
+                    alive: Arc::new(()),
+                }),
+                InsertBehavior::AsRoot,
+            )
+            .unwrap();
+        filled.place(tree, &root, area);
+        for (id, _) in leaves.iter() {
+            if !placed.contains(id) {
+                placed.push(id.clone());
+                move_to(tree, id, &root);
+            }
+        }
+
+        // drop what is left of the previous groups, `update_positions` flattens the new root
+        if tree
+            .get(&old_root)
+            .map(|node| node.data().is_group())
+            .unwrap_or(false)
+        {
+            tree.remove_node(old_root, RemoveBehavior::DropChildren)
+                .unwrap();
+        }
+        let count = tree.children_ids(&root).unwrap().count() as i32;
+        if let Data::Group { sizes, .. } = tree.get_mut(&root).unwrap().data_mut() {
+            *sizes = vec![area.size.w / count; count as usize];
+            *sizes.last_mut().unwrap() += area.size.w % count;
+        }
+
+        complete
+    }
+}
+
+/// All windows of `tree` in order, windows of a stack share the node of the stack
+fn leaves(tree: &Tree<Data>, dh: &DisplayHandle) -> Vec<(NodeId, WindowMatcher)> {
+    let Some(root) = tree.root_node_id() else { return Vec::new() };
+    tree.traverse_pre_order_ids(root)
+        .unwrap()
+        .flat_map(|id| match tree.get(&id).unwrap().data() {
+            Data::Mapped { mapped, .. } => mapped
+                .windows()
+                .map(|(window, _)| (id.clone(), WindowMatcher::for_window(&window, dh)))
+                .collect::<Vec<_>>(),
+            Data::Group { .. } => Vec::new(),
+        })
+        .collect()
+}
+
+/// Assigns the best fitting window to each slot, returning the index of the window per slot
+fn assign(slots: &[&WindowMatcher], windows: &[WindowMatcher]) -> Vec<Option<usize>> {
+    let mut assigned = vec![None; slots.len()];
+    let mut taken = vec![false; windows.len()];
+    // close matches first, so they don't lose their slot to windows only sharing the app_id
+    for score in (0..=2).rev() {
+        for (slot, matcher) in slots.iter().enumerate() {
+            if assigned[slot].is_some() {
+                continue;
+            }
+            if let Some(idx) = (0..windows.len())
+                .find(|idx| !taken[*idx] && matcher.score(&windows[*idx]) == Some(score))
+            {
+                taken[idx] = true;
+                assigned[slot] = Some(idx);
+            }
+        }
+    }
+    assigned
+}
+
+/// A saved node without its empty slots
+enum Filled {
+    Group {
+        orientation: Orientation,
+        ratios: Vec<f64>,
+        children: Vec<Filled>,
+    },
+    Window(NodeId),
+}
+
+impl Filled {
+    fn new(
+        node: &SavedNode,
+        tree: &mut Tree<Data>,
+        output: &Output,
+        assigned: &mut impl Iterator<Item = Option<NodeId>>,
+        placed: &mut Vec<NodeId>,
+    ) -> Option<Filled> {
+        let mut take = |placed: &mut Vec<NodeId>| {
+            let id = assigned.next().flatten()?;
+            if placed.contains(&id) {
+                return None;
+            }
+            placed.push(id.clone());
+            Some(id)
+        };
+
+        match node {
+            SavedNode::Window(_) => take(placed).map(Filled::Window),
+            SavedNode::Stack(matchers) => {
+                let windows = matchers
+                    .iter()
+                    .filter_map(|_| take(placed))
+                    .collect::<Vec<_>>();
+                match windows.len() {
+                    0 => None,
+                    1 => windows.into_iter().next().map(Filled::Window),
+                    _ => Some(Filled::Window(TilingLayout::stack_windows(
+                        tree, output, windows,
+                    ))),
+                }
+            }
+            SavedNode::Group {
+                orientation,
+                ratios,
+                children,
+            } => {
+                let mut filled = children
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(idx, child)| {
+                        let ratio = ratios
+                            .get(idx)
+                            .copied()
+                            .unwrap_or(1.0 / children.len() as f64)
+                            .max(0.05);
+                        Filled::new(child, tree, output, assigned, placed).map(|node| (ratio, node))
+                    })
+                    .collect::<Vec<_>>();
+                match filled.len() {
+                    0 => None,
+                    1 => filled.pop().map(|(_, node)| node),
+                    _ => {
+                        let (ratios, children) = filled.into_iter().unzip();
+                        Some(Filled::Group {
+                            orientation: *orientation,
+                            ratios,
+                            children,
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    fn place(self, tree: &mut Tree<Data>, parent: &NodeId, area: Rectangle<i32, Logical>) {
+        match self {
+            Filled::Window(id) => move_to(tree, &id, parent),
+            Filled::Group {
+                orientation,
+                ratios,
+                children,
+            } => {
+                let group = insert_group(tree, parent, orientation, area, &ratios);
+                for child in children {
+                    child.place(tree, &group, area);
+                }
+            }
+        }
+    }
+}
+
+fn template_path(name: &str) -> anyhow::Result<PathBuf> {
+    if name.is_empty() || name.contains('/') {
+        anyhow::bail!("Invalid layout name {:?}", name);
+    }
+    let xdg = xdg::BaseDirectories::new()?;
+    xdg.place_state_file(format!("cosmic-comp/layouts/{}.ron", name))
+        .with_context(|| format!("Failed to create directory for layout {:?}", name))
+}
+
+/// Stores a layout as `$XDG_STATE_HOME/cosmic-comp/layouts/<name>.ron`
+pub fn save_template(name: &str, template: &[SavedTree]) -> anyhow::Result<()> {
+    let path = template_path(name)?;
+    let file =
+        File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?;
+    ron::ser::to_writer_pretty(file, template, Default::default())
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Reads a layout previously stored with [`save_template`] or written by hand
+pub fn load_template(name: &str) -> anyhow::Result<Vec<SavedTree>> {
+    let path = template_path(name)?;
+    let file = File::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+    ron::de::from_reader(file).with_context(|| format!("Failed to parse {}", path.display()))
+}
@@ -10,6 +10,7 @@ use tracing::warn;
 
 use cosmic_protocols::workspace::v1::server::zcosmic_workspace_handle_v1::State as WState;
 use smithay::{
+    backend::renderer::element::Id,
     desktop::{
         layer_map_for_output, space::SpaceElement, LayerSurface, PopupManager, WindowSurfaceType,
     },
@@ -19,7 +20,7 @@ use smithay::{
     },
     output::Output,
     reexports::wayland_server::{protocol::wl_surface::WlSurface, DisplayHandle},
-    utils::{Logical, Point, Rectangle, Serial, SERIAL_COUNTER},
+    utils::{IsAlive, Logical, Point, Rectangle, Serial, Size, SERIAL_COUNTER},
     wayland::{
         compositor::with_states,
         seat::WaylandFocus,
@@ -34,24 +35,33 @@ use smithay::{
 };
 
 use crate::{
-    config::{Config, KeyModifiers, OutputConfig, WorkspaceMode as ConfigMode},
+    config::{
+        Config, KeyModifiers, OutputConfig, OutputInfo, WorkspaceDefinition,
+        WorkspaceMode as ConfigMode,
+    },
+    input::TouchState,
     utils::prelude::*,
     wayland::protocols::{
+        session_lock::{LockSurface, SessionLockManagerState, SessionLocker},
         toplevel_info::ToplevelInfoState,
         toplevel_management::{ManagementCapabilities, ToplevelManagementState},
         workspace::{
-            WorkspaceCapabilities, WorkspaceGroupHandle, WorkspaceHandle, WorkspaceState,
-            WorkspaceUpdateGuard,
+            GroupCapabilities, WorkspaceCapabilities, WorkspaceGroupHandle, WorkspaceHandle,
+            WorkspaceState, WorkspaceUpdateGuard,
         },
     },
 };
 
 pub mod element;
+mod expose;
 pub mod focus;
 pub mod grabs;
 pub mod layout;
+mod switcher;
 mod workspace;
 pub use self::element::{CosmicMapped, CosmicMappedRenderElement, CosmicSurface};
+pub use self::expose::{Expose, ExposeLayout};
+pub use self::switcher::{SwitcherScope, WindowSwitcher};
 pub use self::workspace::*;
 use self::{
     element::CosmicWindow,
@@ -59,7 +69,9 @@ use self::{
     grabs::ResizeEdge,
     layout::{
         floating::FloatingLayout,
-        tiling::{Direction, TilingLayout, ANIMATION_DURATION},
+        tiling::{
+            Direction, LayoutMode, SavedTree, TilingLayout, WindowMatcher, ANIMATION_DURATION,
+        },
     },
 };
 
@@ -85,89 +97,246 @@ pub struct Shell {
     pub toplevel_management_state: ToplevelManagementState,
     pub xdg_shell_state: XdgShellState,
     pub workspace_state: WorkspaceState<State>,
+    pub session_lock_manager_state: SessionLockManagerState,
+
+    /// Set while the session is locked, even if the locking client is gone
+    pub session_lock: Option<SessionLock>,
+    /// Tiling layouts of the previous session, not yet claimed by any of their windows
+    pub restored_layouts: HashMap<String, Vec<SavedTree>>,
+    /// Open window switcher, committed once its modifiers are released
+    pub switcher: Option<WindowSwitcher>,
+    /// Open overview of all windows and workspaces
+    pub expose: Option<Expose>,
 
     gaps: (u8, u8),
+    tiling_layout: LayoutMode,
+    workspace_definitions: Vec<WorkspaceDefinition>,
     overview_mode: OverviewMode,
+    /// Render id of the overview backdrop, shared by every time it is opened
+    expose_backdrop_id: Id,
+}
+
+#[derive(Debug)]
+pub struct SessionLock {
+    pub locker: SessionLocker,
+    pub surfaces: HashMap<Output, LockSurface>,
+    pub backdrop_id: Id,
+    /// Outputs, that didn't present a locked frame yet
+    pub pending_outputs: Vec<Output>,
 }
 
 #[derive(Debug)]
 pub struct WorkspaceSet {
     previously_active: Option<(usize, Instant)>,
+    /// Progress of a workspace switch following a touchpad gesture
+    swipe_progress: Option<f64>,
     active: usize,
     amount: WorkspaceAmount,
     group: WorkspaceGroupHandle,
     idx: usize,
     tiling_enabled: bool,
     gaps: (u8, u8),
+    layout: LayoutMode,
     pub(crate) workspaces: Vec<Workspace>,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum WorkspaceAmount {
     Dynamic,
     Static(u8),
 }
 
+/// Start of a workspace switch animation, that is currently at `progress`.
+///
+/// Inverts the cubic in-out easing used when rendering the animation.
+fn animation_start_for(progress: f64) -> Instant {
+    let progress = progress.clamp(0.0, 1.0);
+    let time = if progress < 0.5 {
+        (progress / 4.0).cbrt()
+    } else {
+        1.0 + ((progress - 1.0) / 4.0).cbrt()
+    };
+    let now = Instant::now();
+    now.checked_sub(ANIMATION_DURATION.mul_f64(time))
+        .unwrap_or(now)
+}
+
+/// Picks the output to move workspaces and windows of a disconnected output to.
+/// Internal panels are preferred, as they are the least likely to go away as well.
+fn fallback_output(outputs: &[Output]) -> Option<&Output> {
+    outputs
+        .iter()
+        .find(|output| is_internal_output(output))
+        .or_else(|| outputs.first())
+}
+
+/// Workspaces may only be created or removed by clients, if their amount is dynamic.
+/// Workspaces declared in the config can't be removed at all.
+fn workspace_capabilities(
+    amount: WorkspaceAmount,
+    workspace: &Workspace,
+) -> impl Iterator<Item = WorkspaceCapabilities> {
+    let mut capabilities = vec![
+        WorkspaceCapabilities::Activate,
+        WorkspaceCapabilities::Deactivate,
+    ];
+    if amount == WorkspaceAmount::Dynamic && workspace.definition.is_none() {
+        capabilities.push(WorkspaceCapabilities::Remove);
+    }
+    capabilities.into_iter()
+}
+
+fn group_capabilities(amount: WorkspaceAmount) -> impl Iterator<Item = GroupCapabilities> {
+    match amount {
+        WorkspaceAmount::Dynamic => vec![GroupCapabilities::CreateWorkspace],
+        WorkspaceAmount::Static(_) => Vec::new(),
+    }
+    .into_iter()
+}
+
 fn create_workspace(
     state: &mut WorkspaceUpdateGuard<'_, State>,
     group_handle: &WorkspaceGroupHandle,
     active: bool,
+    amount: WorkspaceAmount,
     tiling: bool,
     gaps: (u8, u8),
+    layout: LayoutMode,
 ) -> Workspace {
     let workspace_handle = state.create_workspace(&group_handle).unwrap();
     if active {
         state.add_workspace_state(&workspace_handle, WState::Active);
     }
+    let mut workspace = Workspace::new(workspace_handle, tiling, gaps);
+    workspace.tiling_layer.set_layout_mode(layout, None);
     state.set_workspace_capabilities(
         &workspace_handle,
-        [WorkspaceCapabilities::Activate].into_iter(),
+        workspace_capabilities(amount, &workspace),
+    );
+    workspace
+}
+
+fn create_declared_workspace(
+    state: &mut WorkspaceUpdateGuard<'_, State>,
+    group_handle: &WorkspaceGroupHandle,
+    active: bool,
+    amount: WorkspaceAmount,
+    tiling: bool,
+    gaps: (u8, u8),
+    layout: LayoutMode,
+    definition: &WorkspaceDefinition,
+) -> Workspace {
+    let mut workspace = create_workspace(
+        state,
+        group_handle,
+        active,
+        amount,
+        definition.tiling_enabled.unwrap_or(tiling),
+        gaps,
+        definition.layout.unwrap_or(layout),
     );
-    Workspace::new(workspace_handle, tiling, gaps)
+    workspace.name = Some(definition.name.clone());
+    workspace.definition = Some(definition.clone());
+    apply_definition(&mut workspace, gaps);
+    state.set_workspace_capabilities(
+        &workspace.handle,
+        workspace_capabilities(amount, &workspace),
+    );
+    workspace
+}
+
+/// Applies the layout settings of a declared workspace, falling back to the global ones
+fn apply_definition(workspace: &mut Workspace, gaps: (u8, u8)) {
+    let definition = workspace.definition.as_ref();
+    workspace
+        .tiling_layer
+        .set_gaps(definition.and_then(|d| d.gaps).unwrap_or(gaps));
+    workspace
+        .tiling_layer
+        .set_default_orientation(definition.and_then(|d| d.orientation));
+}
+
+/// Moves all windows of `workspace` onto `target` and removes it.
+fn merge_workspace(
+    state: &mut WorkspaceUpdateGuard<'_, State>,
+    toplevel_info: &mut ToplevelInfoState<State, CosmicSurface>,
+    workspace: Workspace,
+    target: &mut Workspace,
+) {
+    for element in workspace.mapped() {
+        // fixup toplevel state
+        for (toplevel, _) in element.windows() {
+            toplevel_info.toplevel_leave_workspace(&toplevel, &workspace.handle);
+            toplevel_info.toplevel_enter_workspace(&toplevel, &target.handle);
+        }
+    }
+    target.tiling_layer.merge(workspace.tiling_layer);
+    target.floating_layer.merge(workspace.floating_layer);
+    target.fullscreen.extend(workspace.fullscreen.into_iter());
+    state.remove_workspace(workspace.handle);
 }
 
 impl WorkspaceSet {
+    /// Creates a new set, starting with the given declared workspaces
     fn new(
         state: &mut WorkspaceUpdateGuard<'_, State>,
         amount: WorkspaceAmount,
         idx: usize,
         tiling_enabled: bool,
         gaps: (u8, u8),
+        layout: LayoutMode,
+        definitions: &[&WorkspaceDefinition],
     ) -> WorkspaceSet {
         let group_handle = state.create_workspace_group();
+        state.set_group_capabilities(&group_handle, group_capabilities(amount));
 
-        let workspaces = match amount {
-            WorkspaceAmount::Dynamic => {
-                let workspace = create_workspace(state, &group_handle, true, tiling_enabled, gaps);
-                workspace_set_idx(state, 1, idx, &workspace.handle);
-                state.set_workspace_capabilities(
-                    &workspace.handle,
-                    [WorkspaceCapabilities::Activate].into_iter(),
-                );
-                vec![workspace]
-            }
-            WorkspaceAmount::Static(len) => (0..len)
-                .map(|i| {
-                    let workspace =
-                        create_workspace(state, &group_handle, i == 0, tiling_enabled, gaps);
-                    workspace_set_idx(state, i + 1, idx, &workspace.handle);
-                    state.set_workspace_capabilities(
-                        &workspace.handle,
-                        [WorkspaceCapabilities::Activate].into_iter(),
-                    );
-                    workspace
-                })
-                .collect(),
+        let mut workspaces = definitions
+            .iter()
+            .enumerate()
+            .map(|(i, definition)| {
+                create_declared_workspace(
+                    state,
+                    &group_handle,
+                    i == 0,
+                    amount,
+                    tiling_enabled,
+                    gaps,
+                    layout,
+                    definition,
+                )
+            })
+            .collect::<Vec<_>>();
+        let len = match amount {
+            // one empty workspace at the end
+            WorkspaceAmount::Dynamic => workspaces.len() + 1,
+            WorkspaceAmount::Static(len) => workspaces.len().max(len as usize),
         };
+        while workspaces.len() < len {
+            let workspace = create_workspace(
+                state,
+                &group_handle,
+                workspaces.is_empty(),
+                amount,
+                tiling_enabled,
+                gaps,
+                layout,
+            );
+            workspaces.push(workspace);
+        }
+        for (i, workspace) in workspaces.iter().enumerate() {
+            workspace_set_idx(state, i as u8 + 1, idx, workspace);
+        }
 
         WorkspaceSet {
             previously_active: None,
+            swipe_progress: None,
             active: 0,
             amount,
             group: group_handle,
             idx,
             tiling_enabled,
             gaps,
+            layout,
             workspaces,
         }
     }
@@ -176,6 +345,7 @@ impl WorkspaceSet {
         &mut self,
         idx: usize,
         state: &mut WorkspaceUpdateGuard<'_, State>,
+        toplevel_info: &mut ToplevelInfoState<State, CosmicSurface>,
     ) -> Result<bool, InvalidWorkspaceIndex> {
         if idx >= self.workspaces.len() {
             return Err(InvalidWorkspaceIndex);
@@ -185,14 +355,72 @@ impl WorkspaceSet {
             let old_active = self.active;
             state.remove_workspace_state(&self.workspaces[old_active].handle, WState::Active);
             state.add_workspace_state(&self.workspaces[idx].handle, WState::Active);
-            self.previously_active = Some((old_active, Instant::now()));
             self.active = idx;
+            // sticky windows must not take part in the switch animation
+            self.collect_sticky(toplevel_info);
+            self.previously_active = Some((old_active, Instant::now()));
             Ok(true)
         } else {
             Ok(false)
         }
     }
 
+    /// Returns the previously active workspace and the start of the switch animation.
+    ///
+    /// While a gesture is in progress, the start is derived from the gesture's progress,
+    /// so the animation stays where the fingers are.
+    fn previously_active(&self) -> Option<(usize, Instant)> {
+        self.previously_active
+            .map(|(idx, start)| match self.swipe_progress {
+                Some(progress) => (idx, animation_start_for(progress)),
+                None => (idx, start),
+            })
+    }
+
+    fn begin_swipe(
+        &mut self,
+        idx: usize,
+        state: &mut WorkspaceUpdateGuard<'_, State>,
+        toplevel_info: &mut ToplevelInfoState<State, CosmicSurface>,
+    ) -> Result<bool, InvalidWorkspaceIndex> {
+        let activated = self.activate(idx, state, toplevel_info)?;
+        if activated {
+            self.swipe_progress = Some(0.0);
+        }
+        Ok(activated)
+    }
+
+    fn update_swipe(&mut self, progress: f64) {
+        if let Some(swipe_progress) = self.swipe_progress.as_mut() {
+            // never reach the end, or `refresh` would consider the animation done
+            *swipe_progress = progress.clamp(0.0, 0.99);
+        }
+    }
+
+    fn end_swipe(
+        &mut self,
+        cancel: bool,
+        state: &mut WorkspaceUpdateGuard<'_, State>,
+        toplevel_info: &mut ToplevelInfoState<State, CosmicSurface>,
+    ) {
+        let Some(progress) = self.swipe_progress.take() else {
+            return;
+        };
+        let Some((previous, _)) = self.previously_active else {
+            return;
+        };
+
+        if cancel || progress < 0.5 {
+            // animate back from where the fingers left off
+            let _ = self.activate(previous, state, toplevel_info);
+            if let Some((idx, _)) = self.previously_active {
+                self.previously_active = Some((idx, animation_start_for(1.0 - progress)));
+            }
+        } else {
+            self.previously_active = Some((previous, animation_start_for(progress)));
+        }
+    }
+
     fn refresh<'a>(
         &mut self,
         state: &mut WorkspaceState<State>,
@@ -200,21 +428,68 @@ impl WorkspaceSet {
         outputs: impl Iterator<Item = (&'a Output, Point<i32, Logical>)>,
     ) {
         if let Some((_, start)) = self.previously_active {
-            if Instant::now().duration_since(start).as_millis() >= ANIMATION_DURATION.as_millis() {
+            if self.swipe_progress.is_none()
+                && Instant::now().duration_since(start).as_millis()
+                    >= ANIMATION_DURATION.as_millis()
+            {
                 self.previously_active = None;
             }
         }
 
+        // minimized windows are not refreshed with their workspace
+        for workspace in self.workspaces.iter_mut() {
+            workspace.minimized.retain(|m| m.window.alive());
+        }
+        self.collect_sticky(toplevel_info);
+
         match self.amount {
             WorkspaceAmount::Dynamic => self.ensure_last_empty(state, outputs),
             WorkspaceAmount::Static(len) => {
-                self.ensure_static(len as usize, state, toplevel_info, outputs)
+                // declared workspaces always exist
+                let declared = self
+                    .workspaces
+                    .iter()
+                    .filter(|w| w.definition.is_some())
+                    .count();
+                self.ensure_static((len as usize).max(declared), state, toplevel_info, outputs)
             }
         }
 
         self.workspaces[self.active].refresh();
     }
 
+    /// Moves sticky windows of inactive workspaces onto the active one, keeping their position
+    fn collect_sticky(&mut self, toplevel_info: &mut ToplevelInfoState<State, CosmicSurface>) {
+        let active = self.active;
+        for idx in (0..self.workspaces.len()).filter(|idx| *idx != active) {
+            let sticky = self.workspaces[idx]
+                .floating_layer
+                .mapped()
+                .filter(|m| m.is_sticky() && !m.is_fullscreen(true))
+                .cloned()
+                .collect::<Vec<_>>();
+            for mapped in sticky {
+                let workspace = &mut self.workspaces[idx];
+                let space = &workspace.floating_layer.space;
+                let Some(output) = space.outputs_for_element(&mapped).into_iter().next() else {
+                    continue;
+                };
+                let position = space.element_location(&mapped);
+                let from = workspace.handle;
+                workspace.unmap(&mapped);
+
+                let workspace = &mut self.workspaces[active];
+                workspace
+                    .floating_layer
+                    .map_internal(mapped.clone(), &output, position);
+                for (window, _) in mapped.windows() {
+                    toplevel_info.toplevel_leave_workspace(&window, &from);
+                    toplevel_info.toplevel_enter_workspace(&window, &workspace.handle);
+                }
+            }
+        }
+    }
+
     fn ensure_last_empty<'a>(
         &mut self,
         state: &mut WorkspaceState<State>,
@@ -223,23 +498,22 @@ impl WorkspaceSet {
         let mut state = state.update();
 
         // add empty at the end, if necessary
-        if self.workspaces.last().unwrap().windows().next().is_some() {
+        let last = self.workspaces.last().unwrap();
+        if last.windows().next().is_some() || last.name.is_some() {
             let mut workspace = create_workspace(
                 &mut state,
                 &self.group,
                 false,
+                self.amount,
                 self.tiling_enabled,
                 self.gaps,
+                self.layout,
             );
             workspace_set_idx(
                 &mut state,
                 self.workspaces.len() as u8 + 1,
                 self.idx,
-                &workspace.handle,
-            );
-            state.set_workspace_capabilities(
-                &workspace.handle,
-                [WorkspaceCapabilities::Activate].into_iter(),
+                &workspace,
             );
             for (output, location) in outputs {
                 workspace.map_output(output, location);
@@ -249,11 +523,11 @@ impl WorkspaceSet {
 
         let len = self.workspaces.len();
         let mut keep = vec![true; len];
-        // remove empty workspaces in between, if they are not active
+        // remove empty workspaces in between, if they are not active or named
         for (i, workspace) in self.workspaces.iter().enumerate() {
             let has_windows = workspace.windows().next().is_some();
 
-            if !has_windows && i != self.active && i != len - 1 {
+            if !has_windows && workspace.name.is_none() && i != self.active && i != len - 1 {
                 state.remove_workspace(workspace.handle);
                 keep[i] = false;
             }
@@ -268,8 +542,10 @@ impl WorkspaceSet {
             .count();
 
         if keep.iter().any(|val| *val == false) {
-            for (i, workspace) in self.workspaces.iter().enumerate() {
-                workspace_set_idx(&mut state, i as u8 + 1, self.idx, &workspace.handle);
+            for (i, workspace) in self.workspaces.iter_mut().enumerate() {
+                workspace_set_idx(&mut state, i as u8 + 1, self.idx, workspace);
+                // layouts are saved by index
+                workspace.tiling_layer.mark_changed();
             }
         }
     }
@@ -292,19 +568,7 @@ impl WorkspaceSet {
             let last_space = self.workspaces.last_mut().unwrap();
 
             for workspace in overflow {
-                for element in workspace.mapped() {
-                    // fixup toplevel state
-                    for (toplevel, _) in element.windows() {
-                        toplevel_info.toplevel_leave_workspace(&toplevel, &workspace.handle);
-                        toplevel_info.toplevel_enter_workspace(&toplevel, &last_space.handle);
-                    }
-                }
-                last_space.tiling_layer.merge(workspace.tiling_layer);
-                last_space.floating_layer.merge(workspace.floating_layer);
-                last_space
-                    .fullscreen
-                    .extend(workspace.fullscreen.into_iter());
-                state.remove_workspace(workspace.handle);
+                merge_workspace(&mut state, toplevel_info, workspace, last_space);
             }
 
             last_space.refresh();
@@ -317,18 +581,16 @@ impl WorkspaceSet {
                     &mut state,
                     &self.group,
                     false,
+                    self.amount,
                     self.tiling_enabled,
                     self.gaps,
+                    self.layout,
                 );
                 workspace_set_idx(
                     &mut state,
                     self.workspaces.len() as u8 + 1,
                     self.idx,
-                    &workspace.handle,
-                );
-                state.set_workspace_capabilities(
-                    &workspace.handle,
-                    [WorkspaceCapabilities::Activate].into_iter(),
+                    &workspace,
                 );
                 for &(output, location) in outputs.iter() {
                     workspace.map_output(output, location);
@@ -341,18 +603,294 @@ impl WorkspaceSet {
     fn update_idx(&mut self, state: &mut WorkspaceUpdateGuard<'_, State>, idx: usize) {
         self.idx = idx;
         for (i, workspace) in self.workspaces.iter().enumerate() {
-            workspace_set_idx(state, i as u8 + 1, idx, &workspace.handle);
+            workspace_set_idx(state, i as u8 + 1, idx, workspace);
+        }
+    }
+
+    fn update_amount(
+        &mut self,
+        state: &mut WorkspaceUpdateGuard<'_, State>,
+        amount: WorkspaceAmount,
+    ) {
+        self.amount = amount;
+        state.set_group_capabilities(&self.group, group_capabilities(amount));
+        for workspace in &self.workspaces {
+            state.set_workspace_capabilities(
+                &workspace.handle,
+                workspace_capabilities(amount, workspace),
+            );
         }
     }
 
+    /// Adds a named workspace at the end of the set.
+    fn create<'a>(
+        &mut self,
+        name: String,
+        state: &mut WorkspaceUpdateGuard<'_, State>,
+        outputs: impl Iterator<Item = (&'a Output, Point<i32, Logical>)>,
+    ) {
+        let mut workspace = create_workspace(
+            state,
+            &self.group,
+            false,
+            self.amount,
+            self.tiling_enabled,
+            self.gaps,
+            self.layout,
+        );
+        workspace.name = Some(name);
+        for (output, location) in outputs {
+            workspace.map_output(output, location);
+        }
+
+        // stay in front of the trailing empty workspace of dynamic sets
+        let len = self.workspaces.len();
+        let last = self.workspaces.last().unwrap();
+        let idx =
+            if last.windows().next().is_none() && last.name.is_none() && self.active != len - 1 {
+                len - 1
+            } else {
+                len
+            };
+        self.workspaces.insert(idx, workspace);
+        if self.active >= idx {
+            self.active += 1;
+        }
+        for workspace in &mut self.workspaces[idx..] {
+            workspace.tiling_layer.mark_changed();
+        }
+        self.previously_active = None;
+        self.swipe_progress = None;
+        self.update_idx(state, self.idx);
+    }
+
+    /// Removes the workspace at `idx`, its windows move to the previous (or next) workspace.
+    fn remove(
+        &mut self,
+        idx: usize,
+        state: &mut WorkspaceUpdateGuard<'_, State>,
+        toplevel_info: &mut ToplevelInfoState<State, CosmicSurface>,
+    ) -> Result<(), InvalidWorkspaceIndex> {
+        if idx >= self.workspaces.len() || self.workspaces.len() == 1 {
+            return Err(InvalidWorkspaceIndex);
+        }
+
+        let workspace = self.workspaces.remove(idx);
+        let neighbor = idx.saturating_sub(1);
+        let target = &mut self.workspaces[neighbor];
+        merge_workspace(state, toplevel_info, workspace, target);
+        target.refresh();
+
+        if self.active == idx {
+            self.active = neighbor;
+            state.add_workspace_state(&self.workspaces[neighbor].handle, WState::Active);
+        } else if self.active > idx {
+            self.active -= 1;
+        }
+        self.previously_active = None;
+        self.swipe_progress = None;
+        self.update_idx(state, self.idx);
+        Ok(())
+    }
+
+    /// Switches away from the workspace at `idx`, if it is active.
+    fn deactivate(
+        &mut self,
+        idx: usize,
+        state: &mut WorkspaceUpdateGuard<'_, State>,
+        toplevel_info: &mut ToplevelInfoState<State, CosmicSurface>,
+    ) -> Result<bool, InvalidWorkspaceIndex> {
+        if idx >= self.workspaces.len() {
+            return Err(InvalidWorkspaceIndex);
+        }
+        if self.active != idx || self.workspaces.len() == 1 {
+            return Ok(false);
+        }
+
+        let target = match self.previously_active {
+            Some((previous, _)) if previous != idx => previous,
+            _ if idx > 0 => idx - 1,
+            _ => idx + 1,
+        };
+        self.activate(target, state, toplevel_info)
+    }
+
+    /// Moves a workspace of another output's set into this one at `idx`.
+    fn adopt(
+        &mut self,
+        idx: usize,
+        mut workspace: Workspace,
+        from: &Output,
+        to: &Output,
+        state: &mut WorkspaceUpdateGuard<'_, State>,
+        toplevel_info: &mut ToplevelInfoState<State, CosmicSurface>,
+    ) {
+        // update workspace protocol state
+        state.remove_workspace(workspace.handle);
+        let workspace_handle = state.create_workspace(&self.group).unwrap();
+        for toplevel in workspace.windows() {
+            toplevel_info.toplevel_leave_workspace(&toplevel, &workspace.handle);
+            toplevel_info.toplevel_enter_workspace(&toplevel, &workspace_handle);
+        }
+        workspace.handle = workspace_handle;
+        state.set_workspace_capabilities(
+            &workspace_handle,
+            workspace_capabilities(self.amount, &workspace),
+        );
+
+        // update mapping
+        workspace.map_output(to, (0, 0).into());
+        workspace.unmap_output(from, toplevel_info);
+        workspace.refresh();
+
+        self.workspaces.insert(idx, workspace);
+    }
+
+    /// Takes all workspaces matching `predicate` out of the set, always leaving at least one behind.
+    fn take_workspaces(
+        &mut self,
+        state: &mut WorkspaceUpdateGuard<'_, State>,
+        mut predicate: impl FnMut(&Workspace) -> bool,
+    ) -> Vec<Workspace> {
+        if !self.workspaces.iter().any(|w| predicate(w)) {
+            return Vec::new();
+        }
+
+        let active = self.workspaces[self.active].handle;
+        let (taken, mut kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.workspaces)
+            .into_iter()
+            .partition(|w| predicate(w));
+        if kept.is_empty() {
+            kept.push(create_workspace(
+                state,
+                &self.group,
+                false,
+                self.amount,
+                self.tiling_enabled,
+                self.gaps,
+                self.layout,
+            ));
+        }
+        self.workspaces = kept;
+        self.active = match self.workspaces.iter().position(|w| w.handle == active) {
+            Some(idx) => idx,
+            None => {
+                state.add_workspace_state(&self.workspaces[0].handle, WState::Active);
+                0
+            }
+        };
+        self.previously_active = None;
+        self.swipe_progress = None;
+        self.update_idx(state, self.idx);
+        taken
+    }
+
     fn update_tiling_status(&mut self, seat: &Seat<State>, tiling_enabled: bool) {
         self.tiling_enabled = tiling_enabled;
         for workspace in &mut self.workspaces {
+            let tiling_enabled = workspace
+                .definition
+                .as_ref()
+                .and_then(|d| d.tiling_enabled)
+                .unwrap_or(tiling_enabled);
             if workspace.tiling_enabled != tiling_enabled {
                 workspace.toggle_tiling(seat);
             }
         }
     }
+
+    fn update_gaps(&mut self, gaps: (u8, u8)) {
+        self.gaps = gaps;
+        for workspace in &mut self.workspaces {
+            apply_definition(workspace, gaps);
+        }
+    }
+
+    fn update_tiling_layout(&mut self, seat: &Seat<State>, layout: LayoutMode) {
+        self.layout = layout;
+        for workspace in &mut self.workspaces {
+            let layout = workspace
+                .definition
+                .as_ref()
+                .and_then(|d| d.layout)
+                .unwrap_or(layout);
+            workspace.set_layout_mode(layout, seat);
+        }
+    }
+
+    /// Updates the declared workspaces of this set to `definitions`, creating missing ones.
+    ///
+    /// Declared workspaces are kept at the front of the set in the order of their definitions.
+    fn declare<'a>(
+        &mut self,
+        definitions: &[&WorkspaceDefinition],
+        state: &mut WorkspaceUpdateGuard<'_, State>,
+        seat: &Seat<State>,
+        outputs: impl Iterator<Item = (&'a Output, Point<i32, Logical>)>,
+    ) {
+        let outputs = outputs.collect::<Vec<_>>();
+        for definition in definitions {
+            let existing = self
+                .workspaces
+                .iter_mut()
+                .find(|w| w.name.as_deref() == Some(definition.name.as_str()));
+            match existing {
+                Some(workspace) => {
+                    workspace.definition = Some((*definition).clone());
+                    if let Some(tiling_enabled) = definition.tiling_enabled {
+                        if workspace.tiling_enabled != tiling_enabled {
+                            workspace.toggle_tiling(seat);
+                        }
+                    }
+                    if let Some(layout) = definition.layout {
+                        workspace.set_layout_mode(layout, seat);
+                    }
+                }
+                None => {
+                    let mut workspace = create_declared_workspace(
+                        state,
+                        &self.group,
+                        false,
+                        self.amount,
+                        self.tiling_enabled,
+                        self.gaps,
+                        self.layout,
+                        definition,
+                    );
+                    for &(output, location) in outputs.iter() {
+                        workspace.map_output(output, location);
+                    }
+                    self.workspaces.push(workspace);
+                }
+            }
+        }
+
+        let position = |workspace: &Workspace| {
+            workspace
+                .definition
+                .as_ref()
+                .and_then(|d| definitions.iter().position(|def| def.name == d.name))
+                .unwrap_or(definitions.len())
+        };
+        let active = self.workspaces[self.active].handle;
+        self.workspaces.sort_by_key(|w| position(w));
+        self.active = self
+            .workspaces
+            .iter()
+            .position(|w| w.handle == active)
+            .unwrap();
+        self.previously_active = None;
+        self.swipe_progress = None;
+
+        for workspace in &mut self.workspaces {
+            apply_definition(workspace, self.gaps);
+            state.set_workspace_capabilities(
+                &workspace.handle,
+                workspace_capabilities(self.amount, workspace),
+            );
+        }
+        self.update_idx(state, self.idx);
+    }
 }
 
 #[derive(Debug)]
@@ -368,11 +906,19 @@ impl WorkspaceMode {
         state: &mut WorkspaceUpdateGuard<'_, State>,
         tiling_enabled: bool,
         gaps: (u8, u8),
+        layout: LayoutMode,
+        definitions: &[WorkspaceDefinition],
     ) -> WorkspaceMode {
         match config {
-            crate::config::WorkspaceMode::Global => {
-                WorkspaceMode::Global(WorkspaceSet::new(state, amount, 0, tiling_enabled, gaps))
-            }
+            crate::config::WorkspaceMode::Global => WorkspaceMode::Global(WorkspaceSet::new(
+                state,
+                amount,
+                0,
+                tiling_enabled,
+                gaps,
+                layout,
+                &definitions.iter().collect::<Vec<_>>(),
+            )),
             crate::config::WorkspaceMode::OutputBound => {
                 WorkspaceMode::OutputBound(HashMap::new(), amount)
             }
@@ -400,14 +946,14 @@ impl WorkspaceMode {
     pub fn active(&self, output: &Output) -> (Option<(&Workspace, Instant)>, &Workspace) {
         match self {
             WorkspaceMode::Global(set) => (
-                set.previously_active
+                set.previously_active()
                     .map(|(idx, start)| (&set.workspaces[idx], start)),
                 &set.workspaces[set.active],
             ),
             WorkspaceMode::OutputBound(sets, _) => {
                 let set = sets.get(output).unwrap();
                 (
-                    set.previously_active
+                    set.previously_active()
                         .map(|(idx, start)| (&set.workspaces[idx], start)),
                     &set.workspaces[set.active],
                 )
@@ -445,6 +991,19 @@ impl WorkspaceMode {
         }
     }
 
+    /// Identifies a workspace across restarts, to persist its tiling layout
+    pub fn layout_key(&self, num: usize, output: &Output) -> Option<String> {
+        let workspace = self.get(num, output)?;
+        let name = workspace
+            .name
+            .clone()
+            .unwrap_or_else(|| (num + 1).to_string());
+        Some(match self {
+            WorkspaceMode::Global(_) => name,
+            WorkspaceMode::OutputBound(_, _) => format!("{}/{}", output.name(), name),
+        })
+    }
+
     pub fn spaces(&self) -> impl Iterator<Item = &Workspace> {
         match self {
             WorkspaceMode::Global(set) => {
@@ -490,6 +1049,44 @@ impl WorkspaceMode {
             }
         }
     }
+
+    pub fn update_gaps(&mut self, gaps: (u8, u8)) {
+        match self {
+            WorkspaceMode::Global(set) => set.update_gaps(gaps),
+            WorkspaceMode::OutputBound(sets, _) => {
+                for set in sets.values_mut() {
+                    set.update_gaps(gaps)
+                }
+            }
+        }
+    }
+
+    pub fn update_tiling_layout(&mut self, seat: &Seat<State>, layout: LayoutMode) {
+        match self {
+            WorkspaceMode::Global(set) => set.update_tiling_layout(seat, layout),
+            WorkspaceMode::OutputBound(sets, _) => {
+                for set in sets.values_mut() {
+                    set.update_tiling_layout(seat, layout)
+                }
+            }
+        }
+    }
+
+    pub fn update_amount(
+        &mut self,
+        amount: WorkspaceAmount,
+        state: &mut WorkspaceUpdateGuard<'_, State>,
+    ) {
+        match self {
+            WorkspaceMode::Global(set) => set.update_amount(state, amount),
+            WorkspaceMode::OutputBound(sets, old_amount) => {
+                *old_amount = amount;
+                for set in sets.values_mut() {
+                    set.update_amount(state, amount);
+                }
+            }
+        }
+    }
 }
 
 pub struct InvalidWorkspaceIndex;
@@ -509,6 +1106,7 @@ impl Shell {
             vec![
                 ManagementCapabilities::Close,
                 ManagementCapabilities::Activate,
+                ManagementCapabilities::Minimize,
             ],
             //|client| client.get_data::<ClientState>().map_or(false, |s| s.privileged),
             |_| true,
@@ -518,6 +1116,7 @@ impl Shell {
             //|client| client.get_data::<ClientState>().map_or(false, |s| s.privileged),
             |_| true,
         );
+        let session_lock_manager_state = SessionLockManagerState::new::<State>(dh);
 
         let tiling_enabled = config.static_conf.tiling_enabled;
         let mode = WorkspaceMode::new(
@@ -526,6 +1125,8 @@ impl Shell {
             &mut workspace_state.update(),
             tiling_enabled,
             config.static_conf.gaps,
+            config.static_conf.tiling_layout,
+            &config.static_conf.workspaces,
         );
 
         Shell {
@@ -543,9 +1144,18 @@ impl Shell {
             toplevel_management_state,
             xdg_shell_state,
             workspace_state,
+            session_lock_manager_state,
+
+            session_lock: None,
+            restored_layouts: config.dynamic_conf.layouts().workspaces.clone(),
+            switcher: None,
+            expose: None,
 
             gaps: config.static_conf.gaps,
+            tiling_layout: config.static_conf.tiling_layout,
+            workspace_definitions: config.static_conf.workspaces.clone(),
             overview_mode: OverviewMode::None,
+            expose_backdrop_id: Id::new(),
         }
     }
 
@@ -559,14 +1169,36 @@ impl Shell {
 
         match &mut self.workspaces {
             WorkspaceMode::OutputBound(sets, amount) => {
-                // TODO: Restore previously assigned workspaces, if possible!
                 if !sets.contains_key(output) {
+                    // declared workspaces go to their output, or the first one if it isn't connected
+                    let outputs = &self.outputs;
+                    let definitions = self
+                        .workspace_definitions
+                        .iter()
+                        .filter(|definition| {
+                            let exists = sets.values().any(|set| {
+                                set.workspaces
+                                    .iter()
+                                    .any(|w| w.name.as_ref() == Some(&definition.name))
+                            });
+                            !exists
+                                && match definition.output.as_ref() {
+                                    Some(name) => {
+                                        name == &output.name()
+                                            || !outputs.iter().any(|o| &o.name() == name)
+                                    }
+                                    None => true,
+                                }
+                        })
+                        .collect::<Vec<_>>();
                     let set = WorkspaceSet::new(
                         &mut state,
                         *amount,
                         sets.len(),
                         self.tiling_enabled,
                         self.gaps,
+                        self.tiling_layout,
+                        &definitions,
                     );
                     state.add_group_output(&set.group, &output);
                     sets.insert(output.clone(), set);
@@ -574,9 +1206,44 @@ impl Shell {
                 for workspace in &mut sets.get_mut(output).unwrap().workspaces {
                     workspace.map_output(output, (0, 0).into());
                 }
+
+                // move workspaces back, that were displaced when this output got disconnected
+                let origin = OutputInfo::from(output.clone());
+                let name = output.name();
+                let mut restored = Vec::new();
+                for (other, set) in sets.iter_mut().filter(|(o, _)| *o != output) {
+                    restored.extend(
+                        set.take_workspaces(&mut state, |w| {
+                            w.origin.as_ref() == Some(&origin)
+                                || w.definition.as_ref().and_then(|d| d.output.as_ref())
+                                    == Some(&name)
+                        })
+                        .into_iter()
+                        .map(|workspace| (other.clone(), workspace)),
+                    );
+                }
+                if !restored.is_empty() {
+                    let set = sets.get_mut(output).unwrap();
+                    state
+                        .remove_workspace_state(&set.workspaces[set.active].handle, WState::Active);
+                    for (i, (from, mut workspace)) in restored.into_iter().enumerate() {
+                        workspace.origin = None;
+                        set.adopt(
+                            i,
+                            workspace,
+                            &from,
+                            output,
+                            &mut state,
+                            &mut self.toplevel_info_state,
+                        );
+                    }
+                    set.active = 0;
+                    set.previously_active = None;
+                    state.add_workspace_state(&set.workspaces[0].handle, WState::Active);
+                    set.update_idx(&mut state, set.idx);
+                }
             }
             WorkspaceMode::Global(set) => {
-                // TODO: Restore any window positions from previous outputs ???
                 state.add_group_output(&set.group, output);
                 for workspace in &mut set.workspaces {
                     workspace.map_output(
@@ -595,10 +1262,16 @@ impl Shell {
     }
 
     pub fn remove_output(&mut self, output: &Output, seats: impl Iterator<Item = Seat<State>>) {
-        if let Some(first_output) = self.outputs.get(0) {
+        let remaining = self
+            .outputs
+            .iter()
+            .filter(|o| *o != output)
+            .cloned()
+            .collect::<Vec<_>>();
+        if let Some(new_output) = fallback_output(&remaining) {
             for seat in seats {
                 if &seat.active_output() == output {
-                    seat.set_active_output(first_output);
+                    seat.set_active_output(new_output);
                 }
             }
         }
@@ -623,29 +1296,23 @@ impl Shell {
                 // If amount::static merge them instead of appending
 
                 if let Some(set) = sets.remove(output) {
-                    // TODO: Heuristic which output to move to.
-                    // It is supposed to be the *most* internal, we just pick the first one for now
-                    // and hope enumeration order works in our favor.
-                    if let Some(new_output) = self.outputs.get(0) {
+                    if let Some(new_output) = fallback_output(&self.outputs) {
                         let new_set = sets.get_mut(new_output).unwrap();
-                        let workspace_group = new_set.group;
+                        let origin = OutputInfo::from(output.clone());
                         for mut workspace in set.workspaces {
-                            // update workspace protocol state
-                            state.remove_workspace(workspace.handle);
-                            let workspace_handle =
-                                state.create_workspace(&workspace_group).unwrap();
-                            state.set_workspace_capabilities(
-                                &workspace_handle,
-                                [WorkspaceCapabilities::Activate].into_iter(),
+                            // remember where the workspace came from, unless it was moved before
+                            if workspace.origin.is_none() {
+                                workspace.origin = Some(origin.clone());
+                            }
+                            let idx = new_set.workspaces.len();
+                            new_set.adopt(
+                                idx,
+                                workspace,
+                                output,
+                                new_output,
+                                &mut state,
+                                &mut self.toplevel_info_state,
                             );
-                            workspace.handle = workspace_handle;
-
-                            // update mapping
-                            workspace.map_output(new_output, (0, 0).into());
-                            workspace.unmap_output(output, &mut self.toplevel_info_state);
-                            workspace.refresh();
-
-                            new_set.workspaces.push(workspace);
                         }
                         state.remove_workspace_group(set.group);
                     }
@@ -706,6 +1373,8 @@ impl Shell {
                     0,
                     self.tiling_enabled,
                     self.gaps,
+                    self.tiling_layout,
+                    &[],
                 );
                 for output in &self.outputs {
                     state.add_group_output(&new_set.group, output);
@@ -761,11 +1430,6 @@ impl Shell {
                 for (i, (workspaces, active)) in mergers.into_iter().enumerate() {
                     // and then we can merge each vector into one and put that into our new set.
                     let workspace_handle = state.create_workspace(&new_set.group).unwrap();
-                    state.set_workspace_capabilities(
-                        &workspace_handle,
-                        [WorkspaceCapabilities::Activate].into_iter(),
-                    );
-                    workspace_set_idx(&mut state, i as u8 + 1, 0, &workspace_handle);
 
                     let mut new_workspace =
                         Workspace::new(workspace_handle, self.tiling_enabled, self.gaps);
@@ -773,6 +1437,16 @@ impl Shell {
                         new_workspace.map_output(output, output.current_location());
                     }
                     new_workspace.tiling_enabled = workspaces.iter().any(|(_, w)| w.tiling_enabled);
+                    new_workspace.name = workspaces.iter().find_map(|(_, w)| w.name.clone());
+                    new_workspace.definition =
+                        workspaces.iter().find_map(|(_, w)| w.definition.clone());
+                    if let Some((_, workspace)) = workspaces.first() {
+                        new_workspace
+                            .tiling_layer
+                            .set_layout_mode(workspace.tiling_layer.layout_mode(), None);
+                    }
+                    apply_definition(&mut new_workspace, self.gaps);
+                    workspace_set_idx(&mut state, i as u8 + 1, 0, &new_workspace);
 
                     for (_output, workspace) in workspaces.into_iter() {
                         for toplevel in workspace.windows() {
@@ -786,6 +1460,7 @@ impl Shell {
                         new_workspace
                             .fullscreen
                             .extend(workspace.fullscreen.into_iter());
+                        new_workspace.minimized.extend(workspace.minimized);
                         state.remove_workspace(workspace.handle);
                     }
 
@@ -799,7 +1474,7 @@ impl Shell {
                     state.remove_workspace_group(group);
                 }
 
-                new_set.amount = amount;
+                new_set.update_amount(&mut state, amount);
                 *dst = WorkspaceMode::Global(new_set);
             }
             (dst @ WorkspaceMode::Global(_), ConfigMode::OutputBound) => {
@@ -819,6 +1494,8 @@ impl Shell {
                         i,
                         self.tiling_enabled,
                         self.gaps,
+                        self.tiling_layout,
+                        &[],
                     );
                     state.add_group_output(&set.group, output);
                     sets.insert(output.clone(), set);
@@ -828,15 +1505,11 @@ impl Shell {
                         // copy over everything and then remove other outputs to preserve state
                         let new_set = sets.get_mut(output).unwrap();
                         let new_workspace_handle = state.create_workspace(&new_set.group).unwrap();
-                        state.set_workspace_capabilities(
-                            &new_workspace_handle,
-                            [WorkspaceCapabilities::Activate].into_iter(),
-                        );
-                        workspace_set_idx(&mut state, i as u8 + 1, idx, &new_workspace_handle);
 
                         let mut old_tiling_layer = workspace.tiling_layer.clone();
                         let mut new_floating_layer = FloatingLayout::new();
                         let mut new_tiling_layer = TilingLayout::new(self.gaps);
+                        new_tiling_layer.set_layout_mode(old_tiling_layer.layout_mode(), None);
 
                         for element in workspace.mapped() {
                             for (toplevel, _) in element.windows() {
@@ -870,6 +1543,23 @@ impl Shell {
                             }
                         }
 
+                        // minimized windows stay with their output, or go to the first one
+                        let minimized = workspace
+                            .minimized
+                            .iter()
+                            .filter(|m| {
+                                &m.output == output
+                                    || (idx == 0 && !self.outputs.contains(&m.output))
+                            })
+                            .cloned()
+                            .collect::<Vec<_>>();
+                        for m in workspace.minimized.iter() {
+                            for (toplevel, _) in m.window.windows() {
+                                self.toplevel_info_state
+                                    .toplevel_leave_workspace(&toplevel, &workspace.handle);
+                            }
+                        }
+
                         new_floating_layer.map_output(output, (0, 0).into());
                         new_tiling_layer.map_output(output, (0, 0).into());
                         new_tiling_layer.merge(old_tiling_layer);
@@ -884,8 +1574,13 @@ impl Shell {
                                 .filter(|(key, _)| *key == output)
                                 .map(|(o, w)| (o.clone(), w.clone()))
                                 .collect(),
+                            name: workspace.name.clone(),
+                            definition: workspace.definition.clone(),
+                            minimized,
                             ..Workspace::new(new_workspace_handle, true, self.gaps)
                         };
+                        apply_definition(&mut new_workspace, self.gaps);
+                        workspace_set_idx(&mut state, i as u8 + 1, idx, &new_workspace);
                         for toplevel in new_workspace.windows() {
                             self.toplevel_info_state
                                 .toplevel_enter_workspace(&toplevel, &new_workspace_handle);
@@ -900,7 +1595,7 @@ impl Shell {
                 state.remove_workspace_group(set.group);
 
                 for new_set in sets.values_mut() {
-                    new_set.amount = set.amount;
+                    new_set.update_amount(&mut state, set.amount);
                 }
                 *dst = WorkspaceMode::OutputBound(sets, set.amount);
             }
@@ -911,6 +1606,124 @@ impl Shell {
         self.refresh(); // get rid of empty workspaces and enforce potential maximum
     }
 
+    pub fn set_workspace_amount(&mut self, amount: WorkspaceAmount) {
+        self.workspaces
+            .update_amount(amount, &mut self.workspace_state.update());
+        self.refresh(); // add or merge workspaces as necessary
+    }
+
+    pub fn set_gaps(&mut self, gaps: (u8, u8)) {
+        self.gaps = gaps;
+        self.workspaces.update_gaps(gaps);
+    }
+
+    pub fn set_tiling_layout(&mut self, layout: LayoutMode, seat: &Seat<State>) {
+        self.tiling_layout = layout;
+        self.workspaces.update_tiling_layout(seat, layout);
+    }
+
+    pub fn set_workspace_definitions(
+        &mut self,
+        definitions: Vec<WorkspaceDefinition>,
+        seat: &Seat<State>,
+    ) {
+        let mut state = self.workspace_state.update();
+        // workspaces that are no longer declared stay around, as long as they are named
+        for workspace in self.workspaces.spaces_mut() {
+            if workspace
+                .definition
+                .as_ref()
+                .map_or(false, |d| !definitions.iter().any(|def| def.name == d.name))
+            {
+                workspace.definition = None;
+            }
+        }
+
+        match &mut self.workspaces {
+            WorkspaceMode::OutputBound(sets, _) => {
+                let mut assigned = HashMap::<Output, Vec<&WorkspaceDefinition>>::new();
+                for definition in definitions.iter() {
+                    let has_name = |set: &WorkspaceSet| {
+                        set.workspaces
+                            .iter()
+                            .any(|w| w.name.as_ref() == Some(&definition.name))
+                    };
+                    // keep existing workspaces where they are
+                    let output = sets
+                        .iter()
+                        .find(|(_, set)| has_name(set))
+                        .map(|(output, _)| output)
+                        .or_else(|| {
+                            let name = definition.output.as_ref()?;
+                            self.outputs.iter().find(|o| &o.name() == name)
+                        })
+                        .or(self.outputs.first());
+                    if let Some(output) = output {
+                        assigned.entry(output.clone()).or_default().push(definition);
+                    }
+                }
+                for (output, set) in sets.iter_mut() {
+                    let definitions = assigned.remove(output).unwrap_or_default();
+                    set.declare(
+                        &definitions,
+                        &mut state,
+                        seat,
+                        std::iter::once((output, (0, 0).into())),
+                    );
+                }
+            }
+            WorkspaceMode::Global(set) => set.declare(
+                &definitions.iter().collect::<Vec<_>>(),
+                &mut state,
+                seat,
+                self.outputs.iter().map(|o| (o, o.current_location())),
+            ),
+        }
+
+        std::mem::drop(state);
+        self.workspace_definitions = definitions;
+        self.refresh(); // enforce potential maximum
+    }
+
+    /// Output and index of a declared workspace.
+    ///
+    /// The output is `None` in global mode. If a declared workspace exists on multiple outputs,
+    /// the one on its configured output is preferred, then the one on `current_output`.
+    pub fn declared_workspace(
+        &self,
+        name: &str,
+        current_output: &Output,
+    ) -> Option<(Option<&Output>, usize)> {
+        let position = |set: &WorkspaceSet| {
+            set.workspaces.iter().position(|w| {
+                w.definition
+                    .as_ref()
+                    .map_or(false, |definition| definition.name == name)
+            })
+        };
+        match &self.workspaces {
+            WorkspaceMode::Global(set) => position(set).map(|idx| (None, idx)),
+            WorkspaceMode::OutputBound(sets, _) => {
+                let mut candidates = sets
+                    .iter()
+                    .filter_map(|(output, set)| position(set).map(|idx| (output, idx)))
+                    .collect::<Vec<_>>();
+                let preferred = self
+                    .workspace_definitions
+                    .iter()
+                    .find(|d| d.name == name)
+                    .and_then(|d| d.output.clone());
+                candidates.sort_by_key(|(output, _)| {
+                    (Some(output.name()) != preferred, *output != current_output)
+                });
+                candidates
+                    .into_iter()
+                    .next()
+                    .map(|(output, idx)| (Some(output), idx))
+            }
+        }
+    }
+
     pub fn activate(
         &mut self,
         output: &Output,
@@ -919,12 +1732,20 @@ impl Shell {
         if match &mut self.workspaces {
             WorkspaceMode::OutputBound(sets, _) => {
                 if let Some(set) = sets.get_mut(output) {
-                    set.activate(idx, &mut self.workspace_state.update())?
+                    set.activate(
+                        idx,
+                        &mut self.workspace_state.update(),
+                        &mut self.toplevel_info_state,
+                    )?
                 } else {
                     false
                 }
             }
-            WorkspaceMode::Global(set) => set.activate(idx, &mut self.workspace_state.update())?,
+            WorkspaceMode::Global(set) => set.activate(
+                idx,
+                &mut self.workspace_state.update(),
+                &mut self.toplevel_info_state,
+            )?,
         } {
             let output_geo = output.geometry();
             Ok(Some(
@@ -935,6 +1756,130 @@ impl Shell {
         }
     }
 
+    /// Creates a named workspace in the given group, if the workspace amount is dynamic.
+    pub fn create_workspace(&mut self, group: &WorkspaceGroupHandle, name: String) -> bool {
+        let mut state = self.workspace_state.update();
+        match &mut self.workspaces {
+            WorkspaceMode::OutputBound(sets, WorkspaceAmount::Dynamic) => {
+                if let Some((output, set)) = sets.iter_mut().find(|(_, set)| &set.group == group) {
+                    set.create(name, &mut state, std::iter::once((output, (0, 0).into())));
+                    return true;
+                }
+            }
+            WorkspaceMode::Global(set)
+                if &set.group == group && set.amount == WorkspaceAmount::Dynamic =>
+            {
+                set.create(
+                    name,
+                    &mut state,
+                    self.outputs.iter().map(|o| (o, o.current_location())),
+                );
+                return true;
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Removes a workspace, if the workspace amount is dynamic. Its windows move to a neighbor.
+    pub fn remove_workspace(&mut self, handle: &WorkspaceHandle) -> bool {
+        let mut state = self.workspace_state.update();
+        let toplevel_info = &mut self.toplevel_info_state;
+        let set = match &mut self.workspaces {
+            WorkspaceMode::OutputBound(sets, _) => sets
+                .values_mut()
+                .find(|set| set.workspaces.iter().any(|w| &w.handle == handle)),
+            WorkspaceMode::Global(set) => Some(set),
+        };
+        let Some(set) = set.filter(|set| set.amount == WorkspaceAmount::Dynamic) else {
+            return false;
+        };
+        // declared workspaces are defined by the config
+        let Some(idx) = set
+            .workspaces
+            .iter()
+            .position(|w| &w.handle == handle && w.definition.is_none())
+        else {
+            return false;
+        };
+        set.remove(idx, &mut state, toplevel_info).is_ok()
+    }
+
+    /// Switches away from a workspace, if it is currently active.
+    pub fn deactivate_workspace(&mut self, handle: &WorkspaceHandle) -> bool {
+        let mut state = self.workspace_state.update();
+        let set = match &mut self.workspaces {
+            WorkspaceMode::OutputBound(sets, _) => sets
+                .values_mut()
+                .find(|set| set.workspaces.iter().any(|w| &w.handle == handle)),
+            WorkspaceMode::Global(set) => Some(set),
+        };
+        let Some(set) = set else {
+            return false;
+        };
+        let Some(idx) = set.workspaces.iter().position(|w| &w.handle == handle) else {
+            return false;
+        };
+        set.deactivate(idx, &mut state, &mut self.toplevel_info_state)
+            .unwrap_or(false)
+    }
+
+    /// Starts switching to the workspace at `idx` interactively, see `update_workspace_swipe`.
+    pub fn begin_workspace_swipe(
+        &mut self,
+        output: &Output,
+        idx: usize,
+    ) -> Result<bool, InvalidWorkspaceIndex> {
+        match &mut self.workspaces {
+            WorkspaceMode::OutputBound(sets, _) => match sets.get_mut(output) {
+                Some(set) => set.begin_swipe(
+                    idx,
+                    &mut self.workspace_state.update(),
+                    &mut self.toplevel_info_state,
+                ),
+                None => Ok(false),
+            },
+            WorkspaceMode::Global(set) => set.begin_swipe(
+                idx,
+                &mut self.workspace_state.update(),
+                &mut self.toplevel_info_state,
+            ),
+        }
+    }
+
+    /// Moves an interactive workspace switch to `progress` (0.0 to 1.0)
+    pub fn update_workspace_swipe(&mut self, output: &Output, progress: f64) {
+        match &mut self.workspaces {
+            WorkspaceMode::OutputBound(sets, _) => {
+                if let Some(set) = sets.get_mut(output) {
+                    set.update_swipe(progress)
+                }
+            }
+            WorkspaceMode::Global(set) => set.update_swipe(progress),
+        }
+    }
+
+    /// Finishes an interactive workspace switch, either completing or reverting it
+    /// depending on how far it got.
+    pub fn end_workspace_swipe(&mut self, output: &Output, cancel: bool) {
+        match &mut self.workspaces {
+            WorkspaceMode::OutputBound(sets, _) => {
+                if let Some(set) = sets.get_mut(output) {
+                    set.end_swipe(
+                        cancel,
+                        &mut self.workspace_state.update(),
+                        &mut self.toplevel_info_state,
+                    )
+                }
+            }
+            WorkspaceMode::Global(set) => set.end_swipe(
+                cancel,
+                &mut self.workspace_state.update(),
+                &mut self.toplevel_info_state,
+            ),
+        }
+    }
+
     pub fn active_space(&self, output: &Output) -> &Workspace {
         match &self.workspaces {
             WorkspaceMode::OutputBound(sets, _) => {
@@ -975,6 +1920,13 @@ impl Shell {
                     });
                     found.load(Ordering::SeqCst).then_some(output)
                 })
+            })
+            .or_else(|| {
+                self.session_lock.as_ref().and_then(|lock| {
+                    lock.surfaces
+                        .iter()
+                        .find_map(|(output, s)| (s.wl_surface() == surface).then_some(output))
+                })
             }) {
             Some(output) => {
                 Box::new(std::iter::once(output.clone())) as Box<dyn Iterator<Item = Output>>
@@ -1112,6 +2064,8 @@ impl Shell {
                 sets.values().any(|set| set.previously_active.is_some())
             }
         }) || !matches!(self.overview_mode, OverviewMode::None)
+            || self.switcher.is_some()
+            || self.expose.is_some()
             || self
                 .workspaces
                 .spaces()
@@ -1179,10 +2133,98 @@ impl Shell {
             .iter()
             .for_each(|or| or.refresh());
 
+        if let Some(session_lock) = self.session_lock.as_mut() {
+            let outputs = &self.outputs;
+            session_lock
+                .surfaces
+                .retain(|output, surface| surface.alive() && outputs.contains(output));
+            for (output, surface) in session_lock.surfaces.iter() {
+                let size = output.geometry().size;
+                if surface.configured_size() != Some(size) {
+                    surface.send_configure(size);
+                }
+            }
+        }
+
         self.toplevel_info_state
             .refresh(Some(&self.workspace_state));
     }
 
+    /// Whether any tiling layout changed since the last call
+    pub fn take_layout_changes(&mut self) -> bool {
+        let mut changed = false;
+        for workspace in self.workspaces.spaces_mut() {
+            changed |= workspace.tiling_layer.take_changed();
+        }
+        changed
+    }
+
+    /// `output` presented a frame rendered while locked (or is blanked).
+    ///
+    /// The locker is told, that the session is locked, once every output did so.
+    pub fn session_lock_presented(&mut self, output: &Output) {
+        let outputs = &self.outputs;
+        let Some(session_lock) = self.session_lock.as_mut() else {
+            return;
+        };
+        session_lock
+            .pending_outputs
+            .retain(|pending| pending != output && outputs.contains(pending));
+        if session_lock.pending_outputs.is_empty() {
+            session_lock.locker.lock();
+        }
+    }
+
+    /// Tiling layouts of all workspaces, including those not restored yet
+    pub fn saved_layouts(&self, dh: &DisplayHandle) -> HashMap<String, Vec<SavedTree>> {
+        let mut layouts = self.restored_layouts.clone();
+        for output in self.outputs.iter() {
+            for num in 0..self.workspaces.len(output) {
+                let workspace = self.workspaces.get(num, output).unwrap();
+                let trees = workspace.tiling_layer.save(dh);
+                if !trees.is_empty() {
+                    layouts.insert(self.workspaces.layout_key(num, output).unwrap(), trees);
+                }
+            }
+        }
+        layouts
+    }
+
+    /// Number of the workspace on `output`, that has a slot for `window` in its saved layout.
+    ///
+    /// Layouts of the previous session are restored into their workspace on first use.
+    fn workspace_waiting_for(
+        &mut self,
+        window: &CosmicSurface,
+        output: &Output,
+        dh: &DisplayHandle,
+    ) -> Option<usize> {
+        let len = self.workspaces.len(output);
+        if let Some(num) = (0..len).find(|num| {
+            self.workspaces
+                .get(*num, output)
+                .unwrap()
+                .tiling_layer
+                .has_slot_for(window, output, dh)
+        }) {
+            return Some(num);
+        }
+
+        let matcher = WindowMatcher::for_window(window, dh);
+        let (num, key) = (0..len)
+            .filter_map(|num| Some((num, self.workspaces.layout_key(num, output)?)))
+            .find(|(_, key)| {
+                self.restored_layouts
+                    .get(key)
+                    .map(|trees| trees.iter().any(|tree| tree.fits(&matcher)))
+                    .unwrap_or(false)
+            })?;
+        let template = self.restored_layouts.remove(&key).unwrap();
+        let workspace = self.workspaces.get_mut(num, output).unwrap();
+        workspace.tiling_layer.restore(template, output, dh);
+        Some(num)
+    }
+
     pub fn map_window(state: &mut State, window: &CosmicSurface, output: &Output) {
         let pos = state
             .common
@@ -1193,8 +2235,43 @@ impl Shell {
             .unwrap();
         let (window, seat) = state.common.shell.pending_windows.remove(pos);
 
-        let workspace = state.common.shell.workspaces.active_mut(output);
-        workspace.set_fullscreen(None, output);
+        let rules = window
+            .update_window_rules(&state.common.config.static_conf.window_rules)
+            .map(|(_, rules)| rules)
+            .unwrap_or_default();
+        let output = rules
+            .output
+            .as_ref()
+            .and_then(|name| state.common.shell.outputs().find(|o| &o.name() == name))
+            .unwrap_or(output)
+            .clone();
+        let pinned = rules.sticky == Some(true) || rules.always_on_top == Some(true);
+        let floating = pinned
+            || rules
+                .floating
+                .unwrap_or_else(|| layout::should_be_floating(&window));
+        let active_idx = state.common.shell.workspaces.active_num(&output).1;
+        let rule_idx = rules
+            .workspace
+            .map(|num| num.saturating_sub(1) as usize)
+            .filter(|idx| state.common.shell.workspaces.get(*idx, &output).is_some());
+        let template_idx = if rule_idx.is_none() && !floating {
+            state
+                .common
+                .shell
+                .workspace_waiting_for(&window, &output, &state.common.display_handle)
+        } else {
+            None
+        };
+        let workspace_idx = rule_idx.or(template_idx).unwrap_or(active_idx);
+
+        let workspace = state
+            .common
+            .shell
+            .workspaces
+            .get_mut(workspace_idx, &output)
+            .unwrap();
+        workspace.set_fullscreen(None, &output);
         state.common.shell.toplevel_info_state.new_toplevel(&window);
         state
             .common
@@ -1207,6 +2284,7 @@ impl Shell {
             .toplevel_info_state
             .toplevel_enter_workspace(&window, &workspace.handle);
 
+        window.apply_forced_decorations();
         let mapped = CosmicMapped::from(CosmicWindow::new(
             window.clone(),
             state.common.event_loop_handle.clone(),
@@ -1215,36 +2293,131 @@ impl Shell {
         {
             mapped.set_debug(state.common.egui.active);
         }
-        if layout::should_be_floating(&window) || !workspace.tiling_enabled {
-            workspace.floating_layer.map(mapped.clone(), &seat, None);
+        mapped.set_sticky(rules.sticky == Some(true));
+        mapped.set_always_on_top(rules.always_on_top == Some(true));
+        if floating || !workspace.tiling_enabled {
+            if rules.size.is_some() || rules.position.is_some() {
+                let zone = layer_map_for_output(&output).non_exclusive_zone();
+                let size = rules
+                    .size
+                    .map(Into::into)
+                    .unwrap_or_else(|| mapped.geometry().size);
+                let position = rules.position.map(Into::into).unwrap_or_else(|| {
+                    zone.loc + Point::from(((zone.size.w - size.w) / 2, (zone.size.h - size.h) / 2))
+                });
+                let output_loc = workspace
+                    .floating_layer
+                    .space
+                    .output_geometry(&output)
+                    .map(|geo| geo.loc)
+                    .unwrap_or_default();
+                *mapped.last_geometry.lock().unwrap() =
+                    Some(Rectangle::from_loc_and_size(output_loc + position, size));
+            }
+            workspace
+                .floating_layer
+                .map_internal(mapped.clone(), &output, None);
+        } else if template_idx.is_some() {
+            // don't join the stack of the focused window, the layout decides where the window goes
+            workspace.tiling_layer.map_to_output(
+                mapped.clone(),
+                &output,
+                std::iter::empty::<&CosmicMapped>(),
+                None,
+            );
+            workspace
+                .tiling_layer
+                .apply_template(&state.common.display_handle);
         } else {
             let focus_stack = workspace.focus_stack.get(&seat);
             workspace
                 .tiling_layer
-                .map(mapped.clone(), &seat, focus_stack.iter(), None);
+                .map_to_output(mapped.clone(), &output, focus_stack.iter(), None);
+        }
+
+        if rules.fullscreen == Some(true) {
+            workspace.fullscreen_request(&window, &output);
+        } else if rules.maximized == Some(true) {
+            workspace.maximize_request(&window, &output);
         }
 
-        if let CosmicSurface::X11(surface) = window {
+        if let CosmicSurface::X11(surface) = &window {
             if let Some(xwm) = state
                 .common
                 .xwayland_state
                 .as_mut()
                 .and_then(|state| state.xwm.as_mut())
             {
-                if let Err(err) = xwm.raise_window(&surface) {
+                if let Err(err) = xwm.raise_window(surface) {
                     warn!(?err, "Failed to update Xwayland stacking order.");
                 }
             }
         }
 
-        Shell::set_focus(state, Some(&KeyboardFocusTarget::from(mapped)), &seat, None);
+        if workspace_idx == active_idx {
+            // the window might have been added to a stack
+            let mapped = state
+                .common
+                .shell
+                .element_for_surface(&window)
+                .cloned()
+                .unwrap_or(mapped);
+            Shell::set_focus(state, Some(&KeyboardFocusTarget::from(mapped)), &seat, None);
+        }
 
-        let active_space = state.common.shell.active_space(output);
+        let active_space = state.common.shell.active_space(&output);
         for mapped in active_space.mapped() {
             state.common.shell.update_reactive_popups(mapped);
         }
     }
 
+    /// Re-applies the window rules of an already mapped window, if its title or app_id changed
+    /// in a way that matches a different set of rules.
+    ///
+    /// Floating, sticky and always-on-top states are re-applied and reverted once no rule sets them
+    /// anymore, forced decorations are re-applied. Placement rules only affect newly mapped windows.
+    pub fn reapply_window_rules(state: &mut State, window: &CosmicSurface) {
+        let Some((previous, rules)) =
+            window.update_window_rules(&state.common.config.static_conf.window_rules)
+        else {
+            return;
+        };
+        // states no rule sets anymore go back to what the window would have without rules
+        let floating = rules.floating.or_else(|| {
+            previous
+                .floating
+                .map(|_| layout::should_be_floating(window))
+        });
+        let sticky = rules.sticky.or(previous.sticky.map(|_| false));
+        let always_on_top = rules
+            .always_on_top
+            .or(previous.always_on_top.map(|_| false));
+
+        let seat = state.common.last_active_seat().clone();
+        let Some(mapped) = state.common.shell.element_for_surface(window).cloned() else {
+            return;
+        };
+
+        if rules.decorations.is_some() {
+            window.apply_forced_decorations();
+        } else if previous.decorations.is_some() && !mapped.is_stack() {
+            // stacked windows stay server-side decorated by the stack
+            window.restore_requested_decorations();
+        }
+        if let Some(workspace) = state.common.shell.space_for_mut(&mapped) {
+            if let Some(floating) = floating {
+                workspace.set_floating(&mapped, &seat, floating);
+            }
+            if let Some(sticky) = sticky {
+                workspace.set_sticky(&mapped, &seat, sticky);
+            }
+            if let Some(always_on_top) = always_on_top {
+                workspace.set_always_on_top(&mapped, &seat, always_on_top);
+            }
+        }
+        mapped.configure();
+    }
+
     pub fn map_override_redirect(state: &mut State, window: X11Surface) {
         let geo = window.geometry();
         for (output, overlap) in state
@@ -1286,6 +2459,148 @@ impl Shell {
         }
     }
 
+    /// Hides a window in the minimized set of its workspace
+    pub fn minimize(&mut self, window: &CosmicSurface) -> bool {
+        let Some(mapped) = self.element_for_surface(window).cloned() else { return false };
+        self.space_for_mut(&mapped)
+            .map(|workspace| workspace.minimize(&mapped))
+            .unwrap_or(false)
+    }
+
+    /// Shows a minimized window on its workspace again, returning its element
+    pub fn unminimize(
+        &mut self,
+        window: &CosmicSurface,
+        seat: &Seat<State>,
+    ) -> Option<CosmicMapped> {
+        let output = seat.active_output();
+        self.workspaces
+            .spaces_mut()
+            .find(|workspace| workspace.is_minimized(window))?
+            .unminimize(window, &output, seat)
+    }
+
+    /// Focuses a window, restoring it and switching to its workspace if necessary
+    pub fn activate_window(state: &mut State, window: &CosmicSurface, seat: &Seat<State>) {
+        state.common.shell.unminimize(window, seat);
+
+        for output in state
+            .common
+            .shell
+            .outputs()
+            .cloned()
+            .collect::<Vec<_>>()
+            .iter()
+        {
+            let maybe = state
+                .common
+                .shell
+                .workspaces
+                .spaces_for_output(output)
+                .enumerate()
+                .find(|(_, w)| w.windows().any(|w| &w == window));
+            if let Some((idx, workspace)) = maybe {
+                let mapped = workspace
+                    .mapped()
+                    .find(|m| m.windows().any(|(w, _)| &w == window))
+                    .unwrap()
+                    .clone();
+
+                let _ = state.common.shell.activate(&output, idx as usize); // TODO: Move pointer?
+                mapped.focus_window(window);
+                Common::set_focus(state, Some(&mapped.clone().into()), seat, None);
+                return;
+            }
+        }
+    }
+
+    /// Toggles the first window with the given `app_id` as a centered floating overlay on `output`.
+    ///
+    /// A shown window is minimized, a minimized one (or one of another workspace) is moved
+    /// to the active workspace of `output`. Returns the element, if it is shown now.
+    pub fn toggle_scratchpad(&mut self, app_id: &str, output: &Output) -> Option<CosmicMapped> {
+        let workspace = self.workspaces.active_mut(output);
+        if let Some(mapped) = workspace
+            .mapped()
+            .find(|m| m.windows().any(|(w, _)| w.app_id() == app_id))
+            .cloned()
+        {
+            workspace.minimize(&mapped);
+            return None;
+        }
+
+        let window = self
+            .workspaces
+            .spaces()
+            .flat_map(|workspace| workspace.minimized.iter())
+            .flat_map(|m| m.window.windows().map(|(w, _)| w))
+            .find(|w| w.app_id() == app_id)
+            .or_else(|| {
+                self.workspaces
+                    .spaces()
+                    .flat_map(|workspace| workspace.windows())
+                    .find(|w| w.app_id() == app_id)
+            })?;
+
+        let from = self
+            .workspaces
+            .spaces_mut()
+            .find(|workspace| workspace.windows().any(|w| w == window))?;
+        let from_handle = from.handle;
+        let (mapped, from_outputs) = match from.take_minimized(&window) {
+            Some(minimized) => (minimized.window, vec![minimized.output]),
+            None => {
+                let mapped = from.element_for_surface(&window)?.clone();
+                let outputs = from.outputs_for_element(&mapped).collect::<Vec<_>>();
+                from.unmap(&mapped);
+                (mapped, outputs)
+            }
+        };
+
+        let workspace = self.workspaces.active_mut(output);
+        let zone = layer_map_for_output(output).non_exclusive_zone();
+        let size = mapped
+            .last_geometry
+            .lock()
+            .unwrap()
+            .map(|geo| geo.size)
+            .unwrap_or_else(|| mapped.geometry().size);
+        // floating windows are limited to two thirds of the output
+        let size = Size::from((
+            size.w.min(zone.size.w / 3 * 2),
+            size.h.min(zone.size.h / 3 * 2),
+        ));
+        let output_loc = workspace
+            .floating_layer
+            .space
+            .output_geometry(output)
+            .map(|geo| geo.loc)
+            .unwrap_or_default();
+        let position = output_loc
+            + zone.loc
+            + Point::from(((zone.size.w - size.w) / 2, (zone.size.h - size.h) / 2));
+        workspace
+            .floating_layer
+            .map_internal(mapped.clone(), output, Some(position));
+
+        for (toplevel, _) in mapped.windows() {
+            if from_handle != workspace.handle {
+                self.toplevel_info_state
+                    .toplevel_leave_workspace(&toplevel, &from_handle);
+                self.toplevel_info_state
+                    .toplevel_enter_workspace(&toplevel, &workspace.handle);
+            }
+            for from_output in from_outputs.iter().filter(|o| *o != output) {
+                self.toplevel_info_state
+                    .toplevel_leave_output(&toplevel, from_output);
+                self.toplevel_info_state
+                    .toplevel_enter_output(&toplevel, output);
+            }
+        }
+
+        Some(mapped)
+    }
+
     pub fn move_current_window(
         state: &mut State,
         seat: &Seat<State>,
@@ -1293,6 +2608,36 @@ impl Shell {
         to: (&Output, Option<usize>),
         follow: bool,
         direction: Option<Direction>,
+    ) -> Result<Option<Point<i32, Logical>>, InvalidWorkspaceIndex> {
+        let maybe_window = state
+            .common
+            .shell
+            .active_space(from_output)
+            .focus_stack
+            .get(seat)
+            .last()
+            .cloned();
+        let Some(mapped) = maybe_window else {
+            // still report invalid targets, so callers may fall back to other outputs
+            let (to_output, to_idx) = to;
+            let to_idx = to_idx.unwrap_or(state.common.shell.workspaces.active_num(to_output).1);
+            return match state.common.shell.workspaces.get(to_idx, to_output) {
+                Some(_) => Ok(None),
+                None => Err(InvalidWorkspaceIndex),
+            };
+        };
+        Shell::move_window(state, seat, &mapped, from_output, to, follow, direction)
+    }
+
+    /// Moves a window of the active workspace of `from_output` to another workspace
+    pub fn move_window(
+        state: &mut State,
+        seat: &Seat<State>,
+        mapped: &CosmicMapped,
+        from_output: &Output,
+        to: (&Output, Option<usize>),
+        follow: bool,
+        direction: Option<Direction>,
     ) -> Result<Option<Point<i32, Logical>>, InvalidWorkspaceIndex> {
         let (to_output, to_idx) = to;
         let to_idx = to_idx.unwrap_or(state.common.shell.workspaces.active_num(to_output).1);
@@ -1313,10 +2658,7 @@ impl Shell {
         }
 
         let from_workspace = state.common.shell.workspaces.active_mut(from_output);
-        let maybe_window = from_workspace.focus_stack.get(seat).last().cloned();
-
-        let Some(mapped) = maybe_window else { return Ok(None); };
-        let Some(window_state) = from_workspace.unmap(&mapped) else { return Ok(None); };
+        let Some(window_state) = from_workspace.unmap(mapped) else { return Ok(None); };
 
         for (toplevel, _) in mapped.windows() {
             state
@@ -1382,7 +2724,12 @@ impl Shell {
         }
 
         if follow {
-            Common::set_focus(state, Some(&KeyboardFocusTarget::from(mapped)), &seat, None);
+            Common::set_focus(
+                state,
+                Some(&KeyboardFocusTarget::from(mapped.clone())),
+                &seat,
+                None,
+            );
         }
         Ok(new_pos)
     }
@@ -1478,10 +2825,14 @@ fn workspace_set_idx<'a>(
     state: &mut WorkspaceUpdateGuard<'a, State>,
     idx: u8,
     output_pos: usize,
-    handle: &WorkspaceHandle,
+    workspace: &Workspace,
 ) {
-    state.set_workspace_name(&handle, format!("{}", idx));
-    state.set_workspace_coordinates(&handle, [Some(idx as u32), Some(output_pos as u32), None]);
+    let handle = &workspace.handle;
+    match workspace.name.as_ref() {
+        Some(name) => state.set_workspace_name(handle, name.clone()),
+        None => state.set_workspace_name(handle, format!("{}", idx)),
+    }
+    state.set_workspace_coordinates(handle, [Some(idx as u32), Some(output_pos as u32), None]);
 }
 
 pub fn check_grab_preconditions(
@@ -1491,18 +2842,19 @@ pub fn check_grab_preconditions(
 ) -> Option<PointerGrabStartData<State>> {
     use smithay::reexports::wayland_server::Resource;
 
-    // TODO: touch resize.
     let pointer = seat.get_pointer().unwrap();
 
-    // Check that this surface has a click grab.
-    if !match serial {
+    // Check that this surface has a click grab, or was touched.
+    let start_data = if match serial {
         Some(serial) => pointer.has_grab(serial),
         None => pointer.is_grabbed(),
     } {
-        return None;
-    }
-
-    let start_data = pointer.grab_start_data().unwrap();
+        pointer.grab_start_data().unwrap()
+    } else {
+        seat.user_data()
+            .get::<TouchState>()?
+            .take_grab(surface, serial)?
+    };
 
     // If the focus was for a different surface, ignore the request.
     if start_data.focus.is_none()
@@ -0,0 +1,359 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use apply::Apply;
+use calloop::LoopHandle;
+use cosmic::{
+    iced::widget as iced_widget,
+    iced_core::{Background, BorderRadius, Color, Length},
+    theme, Element as CosmicElement,
+};
+use serde::Deserialize;
+use smithay::{
+    backend::renderer::{
+        element::{
+            surface::WaylandSurfaceRenderElement,
+            utils::{CropRenderElement, Relocate, RelocateRenderElement, RescaleRenderElement},
+            AsRenderElements,
+        },
+        ImportAll, ImportMem, Renderer,
+    },
+    desktop::space::SpaceElement,
+    input::{keyboard::ModifiersState, Seat},
+    output::Output,
+    utils::{IsAlive, Logical, Point, Rectangle, Scale, Size},
+};
+
+use super::{
+    element::window::CosmicWindowRenderElement, CosmicMappedRenderElement, CosmicSurface, Shell,
+    Workspace,
+};
+use crate::{
+    config::KeyModifiers,
+    state::Data,
+    utils::{
+        iced::{IcedElement, Program},
+        prelude::*,
+    },
+};
+
+const CARD_WIDTH: i32 = 216;
+const CARD_HEIGHT: i32 = 192;
+const CARD_PADDING: i32 = 8;
+const THUMBNAIL_HEIGHT: i32 = 128;
+const SPACING: i32 = 8;
+const PADDING: i32 = 16;
+
+/// Windows offered by the window switcher
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum SwitcherScope {
+    /// Windows of the active workspace
+    Workspace,
+    /// Windows of all workspaces, starting with the active one
+    All,
+}
+
+/// List of windows ordered by their focus history, shown while the modifiers
+/// of the binding opening it are held
+pub struct WindowSwitcher {
+    pub seat: Seat<State>,
+    pub output: Output,
+    modifiers: KeyModifiers,
+    windows: Vec<CosmicSurface>,
+    selected: usize,
+    /// Number of windows fitting onto the output at once
+    visible: usize,
+    element: IcedElement<SwitcherInternal>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct SwitcherInternal {
+    /// App id and title of the visible windows
+    entries: Arc<Mutex<Vec<(String, String)>>>,
+    selected: Arc<AtomicUsize>,
+}
+
+impl WindowSwitcher {
+    /// Opens a switcher on the active output of `seat`, if there are any windows to switch to
+    pub fn new(
+        shell: &Shell,
+        seat: &Seat<State>,
+        scope: SwitcherScope,
+        modifiers: KeyModifiers,
+        handle: LoopHandle<'static, Data>,
+    ) -> Option<WindowSwitcher> {
+        let output = seat.active_output();
+        let active = shell.active_space(&output);
+
+        let mut windows = Vec::new();
+        collect_windows(active, seat, &mut windows);
+        if scope == SwitcherScope::All {
+            for workspace in shell
+                .workspaces
+                .spaces()
+                .filter(|workspace| !std::ptr::eq(*workspace, active))
+            {
+                collect_windows(workspace, seat, &mut windows);
+            }
+        }
+        if windows.is_empty() {
+            return None;
+        }
+
+        let available = output.geometry().size.w - 2 * PADDING + SPACING;
+        let visible = ((available / (CARD_WIDTH + SPACING)).max(1) as usize).min(windows.len());
+        let size = Size::from((
+            2 * PADDING + visible as i32 * (CARD_WIDTH + SPACING) - SPACING,
+            2 * PADDING + CARD_HEIGHT,
+        ));
+        let element = IcedElement::new(SwitcherInternal::default(), size, handle);
+        element.output_enter(&output, Rectangle::from_loc_and_size((0, 0), size));
+
+        let switcher = WindowSwitcher {
+            seat: seat.clone(),
+            output,
+            modifiers,
+            windows,
+            selected: 0,
+            visible,
+            element,
+        };
+        switcher.update_view();
+        Some(switcher)
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.windows.len();
+        self.update_view();
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = (self.selected + self.windows.len() - 1) % self.windows.len();
+        self.update_view();
+    }
+
+    pub fn selected(&self) -> Option<CosmicSurface> {
+        self.windows
+            .get(self.selected)
+            .filter(|window| window.alive())
+            .cloned()
+    }
+
+    /// Whether the binding opening the switcher had modifiers to hold
+    pub fn holds_modifiers(&self) -> bool {
+        self.modifiers.ctrl || self.modifiers.alt || self.modifiers.logo
+    }
+
+    /// Whether any of the held modifiers was released, ending the selection
+    pub fn modifiers_released(&self, modifiers: &ModifiersState) -> bool {
+        (self.modifiers.ctrl && !modifiers.ctrl)
+            || (self.modifiers.alt && !modifiers.alt)
+            || (self.modifiers.logo && !modifiers.logo)
+    }
+
+    /// Index of the first visible window, keeping the selected one in view
+    fn offset(&self) -> usize {
+        (self.selected + 1).saturating_sub(self.visible)
+    }
+
+    fn update_view(&self) {
+        let offset = self.offset();
+        self.element.with_program(|p| {
+            *p.entries.lock().unwrap() = self.windows[offset..offset + self.visible]
+                .iter()
+                .map(|window| (window.app_id(), window.title()))
+                .collect();
+            p.selected.store(self.selected - offset, Ordering::SeqCst);
+        });
+        self.element.force_redraw();
+    }
+
+    /// Output-local geometry of the switcher, centered on its output
+    fn geometry(&self) -> Rectangle<i32, Logical> {
+        let size = self.element.bbox().size;
+        let output_size = self.output.geometry().size;
+        Rectangle::from_loc_and_size(
+            ((output_size.w - size.w) / 2, (output_size.h - size.h) / 2),
+            size,
+        )
+    }
+
+    /// Thumbnails of the visible windows on top of the switcher itself
+    pub fn render_elements<R>(
+        &self,
+        renderer: &mut R,
+        output: &Output,
+    ) -> Vec<CosmicMappedRenderElement<R>>
+    where
+        R: Renderer + ImportAll + ImportMem,
+        <R as Renderer>::TextureId: 'static,
+    {
+        if output != &self.output {
+            return Vec::new();
+        }
+
+        let scale = output.current_scale().fractional_scale();
+        let geometry = self.geometry();
+        let mut elements = Vec::new();
+
+        for (i, window) in self.windows[self.offset()..]
+            .iter()
+            .take(self.visible)
+            .enumerate()
+        {
+            let window_geo = window.geometry();
+            if !window.alive() || window_geo.size.w <= 0 || window_geo.size.h <= 0 {
+                continue;
+            }
+
+            let slot = Rectangle::<i32, Logical>::from_loc_and_size(
+                geometry.loc
+                    + Point::from((
+                        PADDING + i as i32 * (CARD_WIDTH + SPACING) + CARD_PADDING,
+                        PADDING + CARD_PADDING,
+                    )),
+                (CARD_WIDTH - 2 * CARD_PADDING, THUMBNAIL_HEIGHT),
+            );
+            let factor = f64::min(
+                slot.size.w as f64 / window_geo.size.w as f64,
+                slot.size.h as f64 / window_geo.size.h as f64,
+            )
+            .min(1.0);
+            let thumbnail_size = Size::<i32, Logical>::from((
+                (window_geo.size.w as f64 * factor).round() as i32,
+                (window_geo.size.h as f64 * factor).round() as i32,
+            ));
+            let origin = slot.loc
+                + Point::from((
+                    (slot.size.w - thumbnail_size.w) / 2,
+                    (slot.size.h - thumbnail_size.h) / 2,
+                ));
+            let origin_physical = origin.to_physical_precise_round(scale);
+            let crop = Rectangle::from_loc_and_size(origin, window_geo.size)
+                .to_physical_precise_round(scale);
+
+            elements.extend(
+                AsRenderElements::<R>::render_elements::<WaylandSurfaceRenderElement<R>>(
+                    window,
+                    renderer,
+                    origin_physical - window_geo.loc.to_physical_precise_round(scale),
+                    Scale::from(scale),
+                    1.0,
+                )
+                .into_iter()
+                .flat_map(|elem| {
+                    let cropped = CropRenderElement::from_element(
+                        CosmicWindowRenderElement::from(elem),
+                        scale,
+                        crop,
+                    )?;
+                    let rescaled =
+                        RescaleRenderElement::from_element(cropped, origin_physical, factor);
+                    Some(CosmicMappedRenderElement::TiledWindow(
+                        RelocateRenderElement::from_element(rescaled, (0, 0), Relocate::Relative),
+                    ))
+                }),
+            );
+        }
+
+        elements.extend(
+            AsRenderElements::<R>::render_elements::<CosmicWindowRenderElement<R>>(
+                &self.element,
+                renderer,
+                geometry.loc.to_physical_precise_round(scale),
+                Scale::from(scale),
+                1.0,
+            )
+            .into_iter()
+            .map(CosmicMappedRenderElement::Window),
+        );
+
+        elements
+    }
+}
+
+/// Appends the windows of `workspace` not yet part of `windows`, most recently focused first
+fn collect_windows(workspace: &Workspace, seat: &Seat<State>, windows: &mut Vec<CosmicSurface>) {
+    let focused = workspace
+        .focus_stack
+        .get(seat)
+        .iter()
+        .flat_map(|mapped| {
+            let active = mapped.active_window();
+            std::iter::once(active.clone()).chain(
+                mapped
+                    .windows()
+                    .map(|(window, _)| window)
+                    .filter(move |window| window != &active),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    for window in focused.into_iter().chain(workspace.windows()) {
+        if !windows.contains(&window) {
+            windows.push(window);
+        }
+    }
+}
+
+impl Program for SwitcherInternal {
+    type Message = ();
+
+    fn view(&self) -> CosmicElement<'_, Self::Message> {
+        let selected = self.selected.load(Ordering::SeqCst);
+        let cards = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .map(|(i, (app_id, title))| {
+                iced_widget::column(vec![
+                    // the thumbnail is rendered on top of this
+                    iced_widget::vertical_space(THUMBNAIL_HEIGHT as u16).into(),
+                    iced_widget::text(app_id.clone()).size(14).into(),
+                    iced_widget::text(title.clone()).size(12).into(),
+                ])
+                .spacing(4)
+                .width(Length::Fill)
+                .apply(iced_widget::container)
+                .padding(CARD_PADDING as u16)
+                .width(CARD_WIDTH as u16)
+                .height(CARD_HEIGHT as u16)
+                .style(if i == selected {
+                    theme::Container::custom(|theme| iced_widget::container::Appearance {
+                        text_color: Some(Color::from(theme.cosmic().background.on)),
+                        background: Some(Background::Color(theme.cosmic().accent_color().into())),
+                        border_radius: BorderRadius::from(8.0),
+                        border_width: 0.0,
+                        border_color: Color::TRANSPARENT,
+                    })
+                } else {
+                    theme::Container::Transparent
+                })
+                .into()
+            })
+            .collect::<Vec<_>>();
+
+        iced_widget::row(cards)
+            .spacing(SPACING as u16)
+            .padding(PADDING as u16)
+            .apply(iced_widget::container)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(theme::Container::custom(|theme| {
+                iced_widget::container::Appearance {
+                    text_color: Some(Color::from(theme.cosmic().background.on)),
+                    background: Some(Background::Color(theme.cosmic().palette.neutral_2.into())),
+                    border_radius: BorderRadius::from(16.0),
+                    border_width: 0.0,
+                    border_color: Color::TRANSPARENT,
+                }
+            }))
+            .into()
+    }
+}
@@ -3,10 +3,11 @@ use crate::{
         element::{AsGlowFrame, AsGlowRenderer},
         BackdropShader, GlMultiError, GlMultiFrame, GlMultiRenderer,
     },
+    config::{OutputInfo, WorkspaceDefinition},
     shell::{
         layout::{
             floating::{FloatingLayout, MoveSurfaceGrab},
-            tiling::{TilingLayout, ANIMATION_DURATION},
+            tiling::{LayoutMode, TilingLayout, ANIMATION_DURATION},
         },
         OverviewMode,
     },
@@ -60,12 +61,30 @@ pub struct Workspace {
     pub tiling_enabled: bool,
     pub fullscreen: HashMap<Output, CosmicSurface>,
     pub handle: WorkspaceHandle,
+    /// Named workspaces are kept around while empty
+    pub name: Option<String>,
+    /// Set for workspaces declared in the config
+    pub definition: Option<WorkspaceDefinition>,
+    /// Output this workspace was moved away from, when it got disconnected
+    pub origin: Option<OutputInfo>,
+    /// Hidden windows, that still belong to this workspace
+    pub minimized: Vec<MinimizedWindow>,
     pub focus_stack: FocusStacks,
     pub pending_buffers: Vec<(ScreencopySession, BufferParams)>,
     pub screencopy_sessions: Vec<DropableSession>,
     pub(super) backdrop_id: Id,
 }
 
+/// A window hidden by [`Workspace::minimize`]
+#[derive(Debug, Clone)]
+pub struct MinimizedWindow {
+    pub window: CosmicMapped,
+    /// Output the window was shown on
+    pub output: Output,
+    /// Whether the window was tiled or floating
+    pub tiled: bool,
+}
+
 #[derive(Debug, Default)]
 pub struct FocusStacks(HashMap<Seat<State>, IndexSet<CosmicMapped>>);
 
@@ -83,6 +102,10 @@ impl Workspace {
             tiling_enabled,
             fullscreen: HashMap::new(),
             handle,
+            name: None,
+            definition: None,
+            origin: None,
+            minimized: Vec::new(),
             focus_stack: FocusStacks::default(),
             pending_buffers: Vec::new(),
             screencopy_sessions: Vec::new(),
@@ -160,6 +183,97 @@ impl Workspace {
         }
     }
 
+    /// Hides `mapped`, until it gets restored by [`Workspace::unminimize`]
+    pub fn minimize(&mut self, mapped: &CosmicMapped) -> bool {
+        let Some(output) = self.outputs_for_element(mapped).next() else { return false };
+        let tiled = match self.unmap(mapped) {
+            Some(ManagedState::Tiling) => true,
+            Some(ManagedState::Floating) => false,
+            None => return false,
+        };
+
+        mapped.set_activated(false);
+        mapped.configure();
+        for (window, _) in mapped.windows() {
+            window.set_minimized(true);
+        }
+        self.minimized.push(MinimizedWindow {
+            window: mapped.clone(),
+            output,
+            tiled,
+        });
+        true
+    }
+
+    /// Shows a minimized window again, where it was before.
+    ///
+    /// Falls back to `output`, if the previous output is not part of this workspace anymore.
+    pub fn unminimize(
+        &mut self,
+        window: &CosmicSurface,
+        output: &Output,
+        seat: &Seat<State>,
+    ) -> Option<CosmicMapped> {
+        let minimized = self.take_minimized(window)?;
+        let output = if self.outputs().any(|o| o == &minimized.output) {
+            minimized.output
+        } else {
+            output.clone()
+        };
+
+        if minimized.tiled && self.tiling_enabled {
+            let focus_stack = self.focus_stack.get(seat);
+            self.tiling_layer.map_to_output(
+                minimized.window.clone(),
+                &output,
+                focus_stack.iter(),
+                None,
+            );
+        } else {
+            self.floating_layer
+                .map_internal(minimized.window.clone(), &output, None);
+        }
+
+        // the window might have joined a stack
+        self.element_for_surface(window).cloned()
+    }
+
+    /// Removes a window from the minimized ones, without mapping it again
+    pub fn take_minimized(&mut self, window: &CosmicSurface) -> Option<MinimizedWindow> {
+        let idx = self
+            .minimized
+            .iter()
+            .position(|m| m.window.windows().any(|(w, _)| &w == window))?;
+        let minimized = self.minimized.remove(idx);
+        for (window, _) in minimized.window.windows() {
+            window.set_minimized(false);
+        }
+        Some(minimized)
+    }
+
+    /// Drops a minimized window, that was unmapped by its client
+    pub fn forget_minimized(&mut self, window: &CosmicSurface) {
+        let Some(minimized) = self
+            .minimized
+            .iter()
+            .find(|m| m.window.windows().any(|(w, _)| &w == window))
+        else {
+            return;
+        };
+        match minimized.window.stack_ref() {
+            Some(stack) if stack.len() > 1 => stack.remove_window(window),
+            _ => {
+                self.take_minimized(window);
+            }
+        }
+    }
+
+    pub fn is_minimized(&self, window: &CosmicSurface) -> bool {
+        self.minimized
+            .iter()
+            .any(|m| m.window.windows().any(|(w, _)| &w == window))
+    }
+
     pub fn element_for_surface(&self, surface: &CosmicSurface) -> Option<&CosmicMapped> {
         self.floating_layer
             .mapped()
@@ -202,8 +316,13 @@ impl Workspace {
             .space
             .element_under(location)
             .or_else(|| {
-                self.tiling_layer.mapped().find_map(|(_, mapped, geo)| {
-                    geo.contains(location.to_i32_round())
+                self.tiling_layer
+                    .mapped()
+                    .find_map(|(output, mapped, geo)| {
+                        // columns may be scrolled out of their output
+                        let output_geo = self.floating_layer.space.output_geometry(output);
+                        (geo.contains(location.to_i32_round())
+                            && output_geo.map_or(false, |o| o.contains(location.to_i32_round())))
                         .then(|| {
                             let test_point =
                                 location - geo.loc.to_f64() + mapped.geometry().loc.to_f64();
@@ -212,7 +331,7 @@ impl Workspace {
                                 .then_some((mapped, geo.loc - mapped.geometry().loc))
                         })
                         .flatten()
-                })
+                    })
             })
     }
 
@@ -366,10 +485,28 @@ impl Workspace {
                 .to_i32_round();
         }
 
+        // dragging a snapped window away restores its previous size
+        let pre_snap_size = mapped.pre_snap_size.lock().unwrap().take();
+        if let Some(size) = pre_snap_size {
+            let geo = self.element_geometry(&mapped).unwrap();
+            let ratio = (pos.x - geo.loc.x as f64) / geo.size.w as f64;
+            initial_window_location =
+                Point::<f64, Logical>::from((pos.x - size.w as f64 * ratio, geo.loc.y as f64))
+                    .to_i32_round();
+        }
+
         let was_floating = self.floating_layer.unmap(&mapped);
         //let was_tiled = self.tiling_layer.unmap(&mapped);
         //assert!(was_floating != was_tiled);
 
+        if let Some(size) = pre_snap_size {
+            if let Some(last_geometry) = mapped.last_geometry.lock().unwrap().as_mut() {
+                last_geometry.size = size;
+            }
+            mapped.set_geometry(Rectangle::from_loc_and_size(initial_window_location, size));
+            mapped.configure();
+        }
+
         if was_floating {
             Some(MoveSurfaceGrab::new(
                 start_data,
@@ -414,22 +551,58 @@ impl Workspace {
         }
     }
 
+    /// Switches the arrangement of tiled windows, see [`LayoutMode`]
+    pub fn set_layout_mode(&mut self, mode: LayoutMode, seat: &Seat<State>) {
+        let focus_stack = self.focus_stack.get_mut(seat);
+        self.tiling_layer.set_layout_mode(mode, Some(focus_stack));
+    }
+
     pub fn toggle_floating_window(&mut self, seat: &Seat<State>) {
+        if let Some(window) = self.focus_stack.get(seat).iter().next().cloned() {
+            let floating = !self.floating_layer.mapped().any(|w| w == &window);
+            self.set_floating(&window, seat, floating);
+        }
+    }
+
+    pub fn set_floating(&mut self, window: &CosmicMapped, seat: &Seat<State>, floating: bool) {
         if self.tiling_enabled {
-            if let Some(window) = self.focus_stack.get(seat).iter().next().cloned() {
-                if self.tiling_layer.mapped().any(|(_, m, _)| m == &window) {
-                    self.tiling_layer.unmap(&window);
-                    self.floating_layer.map(window, seat, None);
-                } else if self.floating_layer.mapped().any(|w| w == &window) {
-                    let focus_stack = self.focus_stack.get(seat);
-                    self.floating_layer.unmap(&window);
-                    self.tiling_layer
-                        .map(window, seat, focus_stack.iter(), None)
-                }
+            if floating && self.tiling_layer.mapped().any(|(_, m, _)| m == window) {
+                self.tiling_layer.unmap(window);
+                self.floating_layer.map(window.clone(), seat, None);
+            } else if !floating && self.floating_layer.mapped().any(|w| w == window) {
+                // only floating windows can be sticky or stay on top
+                window.set_sticky(false);
+                window.set_always_on_top(false);
+                let focus_stack = self.focus_stack.get(seat);
+                self.floating_layer.unmap(window);
+                self.tiling_layer
+                    .map(window.clone(), seat, focus_stack.iter(), None)
             }
         }
     }
 
+    /// Lets a window follow workspace switches, floating it if necessary
+    pub fn set_sticky(&mut self, window: &CosmicMapped, seat: &Seat<State>, sticky: bool) {
+        if sticky {
+            self.set_floating(window, seat, true);
+        }
+        window.set_sticky(sticky);
+    }
+
+    /// Keeps a window above all others, floating it if necessary
+    pub fn set_always_on_top(
+        &mut self,
+        window: &CosmicMapped,
+        seat: &Seat<State>,
+        always_on_top: bool,
+    ) {
+        if always_on_top {
+            self.set_floating(window, seat, true);
+        }
+        window.set_always_on_top(always_on_top);
+        self.floating_layer.raise_always_on_top();
+    }
+
     pub fn mapped(&self) -> impl Iterator<Item = &CosmicMapped> {
         self.floating_layer
             .mapped()
@@ -440,10 +613,16 @@ impl Workspace {
         self.floating_layer.space.outputs()
     }
 
+    /// All windows of this workspace, including minimized ones
     pub fn windows(&self) -> impl Iterator<Item = CosmicSurface> + '_ {
         self.floating_layer
             .windows()
             .chain(self.tiling_layer.windows().map(|(_, w, _)| w))
+            .chain(
+                self.minimized
+                    .iter()
+                    .flat_map(|m| m.window.windows().map(|(w, _)| w)),
+            )
     }
 
     pub fn is_fullscreen(&self, mapped: &CosmicMapped) -> bool {
@@ -559,23 +738,7 @@ impl Workspace {
             let focused =
                 draw_focus_indicator.and_then(|seat| self.focus_stack.get(seat).last().cloned());
 
-            // floating surfaces
-            let alpha = match &overview {
-                OverviewMode::Started(_, started) => {
-                    (1.0 - (Instant::now().duration_since(*started).as_millis()
-                        / ANIMATION_DURATION.as_millis()) as f32)
-                        .max(0.0)
-                        * 0.4
-                        + 0.6
-                }
-                OverviewMode::Ended(ended) => {
-                    ((Instant::now().duration_since(*ended).as_millis()
-                        / ANIMATION_DURATION.as_millis()) as f32)
-                        * 0.4
-                        + 0.6
-                }
-                OverviewMode::None => 1.0,
-            };
+            // floating surfaces, always-on-top ones are rendered in a separate pass
             render_elements.extend(
                 self.floating_layer
                     .render_output::<R>(
@@ -583,7 +746,8 @@ impl Workspace {
                         output,
                         focused.as_ref(),
                         indicator_thickness,
-                        alpha,
+                        floating_alpha(&overview),
+                        false,
                     )
                     .into_iter()
                     .map(WorkspaceRenderElement::from),
@@ -645,6 +809,62 @@ impl Workspace {
 
         Ok(render_elements)
     }
+
+    /// Always-on-top windows, which are stacked above everything else including fullscreen windows
+    pub fn render_always_on_top<R>(
+        &self,
+        renderer: &mut R,
+        output: &Output,
+        draw_focus_indicator: Option<&Seat<State>>,
+        overview: &OverviewMode,
+        indicator_thickness: u8,
+    ) -> Vec<WorkspaceRenderElement<R>>
+    where
+        R: Renderer + ImportAll + ImportMem + AsGlowRenderer,
+        <R as Renderer>::TextureId: 'static,
+        CosmicMappedRenderElement<R>: RenderElement<R>,
+        CosmicWindowRenderElement<R>: RenderElement<R>,
+        CosmicStackRenderElement<R>: RenderElement<R>,
+        WorkspaceRenderElement<R>: RenderElement<R>,
+    {
+        #[cfg(feature = "debug")]
+        puffin::profile_function!();
+
+        let focused =
+            draw_focus_indicator.and_then(|seat| self.focus_stack.get(seat).last().cloned());
+
+        self.floating_layer
+            .render_output::<R>(
+                renderer,
+                output,
+                focused.as_ref(),
+                indicator_thickness,
+                floating_alpha(overview),
+                true,
+            )
+            .into_iter()
+            .map(WorkspaceRenderElement::from)
+            .collect()
+    }
+}
+
+fn floating_alpha(overview: &OverviewMode) -> f32 {
+    match overview {
+        OverviewMode::Started(_, started) => {
+            (1.0 - (Instant::now().duration_since(*started).as_millis()
+                / ANIMATION_DURATION.as_millis()) as f32)
+                .max(0.0)
+                * 0.4
+                + 0.6
+        }
+        OverviewMode::Ended(ended) => {
+            ((Instant::now().duration_since(*ended).as_millis() / ANIMATION_DURATION.as_millis())
+                as f32)
+                * 0.4
+                + 0.6
+        }
+        OverviewMode::None => 1.0,
+    }
 }
 
 impl FocusStacks {
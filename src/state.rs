@@ -2,16 +2,22 @@
 
 use crate::{
     backend::{
+        headless::HeadlessState,
         kms::{source_node_for_surface, KmsState},
         winit::WinitState,
         x11::X11State,
     },
     config::{Config, OutputConfig},
+    input::idle::IdleState,
+    ipc::IpcState,
     shell::{layout::floating::SeatMoveGrabState, Shell},
     utils::prelude::*,
     wayland::protocols::{
         drm::WlDrmState,
+        idle_inhibit::IdleInhibitManagerState,
+        idle_notify::IdleNotifierState,
         output_configuration::OutputConfigurationState,
+        pointer_constraints::PointerConstraintsState,
         screencopy::{BufferParams, ScreencopyState, Session as ScreencopySession},
         workspace::WorkspaceClientState,
     },
@@ -40,7 +46,10 @@ use smithay::{
     input::{pointer::CursorImageStatus, Seat, SeatState},
     output::{Mode as OutputMode, Output, Scale},
     reexports::{
-        calloop::{LoopHandle, LoopSignal},
+        calloop::{
+            timer::{TimeoutAction, Timer},
+            LoopHandle, LoopSignal, RegistrationToken,
+        },
         wayland_protocols_misc::server_decoration::server::org_kde_kwin_server_decoration_manager::Mode,
         wayland_server::{
             backend::{ClientData, ClientId, DisconnectReason},
@@ -61,19 +70,25 @@ use smithay::{
         seat::WaylandFocus,
         shell::{kde::decoration::KdeDecorationState, xdg::decoration::XdgDecorationState},
         shm::ShmState,
+        tablet_manager::TabletManagerState,
         viewporter::ViewporterState,
     },
 };
 use tracing::error;
 
-use std::{cell::RefCell, ffi::OsString, time::Duration};
+use std::{cell::RefCell, ffi::OsString, sync::atomic::AtomicBool, time::Duration};
 use std::{collections::VecDeque, time::Instant};
 
+/// Delay before changed tiling layouts are written, to not write on every step of a drag or resize
+const LAYOUTS_SAVE_DELAY: Duration = Duration::from_secs(2);
+
 pub struct ClientState {
     pub compositor_client_state: CompositorClientState,
     pub workspace_client_state: WorkspaceClientState,
     pub drm_node: Option<DrmNode>,
     pub privileged: bool,
+    /// Whether the client bound the tablet manager, handling tablet tools itself
+    pub tablet_bound: AtomicBool,
 }
 impl ClientData for ClientState {
     fn initialized(&self, _client_id: ClientId) {}
@@ -115,14 +130,18 @@ pub struct Common {
     pub data_device_state: DataDeviceState,
     pub dmabuf_state: DmabufState,
     pub fractional_scale_state: FractionalScaleManagerState,
+    pub idle_inhibit_state: IdleInhibitManagerState,
+    pub idle_notifier_state: IdleNotifierState<State>,
     pub keyboard_shortcuts_inhibit_state: KeyboardShortcutsInhibitState,
     pub output_state: OutputManagerState,
     pub output_configuration_state: OutputConfigurationState<State>,
+    pub pointer_constraints_state: PointerConstraintsState,
     pub presentation_state: PresentationState,
     pub primary_selection_state: PrimarySelectionState,
     pub screencopy_state: ScreencopyState,
     pub seat_state: SeatState<State>,
     pub shm_state: ShmState,
+    pub tablet_manager_state: TabletManagerState,
     pub wl_drm_state: WlDrmState,
     pub viewporter_state: ViewporterState,
     pub kde_decoration_state: KdeDecorationState,
@@ -130,12 +149,22 @@ pub struct Common {
 
     // xwayland state
     pub xwayland_state: Option<XWaylandState>,
+
+    // ipc state
+    pub ipc_state: IpcState,
+
+    // idle state
+    pub idle_state: IdleState,
+
+    /// Pending write of the tiling layouts
+    pub layouts_timer: Option<RegistrationToken>,
 }
 
 pub enum BackendData {
     X11(X11State),
     Winit(WinitState),
     Kms(KmsState),
+    Headless(HeadlessState),
     // TODO
     // Wayland(WaylandState),
     Unset,
@@ -169,6 +198,13 @@ impl BackendData {
         }
     }
 
+    pub fn headless(&mut self) -> &mut HeadlessState {
+        match self {
+            BackendData::Headless(ref mut headless_state) => headless_state,
+            _ => unreachable!("Called headless in non headless backend"),
+        }
+    }
+
     pub fn apply_config_for_output(
         &mut self,
         output: &Output,
@@ -183,6 +219,9 @@ impl BackendData {
             }
             BackendData::Winit(ref mut state) => state.apply_config_for_output(output, test_only),
             BackendData::X11(ref mut state) => state.apply_config_for_output(output, test_only),
+            BackendData::Headless(ref mut state) => {
+                state.apply_config_for_output(output, test_only)
+            }
             _ => unreachable!("No backend set when applying output config"),
         };
 
@@ -224,6 +263,7 @@ impl BackendData {
             // Winit has a very strict render-loop and skipping frames breaks atleast the wayland winit-backend.
             // Swapping with damage (which should be empty on these frames) is likely good enough anyway.
             BackendData::X11(ref mut state) => state.schedule_render(output, screencopy),
+            BackendData::Headless(ref mut state) => state.schedule_render(output, screencopy),
             BackendData::Kms(ref mut state) => {
                 if let Err(err) = state.schedule_render(loop_handle, output, None, screencopy) {
                     error!(?err, "Failed to schedule event, are we shutting down?");
@@ -232,6 +272,14 @@ impl BackendData {
             _ => unreachable!("No backend was initialized"),
         }
     }
+
+    pub fn set_dpms(&mut self, loop_handle: &LoopHandle<'_, Data>, on: bool) {
+        match self {
+            BackendData::Kms(ref mut state) => state.set_dpms(loop_handle, on),
+            // nested and virtual outputs have no power state
+            _ => {}
+        }
+    }
 }
 
 impl State {
@@ -240,16 +288,19 @@ impl State {
         socket: OsString,
         handle: LoopHandle<'static, Data>,
         signal: LoopSignal,
+        config: Config,
     ) -> State {
         let clock = Clock::new().expect("Failed to initialize clock");
-        let config = Config::load();
         let compositor_state = CompositorState::new::<Self>(dh);
         let data_device_state = DataDeviceState::new::<Self>(dh);
         let dmabuf_state = DmabufState::new();
         let fractional_scale_state = FractionalScaleManagerState::new::<State>(dh);
+        let idle_inhibit_state = IdleInhibitManagerState::new::<Self>(dh);
+        let idle_notifier_state = IdleNotifierState::new(dh);
         let keyboard_shortcuts_inhibit_state = KeyboardShortcutsInhibitState::new::<Self>(dh);
         let output_state = OutputManagerState::new_with_xdg_output::<Self>(dh);
         let output_configuration_state = OutputConfigurationState::new(dh, |_| true);
+        let pointer_constraints_state = PointerConstraintsState::new::<Self>(dh);
         let presentation_state = PresentationState::new::<Self>(dh, clock.id() as u32);
         let primary_selection_state = PrimarySelectionState::new::<Self>(dh);
         let screencopy_state = ScreencopyState::new::<Self, _, _>(
@@ -260,6 +311,7 @@ impl State {
         let shm_state =
             ShmState::new::<Self>(dh, vec![wl_shm::Format::Xbgr8888, wl_shm::Format::Abgr8888]);
         let seat_state = SeatState::<Self>::new();
+        let tablet_manager_state = TabletManagerState::new::<Self>(dh);
         let viewporter_state = ViewporterState::new::<Self>(dh);
         let wl_drm_state = WlDrmState;
         let kde_decoration_state = KdeDecorationState::new::<Self>(&dh, Mode::Client);
@@ -296,12 +348,16 @@ impl State {
                 data_device_state,
                 dmabuf_state,
                 fractional_scale_state,
+                idle_inhibit_state,
+                idle_notifier_state,
                 screencopy_state,
                 shm_state,
                 seat_state,
+                tablet_manager_state,
                 keyboard_shortcuts_inhibit_state,
                 output_state,
                 output_configuration_state,
+                pointer_constraints_state,
                 presentation_state,
                 primary_selection_state,
                 viewporter_state,
@@ -310,6 +366,12 @@ impl State {
                 xdg_decoration_state,
 
                 xwayland_state: None,
+
+                ipc_state: IpcState::default(),
+
+                idle_state: IdleState::default(),
+
+                layouts_timer: None,
             },
             backend: BackendData::Unset,
         }
@@ -335,6 +397,7 @@ impl State {
                 _ => None,
             },
             privileged: false,
+            tablet_bound: AtomicBool::new(false),
         }
     }
 
@@ -344,6 +407,7 @@ impl State {
             workspace_client_state: WorkspaceClientState::default(),
             drm_node: Some(drm_node),
             privileged: false,
+            tablet_bound: AtomicBool::new(false),
         }
     }
 
@@ -356,8 +420,23 @@ impl State {
                 _ => None,
             },
             privileged: true,
+            tablet_bound: AtomicBool::new(false),
         }
     }
+
+    /// Updates everything derived from the shell and input state, once per event loop iteration
+    pub fn refresh_loop_iteration(&mut self) {
+        self.common
+            .shell
+            .update_animations(&self.common.event_loop_handle);
+        self.common.shell.refresh();
+        self.common.refresh_layouts();
+        Common::refresh_focus(self);
+        crate::input::layout::refresh(self);
+        crate::input::constraints::refresh(self);
+        crate::ipc::refresh(self);
+        self.refresh_idle();
+    }
 }
 
 impl Common {
@@ -385,6 +464,41 @@ impl Common {
         self.last_active_seat.as_ref().expect("No seat?")
     }
 
+    /// Schedules persisting the tiling layouts of all workspaces, if any tree changed
+    pub fn refresh_layouts(&mut self) {
+        if !self.shell.take_layout_changes() || self.layouts_timer.is_some() {
+            return;
+        }
+        // changes until the timer fires are written together
+        match self.event_loop_handle.insert_source(
+            Timer::from_duration(LAYOUTS_SAVE_DELAY),
+            |_, _, data| {
+                let common = &mut data.state.common;
+                common.layouts_timer = None;
+                common.save_layouts();
+                TimeoutAction::Drop
+            },
+        ) {
+            Ok(token) => self.layouts_timer = Some(token),
+            Err(err) => error!(?err, "Failed to schedule saving layouts."),
+        }
+    }
+
+    /// Writes the tiling layouts right away, if a write is pending
+    pub fn flush_layouts(&mut self) {
+        if let Some(token) = self.layouts_timer.take() {
+            self.event_loop_handle.remove(token);
+            self.save_layouts();
+        }
+    }
+
+    fn save_layouts(&mut self) {
+        let layouts = self.shell.saved_layouts(&self.display_handle);
+        if self.config.dynamic_conf.layouts().workspaces != layouts {
+            self.config.dynamic_conf.layouts_mut().workspaces = layouts;
+        }
+    }
+
     pub fn send_frames(
         &self,
         output: &Output,
@@ -448,6 +562,36 @@ impl Common {
             }
         }
 
+        if let Some(lock_surface) = self
+            .shell
+            .session_lock
+            .as_ref()
+            .and_then(|session_lock| session_lock.surfaces.get(output))
+        {
+            with_surfaces_surface_tree(lock_surface.wl_surface(), |surface, states| {
+                let primary_scanout_output = update_surface_primary_scanout_output(
+                    surface,
+                    output,
+                    states,
+                    render_element_states,
+                    default_primary_scanout_output_compare,
+                );
+                if let Some(output) = primary_scanout_output {
+                    with_fractional_scale(states, |fraction_scale| {
+                        fraction_scale
+                            .set_preferred_scale(output.current_scale().fractional_scale());
+                    });
+                }
+            });
+            send_frames_surface_tree(
+                lock_surface.wl_surface(),
+                output,
+                time,
+                None,
+                surface_primary_scanout_output,
+            );
+        }
+
         let active = self.shell.active_space(output);
         active.mapped().for_each(|mapped| {
             let outputs_for_element: Vec<_> = active.outputs_for_element(mapped).collect();
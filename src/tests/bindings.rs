@@ -0,0 +1,263 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::{TestCompositor, KEY_2, KEY_SUPER};
+use crate::{
+    config::{
+        Action, ButtonPattern, Gesture, GesturePattern, KeyModifier, KeyModifiers, KeyPattern,
+        MouseButton, ScrollDirection, ScrollPattern, StaticConfig, SwipeDirection,
+    },
+    input::bindings::{binding_mode, match_scroll},
+    shell::WorkspaceAmount,
+};
+use smithay::{
+    backend::input::{AxisSource, KeyState},
+    input::keyboard::{keysyms as KeySyms, FilterResult, ModifiersState},
+};
+
+#[test]
+fn keycode_binding() {
+    let mut config = StaticConfig {
+        workspace_amount: WorkspaceAmount::Static(3),
+        ..Default::default()
+    };
+    config.key_bindings.insert(
+        KeyPattern::from_keycode(KeyModifier::Super, KEY_2),
+        Action::Workspace(3),
+    );
+    let mut compositor = TestCompositor::new(config);
+
+    compositor.press_keys(&[KEY_SUPER, KEY_2]);
+    compositor.dispatch();
+
+    assert_eq!(compositor.active_workspace(), 2);
+}
+
+#[test]
+fn binding_mode() {
+    // evdev keycodes of the escape and right arrow key
+    const KEY_ESC: u32 = 1;
+    const KEY_RIGHT: u32 = 106;
+
+    let mut config = StaticConfig {
+        workspace_amount: WorkspaceAmount::Static(3),
+        ..Default::default()
+    };
+    config.key_bindings.insert(
+        KeyPattern::new(KeyModifier::Super, KeySyms::KEY_Right),
+        Action::EnterMode(String::from("workspaces")),
+    );
+    config.binding_modes.insert(
+        String::from("workspaces"),
+        [(
+            KeyPattern::new(KeyModifiers::default(), KeySyms::KEY_2),
+            Action::Workspace(2),
+        )]
+        .into_iter()
+        .collect(),
+    );
+    let mut compositor = TestCompositor::new(config);
+    let seat = compositor.seat();
+
+    compositor.press_keys(&[KEY_SUPER, KEY_RIGHT]);
+    compositor.dispatch();
+    assert_eq!(binding_mode(&seat).as_deref(), Some("workspaces"));
+
+    compositor.press_keys(&[KEY_2]);
+    compositor.dispatch();
+    assert_eq!(compositor.active_workspace(), 1);
+
+    compositor.press_keys(&[KEY_ESC]);
+    compositor.dispatch();
+    assert_eq!(binding_mode(&seat), None);
+}
+
+#[test]
+fn chord_binding() {
+    let mut config = StaticConfig {
+        workspace_amount: WorkspaceAmount::Static(3),
+        ..Default::default()
+    };
+    config.chord_bindings.insert(
+        vec![
+            KeyPattern::new(KeyModifier::Super, KeySyms::KEY_2),
+            KeyPattern::new(KeyModifiers::default(), KeySyms::KEY_2),
+        ],
+        Action::Workspace(3),
+    );
+    let mut compositor = TestCompositor::new(config);
+
+    compositor.press_keys(&[KEY_SUPER, KEY_2]);
+    compositor.dispatch();
+    assert_eq!(compositor.active_workspace(), 0);
+
+    compositor.press_keys(&[KEY_2]);
+    compositor.dispatch();
+    assert_eq!(compositor.active_workspace(), 2);
+}
+
+#[test]
+fn binding_aborts_chord() {
+    // evdev keycode of the right arrow key
+    const KEY_RIGHT: u32 = 106;
+
+    let mut config = StaticConfig {
+        workspace_amount: WorkspaceAmount::Static(3),
+        ..Default::default()
+    };
+    config.chord_bindings.insert(
+        vec![
+            KeyPattern::new(KeyModifier::Super, KeySyms::KEY_2),
+            KeyPattern::new(KeyModifiers::default(), KeySyms::KEY_2),
+        ],
+        Action::Workspace(3),
+    );
+    config.key_bindings.insert(
+        KeyPattern::new(KeyModifier::Super, KeySyms::KEY_Right),
+        Action::NextWorkspace,
+    );
+    let mut compositor = TestCompositor::new(config);
+
+    compositor.press_keys(&[KEY_SUPER, KEY_2]);
+    compositor.press_keys(&[KEY_SUPER, KEY_RIGHT]);
+    compositor.dispatch();
+    assert_eq!(compositor.active_workspace(), 1);
+
+    // the chord was cleared
+    compositor.press_keys(&[KEY_2]);
+    compositor.dispatch();
+    assert_eq!(compositor.active_workspace(), 1);
+}
+
+#[test]
+fn release_binding() {
+    let mut config = StaticConfig {
+        workspace_amount: WorkspaceAmount::Static(3),
+        ..Default::default()
+    };
+    let mut pattern = KeyPattern::new(KeyModifiers::default(), KeySyms::KEY_Super_L);
+    pattern.on_release = true;
+    config.key_bindings.insert(pattern, Action::NextWorkspace);
+    let mut compositor = TestCompositor::new(config);
+
+    // pressing another key in between cancels the binding
+    compositor.press_keys(&[KEY_SUPER, KEY_2]);
+    compositor.dispatch();
+    assert_eq!(compositor.active_workspace(), 0);
+
+    compositor.press_keys(&[KEY_SUPER]);
+    compositor.dispatch();
+    assert_eq!(compositor.active_workspace(), 1);
+}
+
+#[test]
+fn button_binding() {
+    const BTN_MIDDLE: u32 = 0x112;
+
+    let mut config = StaticConfig {
+        workspace_amount: WorkspaceAmount::Static(3),
+        ..Default::default()
+    };
+    config.button_bindings.insert(
+        ButtonPattern::new(KeyModifier::Super, MouseButton::Middle),
+        Action::Workspace(3),
+    );
+    let mut compositor = TestCompositor::new(config);
+
+    compositor.click(BTN_MIDDLE);
+    compositor.dispatch();
+    assert_eq!(compositor.active_workspace(), 0);
+
+    compositor.key(KEY_SUPER, KeyState::Pressed);
+    compositor.click(BTN_MIDDLE);
+    compositor.key(KEY_SUPER, KeyState::Released);
+    compositor.dispatch();
+    assert_eq!(compositor.active_workspace(), 2);
+}
+
+#[test]
+fn scroll_binding() {
+    let mut config = StaticConfig {
+        workspace_amount: WorkspaceAmount::Static(3),
+        ..Default::default()
+    };
+    config.scroll_bindings.insert(
+        ScrollPattern::new(KeyModifier::Super, ScrollDirection::Down),
+        Action::NextWorkspace,
+    );
+    let mut compositor = TestCompositor::new(config);
+
+    compositor.key(KEY_SUPER, KeyState::Pressed);
+    // continuous scrolling only triggers once enough distance was covered
+    compositor.scroll(0.0, 20.0);
+    assert_eq!(compositor.active_workspace(), 0);
+    compositor.scroll(0.0, 20.0);
+    compositor.key(KEY_SUPER, KeyState::Released);
+    compositor.dispatch();
+    assert_eq!(compositor.active_workspace(), 1);
+}
+
+#[test]
+fn unbound_scroll_direction_is_forwarded() {
+    let mut config = StaticConfig::default();
+    config.scroll_bindings.insert(
+        ScrollPattern::new(KeyModifier::Super, ScrollDirection::Down),
+        Action::NextWorkspace,
+    );
+    let compositor = TestCompositor::new(config);
+    let seat = compositor.seat();
+    let config = &compositor.data.state.common.config;
+    let modifiers = ModifiersState {
+        logo: true,
+        ..Default::default()
+    };
+
+    // nothing is bound to horizontal scrolling, so the client gets it
+    assert!(matches!(
+        match_scroll(config, &seat, &modifiers, AxisSource::Finger, (50.0, 0.0)),
+        FilterResult::Forward
+    ));
+    // scrolling towards a binding is swallowed, until it triggers
+    assert!(matches!(
+        match_scroll(config, &seat, &modifiers, AxisSource::Finger, (0.0, 20.0)),
+        FilterResult::Intercept(None)
+    ));
+    assert!(matches!(
+        match_scroll(config, &seat, &modifiers, AxisSource::Finger, (0.0, 20.0)),
+        FilterResult::Intercept(Some((Action::NextWorkspace, _)))
+    ));
+}
+
+#[test]
+fn swipe_gesture_binding() {
+    let mut config = StaticConfig {
+        workspace_amount: WorkspaceAmount::Static(3),
+        ..Default::default()
+    };
+    config.gesture_bindings.insert(
+        GesturePattern::new(3, Gesture::Swipe(SwipeDirection::Up)),
+        Action::NextWorkspace,
+    );
+    config.gesture_bindings.insert(
+        GesturePattern::new(3, Gesture::Swipe(SwipeDirection::Down)),
+        Action::PreviousWorkspace,
+    );
+    let mut compositor = TestCompositor::new(config);
+
+    // too short to be recognized, so it stays with the client
+    compositor.swipe(3, &[(0.0, -10.0)]);
+    assert_eq!(compositor.active_workspace(), 0);
+    // unbound direction
+    compositor.swipe(3, &[(-400.0, 0.0), (-400.0, 0.0)]);
+    assert_eq!(compositor.active_workspace(), 0);
+    // other amount of fingers
+    compositor.swipe(4, &[(0.0, -400.0), (0.0, -400.0)]);
+    assert_eq!(compositor.active_workspace(), 0);
+
+    // following the fingers across more than half of the output switches
+    compositor.swipe(3, &[(0.0, -10.0), (0.0, -400.0), (0.0, -400.0)]);
+    assert_eq!(compositor.active_workspace(), 1);
+    compositor.swipe(3, &[(0.0, 200.0)]);
+    assert_eq!(compositor.active_workspace(), 1);
+    compositor.swipe(3, &[(0.0, 700.0)]);
+    assert_eq!(compositor.active_workspace(), 0);
+}
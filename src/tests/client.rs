@@ -0,0 +1,897 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic_protocols::{
+    toplevel_info::v1::client::{zcosmic_toplevel_handle_v1, zcosmic_toplevel_info_v1},
+    workspace::v1::client::{
+        zcosmic_workspace_group_handle_v1, zcosmic_workspace_handle_v1,
+        zcosmic_workspace_manager_v1,
+    },
+};
+use std::{
+    fs::File,
+    io::ErrorKind,
+    os::unix::{io::AsRawFd, net::UnixStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use wayland_client::{
+    backend::WaylandError,
+    delegate_noop, event_created_child,
+    protocol::{
+        wl_buffer, wl_callback, wl_compositor, wl_keyboard, wl_output, wl_pointer, wl_registry,
+        wl_seat, wl_shm, wl_shm_pool, wl_surface,
+    },
+    Connection, Dispatch, EventQueue, QueueHandle, WEnum,
+};
+use wayland_protocols::{
+    ext::session_lock::v1::client::{
+        ext_session_lock_manager_v1, ext_session_lock_surface_v1, ext_session_lock_v1,
+    },
+    wp::pointer_constraints::zv1::client::{
+        zwp_confined_pointer_v1, zwp_locked_pointer_v1, zwp_pointer_constraints_v1,
+    },
+    xdg::shell::client::{xdg_surface, xdg_toplevel, xdg_wm_base},
+};
+
+/// Size clients pick, when the compositor lets them choose
+const DEFAULT_SIZE: (i32, i32) = (300, 200);
+
+/// A wayland client, talking to a [`super::TestCompositor`] over a socketpair.
+///
+/// The client behaves like a well-behaved toplevel: every configure is acked
+/// and answered with a buffer of the requested size.
+pub struct TestClient {
+    conn: Connection,
+    queue: EventQueue<ClientState>,
+    pub state: ClientState,
+}
+
+/// Everything a [`TestClient`] was told by the compositor
+#[derive(Default)]
+pub struct ClientState {
+    compositor: Option<wl_compositor::WlCompositor>,
+    shm: Option<wl_shm::WlShm>,
+    wm_base: Option<xdg_wm_base::XdgWmBase>,
+    _seat: Option<wl_seat::WlSeat>,
+    keyboard: Option<wl_keyboard::WlKeyboard>,
+    pointer: Option<wl_pointer::WlPointer>,
+    outputs: Vec<wl_output::WlOutput>,
+    pointer_constraints: Option<zwp_pointer_constraints_v1::ZwpPointerConstraintsV1>,
+    lock_manager: Option<ext_session_lock_manager_v1::ExtSessionLockManagerV1>,
+    workspace_manager: Option<zcosmic_workspace_manager_v1::ZcosmicWorkspaceManagerV1>,
+
+    pub windows: Vec<TestWindow>,
+    /// Surface that currently has keyboard focus
+    pub keyboard_focus: Option<wl_surface::WlSurface>,
+    /// Toplevels announced via toplevel-info
+    pub toplevels: Vec<ToplevelInfo>,
+    /// Workspace groups announced via the workspace protocol
+    pub workspace_groups: Vec<WorkspaceGroupInfo>,
+    /// Workspaces announced via the workspace protocol, in order of creation
+    pub workspaces: Vec<WorkspaceInfo>,
+    /// Session lock requested via ext-session-lock
+    pub lock: Option<TestLock>,
+    /// Whether the pointer is currently locked or confined on behalf of the client
+    pub pointer_constrained: bool,
+}
+
+pub struct TestWindow {
+    pub surface: wl_surface::WlSurface,
+    xdg_surface: xdg_surface::XdgSurface,
+    pub toplevel: xdg_toplevel::XdgToplevel,
+    pub app_id: String,
+    /// Size of the last acked configure, `(0, 0)` if the client may decide
+    pub size: (i32, i32),
+    pub states: Vec<xdg_toplevel::State>,
+    pub configures: usize,
+    pub closed: bool,
+    pending_size: (i32, i32),
+    pending_states: Vec<xdg_toplevel::State>,
+    // keeps the backing storage of the attached buffer alive
+    buffer: Option<(wl_buffer::WlBuffer, File)>,
+}
+
+pub struct TestLock {
+    lock: ext_session_lock_v1::ExtSessionLockV1,
+    pub locked: bool,
+    pub finished: bool,
+    pub surfaces: Vec<TestLockSurface>,
+}
+
+pub struct TestLockSurface {
+    pub surface: wl_surface::WlSurface,
+    lock_surface: ext_session_lock_surface_v1::ExtSessionLockSurfaceV1,
+    /// Size of the last acked configure
+    pub size: (i32, i32),
+    buffer: Option<(wl_buffer::WlBuffer, File)>,
+}
+
+#[derive(Debug)]
+pub struct ToplevelInfo {
+    pub handle: zcosmic_toplevel_handle_v1::ZcosmicToplevelHandleV1,
+    pub title: String,
+    pub app_id: String,
+    pub states: Vec<u32>,
+    pub workspaces: Vec<zcosmic_workspace_handle_v1::ZcosmicWorkspaceHandleV1>,
+    pub closed: bool,
+}
+
+#[derive(Debug)]
+pub struct WorkspaceGroupInfo {
+    pub handle: zcosmic_workspace_group_handle_v1::ZcosmicWorkspaceGroupHandleV1,
+    pub capabilities: Vec<u32>,
+}
+
+#[derive(Debug)]
+pub struct WorkspaceInfo {
+    pub handle: zcosmic_workspace_handle_v1::ZcosmicWorkspaceHandleV1,
+    pub name: String,
+    pub states: Vec<u32>,
+    pub capabilities: Vec<u32>,
+    pub removed: bool,
+}
+
+impl WorkspaceInfo {
+    pub fn is_active(&self) -> bool {
+        self.states
+            .contains(&(zcosmic_workspace_handle_v1::State::Active as u32))
+    }
+
+    pub fn can(
+        &self,
+        capability: zcosmic_workspace_handle_v1::ZcosmicWorkspaceCapabilitiesV1,
+    ) -> bool {
+        self.capabilities.contains(&(capability as u32))
+    }
+}
+
+impl TestClient {
+    pub(super) fn new(stream: UnixStream) -> TestClient {
+        let conn = Connection::from_socket(stream).expect("Failed to connect test client");
+        let queue = conn.new_event_queue();
+        conn.display().get_registry(&queue.handle(), ());
+
+        TestClient {
+            conn,
+            queue,
+            state: ClientState::default(),
+        }
+    }
+
+    /// Sends a sync request, the returned flag is set once the compositor answered it.
+    pub(super) fn sync(&mut self) -> Arc<AtomicBool> {
+        let done = Arc::new(AtomicBool::new(false));
+        self.conn.display().sync(&self.queue.handle(), done.clone());
+        done
+    }
+
+    pub(super) fn flush(&mut self) {
+        self.conn.flush().expect("Failed to flush test client");
+    }
+
+    /// Reads and handles all events the compositor has sent so far
+    pub(super) fn dispatch(&mut self) {
+        self.queue
+            .dispatch_pending(&mut self.state)
+            .expect("Failed to dispatch test client");
+        if let Ok(guard) = self.conn.prepare_read() {
+            match guard.read() {
+                Ok(_) => {}
+                Err(WaylandError::Io(err)) if err.kind() == ErrorKind::WouldBlock => {}
+                Err(err) => panic!("Failed to read events: {}", err),
+            }
+        }
+        self.queue
+            .dispatch_pending(&mut self.state)
+            .expect("Failed to dispatch test client");
+    }
+
+    /// Creates a new toplevel, that will be mapped after the next roundtrip
+    pub fn create_window(&mut self, app_id: &str, title: &str) -> usize {
+        let qh = self.queue.handle();
+        let surface = self
+            .state
+            .compositor
+            .as_ref()
+            .expect("Compositor doesn't offer wl_compositor")
+            .create_surface(&qh, ());
+        let xdg_surface = self
+            .state
+            .wm_base
+            .as_ref()
+            .expect("Compositor doesn't offer xdg_wm_base")
+            .get_xdg_surface(&surface, &qh, ());
+        let toplevel = xdg_surface.get_toplevel(&qh, ());
+        toplevel.set_app_id(app_id.into());
+        toplevel.set_title(title.into());
+        // initial commit, asking for a configure
+        surface.commit();
+
+        self.state.windows.push(TestWindow {
+            surface,
+            xdg_surface,
+            toplevel,
+            app_id: app_id.into(),
+            size: (0, 0),
+            states: Vec::new(),
+            configures: 0,
+            closed: false,
+            pending_size: (0, 0),
+            pending_states: Vec::new(),
+            buffer: None,
+        });
+        self.state.windows.len() - 1
+    }
+
+    /// Destroys a toplevel created by [`TestClient::create_window`]
+    pub fn destroy_window(&mut self, idx: usize) {
+        let window = self.state.windows.remove(idx);
+        window.toplevel.destroy();
+        window.xdg_surface.destroy();
+        window.surface.destroy();
+        if let Some((buffer, _)) = window.buffer {
+            buffer.destroy();
+        }
+    }
+
+    pub fn window(&self, app_id: &str) -> &TestWindow {
+        self.state
+            .windows
+            .iter()
+            .find(|w| w.app_id == app_id)
+            .unwrap_or_else(|| panic!("No window with app_id {}", app_id))
+    }
+
+    /// Locks the pointer, while it is over the window with the given app_id
+    pub fn lock_pointer(&mut self, app_id: &str) {
+        let qh = self.queue.handle();
+        let surface = self.window(app_id).surface.clone();
+        let (constraints, pointer) = self.pointer_constraints();
+        constraints.lock_pointer(
+            &surface,
+            &pointer,
+            None,
+            zwp_pointer_constraints_v1::Lifetime::Persistent,
+            &qh,
+            (),
+        );
+    }
+
+    /// Confines the pointer to the window with the given app_id
+    pub fn confine_pointer(&mut self, app_id: &str) {
+        let qh = self.queue.handle();
+        let surface = self.window(app_id).surface.clone();
+        let (constraints, pointer) = self.pointer_constraints();
+        constraints.confine_pointer(
+            &surface,
+            &pointer,
+            None,
+            zwp_pointer_constraints_v1::Lifetime::Persistent,
+            &qh,
+            (),
+        );
+    }
+
+    fn pointer_constraints(
+        &self,
+    ) -> (
+        zwp_pointer_constraints_v1::ZwpPointerConstraintsV1,
+        wl_pointer::WlPointer,
+    ) {
+        let constraints = self
+            .state
+            .pointer_constraints
+            .clone()
+            .expect("Compositor doesn't offer zwp_pointer_constraints_v1");
+        let pointer = self.state.pointer.clone().expect("Seat has no pointer");
+        (constraints, pointer)
+    }
+
+    /// Requests to lock the session and creates a lock surface for every output
+    pub fn lock_session(&mut self) {
+        let qh = self.queue.handle();
+        let lock = self
+            .state
+            .lock_manager
+            .as_ref()
+            .expect("Compositor doesn't offer ext_session_lock_manager_v1")
+            .lock(&qh, ());
+        let compositor = self
+            .state
+            .compositor
+            .as_ref()
+            .expect("Compositor doesn't offer wl_compositor");
+        let surfaces = self
+            .state
+            .outputs
+            .iter()
+            .map(|output| {
+                let surface = compositor.create_surface(&qh, ());
+                let lock_surface = lock.get_lock_surface(&surface, output, &qh, ());
+                TestLockSurface {
+                    surface,
+                    lock_surface,
+                    size: (0, 0),
+                    buffer: None,
+                }
+            })
+            .collect();
+        self.state.lock = Some(TestLock {
+            lock,
+            locked: false,
+            finished: false,
+            surfaces,
+        });
+    }
+
+    pub fn unlock_session(&mut self) {
+        let lock = self.state.lock.take().expect("Session was not locked");
+        lock.lock.unlock_and_destroy();
+        for surface in lock.surfaces {
+            surface.lock_surface.destroy();
+            surface.surface.destroy();
+        }
+    }
+
+    /// Whether any of the clients lock surfaces has keyboard focus
+    pub fn lock_surface_has_focus(&self) -> bool {
+        self.state.lock.as_ref().map_or(false, |lock| {
+            lock.surfaces
+                .iter()
+                .any(|s| self.state.keyboard_focus.as_ref() == Some(&s.surface))
+        })
+    }
+
+    pub fn has_keyboard_focus(&self, app_id: &str) -> bool {
+        self.state.keyboard_focus.as_ref() == Some(&self.window(app_id).surface)
+    }
+
+    pub fn toplevel(&self, app_id: &str) -> Option<&ToplevelInfo> {
+        self.state
+            .toplevels
+            .iter()
+            .find(|t| !t.closed && t.app_id == app_id)
+    }
+
+    /// Index of the workspace toplevel-info reports for the given window
+    pub fn toplevel_workspace(&self, app_id: &str) -> Option<usize> {
+        let toplevel = self.toplevel(app_id)?;
+        let workspace = toplevel.workspaces.first()?;
+        self.state
+            .workspaces
+            .iter()
+            .filter(|w| !w.removed)
+            .position(|w| &w.handle == workspace)
+    }
+
+    /// Index of the workspace reported as active via the workspace protocol
+    pub fn active_workspace(&self) -> Option<usize> {
+        self.state
+            .workspaces
+            .iter()
+            .filter(|w| !w.removed)
+            .position(|w| w.is_active())
+    }
+
+    pub fn workspace(&self, name: &str) -> Option<&WorkspaceInfo> {
+        self.state
+            .workspaces
+            .iter()
+            .find(|w| !w.removed && w.name == name)
+    }
+
+    /// Requests a new workspace in the first workspace group
+    pub fn create_workspace(&mut self, name: &str) {
+        let group = &self.state.workspace_groups[0];
+        group.handle.create_workspace(name.into());
+        self.commit_workspaces();
+    }
+
+    pub fn remove_workspace(&mut self, name: &str) {
+        self.workspace(name)
+            .expect("No workspace with that name")
+            .handle
+            .remove();
+        self.commit_workspaces();
+    }
+
+    pub fn deactivate_workspace(&mut self, name: &str) {
+        self.workspace(name)
+            .expect("No workspace with that name")
+            .handle
+            .deactivate();
+        self.commit_workspaces();
+    }
+
+    fn commit_workspaces(&mut self) {
+        self.state
+            .workspace_manager
+            .as_ref()
+            .expect("Workspace protocol not bound")
+            .commit();
+    }
+}
+
+impl ClientState {
+    fn window_mut(&mut self, xdg_surface: &xdg_surface::XdgSurface) -> Option<&mut TestWindow> {
+        self.windows
+            .iter_mut()
+            .find(|w| &w.xdg_surface == xdg_surface)
+    }
+
+    fn toplevel_mut(
+        &mut self,
+        handle: &zcosmic_toplevel_handle_v1::ZcosmicToplevelHandleV1,
+    ) -> Option<&mut ToplevelInfo> {
+        self.toplevels.iter_mut().find(|t| &t.handle == handle)
+    }
+
+    fn workspace_mut(
+        &mut self,
+        handle: &zcosmic_workspace_handle_v1::ZcosmicWorkspaceHandleV1,
+    ) -> Option<&mut WorkspaceInfo> {
+        self.workspaces.iter_mut().find(|w| &w.handle == handle)
+    }
+}
+
+fn create_buffer(
+    shm: &wl_shm::WlShm,
+    qh: &QueueHandle<ClientState>,
+    (width, height): (i32, i32),
+) -> (wl_buffer::WlBuffer, File) {
+    let stride = width * 4;
+    let size = stride * height;
+    let file = tempfile::tempfile().expect("Failed to create buffer storage");
+    file.set_len(size as u64)
+        .expect("Failed to allocate buffer storage");
+
+    let pool = shm.create_pool(file.as_raw_fd(), size, qh, ());
+    let buffer = pool.create_buffer(0, width, height, stride, wl_shm::Format::Argb8888, qh, ());
+    pool.destroy();
+    (buffer, file)
+}
+
+fn u32_array(array: &[u8]) -> impl Iterator<Item = u32> + '_ {
+    array
+        .chunks_exact(4)
+        .map(|chunk| u32::from_ne_bytes(chunk.try_into().unwrap()))
+}
+
+impl Dispatch<wl_registry::WlRegistry, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        registry: &wl_registry::WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        {
+            match interface.as_str() {
+                "wl_compositor" => {
+                    state.compositor = Some(registry.bind(name, version.min(4), qh, ()));
+                }
+                "wl_shm" => {
+                    state.shm = Some(registry.bind(name, 1, qh, ()));
+                }
+                "xdg_wm_base" => {
+                    state.wm_base = Some(registry.bind(name, version.min(3), qh, ()));
+                }
+                "wl_seat" => {
+                    state._seat = Some(registry.bind(name, version.min(5), qh, ()));
+                }
+                "wl_output" => {
+                    state
+                        .outputs
+                        .push(registry.bind(name, version.min(3), qh, ()));
+                }
+                "ext_session_lock_manager_v1" => {
+                    state.lock_manager = Some(registry.bind(name, 1, qh, ()));
+                }
+                "zwp_pointer_constraints_v1" => {
+                    state.pointer_constraints = Some(registry.bind(name, 1, qh, ()));
+                }
+                "zcosmic_workspace_manager_v1" => {
+                    state.workspace_manager = Some(registry.bind(name, 1, qh, ()));
+                }
+                "zcosmic_toplevel_info_v1" => {
+                    registry.bind::<zcosmic_toplevel_info_v1::ZcosmicToplevelInfoV1, _, _>(
+                        name,
+                        1,
+                        qh,
+                        (),
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Dispatch<wl_callback::WlCallback, Arc<AtomicBool>> for ClientState {
+    fn event(
+        _: &mut Self,
+        _: &wl_callback::WlCallback,
+        event: wl_callback::Event,
+        done: &Arc<AtomicBool>,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let wl_callback::Event::Done { .. } = event {
+            done.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+impl Dispatch<wl_seat::WlSeat, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        seat: &wl_seat::WlSeat,
+        event: wl_seat::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wl_seat::Event::Capabilities {
+            capabilities: WEnum::Value(capabilities),
+        } = event
+        {
+            if capabilities.contains(wl_seat::Capability::Keyboard) && state.keyboard.is_none() {
+                state.keyboard = Some(seat.get_keyboard(qh, ()));
+            }
+            if capabilities.contains(wl_seat::Capability::Pointer) && state.pointer.is_none() {
+                state.pointer = Some(seat.get_pointer(qh, ()));
+            }
+        }
+    }
+}
+
+impl Dispatch<wl_keyboard::WlKeyboard, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &wl_keyboard::WlKeyboard,
+        event: wl_keyboard::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_keyboard::Event::Enter { surface, .. } => state.keyboard_focus = Some(surface),
+            wl_keyboard::Event::Leave { .. } => state.keyboard_focus = None,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<xdg_wm_base::XdgWmBase, ()> for ClientState {
+    fn event(
+        _: &mut Self,
+        wm_base: &xdg_wm_base::XdgWmBase,
+        event: xdg_wm_base::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let xdg_wm_base::Event::Ping { serial } = event {
+            wm_base.pong(serial);
+        }
+    }
+}
+
+impl Dispatch<xdg_surface::XdgSurface, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        xdg_surface: &xdg_surface::XdgSurface,
+        event: xdg_surface::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let xdg_surface::Event::Configure { serial } = event {
+            let shm = state.shm.clone().expect("Compositor doesn't offer wl_shm");
+            let Some(window) = state.window_mut(xdg_surface) else {
+                return;
+            };
+            xdg_surface.ack_configure(serial);
+            window.configures += 1;
+            window.size = window.pending_size;
+            window.states = std::mem::take(&mut window.pending_states);
+
+            let size = match window.size {
+                (0, _) | (_, 0) => DEFAULT_SIZE,
+                size => size,
+            };
+            let (buffer, file) = create_buffer(&shm, qh, size);
+            window.surface.attach(Some(&buffer), 0, 0);
+            window.surface.damage_buffer(0, 0, size.0, size.1);
+            window.surface.commit();
+            if let Some((old_buffer, _)) = window.buffer.replace((buffer, file)) {
+                old_buffer.destroy();
+            }
+        }
+    }
+}
+
+impl Dispatch<xdg_toplevel::XdgToplevel, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        toplevel: &xdg_toplevel::XdgToplevel,
+        event: xdg_toplevel::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Some(window) = state.windows.iter_mut().find(|w| &w.toplevel == toplevel) else {
+            return;
+        };
+        match event {
+            xdg_toplevel::Event::Configure {
+                width,
+                height,
+                states,
+            } => {
+                window.pending_size = (width, height);
+                window.pending_states = u32_array(&states)
+                    .filter_map(|state| xdg_toplevel::State::try_from(state).ok())
+                    .collect();
+            }
+            xdg_toplevel::Event::Close => window.closed = true,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<zcosmic_toplevel_info_v1::ZcosmicToplevelInfoV1, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &zcosmic_toplevel_info_v1::ZcosmicToplevelInfoV1,
+        event: zcosmic_toplevel_info_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let zcosmic_toplevel_info_v1::Event::Toplevel { toplevel } = event {
+            state.toplevels.push(ToplevelInfo {
+                handle: toplevel,
+                title: String::new(),
+                app_id: String::new(),
+                states: Vec::new(),
+                workspaces: Vec::new(),
+                closed: false,
+            });
+        }
+    }
+
+    event_created_child!(ClientState, zcosmic_toplevel_info_v1::ZcosmicToplevelInfoV1, [
+        zcosmic_toplevel_info_v1::EVT_TOPLEVEL_OPCODE => (zcosmic_toplevel_handle_v1::ZcosmicToplevelHandleV1, ())
+    ]);
+}
+
+impl Dispatch<zcosmic_toplevel_handle_v1::ZcosmicToplevelHandleV1, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        handle: &zcosmic_toplevel_handle_v1::ZcosmicToplevelHandleV1,
+        event: zcosmic_toplevel_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Some(toplevel) = state.toplevel_mut(handle) else {
+            return;
+        };
+        match event {
+            zcosmic_toplevel_handle_v1::Event::Title { title } => toplevel.title = title,
+            zcosmic_toplevel_handle_v1::Event::AppId { app_id } => toplevel.app_id = app_id,
+            zcosmic_toplevel_handle_v1::Event::State { state } => {
+                toplevel.states = u32_array(&state).collect();
+            }
+            zcosmic_toplevel_handle_v1::Event::WorkspaceEnter { workspace } => {
+                toplevel.workspaces.push(workspace);
+            }
+            zcosmic_toplevel_handle_v1::Event::WorkspaceLeave { workspace } => {
+                toplevel.workspaces.retain(|w| w != &workspace);
+            }
+            zcosmic_toplevel_handle_v1::Event::Closed => toplevel.closed = true,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<zcosmic_workspace_manager_v1::ZcosmicWorkspaceManagerV1, ()> for ClientState {
+    fn event(
+        _: &mut Self,
+        _: &zcosmic_workspace_manager_v1::ZcosmicWorkspaceManagerV1,
+        _: zcosmic_workspace_manager_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+
+    event_created_child!(ClientState, zcosmic_workspace_manager_v1::ZcosmicWorkspaceManagerV1, [
+        zcosmic_workspace_manager_v1::EVT_WORKSPACE_GROUP_OPCODE => (zcosmic_workspace_group_handle_v1::ZcosmicWorkspaceGroupHandleV1, ())
+    ]);
+}
+
+impl Dispatch<zcosmic_workspace_group_handle_v1::ZcosmicWorkspaceGroupHandleV1, ()>
+    for ClientState
+{
+    fn event(
+        state: &mut Self,
+        handle: &zcosmic_workspace_group_handle_v1::ZcosmicWorkspaceGroupHandleV1,
+        event: zcosmic_workspace_group_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if !state.workspace_groups.iter().any(|g| &g.handle == handle) {
+            state.workspace_groups.push(WorkspaceGroupInfo {
+                handle: handle.clone(),
+                capabilities: Vec::new(),
+            });
+        }
+        match event {
+            zcosmic_workspace_group_handle_v1::Event::Workspace { workspace } => {
+                state.workspaces.push(WorkspaceInfo {
+                    handle: workspace,
+                    name: String::new(),
+                    states: Vec::new(),
+                    capabilities: Vec::new(),
+                    removed: false,
+                });
+            }
+            zcosmic_workspace_group_handle_v1::Event::Capabilities { capabilities } => {
+                let group = state
+                    .workspace_groups
+                    .iter_mut()
+                    .find(|g| &g.handle == handle)
+                    .unwrap();
+                group.capabilities = u32_array(&capabilities).collect();
+            }
+            zcosmic_workspace_group_handle_v1::Event::Remove => {
+                state.workspace_groups.retain(|g| &g.handle != handle);
+            }
+            _ => {}
+        }
+    }
+
+    event_created_child!(ClientState, zcosmic_workspace_group_handle_v1::ZcosmicWorkspaceGroupHandleV1, [
+        zcosmic_workspace_group_handle_v1::EVT_WORKSPACE_OPCODE => (zcosmic_workspace_handle_v1::ZcosmicWorkspaceHandleV1, ())
+    ]);
+}
+
+impl Dispatch<zcosmic_workspace_handle_v1::ZcosmicWorkspaceHandleV1, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        handle: &zcosmic_workspace_handle_v1::ZcosmicWorkspaceHandleV1,
+        event: zcosmic_workspace_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Some(workspace) = state.workspace_mut(handle) else {
+            return;
+        };
+        match event {
+            zcosmic_workspace_handle_v1::Event::Name { name } => workspace.name = name,
+            zcosmic_workspace_handle_v1::Event::State { state } => {
+                workspace.states = u32_array(&state).collect();
+            }
+            zcosmic_workspace_handle_v1::Event::Capabilities { capabilities } => {
+                workspace.capabilities = u32_array(&capabilities).collect();
+            }
+            zcosmic_workspace_handle_v1::Event::Remove => workspace.removed = true,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ext_session_lock_v1::ExtSessionLockV1, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &ext_session_lock_v1::ExtSessionLockV1,
+        event: ext_session_lock_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Some(lock) = state.lock.as_mut() else {
+            return;
+        };
+        match event {
+            ext_session_lock_v1::Event::Locked => lock.locked = true,
+            ext_session_lock_v1::Event::Finished => lock.finished = true,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ext_session_lock_surface_v1::ExtSessionLockSurfaceV1, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        lock_surface: &ext_session_lock_surface_v1::ExtSessionLockSurfaceV1,
+        event: ext_session_lock_surface_v1::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let ext_session_lock_surface_v1::Event::Configure {
+            serial,
+            width,
+            height,
+        } = event
+        {
+            let shm = state.shm.clone().expect("Compositor doesn't offer wl_shm");
+            let Some(surface) = state
+                .lock
+                .as_mut()
+                .and_then(|lock| lock.surfaces.iter_mut().find(|s| &s.lock_surface == lock_surface))
+            else {
+                return;
+            };
+            lock_surface.ack_configure(serial);
+            surface.size = (width as i32, height as i32);
+
+            let (buffer, file) = create_buffer(&shm, qh, surface.size);
+            surface.surface.attach(Some(&buffer), 0, 0);
+            surface
+                .surface
+                .damage_buffer(0, 0, surface.size.0, surface.size.1);
+            surface.surface.commit();
+            if let Some((old_buffer, _)) = surface.buffer.replace((buffer, file)) {
+                old_buffer.destroy();
+            }
+        }
+    }
+}
+
+impl Dispatch<zwp_locked_pointer_v1::ZwpLockedPointerV1, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &zwp_locked_pointer_v1::ZwpLockedPointerV1,
+        event: zwp_locked_pointer_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwp_locked_pointer_v1::Event::Locked => state.pointer_constrained = true,
+            zwp_locked_pointer_v1::Event::Unlocked => state.pointer_constrained = false,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<zwp_confined_pointer_v1::ZwpConfinedPointerV1, ()> for ClientState {
+    fn event(
+        state: &mut Self,
+        _: &zwp_confined_pointer_v1::ZwpConfinedPointerV1,
+        event: zwp_confined_pointer_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwp_confined_pointer_v1::Event::Confined => state.pointer_constrained = true,
+            zwp_confined_pointer_v1::Event::Unconfined => state.pointer_constrained = false,
+            _ => {}
+        }
+    }
+}
+
+delegate_noop!(ClientState: ignore wl_compositor::WlCompositor);
+delegate_noop!(ClientState: ignore wl_pointer::WlPointer);
+delegate_noop!(ClientState: zwp_pointer_constraints_v1::ZwpPointerConstraintsV1);
+delegate_noop!(ClientState: ignore wl_output::WlOutput);
+delegate_noop!(ClientState: ext_session_lock_manager_v1::ExtSessionLockManagerV1);
+delegate_noop!(ClientState: ignore wl_surface::WlSurface);
+delegate_noop!(ClientState: ignore wl_shm::WlShm);
+delegate_noop!(ClientState: ignore wl_shm_pool::WlShmPool);
+delegate_noop!(ClientState: ignore wl_buffer::WlBuffer);
@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use smithay::{backend::input::ButtonState, utils::Rectangle, wayland::seat::WaylandFocus};
+
+use super::{TestCompositor, BTN_LEFT};
+use crate::{
+    config::StaticConfig,
+    shell::{layout::floating::SnapZone, Shell},
+};
+
+#[test]
+fn snap_zones_at_output_edges() {
+    let compositor = TestCompositor::new(StaticConfig::default());
+    let output = compositor.output();
+    let outputs = [output.clone()];
+
+    assert_eq!(
+        SnapZone::at(&output, &outputs, (2.0, 500.0).into()),
+        Some(SnapZone::Left)
+    );
+    assert_eq!(
+        SnapZone::at(&output, &outputs, (1000.0, 1.0).into()),
+        Some(SnapZone::Top)
+    );
+    assert_eq!(
+        SnapZone::at(&output, &outputs, (1919.0, 60.0).into()),
+        Some(SnapZone::TopRight)
+    );
+    assert_eq!(
+        SnapZone::at(&output, &outputs, (30.0, 1079.0).into()),
+        Some(SnapZone::BottomLeft)
+    );
+    assert_eq!(SnapZone::at(&output, &outputs, (960.0, 540.0).into()), None);
+
+    assert_eq!(
+        SnapZone::Left.geometry(&output),
+        Rectangle::from_loc_and_size((0, 0), (960, 1080))
+    );
+    assert_eq!(
+        SnapZone::BottomRight.geometry(&output),
+        Rectangle::from_loc_and_size((960, 540), (960, 540))
+    );
+}
+
+#[test]
+fn dragging_to_shared_edge_does_not_snap() {
+    let mut compositor = TestCompositor::with_outputs(
+        StaticConfig::default(),
+        vec![((1920, 1080).into(), 1.0), ((1920, 1080).into(), 1.0)],
+    );
+    let mut client = compositor.new_client();
+    compositor.map_window(&mut client, "dragged");
+    compositor.roundtrip(&mut client);
+    let outputs = compositor
+        .data
+        .state
+        .common
+        .shell
+        .outputs()
+        .cloned()
+        .collect::<Vec<_>>();
+
+    // the outputs are next to each other, so only the outer edges snap
+    assert_eq!(
+        SnapZone::at(&outputs[0], &outputs, (1919.0, 500.0).into()),
+        None
+    );
+    assert_eq!(
+        SnapZone::at(&outputs[1], &outputs, (1920.0, 500.0).into()),
+        None
+    );
+    assert_eq!(
+        SnapZone::at(&outputs[1], &outputs, (3839.0, 500.0).into()),
+        Some(SnapZone::Right)
+    );
+
+    let mut drag_to = |compositor: &mut TestCompositor, x: f64, y: f64| {
+        let geometry = compositor.window_geometry("dragged");
+        compositor.move_pointer(
+            geometry.loc.x as f64 + geometry.size.w as f64 / 2.0,
+            geometry.loc.y as f64 + geometry.size.h as f64 / 2.0,
+        );
+        compositor.button(BTN_LEFT, ButtonState::Pressed);
+        let surface = compositor
+            .window("dragged")
+            .active_window()
+            .wl_surface()
+            .unwrap();
+        let seat = compositor.seat();
+        Shell::move_request(&mut compositor.data.state, &surface, &seat, None);
+        compositor.move_pointer(x, y);
+        compositor.button(BTN_LEFT, ButtonState::Released);
+        compositor.roundtrip(&mut client);
+        compositor.window_geometry("dragged")
+    };
+
+    assert_eq!(
+        drag_to(&mut compositor, 1.0, 500.0),
+        SnapZone::Left.geometry(&outputs[0])
+    );
+    assert_ne!(
+        drag_to(&mut compositor, 1919.0, 500.0),
+        SnapZone::Right.geometry(&outputs[0])
+    );
+}
@@ -0,0 +1,208 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic_protocols::toplevel_info::v1::client::zcosmic_toplevel_handle_v1;
+use smithay::{backend::input::KeyState, input::keyboard::keysyms as KeySyms, utils::Rectangle};
+
+use super::{TestCompositor, BTN_LEFT, KEY_ALT, KEY_TAB};
+use crate::{
+    config::{Action, KeyModifier, KeyPattern, StaticConfig},
+    shell::SwitcherScope,
+    utils::prelude::*,
+};
+
+fn tiling_config() -> StaticConfig {
+    StaticConfig {
+        tiling_enabled: true,
+        gaps: (0, 0),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn new_window_gets_focus() {
+    let mut compositor = TestCompositor::new(tiling_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.roundtrip(&mut client);
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("first"));
+    assert!(client.has_keyboard_focus("first"));
+
+    compositor.map_window(&mut client, "second");
+    compositor.roundtrip(&mut client);
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("second"));
+    assert!(client.has_keyboard_focus("second"));
+    assert_eq!(compositor.focus_stack(), vec!["second", "first"]);
+}
+
+#[test]
+fn click_focuses_window() {
+    let mut compositor = TestCompositor::new(tiling_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.map_window(&mut client, "second");
+    compositor.roundtrip(&mut client);
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("second"));
+
+    let geometry = compositor.window_geometry("first");
+    compositor.move_pointer(
+        (geometry.loc.x + geometry.size.w / 2) as f64,
+        (geometry.loc.y + geometry.size.h / 2) as f64,
+    );
+    compositor.click(BTN_LEFT);
+    compositor.roundtrip(&mut client);
+
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("first"));
+    assert!(client.has_keyboard_focus("first"));
+    assert_eq!(compositor.focus_stack(), vec!["first", "second"]);
+}
+
+#[test]
+fn closing_window_restores_focus() {
+    let mut compositor = TestCompositor::new(tiling_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    let second = compositor.map_window(&mut client, "second");
+    compositor.roundtrip(&mut client);
+
+    client.destroy_window(second);
+    compositor.roundtrip(&mut client);
+    compositor.roundtrip(&mut client);
+
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("first"));
+    assert!(client.has_keyboard_focus("first"));
+    assert_eq!(compositor.focus_stack(), vec!["first"]);
+    // the remaining window takes over the whole output again
+    assert_eq!(client.window("first").size, (1920, 1080));
+}
+
+#[test]
+fn minimized_window_is_restored() {
+    let mut compositor = TestCompositor::new(tiling_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.map_window(&mut client, "second");
+    compositor.roundtrip(&mut client);
+
+    client.window("second").toplevel.set_minimized();
+    compositor.roundtrip(&mut client);
+    compositor.roundtrip(&mut client);
+
+    let minimized = zcosmic_toplevel_handle_v1::State::Minimized as u32;
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("first"));
+    assert_eq!(compositor.focus_stack(), vec!["first"]);
+    assert_eq!(client.window("first").size, (1920, 1080));
+    assert!(client
+        .toplevel("second")
+        .unwrap()
+        .states
+        .contains(&minimized));
+
+    let seat = compositor.seat();
+    let state = &mut compositor.data.state;
+    let window = state
+        .common
+        .shell
+        .workspaces
+        .spaces()
+        .flat_map(|space| space.windows())
+        .find(|window| window.app_id() == "second")
+        .unwrap();
+    let mapped = state.common.shell.unminimize(&window, &seat).unwrap();
+    Common::set_focus(state, Some(&mapped.into()), &seat, None);
+    compositor.roundtrip(&mut client);
+    compositor.roundtrip(&mut client);
+
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("second"));
+    assert_eq!(client.window("second").size, (960, 1080));
+    assert!(!client
+        .toplevel("second")
+        .unwrap()
+        .states
+        .contains(&minimized));
+}
+
+#[test]
+fn scratchpad_follows_active_workspace() {
+    let mut compositor = TestCompositor::new(StaticConfig::default());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "term");
+    compositor.roundtrip(&mut client);
+    let output = compositor.output();
+    let shell = &mut compositor.data.state.common.shell;
+    let _ = shell.activate(&output, 1);
+
+    assert!(shell.toggle_scratchpad("term", &output).is_some());
+    compositor.roundtrip(&mut client);
+    assert_eq!(compositor.workspace_of("term"), Some(1));
+    assert_eq!(
+        compositor.window_geometry("term"),
+        Rectangle::from_loc_and_size((810, 440), (300, 200))
+    );
+
+    let shell = &mut compositor.data.state.common.shell;
+    assert!(shell.toggle_scratchpad("term", &output).is_none());
+    assert!(shell.active_space(&output).mapped().next().is_none());
+}
+
+#[test]
+fn always_on_top_stays_above_focused_window() {
+    let mut compositor = TestCompositor::new(StaticConfig::default());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "video");
+    compositor.roundtrip(&mut client);
+    let seat = compositor.seat();
+    let output = compositor.output();
+    let video = compositor.window("video");
+    let shell = &mut compositor.data.state.common.shell;
+    shell
+        .active_space_mut(&output)
+        .set_always_on_top(&video, &seat, true);
+
+    compositor.map_window(&mut client, "editor");
+    compositor.roundtrip(&mut client);
+
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("editor"));
+    let shell = &compositor.data.state.common.shell;
+    let stacking = shell
+        .active_space(&output)
+        .floating_layer
+        .mapped()
+        .map(|mapped| mapped.active_window().app_id())
+        .collect::<Vec<_>>();
+    assert_eq!(stacking, vec!["video", "editor"]);
+}
+
+#[test]
+fn window_switcher_commits_on_modifier_release() {
+    let mut config = tiling_config();
+    config.key_bindings.insert(
+        KeyPattern::new(KeyModifier::Alt, KeySyms::KEY_Tab),
+        Action::SwitchWindows(SwitcherScope::Workspace),
+    );
+    let mut compositor = TestCompositor::new(config);
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.map_window(&mut client, "second");
+    compositor.map_window(&mut client, "third");
+    compositor.roundtrip(&mut client);
+    assert_eq!(compositor.focus_stack(), vec!["third", "second", "first"]);
+
+    compositor.key(KEY_ALT, KeyState::Pressed);
+    compositor.press_keys(&[KEY_TAB]);
+    compositor.press_keys(&[KEY_TAB]);
+    assert!(compositor.data.state.common.shell.switcher.is_some());
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("third"));
+
+    compositor.key(KEY_ALT, KeyState::Released);
+    compositor.roundtrip(&mut client);
+    assert!(compositor.data.state.common.shell.switcher.is_none());
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("first"));
+    assert!(client.has_keyboard_focus("first"));
+}
@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::{TestCompositor, BTN_LEFT, KEY_2, KEY_SUPER};
+use crate::{
+    config::{Action, KeyModifier, KeyPattern, StaticConfig, XkbConfig, XkbLayout, MAX_LAYOUTS},
+    input::layout::current_layout,
+    shell::WorkspaceAmount,
+};
+use smithay::input::keyboard::keysyms as KeySyms;
+
+/// evdev keycode of the space bar
+const KEY_SPACE: u32 = 57;
+
+fn layout_config() -> StaticConfig {
+    let mut config = StaticConfig {
+        tiling_enabled: true,
+        gaps: (0, 0),
+        ..Default::default()
+    };
+    config.key_bindings.insert(
+        KeyPattern::from_keycode(KeyModifier::Super, KEY_SPACE),
+        Action::NextLayout,
+    );
+    config.key_bindings.insert(
+        KeyPattern::from_keycode(KeyModifier::Super, KEY_2),
+        Action::SetLayout(2),
+    );
+    config
+}
+
+fn xkb(per_window_layout: bool) -> XkbConfig {
+    XkbConfig {
+        per_window_layout,
+        ..xkb_layouts(&["us", "de", "fr"])
+    }
+}
+
+fn xkb_layouts(layouts: &[&str]) -> XkbConfig {
+    XkbConfig {
+        layouts: layouts
+            .iter()
+            .map(|layout| XkbLayout {
+                layout: layout.to_string(),
+                variant: String::new(),
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn next_layout_binding() {
+    let mut compositor = TestCompositor::with_xkb(layout_config(), xkb(false));
+    let seat = compositor.seat();
+    assert_eq!(current_layout(&seat), 0);
+
+    compositor.press_keys(&[KEY_SUPER, KEY_SPACE]);
+    compositor.dispatch();
+    assert_eq!(current_layout(&seat), 1);
+
+    compositor.press_keys(&[KEY_SUPER, KEY_SPACE]);
+    compositor.press_keys(&[KEY_SUPER, KEY_SPACE]);
+    compositor.dispatch();
+    // wraps around
+    assert_eq!(current_layout(&seat), 0);
+}
+
+#[test]
+fn layouts_capped_to_xkb_groups() {
+    let xkb = xkb_layouts(&["us", "de", "fr", "es", "it"]);
+    assert_eq!(xkb.layouts().len(), MAX_LAYOUTS);
+    assert_eq!(xkb.unusable_layouts()[0].layout, "it");
+
+    let mut compositor = TestCompositor::with_xkb(layout_config(), xkb);
+    let seat = compositor.seat();
+    for _ in 0..MAX_LAYOUTS {
+        compositor.press_keys(&[KEY_SUPER, KEY_SPACE]);
+    }
+    compositor.dispatch();
+    // wraps around after the last usable layout
+    assert_eq!(current_layout(&seat), 0);
+}
+
+#[test]
+fn set_layout_binding() {
+    let mut compositor = TestCompositor::with_xkb(layout_config(), xkb(false));
+    let seat = compositor.seat();
+
+    compositor.press_keys(&[KEY_SUPER, KEY_2]);
+    compositor.dispatch();
+    assert_eq!(current_layout(&seat), 2);
+}
+
+#[test]
+fn per_window_layout_restored_on_focus() {
+    let mut compositor = TestCompositor::with_xkb(layout_config(), xkb(true));
+    let mut client = compositor.new_client();
+    let seat = compositor.seat();
+
+    compositor.map_window(&mut client, "first");
+    compositor.press_keys(&[KEY_SUPER, KEY_SPACE]);
+    compositor.roundtrip(&mut client);
+    assert_eq!(current_layout(&seat), 1);
+
+    // new windows start out with the first layout
+    compositor.map_window(&mut client, "second");
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("second"));
+    assert_eq!(current_layout(&seat), 0);
+
+    // "first" is tiled on the left half
+    compositor.move_pointer(480.0, 540.0);
+    compositor.click(BTN_LEFT);
+    compositor.roundtrip(&mut client);
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("first"));
+    assert_eq!(current_layout(&seat), 1);
+}
+
+#[test]
+fn bindings_fall_back_to_first_layout() {
+    /// evdev keycode of the h key, a cyrillic letter in the russian layout
+    const KEY_H: u32 = 35;
+
+    let mut config = StaticConfig {
+        workspace_amount: WorkspaceAmount::Static(3),
+        ..layout_config()
+    };
+    config.key_bindings.insert(
+        KeyPattern::new(KeyModifier::Super, KeySyms::KEY_h),
+        Action::Workspace(2),
+    );
+    let mut compositor = TestCompositor::with_xkb(config, xkb_layouts(&["us", "ru"]));
+    let seat = compositor.seat();
+
+    compositor.press_keys(&[KEY_SUPER, KEY_SPACE]);
+    compositor.dispatch();
+    assert_eq!(current_layout(&seat), 1);
+
+    compositor.press_keys(&[KEY_SUPER, KEY_H]);
+    compositor.dispatch();
+    assert_eq!(compositor.active_workspace(), 1);
+    assert_eq!(current_layout(&seat), 1);
+}
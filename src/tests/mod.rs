@@ -0,0 +1,323 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! In-process integration tests.
+//!
+//! A [`TestCompositor`] runs the full compositor state on top of the headless
+//! backend without rendering, while [`TestClient`]s talk to it over a socketpair.
+
+use crate::{
+    backend::headless::{self, HeadlessInputEvent},
+    config::{Config, StaticConfig, XkbConfig},
+    shell::{element::CosmicMapped, focus::target::KeyboardFocusTarget},
+    state::{BackendData, Data},
+    utils::prelude::*,
+};
+use smithay::{
+    backend::input::{ButtonState, KeyState},
+    input::Seat,
+    output::Output,
+    reexports::{calloop::channel::Sender, calloop::EventLoop, wayland_server::Display},
+    utils::{Logical, Physical, Rectangle, Size},
+};
+use std::{os::unix::net::UnixStream, sync::atomic::Ordering, sync::Arc, time::Duration};
+
+mod bindings;
+mod client;
+mod floating;
+mod focus;
+mod keyboard_layouts;
+mod pointer_constraints;
+mod session_lock;
+mod tiling;
+mod workspaces;
+
+pub use self::client::TestClient;
+
+/// Maximum number of dispatch iterations a roundtrip may take
+const MAX_ITERATIONS: usize = 100;
+
+/// evdev keycodes used by the tests
+pub const KEY_SUPER: u32 = 125;
+pub const KEY_2: u32 = 3;
+pub const KEY_ALT: u32 = 56;
+pub const KEY_TAB: u32 = 15;
+pub const BTN_LEFT: u32 = 0x110;
+
+pub struct TestCompositor {
+    event_loop: EventLoop<'static, Data>,
+    pub data: Data,
+    input: Sender<HeadlessInputEvent>,
+}
+
+impl TestCompositor {
+    /// Starts a compositor with a single 1920x1080 output
+    pub fn new(config: StaticConfig) -> TestCompositor {
+        TestCompositor::with_outputs(config, vec![((1920, 1080).into(), 1.0)])
+    }
+
+    pub fn with_outputs(
+        config: StaticConfig,
+        outputs: Vec<(Size<i32, Physical>, f64)>,
+    ) -> TestCompositor {
+        TestCompositor::with_config(Config::in_memory(config), outputs)
+    }
+
+    /// Starts a compositor with a single 1920x1080 output and the given keymap
+    pub fn with_xkb(config: StaticConfig, xkb: XkbConfig) -> TestCompositor {
+        let mut config = Config::in_memory(config);
+        config.set_xkb_config(xkb);
+        TestCompositor::with_config(config, vec![((1920, 1080).into(), 1.0)])
+    }
+
+    fn with_config(config: Config, outputs: Vec<(Size<i32, Physical>, f64)>) -> TestCompositor {
+        let mut event_loop = EventLoop::try_new().expect("Failed to initialize event loop");
+        let display = Display::new().expect("Failed to initialize wayland display");
+        let mut state = State::new(
+            &display.handle(),
+            "wayland-test".into(),
+            event_loop.handle(),
+            event_loop.get_signal(),
+            config,
+        );
+        headless::init_backend_without_renderer(&mut event_loop, &mut state, outputs)
+            .expect("Failed to initialize headless backend");
+
+        let output = state
+            .common
+            .shell
+            .outputs()
+            .next()
+            .cloned()
+            .expect("Backend initialized without output");
+        let seat = crate::input::add_seat(
+            &display.handle(),
+            &mut state.common.seat_state,
+            &output,
+            &state.common.config,
+            "seat-0".into(),
+        );
+        state.common.add_seat(seat);
+
+        let input = match &state.backend {
+            BackendData::Headless(headless) => headless.input_sender(),
+            _ => unreachable!(),
+        };
+
+        let mut compositor = TestCompositor {
+            event_loop,
+            data: Data { display, state },
+            input,
+        };
+        compositor.dispatch();
+        compositor
+    }
+
+    /// Removes an output from the shell, as if it was unplugged
+    pub fn disconnect_output(&mut self, output: &Output) {
+        let state = &mut self.data.state;
+        let seats = state.common.seats().cloned().collect::<Vec<_>>();
+        state.common.shell.remove_output(output, seats.into_iter());
+        self.dispatch();
+    }
+
+    /// Adds a previously disconnected output back to the shell
+    pub fn connect_output(&mut self, output: &Output) {
+        self.data.state.common.shell.add_output(output);
+        self.dispatch();
+    }
+
+    /// Runs one iteration of the compositors main loop
+    pub fn dispatch(&mut self) {
+        self.event_loop
+            .dispatch(Some(Duration::ZERO), &mut self.data)
+            .expect("Failed to dispatch event loop");
+        self.data
+            .display
+            .dispatch_clients(&mut self.data.state)
+            .expect("Failed to dispatch clients");
+
+        self.data.state.refresh_loop_iteration();
+
+        self.data
+            .display
+            .flush_clients()
+            .expect("Failed to flush clients");
+    }
+
+    /// Connects a new (privileged) client and waits until it bound all globals
+    pub fn new_client(&mut self) -> TestClient {
+        let (client_stream, server_stream) =
+            UnixStream::pair().expect("Failed to create socketpair");
+        let client_state = Arc::new(self.data.state.new_privileged_client_state());
+        self.data
+            .display
+            .handle()
+            .insert_client(server_stream, client_state)
+            .expect("Failed to insert client");
+
+        let mut client = TestClient::new(client_stream);
+        // first roundtrip gets the globals, the second their initial events
+        self.roundtrip(&mut client);
+        self.roundtrip(&mut client);
+        client
+    }
+
+    /// Waits until the compositor processed all requests of the client so far
+    /// and the client handled all resulting events.
+    pub fn roundtrip(&mut self, client: &mut TestClient) {
+        let done = client.sync();
+        for _ in 0..MAX_ITERATIONS {
+            client.flush();
+            self.dispatch();
+            client.dispatch();
+            if done.load(Ordering::SeqCst) {
+                return;
+            }
+        }
+        panic!("Roundtrip timed out");
+    }
+
+    /// Creates a window and waits until it is mapped
+    pub fn map_window(&mut self, client: &mut TestClient, app_id: &str) -> usize {
+        let idx = client.create_window(app_id, app_id);
+        // initial configure, then commit of the first buffer
+        self.roundtrip(client);
+        self.roundtrip(client);
+        idx
+    }
+
+    pub fn key(&mut self, key_code: u32, state: KeyState) {
+        self.send_input(HeadlessInputEvent::Key { key_code, state });
+    }
+
+    /// Presses and releases a key combination
+    pub fn press_keys(&mut self, key_codes: &[u32]) {
+        for key_code in key_codes {
+            self.key(*key_code, KeyState::Pressed);
+        }
+        for key_code in key_codes.iter().rev() {
+            self.key(*key_code, KeyState::Released);
+        }
+    }
+
+    /// Moves the pointer to a position in global logical coordinates
+    pub fn move_pointer(&mut self, x: f64, y: f64) {
+        let output = self.output();
+        let geometry = output.geometry();
+        self.send_input(HeadlessInputEvent::PointerMotionAbsolute {
+            x: (x - geometry.loc.x as f64) / geometry.size.w as f64,
+            y: (y - geometry.loc.y as f64) / geometry.size.h as f64,
+        });
+    }
+
+    pub fn button(&mut self, button: u32, state: ButtonState) {
+        self.send_input(HeadlessInputEvent::PointerButton { button, state });
+    }
+
+    pub fn click(&mut self, button: u32) {
+        self.button(button, ButtonState::Pressed);
+        self.button(button, ButtonState::Released);
+    }
+
+    pub fn scroll(&mut self, horizontal: f64, vertical: f64) {
+        self.send_input(HeadlessInputEvent::PointerAxis {
+            horizontal,
+            vertical,
+        });
+    }
+
+    /// Swipes on the touchpad with `fingers`, moving them in the given steps
+    pub fn swipe(&mut self, fingers: u32, steps: &[(f64, f64)]) {
+        self.send_input(HeadlessInputEvent::GestureSwipeBegin { fingers });
+        for (dx, dy) in steps {
+            self.send_input(HeadlessInputEvent::GestureSwipeUpdate { dx: *dx, dy: *dy });
+        }
+        self.send_input(HeadlessInputEvent::GestureSwipeEnd { cancelled: false });
+    }
+
+    fn send_input(&mut self, event: HeadlessInputEvent) {
+        self.input
+            .send(event)
+            .expect("Headless input queue disconnected");
+        self.dispatch();
+    }
+
+    pub fn seat(&self) -> Seat<State> {
+        self.data.state.common.last_active_seat().clone()
+    }
+
+    /// The output the seat is currently on
+    pub fn output(&self) -> Output {
+        self.seat().active_output()
+    }
+
+    pub fn window(&self, app_id: &str) -> CosmicMapped {
+        self.data
+            .state
+            .common
+            .shell
+            .workspaces
+            .spaces()
+            .flat_map(|space| space.mapped())
+            .find(|mapped| mapped.active_window().app_id() == app_id)
+            .cloned()
+            .unwrap_or_else(|| panic!("No mapped window with app_id {}", app_id))
+    }
+
+    pub fn window_geometry(&self, app_id: &str) -> Rectangle<i32, Logical> {
+        let mapped = self.window(app_id);
+        self.data
+            .state
+            .common
+            .shell
+            .workspaces
+            .spaces()
+            .find_map(|space| space.element_geometry(&mapped))
+            .unwrap()
+    }
+
+    /// Index of the workspace on the current output containing the window
+    pub fn workspace_of(&self, app_id: &str) -> Option<usize> {
+        let mapped = self.window(app_id);
+        self.data
+            .state
+            .common
+            .shell
+            .workspaces
+            .spaces_for_output(&self.output())
+            .position(|space| space.mapped().any(|m| m == &mapped))
+    }
+
+    pub fn active_workspace(&self) -> usize {
+        self.data
+            .state
+            .common
+            .shell
+            .workspaces
+            .active_num(&self.output())
+            .1
+    }
+
+    /// App id of the window with keyboard focus
+    pub fn focused_app_id(&self) -> Option<String> {
+        match self.seat().get_keyboard().unwrap().current_focus()? {
+            KeyboardFocusTarget::Element(mapped) => Some(mapped.active_window().app_id()),
+            _ => None,
+        }
+    }
+
+    /// App ids of the focus stack of the active workspace, most recently focused first
+    pub fn focus_stack(&self) -> Vec<String> {
+        let seat = self.seat();
+        self.data
+            .state
+            .common
+            .shell
+            .active_space(&self.output())
+            .focus_stack
+            .get(&seat)
+            .iter()
+            .map(|mapped| mapped.active_window().app_id())
+            .collect()
+    }
+}
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::TestCompositor;
+use crate::{config::StaticConfig, shell::layout::tiling::LayoutMode};
+use smithay::utils::{Logical, Point};
+
+fn constraints_config() -> StaticConfig {
+    StaticConfig {
+        tiling_enabled: true,
+        gaps: (0, 0),
+        ..Default::default()
+    }
+}
+
+fn pointer_location(compositor: &TestCompositor) -> Point<i32, Logical> {
+    compositor
+        .seat()
+        .get_pointer()
+        .unwrap()
+        .current_location()
+        .to_i32_round()
+}
+
+#[test]
+fn locked_pointer_stays_in_place() {
+    let mut compositor = TestCompositor::new(constraints_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "game");
+    compositor.move_pointer(500.0, 500.0);
+    client.lock_pointer("game");
+    compositor.roundtrip(&mut client);
+
+    compositor.move_pointer(800.0, 300.0);
+    compositor.roundtrip(&mut client);
+    assert!(client.state.pointer_constrained);
+    assert_eq!(pointer_location(&compositor), (500, 500).into());
+}
+
+#[test]
+fn confined_pointer_excludes_stack_tabs() {
+    let mut compositor = TestCompositor::new(StaticConfig {
+        tiling_layout: LayoutMode::Monocle,
+        ..constraints_config()
+    });
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.map_window(&mut client, "second");
+    compositor.roundtrip(&mut client);
+    let focused = compositor.focused_app_id().expect("No window focused");
+    assert!(compositor.window(&focused).is_stack());
+
+    compositor.move_pointer(500.0, 500.0);
+    client.confine_pointer(&focused);
+    compositor.roundtrip(&mut client);
+
+    compositor.move_pointer(600.0, 550.0);
+    compositor.roundtrip(&mut client);
+    assert!(client.state.pointer_constrained);
+    assert_eq!(pointer_location(&compositor), (600, 550).into());
+
+    // the tab bar above the window is outside of it, so the pointer stops right below it
+    compositor.move_pointer(700.0, 10.0);
+    compositor.roundtrip(&mut client);
+    assert_eq!(pointer_location(&compositor), (700, 24).into());
+}
@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::{TestClient, TestCompositor, KEY_2, KEY_SUPER};
+use crate::{
+    config::{Action, KeyModifier, KeyPattern, StaticConfig},
+    shell::WorkspaceAmount,
+};
+use smithay::input::keyboard::keysyms as KeySyms;
+
+fn lock_config() -> StaticConfig {
+    let mut config = StaticConfig {
+        tiling_enabled: true,
+        gaps: (0, 0),
+        workspace_amount: WorkspaceAmount::Static(3),
+        ..Default::default()
+    };
+    config.key_bindings.insert(
+        KeyPattern::new(KeyModifier::Super, KeySyms::KEY_2),
+        Action::Workspace(2),
+    );
+    config
+}
+
+/// Locks the session with a new client and waits until its lock surfaces are mapped
+fn lock(compositor: &mut TestCompositor) -> TestClient {
+    let mut locker = compositor.new_client();
+    locker.lock_session();
+    // lock and configure, then commit of the first buffer
+    compositor.roundtrip(&mut locker);
+    compositor.roundtrip(&mut locker);
+    locker
+}
+
+fn is_locked(compositor: &TestCompositor) -> bool {
+    compositor.data.state.common.shell.session_lock.is_some()
+}
+
+#[test]
+fn lock_surface_gets_focus() {
+    let mut compositor = TestCompositor::new(lock_config());
+    let mut client = compositor.new_client();
+    compositor.map_window(&mut client, "first");
+    compositor.roundtrip(&mut client);
+    assert!(client.has_keyboard_focus("first"));
+
+    let locker = lock(&mut compositor);
+    compositor.roundtrip(&mut client);
+
+    let lock = locker.state.lock.as_ref().unwrap();
+    assert!(lock.locked);
+    assert_eq!(lock.surfaces[0].size, (1920, 1080));
+    assert!(locker.lock_surface_has_focus());
+    assert!(client.state.keyboard_focus.is_none());
+}
+
+#[test]
+fn bindings_are_blocked_while_locked() {
+    let mut compositor = TestCompositor::new(lock_config());
+    let mut locker = lock(&mut compositor);
+
+    compositor.press_keys(&[KEY_SUPER, KEY_2]);
+    compositor.roundtrip(&mut locker);
+
+    assert_eq!(compositor.active_workspace(), 0);
+    assert!(locker.lock_surface_has_focus());
+}
+
+#[test]
+fn unlock_restores_focus() {
+    let mut compositor = TestCompositor::new(lock_config());
+    let mut client = compositor.new_client();
+    compositor.map_window(&mut client, "first");
+
+    let mut locker = lock(&mut compositor);
+    locker.unlock_session();
+    compositor.roundtrip(&mut locker);
+    compositor.roundtrip(&mut client);
+
+    assert!(!is_locked(&compositor));
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("first"));
+    assert!(client.has_keyboard_focus("first"));
+}
+
+#[test]
+fn crashed_locker_keeps_session_locked() {
+    let mut compositor = TestCompositor::new(lock_config());
+    let mut client = compositor.new_client();
+    compositor.map_window(&mut client, "first");
+
+    let locker = lock(&mut compositor);
+    std::mem::drop(locker);
+    compositor.dispatch();
+    compositor.roundtrip(&mut client);
+
+    assert!(is_locked(&compositor));
+    assert!(client.state.keyboard_focus.is_none());
+
+    // a new locker may take over
+    let locker = lock(&mut compositor);
+    assert!(locker.state.lock.as_ref().unwrap().locked);
+    assert!(locker.lock_surface_has_focus());
+}
+
+#[test]
+fn second_lock_is_denied() {
+    let mut compositor = TestCompositor::new(lock_config());
+    let _locker = lock(&mut compositor);
+
+    let second = lock(&mut compositor);
+    let lock = second.state.lock.as_ref().unwrap();
+    assert!(lock.finished);
+    assert!(!lock.locked);
+}
@@ -0,0 +1,296 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::TestCompositor;
+use crate::{
+    config::{RegexMatcher, StaticConfig, WindowRule},
+    shell::layout::tiling::LayoutMode,
+    utils::prelude::*,
+};
+use smithay::utils::Rectangle;
+
+fn tiling_config() -> StaticConfig {
+    StaticConfig {
+        tiling_enabled: true,
+        gaps: (0, 0),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn single_window_fills_output() {
+    let mut compositor = TestCompositor::new(tiling_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+
+    assert_eq!(
+        compositor.window_geometry("first"),
+        Rectangle::from_loc_and_size((0, 0), (1920, 1080))
+    );
+    assert_eq!(client.window("first").size, (1920, 1080));
+}
+
+#[test]
+fn second_window_splits_output() {
+    let mut compositor = TestCompositor::new(tiling_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.map_window(&mut client, "second");
+    compositor.roundtrip(&mut client);
+
+    assert_eq!(
+        compositor.window_geometry("first"),
+        Rectangle::from_loc_and_size((0, 0), (960, 1080))
+    );
+    assert_eq!(
+        compositor.window_geometry("second"),
+        Rectangle::from_loc_and_size((960, 0), (960, 1080))
+    );
+    assert_eq!(client.window("first").size, (960, 1080));
+    assert_eq!(client.window("second").size, (960, 1080));
+}
+
+#[test]
+fn third_window_splits_focused_window() {
+    let mut compositor = TestCompositor::new(tiling_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.map_window(&mut client, "second");
+    compositor.map_window(&mut client, "third");
+    compositor.roundtrip(&mut client);
+
+    let first = compositor.window_geometry("first");
+    let second = compositor.window_geometry("second");
+    let third = compositor.window_geometry("third");
+    assert_eq!(first, Rectangle::from_loc_and_size((0, 0), (960, 1080)));
+    // the focused window is split, leaving the first one untouched
+    assert!(second.loc.x >= 960 && third.loc.x >= 960);
+    assert!(!second.overlaps(third));
+    assert_eq!(
+        second.size.w * second.size.h + third.size.w * third.size.h,
+        960 * 1080
+    );
+}
+
+#[test]
+fn floating_without_tiling() {
+    let mut compositor = TestCompositor::new(StaticConfig::default());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "floating");
+
+    // floating windows keep the size picked by the client
+    let geometry = compositor.window_geometry("floating");
+    assert_eq!((geometry.size.w, geometry.size.h), (300, 200));
+}
+
+#[test]
+fn master_stack_layout() {
+    let mut compositor = TestCompositor::new(StaticConfig {
+        tiling_layout: LayoutMode::MasterStack,
+        ..tiling_config()
+    });
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.map_window(&mut client, "second");
+    compositor.map_window(&mut client, "third");
+    compositor.roundtrip(&mut client);
+
+    assert_eq!(
+        compositor.window_geometry("first"),
+        Rectangle::from_loc_and_size((0, 0), (1056, 1080))
+    );
+    assert_eq!(
+        compositor.window_geometry("second"),
+        Rectangle::from_loc_and_size((1056, 0), (864, 540))
+    );
+    assert_eq!(
+        compositor.window_geometry("third"),
+        Rectangle::from_loc_and_size((1056, 540), (864, 540))
+    );
+}
+
+#[test]
+fn switching_layouts_keeps_windows() {
+    let mut compositor = TestCompositor::new(tiling_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.map_window(&mut client, "second");
+    compositor.map_window(&mut client, "third");
+    compositor.roundtrip(&mut client);
+
+    let set_layout = |compositor: &mut TestCompositor, mode| {
+        let (seat, output) = (compositor.seat(), compositor.output());
+        compositor
+            .data
+            .state
+            .common
+            .shell
+            .active_space_mut(&output)
+            .set_layout_mode(mode, &seat);
+    };
+
+    // monocle puts everything into a single stack
+    set_layout(&mut compositor, LayoutMode::Monocle);
+    compositor.roundtrip(&mut client);
+    let output = compositor.output();
+    let space = compositor.data.state.common.shell.active_space(&output);
+    let mapped = space.tiling_layer.mapped().collect::<Vec<_>>();
+    assert_eq!(mapped.len(), 1);
+    assert!(mapped[0].1.is_stack());
+    assert_eq!(mapped[0].1.windows().count(), 3);
+    assert_eq!(
+        mapped[0].2,
+        Rectangle::from_loc_and_size((0, 0), (1920, 1080))
+    );
+
+    // and columns split it up again, side by side on a strip wider than the output
+    set_layout(&mut compositor, LayoutMode::Columns);
+    compositor.roundtrip(&mut client);
+    let mut columns = ["first", "second", "third"]
+        .into_iter()
+        .map(|app_id| compositor.window_geometry(app_id))
+        .collect::<Vec<_>>();
+    columns.sort_by_key(|geo| geo.loc.x);
+    let start = columns[0].loc.x;
+    assert_eq!(
+        columns,
+        vec![
+            Rectangle::from_loc_and_size((start, 0), (960, 1080)),
+            Rectangle::from_loc_and_size((start + 960, 0), (960, 1080)),
+            Rectangle::from_loc_and_size((start + 1920, 0), (960, 1080)),
+        ]
+    );
+}
+
+#[test]
+fn columns_scroll_to_focus() {
+    let mut compositor = TestCompositor::new(StaticConfig {
+        tiling_layout: LayoutMode::Columns,
+        ..tiling_config()
+    });
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.map_window(&mut client, "second");
+    compositor.roundtrip(&mut client);
+    assert_eq!(
+        compositor.window_geometry("second"),
+        Rectangle::from_loc_and_size((960, 0), (960, 1080))
+    );
+
+    // columns keep their width, the new window is scrolled into view
+    compositor.map_window(&mut client, "third");
+    compositor.roundtrip(&mut client);
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("third"));
+    assert_eq!(
+        compositor.window_geometry("first"),
+        Rectangle::from_loc_and_size((-960, 0), (960, 1080))
+    );
+    assert_eq!(
+        compositor.window_geometry("third"),
+        Rectangle::from_loc_and_size((960, 0), (960, 1080))
+    );
+    assert_eq!(client.window("first").size, (960, 1080));
+
+    // and focusing the first one scrolls back
+    let seat = compositor.seat();
+    let mapped = compositor.window("first");
+    let state = &mut compositor.data.state;
+    Common::set_focus(state, Some(&mapped.into()), &seat, None);
+    compositor.roundtrip(&mut client);
+    assert_eq!(
+        compositor.window_geometry("first"),
+        Rectangle::from_loc_and_size((0, 0), (960, 1080))
+    );
+    assert_eq!(
+        compositor.window_geometry("third"),
+        Rectangle::from_loc_and_size((1920, 0), (960, 1080))
+    );
+}
+
+#[test]
+fn saved_layout_is_restored() {
+    let mut compositor = TestCompositor::new(tiling_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.map_window(&mut client, "second");
+    compositor.map_window(&mut client, "third");
+    compositor.roundtrip(&mut client);
+
+    let geometries = ["first", "second", "third"].map(|app_id| compositor.window_geometry(app_id));
+    let state = &compositor.data.state;
+    let saved = state
+        .common
+        .shell
+        .saved_layouts(&state.common.display_handle);
+
+    // windows of a restarted session come back in a different order
+    let mut compositor = TestCompositor::new(tiling_config());
+    compositor.data.state.common.shell.restored_layouts = saved;
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "third");
+    compositor.map_window(&mut client, "first");
+    compositor.map_window(&mut client, "second");
+    compositor.roundtrip(&mut client);
+
+    assert!(compositor
+        .data
+        .state
+        .common
+        .shell
+        .restored_layouts
+        .is_empty());
+    assert_eq!(
+        ["first", "second", "third"].map(|app_id| compositor.window_geometry(app_id)),
+        geometries
+    );
+    assert_eq!(
+        geometries[0],
+        Rectangle::from_loc_and_size((0, 0), (960, 1080))
+    );
+}
+
+#[test]
+fn window_rule_reverted_after_title_change() {
+    let mut config = tiling_config();
+    config.window_rules.push(WindowRule {
+        title: Some(RegexMatcher::new("^Picture").unwrap()),
+        floating: Some(true),
+        ..Default::default()
+    });
+    let mut compositor = TestCompositor::new(config);
+    let mut client = compositor.new_client();
+    let output = compositor.output();
+
+    let idx = compositor.map_window(&mut client, "player");
+    let is_floating = |compositor: &TestCompositor| {
+        let window = compositor.window("player");
+        compositor
+            .data
+            .state
+            .common
+            .shell
+            .active_space(&output)
+            .is_floating(&window)
+    };
+    assert!(!is_floating(&compositor));
+
+    let window = &client.state.windows[idx];
+    window.toplevel.set_title("Picture-in-Picture".into());
+    window.surface.commit();
+    compositor.roundtrip(&mut client);
+    assert!(is_floating(&compositor));
+
+    let window = &client.state.windows[idx];
+    window.toplevel.set_title("player".into());
+    window.surface.commit();
+    compositor.roundtrip(&mut client);
+    assert!(!is_floating(&compositor));
+}
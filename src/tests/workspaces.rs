@@ -0,0 +1,380 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::{TestCompositor, BTN_LEFT, KEY_2, KEY_SUPER};
+use crate::{
+    config::{Action, KeyModifier, KeyPattern, StaticConfig, WorkspaceDefinition, WorkspaceMode},
+    shell::{Expose, ExposeLayout, WorkspaceAmount},
+    utils::prelude::*,
+};
+use cosmic_protocols::workspace::v1::client::{
+    zcosmic_workspace_group_handle_v1::ZcosmicWorkspaceGroupCapabilitiesV1 as GroupCapabilities,
+    zcosmic_workspace_handle_v1::ZcosmicWorkspaceCapabilitiesV1 as WorkspaceCapabilities,
+};
+use smithay::{
+    backend::input::ButtonState,
+    input::keyboard::keysyms as KeySyms,
+    utils::{Logical, Rectangle},
+};
+
+fn workspace_config() -> StaticConfig {
+    let mut config = StaticConfig {
+        tiling_enabled: true,
+        gaps: (0, 0),
+        workspace_amount: WorkspaceAmount::Static(3),
+        ..Default::default()
+    };
+    config.key_bindings.insert(
+        KeyPattern::new(KeyModifier::Super, KeySyms::KEY_2),
+        Action::MoveToWorkspace(2),
+    );
+    config.key_bindings.insert(
+        KeyPattern::new(KeyModifier::Super, KeySyms::KEY_Right),
+        Action::NextWorkspace,
+    );
+    config
+}
+
+#[test]
+fn toplevel_info_reports_workspace() {
+    let mut compositor = TestCompositor::new(workspace_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.roundtrip(&mut client);
+
+    assert_eq!(compositor.workspace_of("first"), Some(0));
+    assert_eq!(client.state.workspaces.len(), 3);
+    assert_eq!(client.active_workspace(), Some(0));
+    let toplevel = client
+        .toplevel("first")
+        .expect("No toplevel-info for window");
+    assert_eq!(toplevel.title, "first");
+    assert_eq!(client.toplevel_workspace("first"), Some(0));
+}
+
+#[test]
+fn move_to_workspace_binding() {
+    let mut compositor = TestCompositor::new(workspace_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.roundtrip(&mut client);
+
+    compositor.press_keys(&[KEY_SUPER, KEY_2]);
+    compositor.roundtrip(&mut client);
+
+    assert_eq!(compositor.workspace_of("first"), Some(1));
+    // MoveToWorkspace follows the window
+    assert_eq!(compositor.active_workspace(), 1);
+    assert_eq!(client.active_workspace(), Some(1));
+    assert_eq!(client.toplevel_workspace("first"), Some(1));
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("first"));
+}
+
+#[test]
+fn next_workspace_binding() {
+    // evdev keycode of the right arrow key
+    const KEY_RIGHT: u32 = 106;
+
+    let mut compositor = TestCompositor::new(workspace_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.roundtrip(&mut client);
+
+    compositor.press_keys(&[KEY_SUPER, KEY_RIGHT]);
+    compositor.roundtrip(&mut client);
+
+    assert_eq!(compositor.active_workspace(), 1);
+    assert_eq!(client.active_workspace(), Some(1));
+    // the window stays behind
+    assert_eq!(compositor.workspace_of("first"), Some(0));
+    assert_eq!(client.toplevel_workspace("first"), Some(0));
+}
+
+fn dynamic_config() -> StaticConfig {
+    StaticConfig {
+        workspace_amount: WorkspaceAmount::Dynamic,
+        ..workspace_config()
+    }
+}
+
+#[test]
+fn create_named_workspace() {
+    let mut compositor = TestCompositor::new(dynamic_config());
+    let mut client = compositor.new_client();
+
+    let group = &client.state.workspace_groups[0];
+    assert!(group
+        .capabilities
+        .contains(&(GroupCapabilities::CreateWorkspace as u32)));
+
+    client.create_workspace("mail");
+    compositor.roundtrip(&mut client);
+    // named workspaces are kept around while empty
+    compositor.roundtrip(&mut client);
+
+    let workspace = client.workspace("mail").expect("Workspace was not created");
+    assert!(!workspace.is_active());
+    assert!(workspace.can(WorkspaceCapabilities::Remove));
+    assert_eq!(client.workspace("1").map(|w| w.is_active()), Some(true));
+    // a new empty workspace is added behind it
+    assert_eq!(client.workspace("3").map(|w| w.is_active()), Some(false));
+}
+
+#[test]
+fn remove_workspace_moves_windows() {
+    let mut compositor = TestCompositor::new(dynamic_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.roundtrip(&mut client);
+    client.create_workspace("mail");
+    compositor.roundtrip(&mut client);
+
+    let removed = client.workspace("1").unwrap().handle.clone();
+    client.remove_workspace("1");
+    compositor.roundtrip(&mut client);
+
+    assert!(client
+        .state
+        .workspaces
+        .iter()
+        .any(|w| w.handle == removed && w.removed));
+    // the window moved to the next workspace, which became active
+    assert_eq!(compositor.workspace_of("first"), Some(0));
+    assert_eq!(compositor.active_workspace(), 0);
+    assert_eq!(client.workspace("mail").map(|w| w.is_active()), Some(true));
+    assert_eq!(
+        client.toplevel_workspace("first"),
+        client.active_workspace()
+    );
+}
+
+#[test]
+fn deactivate_workspace() {
+    let mut compositor = TestCompositor::new(workspace_config());
+    let mut client = compositor.new_client();
+
+    assert_eq!(compositor.active_workspace(), 0);
+    client.deactivate_workspace("1");
+    compositor.roundtrip(&mut client);
+
+    assert_eq!(compositor.active_workspace(), 1);
+    assert_eq!(client.workspace("2").map(|w| w.is_active()), Some(true));
+}
+
+#[test]
+fn static_workspaces_deny_remove() {
+    let mut compositor = TestCompositor::new(workspace_config());
+    let mut client = compositor.new_client();
+
+    assert!(client.state.workspace_groups[0].capabilities.is_empty());
+    let workspace = client.workspace("2").unwrap();
+    assert!(workspace.can(WorkspaceCapabilities::Activate));
+    assert!(!workspace.can(WorkspaceCapabilities::Remove));
+
+    client.remove_workspace("2");
+    client.create_workspace("mail");
+    compositor.roundtrip(&mut client);
+
+    assert!(client.workspace("2").is_some());
+    assert!(client.workspace("mail").is_none());
+    assert_eq!(
+        client
+            .state
+            .workspaces
+            .iter()
+            .filter(|w| !w.removed)
+            .count(),
+        3
+    );
+}
+
+fn declared_config() -> StaticConfig {
+    let definition = |name: &str| WorkspaceDefinition {
+        name: name.into(),
+        output: None,
+        tiling_enabled: None,
+        gaps: None,
+        orientation: None,
+        layout: None,
+    };
+    let mut config = StaticConfig {
+        workspaces: vec![
+            WorkspaceDefinition {
+                tiling_enabled: Some(false),
+                ..definition("web")
+            },
+            definition("mail"),
+        ],
+        ..dynamic_config()
+    };
+    config.key_bindings.insert(
+        KeyPattern::new(KeyModifier::Super, KeySyms::KEY_2),
+        Action::Workspace(2),
+    );
+    config
+}
+
+#[test]
+fn declared_workspaces() {
+    let mut compositor = TestCompositor::new(declared_config());
+    let mut client = compositor.new_client();
+    compositor.roundtrip(&mut client);
+
+    // declared workspaces come first and are kept while empty
+    let web = client.workspace("web").expect("Missing declared workspace");
+    assert!(web.is_active());
+    assert!(!web.can(WorkspaceCapabilities::Remove));
+    assert!(client.workspace("mail").is_some());
+    assert!(client.workspace("3").is_some());
+    let output = compositor.output();
+    assert!(
+        !compositor
+            .data
+            .state
+            .common
+            .shell
+            .active_space(&output)
+            .tiling_enabled
+    );
+
+    compositor.press_keys(&[KEY_SUPER, KEY_2]);
+    compositor.roundtrip(&mut client);
+
+    assert_eq!(compositor.active_workspace(), 1);
+    assert_eq!(client.workspace("mail").map(|w| w.is_active()), Some(true));
+    assert!(client.workspace("web").is_some());
+}
+
+#[test]
+fn workspaces_return_to_reconnected_output() {
+    let config = StaticConfig {
+        workspace_mode: WorkspaceMode::OutputBound,
+        ..dynamic_config()
+    };
+    let mut compositor = TestCompositor::with_outputs(
+        config,
+        vec![((1920, 1080).into(), 1.0), ((1920, 1080).into(), 1.0)],
+    );
+    let mut client = compositor.new_client();
+    let outputs = compositor
+        .data
+        .state
+        .common
+        .shell
+        .outputs()
+        .cloned()
+        .collect::<Vec<_>>();
+
+    compositor.seat().set_active_output(&outputs[1]);
+    compositor.map_window(&mut client, "external");
+    compositor.roundtrip(&mut client);
+    assert_eq!(compositor.workspace_of("external"), Some(0));
+
+    // unplugging moves the workspace over to the remaining output
+    compositor.disconnect_output(&outputs[1]);
+    assert_eq!(compositor.output(), outputs[0]);
+    assert!(compositor.workspace_of("external").is_some());
+
+    // and plugging it back in restores it
+    compositor.connect_output(&outputs[1]);
+    assert_eq!(compositor.workspace_of("external"), None);
+    compositor.seat().set_active_output(&outputs[1]);
+    assert_eq!(compositor.workspace_of("external"), Some(0));
+    assert_eq!(compositor.active_workspace(), 0);
+}
+
+#[test]
+fn sticky_window_follows_workspace_switch() {
+    let mut compositor = TestCompositor::new(workspace_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.map_window(&mut client, "pip");
+    compositor.roundtrip(&mut client);
+
+    let seat = compositor.seat();
+    let output = compositor.output();
+    let pip = compositor.window("pip");
+    let shell = &mut compositor.data.state.common.shell;
+    shell
+        .active_space_mut(&output)
+        .set_sticky(&pip, &seat, true);
+    compositor.roundtrip(&mut client);
+    let geometry = compositor.window_geometry("pip");
+
+    let _ = compositor.data.state.common.shell.activate(&output, 2);
+    // moved right away, so it doesn't slide out with the previous workspace
+    assert_eq!(compositor.workspace_of("pip"), Some(2));
+    compositor.roundtrip(&mut client);
+
+    assert_eq!(compositor.workspace_of("first"), Some(0));
+    assert_eq!(compositor.workspace_of("pip"), Some(2));
+    assert_eq!(compositor.window_geometry("pip"), geometry);
+    assert_eq!(client.toplevel_workspace("pip"), Some(2));
+}
+
+#[test]
+fn expose_drag_moves_window_to_workspace() {
+    let mut compositor = TestCompositor::new(workspace_config());
+    let mut client = compositor.new_client();
+
+    compositor.map_window(&mut client, "first");
+    compositor.map_window(&mut client, "second");
+    compositor.roundtrip(&mut client);
+
+    Expose::open(&mut compositor.data.state);
+    let output = compositor.output();
+    let layout = ExposeLayout::new(&compositor.data.state.common.shell, &output);
+    assert_eq!(layout.windows.len(), 2);
+    assert_eq!(layout.workspaces.len(), 3);
+    let first = compositor.window("first");
+    let window_geo = layout
+        .windows
+        .iter()
+        .find(|(mapped, _)| mapped == &first)
+        .unwrap()
+        .1;
+
+    let center = |geo: Rectangle<i32, Logical>| {
+        let loc = output.geometry().loc + geo.loc;
+        (
+            (loc.x + geo.size.w / 2) as f64,
+            (loc.y + geo.size.h / 2) as f64,
+        )
+    };
+    let (x, y) = center(window_geo);
+    compositor.move_pointer(x, y);
+    compositor.button(BTN_LEFT, ButtonState::Pressed);
+    let (x, y) = center(layout.workspaces[1]);
+    compositor.move_pointer(x, y);
+    compositor.button(BTN_LEFT, ButtonState::Released);
+    compositor.roundtrip(&mut client);
+
+    assert_eq!(compositor.workspace_of("first"), Some(1));
+    assert_eq!(compositor.active_workspace(), 0);
+    assert!(compositor.data.state.common.shell.expose.is_some());
+
+    // clicking a window focuses it and leaves the overview
+    let layout = ExposeLayout::new(&compositor.data.state.common.shell, &output);
+    let (x, y) = center(layout.windows[0].1);
+    compositor.move_pointer(x, y);
+    compositor.click(BTN_LEFT);
+    compositor.roundtrip(&mut client);
+
+    assert!(compositor.data.state.common.shell.expose.is_none());
+    assert_eq!(compositor.focused_app_id().as_deref(), Some("second"));
+    assert!(!compositor.seat().get_pointer().unwrap().is_grabbed());
+
+    // and so does clicking the background, releasing the pointer again
+    Expose::open(&mut compositor.data.state);
+    assert!(compositor.seat().get_pointer().unwrap().is_grabbed());
+    compositor.move_pointer(1.0, 1079.0);
+    compositor.click(BTN_LEFT);
+    compositor.roundtrip(&mut client);
+
+    assert!(compositor.data.state.common.shell.expose.is_none());
+    assert!(!compositor.seat().get_pointer().unwrap().is_grabbed());
+}
@@ -39,6 +39,17 @@ impl OutputExt for Output {
     }
 }
 
+/// Connector prefixes of panels built into the device
+const INTERNAL_CONNECTORS: [&str; 3] = ["eDP", "LVDS", "DSI"];
+
+/// Whether the output is a built-in panel, e.g. of a laptop
+pub fn is_internal_output(output: &Output) -> bool {
+    let name = output.name();
+    INTERNAL_CONNECTORS
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
 pub trait SeatExt {
     fn id(&self) -> usize;
 
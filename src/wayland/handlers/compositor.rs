@@ -167,6 +167,8 @@ impl CompositorHandler for State {
         X11Wm::commit_hook::<Data>(surface);
         // first load the buffer for various smithay helper functions
         on_commit_buffer_handler::<Self>(surface);
+        // pointer constraint regions and cursor hints are double-buffered
+        self.common.pointer_constraints_state.commit(surface);
 
         // then handle initial configure events and map windows if necessary
         if let Some((window, seat)) = self
@@ -224,6 +226,14 @@ impl CompositorHandler for State {
                 );
                 workspace.commit(surface);
             }
+
+            // title and app_id changes might match different window rules
+            if let Some((window, _)) = element
+                .windows()
+                .find(|(window, _)| window.wl_surface().as_ref() == Some(surface))
+            {
+                Shell::reapply_window_rules(self, &window);
+            }
         }
 
         //handle window screencopy sessions
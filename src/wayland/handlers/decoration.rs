@@ -71,29 +71,41 @@ impl State {
             }
             KdeMode::Server
         } else {
-            if let Some((CosmicSurface::Wayland(window), _)) = mapped
+            let mut mode = XdgMode::ClientSide;
+            if let Some((window, _)) = mapped
                 .windows()
                 .find(|(window, _)| window.wl_surface().as_ref() == Some(surface))
             {
-                window
-                    .toplevel()
-                    .with_pending_state(|state| state.decoration_mode = Some(XdgMode::ClientSide));
-                window.toplevel().send_configure();
+                if let Some(forced) = window.forced_decorations() {
+                    mode = forced.into();
+                }
+                if let CosmicSurface::Wayland(window) = window {
+                    window
+                        .toplevel()
+                        .with_pending_state(|state| state.decoration_mode = Some(mode));
+                    window.toplevel().send_configure();
+                }
+            }
+            match mode {
+                XdgMode::ServerSide => KdeMode::Server,
+                _ => KdeMode::Client,
             }
-            KdeMode::Client
         }
     }
 
     pub fn request_mode(mapped: &CosmicMapped, surface: &WlSurface, mode: XdgMode) {
-        if let Some((CosmicSurface::Wayland(window), _)) = mapped
+        if let Some((window, _)) = mapped
             .windows()
             .find(|(window, _)| window.wl_surface().as_ref() == Some(surface))
         {
-            PreferredDecorationMode::update(&window, Some(mode));
-            window.toplevel().with_pending_state(|state| {
-                state.decoration_mode = Some(mode);
-            });
-            window.toplevel().send_configure();
+            let forced = window.forced_decorations();
+            if let CosmicSurface::Wayland(window) = window {
+                PreferredDecorationMode::update(&window, Some(mode));
+                window.toplevel().with_pending_state(|state| {
+                    state.decoration_mode = Some(forced.map(Into::into).unwrap_or(mode));
+                });
+                window.toplevel().send_configure();
+            }
         }
     }
 
@@ -32,6 +32,13 @@ impl DmabufHandler for State {
                 .import_dmabuf(&dmabuf, None)
                 .map(|_| ())
                 .map_err(|_| ImportError::Failed),
+            BackendData::Headless(ref mut state) => state
+                .renderer
+                .as_mut()
+                .ok_or(ImportError::Failed)?
+                .import_dmabuf(&dmabuf, None)
+                .map(|_| ())
+                .map_err(|_| ImportError::Failed),
             _ => unreachable!("No backend set when importing dmabuf"),
         }
     }
@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use crate::{
+    state::State,
+    wayland::protocols::idle_inhibit::{
+        delegate_idle_inhibit, IdleInhibitHandler, IdleInhibitManagerState,
+    },
+};
+
+impl IdleInhibitHandler for State {
+    fn idle_inhibit_state(&mut self) -> &mut IdleInhibitManagerState {
+        &mut self.common.idle_inhibit_state
+    }
+}
+
+delegate_idle_inhibit!(State);
@@ -6,16 +6,22 @@ pub mod data_device;
 pub mod decoration;
 pub mod dmabuf;
 pub mod fractional_scale;
+pub mod idle_inhibit;
+pub mod idle_notify;
 pub mod keyboard_shortcuts_inhibit;
 pub mod layer_shell;
 pub mod output;
 pub mod output_configuration;
+pub mod pointer_constraints;
+pub mod pointer_gestures;
 pub mod presentation;
 pub mod primary_selection;
 pub mod relative_pointer;
 pub mod screencopy;
 pub mod seat;
+pub mod session_lock;
 pub mod shm;
+pub mod tablet_manager;
 pub mod toplevel_info;
 pub mod toplevel_management;
 pub mod viewporter;
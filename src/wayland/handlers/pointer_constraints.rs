@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use crate::{
+    state::State,
+    wayland::protocols::pointer_constraints::{
+        delegate_pointer_constraints, PointerConstraintsHandler, PointerConstraintsState,
+    },
+};
+
+impl PointerConstraintsHandler for State {
+    fn pointer_constraints_state(&mut self) -> &mut PointerConstraintsState {
+        &mut self.common.pointer_constraints_state
+    }
+}
+
+delegate_pointer_constraints!(State);
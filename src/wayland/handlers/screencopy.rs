@@ -192,6 +192,10 @@ impl ScreencopyHandler for State {
             }
             BackendData::Winit(ref mut winit) => winit.backend.renderer(),
             BackendData::X11(ref mut x11) => &mut x11.renderer,
+            BackendData::Headless(ref mut headless) => match headless.renderer.as_mut() {
+                Some(renderer) => renderer,
+                None => return Vec::new(),
+            },
             _ => unreachable!(),
         };
 
@@ -459,6 +463,10 @@ fn formats_for_output(
         }
         BackendData::Winit(ref mut winit) => winit.backend.renderer(),
         BackendData::X11(ref mut x11) => &mut x11.renderer,
+        BackendData::Headless(ref mut headless) => match headless.renderer.as_mut() {
+            Some(renderer) => renderer,
+            None => return Err(FailureReason::Unspec),
+        },
         _ => unreachable!(),
     };
 
@@ -757,6 +765,23 @@ pub fn render_output_to_buffer(
             },
         )
         .map_err(|err| (FailureReason::Unspec, err.into())),
+        BackendData::Headless(headless) => match headless.renderer.as_mut() {
+            Some(renderer) => render_session::<_, _>(
+                node,
+                renderer,
+                session,
+                &params,
+                output.current_transform(),
+                |node, buffer, renderer, dt, age| {
+                    render_fn(node, buffer, renderer, dt, age, common, session, output)
+                },
+            )
+            .map_err(|err| (FailureReason::Unspec, err.into())),
+            None => Err((
+                FailureReason::Unspec,
+                anyhow!("Headless backend is not rendering"),
+            )),
+        },
         _ => unreachable!(),
     }
 }
@@ -768,6 +793,10 @@ pub fn render_workspace_to_buffer(
     output: &Output,
     handle: (WorkspaceHandle, usize),
 ) -> Result<bool, (FailureReason, anyhow::Error)> {
+    if state.common.shell.session_lock.is_some() {
+        return Err((FailureReason::Unspec, anyhow!("Session is locked")));
+    }
+
     let mode = output
         .current_mode()
         .map(|mode| mode.size.to_logical(1).to_buffer(1, Transform::Normal));
@@ -900,6 +929,25 @@ pub fn render_workspace_to_buffer(
             },
         )
         .map_err(|err| (FailureReason::Unspec, err.into())),
+        BackendData::Headless(headless) => match headless.renderer.as_mut() {
+            Some(renderer) => render_session::<_, _>(
+                node,
+                renderer,
+                session,
+                &params,
+                output.current_transform(),
+                |node, buffer, renderer, dt, age| {
+                    render_fn(
+                        node, buffer, renderer, dt, age, common, session, output, handle,
+                    )
+                },
+            )
+            .map_err(|err| (FailureReason::Unspec, err.into())),
+            None => Err((
+                FailureReason::Unspec,
+                anyhow!("Headless backend is not rendering"),
+            )),
+        },
         _ => unreachable!(),
     }
 }
@@ -919,6 +967,10 @@ pub fn render_window_to_buffer(
     #[cfg(feature = "debug")]
     puffin::profile_function!();
 
+    if state.common.shell.session_lock.is_some() {
+        return Err((FailureReason::Unspec, anyhow!("Session is locked")));
+    }
+
     let geometry = window.geometry();
     let buffer_size = buffer_dimensions(&params.buffer).unwrap();
     if buffer_size != geometry.size.to_buffer(1, Transform::Normal) {
@@ -1061,6 +1113,23 @@ pub fn render_window_to_buffer(
             },
         )
         .map_err(|err| (FailureReason::Unspec, err.into())),
+        BackendData::Headless(headless) => match headless.renderer.as_mut() {
+            Some(renderer) => render_session::<_, _>(
+                node,
+                renderer,
+                session,
+                &params,
+                Transform::Normal,
+                |_node, buffer, renderer, dt, age| {
+                    render_fn(buffer, renderer, dt, age, session, common, window, geometry)
+                },
+            )
+            .map_err(|err| (FailureReason::Unspec, err.into())),
+            None => Err((
+                FailureReason::Unspec,
+                anyhow!("Headless backend is not rendering"),
+            )),
+        },
         _ => unreachable!(),
     }
 }
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use crate::{
+    shell::SessionLock,
+    utils::prelude::*,
+    wayland::protocols::session_lock::{
+        delegate_session_lock, LockSurface, SessionLockHandler, SessionLockManagerState,
+        SessionLocker,
+    },
+};
+use smithay::{
+    backend::renderer::element::Id, input::pointer::MotionEvent, output::Output,
+    utils::SERIAL_COUNTER,
+};
+use std::{collections::HashMap, time::Duration};
+use tracing::{info, warn};
+
+impl SessionLockHandler for State {
+    fn session_lock_state(&mut self) -> &mut SessionLockManagerState {
+        &mut self.common.shell.session_lock_manager_state
+    }
+
+    fn lock(&mut self, locker: SessionLocker) {
+        match self.common.shell.session_lock.as_mut() {
+            // only one client may hold the lock
+            Some(session_lock) if session_lock.locker.alive() => {
+                warn!("Session is already locked, denying new lock");
+                locker.finished();
+                return;
+            }
+            // the previous locker died, let the new one take over, the outputs are locked already
+            Some(session_lock) => {
+                session_lock.locker = locker.clone();
+                session_lock.surfaces.clear();
+                session_lock.pending_outputs.clear();
+                locker.lock();
+            }
+            None => {
+                info!("Locking session");
+                self.common.shell.session_lock = Some(SessionLock {
+                    locker: locker.clone(),
+                    surfaces: HashMap::new(),
+                    backdrop_id: Id::new(),
+                    pending_outputs: self.common.shell.outputs().cloned().collect(),
+                });
+            }
+        }
+
+        // stop any interaction with the rest of the session
+        self.common.shell.switcher = None;
+        self.common.shell.expose = None;
+        let serial = SERIAL_COUNTER.next_serial();
+        let time = Duration::from(self.common.clock.now()).as_millis() as u32;
+        for seat in self.common.seats().cloned().collect::<Vec<_>>() {
+            if let Some(pointer) = seat.get_pointer() {
+                pointer.unset_grab(self, serial, time);
+                // keyboard focus is moved by `Common::refresh_focus`, but the pointer only moves on input
+                let location = pointer.current_location();
+                pointer.motion(
+                    self,
+                    None,
+                    &MotionEvent {
+                        location,
+                        serial,
+                        time,
+                    },
+                );
+            }
+            if let Some(keyboard) = seat.get_keyboard() {
+                keyboard.unset_grab();
+            }
+        }
+
+        // nothing but the lock surfaces get rendered from here on,
+        // the locker is told once every output presented such a frame
+        let outputs = self.common.shell.outputs().cloned().collect::<Vec<_>>();
+        if outputs.is_empty() {
+            locker.lock();
+        }
+        for output in outputs {
+            self.backend
+                .schedule_render(&self.common.event_loop_handle, &output, None);
+        }
+    }
+
+    fn unlock(&mut self, locker: SessionLocker) {
+        if self
+            .common
+            .shell
+            .session_lock
+            .as_ref()
+            .map_or(true, |session_lock| session_lock.locker != locker)
+        {
+            warn!("Ignoring unlock of a lock, that doesn't hold the session");
+            return;
+        }
+        info!("Unlocking session");
+        self.common.shell.session_lock = None;
+        for output in self.common.shell.outputs().cloned().collect::<Vec<_>>() {
+            self.backend
+                .schedule_render(&self.common.event_loop_handle, &output, None);
+        }
+    }
+
+    fn new_surface(&mut self, surface: LockSurface, output: Output) {
+        if let Some(session_lock) = self.common.shell.session_lock.as_mut() {
+            if !self.common.shell.outputs.contains(&output) {
+                return;
+            }
+            surface.send_configure(output.geometry().size);
+            session_lock.surfaces.insert(output, surface);
+        }
+    }
+}
+
+delegate_session_lock!(State);
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::sync::atomic::Ordering;
+
+use crate::state::{ClientState, State};
+use smithay::{
+    reexports::{
+        wayland_protocols::wp::tablet::zv2::server::{
+            zwp_tablet_manager_v2::ZwpTabletManagerV2, zwp_tablet_seat_v2::ZwpTabletSeatV2,
+            zwp_tablet_tool_v2::ZwpTabletToolV2, zwp_tablet_v2::ZwpTabletV2,
+        },
+        wayland_server::{delegate_dispatch, Client, DataInit, DisplayHandle, GlobalDispatch, New},
+    },
+    wayland::tablet_manager::{
+        TabletManagerState, TabletSeatUserData, TabletToolUserData, TabletUserData,
+    },
+};
+
+// like `delegate_tablet_manager!`, but remembering which clients handle tablets themselves,
+// so the others can be driven by the pointer instead
+impl GlobalDispatch<ZwpTabletManagerV2, ()> for State {
+    fn bind(
+        state: &mut State,
+        dh: &DisplayHandle,
+        client: &Client,
+        resource: New<ZwpTabletManagerV2>,
+        global_data: &(),
+        data_init: &mut DataInit<'_, State>,
+    ) {
+        if let Some(client_state) = client.get_data::<ClientState>() {
+            client_state.tablet_bound.store(true, Ordering::SeqCst);
+        }
+        <TabletManagerState as GlobalDispatch<ZwpTabletManagerV2, (), State>>::bind(
+            state,
+            dh,
+            client,
+            resource,
+            global_data,
+            data_init,
+        )
+    }
+}
+
+delegate_dispatch!(State: [ZwpTabletManagerV2: ()] => TabletManagerState);
+delegate_dispatch!(State: [ZwpTabletSeatV2: TabletSeatUserData] => TabletManagerState);
+delegate_dispatch!(State: [ZwpTabletV2: TabletUserData] => TabletManagerState);
+delegate_dispatch!(State: [ZwpTabletToolV2: TabletToolUserData] => TabletManagerState);
@@ -43,7 +43,7 @@ impl Window for CosmicSurface {
     }
 
     fn is_minimized(&self) -> bool {
-        false // TODO
+        CosmicSurface::is_minimized(self)
     }
 
     fn user_data(&self) -> &UserDataMap {
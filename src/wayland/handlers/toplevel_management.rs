@@ -3,7 +3,7 @@
 use smithay::{input::Seat, reexports::wayland_server::DisplayHandle};
 
 use crate::{
-    shell::CosmicSurface,
+    shell::{CosmicSurface, Shell},
     utils::prelude::*,
     wayland::protocols::{
         toplevel_info::ToplevelInfoHandler,
@@ -25,39 +25,48 @@ impl ToplevelManagementHandler for State {
         window: &<Self as ToplevelInfoHandler>::Window,
         seat: Option<Seat<Self>>,
     ) {
-        for output in self
-            .common
-            .shell
-            .outputs()
-            .cloned()
-            .collect::<Vec<_>>()
-            .iter()
-        {
-            let maybe = self
-                .common
-                .shell
-                .workspaces
-                .spaces_for_output(output)
-                .enumerate()
-                .find(|(_, w)| w.windows().any(|w| &w == window));
-            if let Some((idx, workspace)) = maybe {
-                let seat = seat.unwrap_or(self.common.last_active_seat().clone());
-                let mapped = workspace
-                    .mapped()
-                    .find(|m| m.windows().any(|(w, _)| &w == window))
-                    .unwrap()
-                    .clone();
+        let seat = seat.unwrap_or(self.common.last_active_seat().clone());
+        Shell::activate_window(self, window, &seat);
+    }
+
+    fn close(&mut self, _dh: &DisplayHandle, window: &<Self as ToplevelInfoHandler>::Window) {
+        window.close();
+    }
 
-                let _ = self.common.shell.activate(&output, idx as usize); // TODO: Move pointer?
-                mapped.focus_window(window);
-                Common::set_focus(self, Some(&mapped.clone().into()), &seat, None);
-                return;
+    fn minimize(&mut self, _dh: &DisplayHandle, window: &<Self as ToplevelInfoHandler>::Window) {
+        self.common.shell.minimize(window);
+    }
+
+    fn unminimize(&mut self, dh: &DisplayHandle, window: &<Self as ToplevelInfoHandler>::Window) {
+        self.activate(dh, window, None);
+    }
+
+    fn set_sticky(
+        &mut self,
+        _dh: &DisplayHandle,
+        window: &<Self as ToplevelInfoHandler>::Window,
+        sticky: bool,
+    ) {
+        let seat = self.common.last_active_seat().clone();
+        if let Some(mapped) = self.common.shell.element_for_surface(window).cloned() {
+            if let Some(workspace) = self.common.shell.space_for_mut(&mapped) {
+                workspace.set_sticky(&mapped, &seat, sticky);
             }
         }
     }
 
-    fn close(&mut self, _dh: &DisplayHandle, window: &<Self as ToplevelInfoHandler>::Window) {
-        window.close();
+    fn set_always_on_top(
+        &mut self,
+        _dh: &DisplayHandle,
+        window: &<Self as ToplevelInfoHandler>::Window,
+        always_on_top: bool,
+    ) {
+        let seat = self.common.last_active_seat().clone();
+        if let Some(mapped) = self.common.shell.element_for_surface(window).cloned() {
+            if let Some(workspace) = self.common.shell.space_for_mut(&mapped) {
+                workspace.set_always_on_top(&mapped, &seat, always_on_top);
+            }
+        }
     }
 }
 
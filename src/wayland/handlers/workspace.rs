@@ -10,6 +10,7 @@ use crate::{
     },
 };
 use smithay::reexports::wayland_server::DisplayHandle;
+use tracing::debug;
 
 impl WorkspaceClientHandler for ClientState {
     fn workspace_state(&self) -> &WorkspaceClientState {
@@ -48,7 +49,19 @@ impl WorkspaceHandler for State {
                         let _ = self.common.shell.activate(&output, idx); // TODO: move cursor?
                     }
                 }
-                _ => {}
+                Request::Deactivate(handle) => {
+                    self.common.shell.deactivate_workspace(&handle);
+                }
+                Request::Remove(handle) => {
+                    if !self.common.shell.remove_workspace(&handle) {
+                        debug!(?handle, "Denied removing workspace");
+                    }
+                }
+                Request::Create { in_group, name } => {
+                    if !self.common.shell.create_workspace(&in_group, name) {
+                        debug!(group = ?in_group, "Denied creating workspace");
+                    }
+                }
             }
         }
     }
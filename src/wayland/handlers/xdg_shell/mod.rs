@@ -228,6 +228,21 @@ impl XdgShellHandler for State {
         }
     }
 
+    fn minimize_request(&mut self, surface: ToplevelSurface) {
+        let window = self
+            .common
+            .shell
+            .element_for_wl_surface(surface.wl_surface())
+            .and_then(|mapped| {
+                mapped
+                    .windows()
+                    .find(|(w, _)| w.wl_surface().as_ref() == Some(surface.wl_surface()))
+            });
+        if let Some((window, _)) = window {
+            self.common.shell.minimize(&window);
+        }
+    }
+
     fn toplevel_destroyed(&mut self, surface: ToplevelSurface) {
         let outputs = self
             .common
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use smithay::reexports::{
+    wayland_protocols::wp::idle_inhibit::zv1::server::{
+        zwp_idle_inhibit_manager_v1::{self, ZwpIdleInhibitManagerV1},
+        zwp_idle_inhibitor_v1::{self, ZwpIdleInhibitorV1},
+    },
+    wayland_server::{
+        backend::{ClientId, GlobalId, ObjectId},
+        protocol::wl_surface::WlSurface,
+        Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+    },
+};
+
+pub struct IdleInhibitManagerState {
+    global: GlobalId,
+    inhibitors: Vec<(ZwpIdleInhibitorV1, WlSurface)>,
+}
+
+pub trait IdleInhibitHandler {
+    fn idle_inhibit_state(&mut self) -> &mut IdleInhibitManagerState;
+}
+
+impl IdleInhibitManagerState {
+    pub fn new<D>(dh: &DisplayHandle) -> IdleInhibitManagerState
+    where
+        D: GlobalDispatch<ZwpIdleInhibitManagerV1, ()>
+            + Dispatch<ZwpIdleInhibitManagerV1, ()>
+            + Dispatch<ZwpIdleInhibitorV1, ()>
+            + IdleInhibitHandler
+            + 'static,
+    {
+        let global = dh.create_global::<D, ZwpIdleInhibitManagerV1, _>(1, ());
+        IdleInhibitManagerState {
+            global,
+            inhibitors: Vec::new(),
+        }
+    }
+
+    /// Surfaces that want to inhibit idling, if they are visible
+    pub fn inhibiting_surfaces(&self) -> impl Iterator<Item = &WlSurface> {
+        self.inhibitors
+            .iter()
+            .map(|(_, surface)| surface)
+            .filter(|surface| surface.is_alive())
+    }
+
+    pub fn global_id(&self) -> GlobalId {
+        self.global.clone()
+    }
+}
+
+impl<D> GlobalDispatch<ZwpIdleInhibitManagerV1, (), D> for IdleInhibitManagerState
+where
+    D: GlobalDispatch<ZwpIdleInhibitManagerV1, ()>
+        + Dispatch<ZwpIdleInhibitManagerV1, ()>
+        + Dispatch<ZwpIdleInhibitorV1, ()>
+        + IdleInhibitHandler
+        + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _dh: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwpIdleInhibitManagerV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl<D> Dispatch<ZwpIdleInhibitManagerV1, (), D> for IdleInhibitManagerState
+where
+    D: GlobalDispatch<ZwpIdleInhibitManagerV1, ()>
+        + Dispatch<ZwpIdleInhibitManagerV1, ()>
+        + Dispatch<ZwpIdleInhibitorV1, ()>
+        + IdleInhibitHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _obj: &ZwpIdleInhibitManagerV1,
+        request: zwp_idle_inhibit_manager_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_idle_inhibit_manager_v1::Request::CreateInhibitor { id, surface } => {
+                let inhibitor = data_init.init(id, ());
+                state
+                    .idle_inhibit_state()
+                    .inhibitors
+                    .push((inhibitor, surface));
+            }
+            zwp_idle_inhibit_manager_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ZwpIdleInhibitorV1, (), D> for IdleInhibitManagerState
+where
+    D: GlobalDispatch<ZwpIdleInhibitManagerV1, ()>
+        + Dispatch<ZwpIdleInhibitManagerV1, ()>
+        + Dispatch<ZwpIdleInhibitorV1, ()>
+        + IdleInhibitHandler
+        + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _obj: &ZwpIdleInhibitorV1,
+        request: zwp_idle_inhibitor_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_idle_inhibitor_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, resource: ObjectId, _data: &()) {
+        state
+            .idle_inhibit_state()
+            .inhibitors
+            .retain(|(inhibitor, _)| inhibitor.id() != resource);
+    }
+}
+
+macro_rules! delegate_idle_inhibit {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::idle_inhibit::zv1::server::zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1: ()
+        ] => $crate::wayland::protocols::idle_inhibit::IdleInhibitManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::idle_inhibit::zv1::server::zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1: ()
+        ] => $crate::wayland::protocols::idle_inhibit::IdleInhibitManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::idle_inhibit::zv1::server::zwp_idle_inhibitor_v1::ZwpIdleInhibitorV1: ()
+        ] => $crate::wayland::protocols::idle_inhibit::IdleInhibitManagerState);
+    };
+}
+pub(crate) use delegate_idle_inhibit;
@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::time::Duration;
+
+use smithay::{
+    input::{Seat, SeatHandler},
+    reexports::{
+        wayland_protocols::ext::idle_notify::v1::server::{
+            ext_idle_notification_v1::{self, ExtIdleNotificationV1},
+            ext_idle_notifier_v1::{self, ExtIdleNotifierV1},
+        },
+        wayland_server::{
+            backend::{ClientId, GlobalId, ObjectId},
+            Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+        },
+    },
+};
+
+pub struct IdleNotifierState<D: SeatHandler> {
+    global: GlobalId,
+    notifications: Vec<IdleNotification<D>>,
+    inhibited: bool,
+}
+
+struct IdleNotification<D: SeatHandler> {
+    resource: ExtIdleNotificationV1,
+    seat: Seat<D>,
+    timeout: Duration,
+    idle: bool,
+}
+
+pub trait IdleNotifierHandler: SeatHandler + Sized {
+    fn idle_notifier_state(&mut self) -> &mut IdleNotifierState<Self>;
+}
+
+impl<D> IdleNotifierState<D>
+where
+    D: GlobalDispatch<ExtIdleNotifierV1, ()>
+        + Dispatch<ExtIdleNotifierV1, ()>
+        + Dispatch<ExtIdleNotificationV1, ()>
+        + IdleNotifierHandler
+        + 'static,
+{
+    pub fn new(dh: &DisplayHandle) -> IdleNotifierState<D> {
+        let global = dh.create_global::<D, ExtIdleNotifierV1, _>(1, ());
+        IdleNotifierState {
+            global,
+            notifications: Vec::new(),
+            inhibited: false,
+        }
+    }
+
+    /// The seat received input, resumes all of its idle notifications
+    pub fn notify_activity(&mut self, seat: &Seat<D>) {
+        for notification in self
+            .notifications
+            .iter_mut()
+            .filter(|n| n.idle && &n.seat == seat)
+        {
+            notification.resource.resumed();
+            notification.idle = false;
+        }
+    }
+
+    /// While inhibited no notification becomes idle.
+    ///
+    /// Notifications already idle stay so, as inhibiting is no user activity.
+    pub fn set_inhibited(&mut self, inhibited: bool) {
+        self.inhibited = inhibited;
+    }
+
+    pub fn is_inhibited(&self) -> bool {
+        self.inhibited
+    }
+
+    /// Sends out idle events given how long every seat has been idle.
+    ///
+    /// Returns the time until the next notification fires, if any.
+    pub fn refresh(&mut self, idle_time: impl Fn(&Seat<D>) -> Duration) -> Option<Duration> {
+        if self.inhibited {
+            return None;
+        }
+
+        let mut next = None;
+        for notification in self.notifications.iter_mut().filter(|n| !n.idle) {
+            let idle_time = idle_time(&notification.seat);
+            if idle_time >= notification.timeout {
+                notification.resource.idled();
+                notification.idle = true;
+            } else {
+                let remaining = notification.timeout - idle_time;
+                next = Some(next.map_or(remaining, |next: Duration| next.min(remaining)));
+            }
+        }
+        next
+    }
+
+    pub fn global_id(&self) -> GlobalId {
+        self.global.clone()
+    }
+}
+
+impl<D> GlobalDispatch<ExtIdleNotifierV1, (), D> for IdleNotifierState<D>
+where
+    D: GlobalDispatch<ExtIdleNotifierV1, ()>
+        + Dispatch<ExtIdleNotifierV1, ()>
+        + Dispatch<ExtIdleNotificationV1, ()>
+        + IdleNotifierHandler
+        + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _dh: &DisplayHandle,
+        _client: &Client,
+        resource: New<ExtIdleNotifierV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl<D> Dispatch<ExtIdleNotifierV1, (), D> for IdleNotifierState<D>
+where
+    D: GlobalDispatch<ExtIdleNotifierV1, ()>
+        + Dispatch<ExtIdleNotifierV1, ()>
+        + Dispatch<ExtIdleNotificationV1, ()>
+        + IdleNotifierHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _obj: &ExtIdleNotifierV1,
+        request: ext_idle_notifier_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_idle_notifier_v1::Request::GetIdleNotification { id, timeout, seat } => {
+                let resource = data_init.init(id, ());
+                // the seat might already be gone, in which case it never becomes idle
+                if let Some(seat) = Seat::<D>::from_resource(&seat) {
+                    state
+                        .idle_notifier_state()
+                        .notifications
+                        .push(IdleNotification {
+                            resource,
+                            seat,
+                            timeout: Duration::from_millis(timeout as u64),
+                            idle: false,
+                        });
+                }
+            }
+            ext_idle_notifier_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ExtIdleNotificationV1, (), D> for IdleNotifierState<D>
+where
+    D: GlobalDispatch<ExtIdleNotifierV1, ()>
+        + Dispatch<ExtIdleNotifierV1, ()>
+        + Dispatch<ExtIdleNotificationV1, ()>
+        + IdleNotifierHandler
+        + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _obj: &ExtIdleNotificationV1,
+        request: ext_idle_notification_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_idle_notification_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, resource: ObjectId, _data: &()) {
+        state
+            .idle_notifier_state()
+            .notifications
+            .retain(|n| n.resource.id() != resource);
+    }
+}
+
+macro_rules! delegate_idle_notify {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::idle_notify::v1::server::ext_idle_notifier_v1::ExtIdleNotifierV1: ()
+        ] => $crate::wayland::protocols::idle_notify::IdleNotifierState<Self>);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::idle_notify::v1::server::ext_idle_notifier_v1::ExtIdleNotifierV1: ()
+        ] => $crate::wayland::protocols::idle_notify::IdleNotifierState<Self>);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::idle_notify::v1::server::ext_idle_notification_v1::ExtIdleNotificationV1: ()
+        ] => $crate::wayland::protocols::idle_notify::IdleNotifierState<Self>);
+    };
+}
+pub(crate) use delegate_idle_notify;
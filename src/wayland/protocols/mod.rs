@@ -2,8 +2,12 @@
 
 pub mod drm;
 //pub mod export_dmabuf;
+pub mod idle_inhibit;
+pub mod idle_notify;
 pub mod output_configuration;
+pub mod pointer_constraints;
 pub mod screencopy;
+pub mod session_lock;
 pub mod toplevel_info;
 pub mod toplevel_management;
 pub mod workspace;
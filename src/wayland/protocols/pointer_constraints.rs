@@ -0,0 +1,405 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use smithay::{
+    reexports::{
+        wayland_protocols::wp::pointer_constraints::zv1::server::{
+            zwp_confined_pointer_v1::{self, ZwpConfinedPointerV1},
+            zwp_locked_pointer_v1::{self, ZwpLockedPointerV1},
+            zwp_pointer_constraints_v1::{self, Lifetime, ZwpPointerConstraintsV1},
+        },
+        wayland_server::{
+            backend::{ClientId, GlobalId, ObjectId},
+            protocol::{wl_pointer::WlPointer, wl_region::WlRegion, wl_surface::WlSurface},
+            Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource, WEnum,
+        },
+    },
+    utils::{Logical, Point},
+    wayland::compositor::{get_region_attributes, RegionAttributes},
+};
+
+pub struct PointerConstraintsState {
+    global: GlobalId,
+    constraints: Vec<PointerConstraint>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ConstraintObject {
+    Locked(ZwpLockedPointerV1),
+    Confined(ZwpConfinedPointerV1),
+}
+
+impl ConstraintObject {
+    fn id(&self) -> ObjectId {
+        match self {
+            ConstraintObject::Locked(locked) => locked.id(),
+            ConstraintObject::Confined(confined) => confined.id(),
+        }
+    }
+}
+
+/// Lock or confinement of the pointer requested by a client
+#[derive(Debug)]
+pub struct PointerConstraint {
+    object: ConstraintObject,
+    surface: WlSurface,
+    pointer: WlPointer,
+    oneshot: bool,
+    region: Option<RegionAttributes>,
+    pending_region: Option<Option<RegionAttributes>>,
+    cursor_hint: Option<Point<f64, Logical>>,
+    pending_cursor_hint: Option<Point<f64, Logical>>,
+    active: bool,
+    /// Oneshot constraints are never activated again, once deactivated
+    defunct: bool,
+}
+
+impl PointerConstraint {
+    pub fn surface(&self) -> &WlSurface {
+        &self.surface
+    }
+
+    pub fn is_locked(&self) -> bool {
+        matches!(self.object, ConstraintObject::Locked(_))
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Surface-local region the constraint applies in, `None` for the whole surface
+    pub fn region(&self) -> Option<&RegionAttributes> {
+        self.region.as_ref()
+    }
+
+    /// Surface-local position the cursor should be moved to, when a lock ends
+    pub fn cursor_hint(&self) -> Option<Point<f64, Logical>> {
+        self.cursor_hint
+    }
+}
+
+pub trait PointerConstraintsHandler {
+    fn pointer_constraints_state(&mut self) -> &mut PointerConstraintsState;
+}
+
+impl PointerConstraintsState {
+    pub fn new<D>(dh: &DisplayHandle) -> PointerConstraintsState
+    where
+        D: GlobalDispatch<ZwpPointerConstraintsV1, ()>
+            + Dispatch<ZwpPointerConstraintsV1, ()>
+            + Dispatch<ZwpLockedPointerV1, ()>
+            + Dispatch<ZwpConfinedPointerV1, ()>
+            + PointerConstraintsHandler
+            + 'static,
+    {
+        let global = dh.create_global::<D, ZwpPointerConstraintsV1, _>(1, ());
+        PointerConstraintsState {
+            global,
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Constraint of `surface`, that may be activated
+    pub fn constraint(&self, surface: &WlSurface) -> Option<&PointerConstraint> {
+        self.constraints
+            .iter()
+            .find(|constraint| &constraint.surface == surface && !constraint.defunct)
+    }
+
+    /// Whether any surface has a constraint, that may be activated
+    pub fn has_constraints(&self) -> bool {
+        self.constraints
+            .iter()
+            .any(|constraint| !constraint.defunct)
+    }
+
+    pub fn active_constraints(&self) -> impl Iterator<Item = &PointerConstraint> {
+        self.constraints
+            .iter()
+            .filter(|constraint| constraint.active)
+    }
+
+    pub fn activate(&mut self, surface: &WlSurface) {
+        let Some(constraint) = self
+            .constraints
+            .iter_mut()
+            .find(|constraint| &constraint.surface == surface && !constraint.defunct)
+        else {
+            return;
+        };
+        if constraint.active {
+            return;
+        }
+        constraint.active = true;
+        match &constraint.object {
+            ConstraintObject::Locked(locked) => locked.locked(),
+            ConstraintObject::Confined(confined) => confined.confined(),
+        }
+    }
+
+    pub fn deactivate(&mut self, surface: &WlSurface) {
+        let Some(constraint) = self
+            .constraints
+            .iter_mut()
+            .find(|constraint| &constraint.surface == surface && constraint.active)
+        else {
+            return;
+        };
+        constraint.active = false;
+        constraint.defunct = constraint.oneshot;
+        match &constraint.object {
+            ConstraintObject::Locked(locked) => locked.unlocked(),
+            ConstraintObject::Confined(confined) => confined.unconfined(),
+        }
+    }
+
+    /// Applies the double-buffered state of the constraints of `surface`
+    pub fn commit(&mut self, surface: &WlSurface) {
+        for constraint in self
+            .constraints
+            .iter_mut()
+            .filter(|constraint| &constraint.surface == surface)
+        {
+            if let Some(region) = constraint.pending_region.take() {
+                constraint.region = region;
+            }
+            if let Some(hint) = constraint.pending_cursor_hint.take() {
+                constraint.cursor_hint = Some(hint);
+            }
+        }
+    }
+
+    pub fn global_id(&self) -> GlobalId {
+        self.global.clone()
+    }
+
+    fn add(
+        &mut self,
+        manager: &ZwpPointerConstraintsV1,
+        object: ConstraintObject,
+        surface: WlSurface,
+        pointer: WlPointer,
+        region: Option<WlRegion>,
+        lifetime: WEnum<Lifetime>,
+    ) {
+        if self
+            .constraints
+            .iter()
+            .any(|constraint| constraint.surface == surface && constraint.pointer == pointer)
+        {
+            manager.post_error(
+                zwp_pointer_constraints_v1::Error::AlreadyConstrained,
+                "Surface is already constrained for this pointer",
+            );
+            return;
+        }
+
+        self.constraints.push(PointerConstraint {
+            object,
+            surface,
+            pointer,
+            oneshot: !matches!(lifetime, WEnum::Value(Lifetime::Persistent)),
+            region: region.as_ref().map(get_region_attributes),
+            pending_region: None,
+            cursor_hint: None,
+            pending_cursor_hint: None,
+            active: false,
+            defunct: false,
+        });
+    }
+
+    fn with_constraint(
+        &mut self,
+        object: &ConstraintObject,
+        f: impl FnOnce(&mut PointerConstraint),
+    ) {
+        if let Some(constraint) = self
+            .constraints
+            .iter_mut()
+            .find(|constraint| &constraint.object == object)
+        {
+            f(constraint);
+        }
+    }
+}
+
+impl<D> GlobalDispatch<ZwpPointerConstraintsV1, (), D> for PointerConstraintsState
+where
+    D: GlobalDispatch<ZwpPointerConstraintsV1, ()>
+        + Dispatch<ZwpPointerConstraintsV1, ()>
+        + Dispatch<ZwpLockedPointerV1, ()>
+        + Dispatch<ZwpConfinedPointerV1, ()>
+        + PointerConstraintsHandler
+        + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _dh: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwpPointerConstraintsV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl<D> Dispatch<ZwpPointerConstraintsV1, (), D> for PointerConstraintsState
+where
+    D: GlobalDispatch<ZwpPointerConstraintsV1, ()>
+        + Dispatch<ZwpPointerConstraintsV1, ()>
+        + Dispatch<ZwpLockedPointerV1, ()>
+        + Dispatch<ZwpConfinedPointerV1, ()>
+        + PointerConstraintsHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        obj: &ZwpPointerConstraintsV1,
+        request: zwp_pointer_constraints_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_pointer_constraints_v1::Request::LockPointer {
+                id,
+                surface,
+                pointer,
+                region,
+                lifetime,
+            } => {
+                let locked = data_init.init(id, ());
+                state.pointer_constraints_state().add(
+                    obj,
+                    ConstraintObject::Locked(locked),
+                    surface,
+                    pointer,
+                    region,
+                    lifetime,
+                );
+            }
+            zwp_pointer_constraints_v1::Request::ConfinePointer {
+                id,
+                surface,
+                pointer,
+                region,
+                lifetime,
+            } => {
+                let confined = data_init.init(id, ());
+                state.pointer_constraints_state().add(
+                    obj,
+                    ConstraintObject::Confined(confined),
+                    surface,
+                    pointer,
+                    region,
+                    lifetime,
+                );
+            }
+            zwp_pointer_constraints_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ZwpLockedPointerV1, (), D> for PointerConstraintsState
+where
+    D: GlobalDispatch<ZwpPointerConstraintsV1, ()>
+        + Dispatch<ZwpPointerConstraintsV1, ()>
+        + Dispatch<ZwpLockedPointerV1, ()>
+        + Dispatch<ZwpConfinedPointerV1, ()>
+        + PointerConstraintsHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        obj: &ZwpLockedPointerV1,
+        request: zwp_locked_pointer_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        let object = ConstraintObject::Locked(obj.clone());
+        match request {
+            zwp_locked_pointer_v1::Request::SetCursorPositionHint {
+                surface_x,
+                surface_y,
+            } => state
+                .pointer_constraints_state()
+                .with_constraint(&object, |constraint| {
+                    constraint.pending_cursor_hint = Some((surface_x, surface_y).into());
+                }),
+            zwp_locked_pointer_v1::Request::SetRegion { region } => state
+                .pointer_constraints_state()
+                .with_constraint(&object, |constraint| {
+                    constraint.pending_region = Some(region.as_ref().map(get_region_attributes));
+                }),
+            zwp_locked_pointer_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, resource: ObjectId, _data: &()) {
+        state
+            .pointer_constraints_state()
+            .constraints
+            .retain(|constraint| constraint.object.id() != resource);
+    }
+}
+
+impl<D> Dispatch<ZwpConfinedPointerV1, (), D> for PointerConstraintsState
+where
+    D: GlobalDispatch<ZwpPointerConstraintsV1, ()>
+        + Dispatch<ZwpPointerConstraintsV1, ()>
+        + Dispatch<ZwpLockedPointerV1, ()>
+        + Dispatch<ZwpConfinedPointerV1, ()>
+        + PointerConstraintsHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        obj: &ZwpConfinedPointerV1,
+        request: zwp_confined_pointer_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        let object = ConstraintObject::Confined(obj.clone());
+        match request {
+            zwp_confined_pointer_v1::Request::SetRegion { region } => state
+                .pointer_constraints_state()
+                .with_constraint(&object, |constraint| {
+                    constraint.pending_region = Some(region.as_ref().map(get_region_attributes));
+                }),
+            zwp_confined_pointer_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, resource: ObjectId, _data: &()) {
+        state
+            .pointer_constraints_state()
+            .constraints
+            .retain(|constraint| constraint.object.id() != resource);
+    }
+}
+
+macro_rules! delegate_pointer_constraints {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::pointer_constraints::zv1::server::zwp_pointer_constraints_v1::ZwpPointerConstraintsV1: ()
+        ] => $crate::wayland::protocols::pointer_constraints::PointerConstraintsState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::pointer_constraints::zv1::server::zwp_pointer_constraints_v1::ZwpPointerConstraintsV1: ()
+        ] => $crate::wayland::protocols::pointer_constraints::PointerConstraintsState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::pointer_constraints::zv1::server::zwp_locked_pointer_v1::ZwpLockedPointerV1: ()
+        ] => $crate::wayland::protocols::pointer_constraints::PointerConstraintsState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::pointer_constraints::zv1::server::zwp_confined_pointer_v1::ZwpConfinedPointerV1: ()
+        ] => $crate::wayland::protocols::pointer_constraints::PointerConstraintsState);
+    };
+}
+pub(crate) use delegate_pointer_constraints;
@@ -0,0 +1,347 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use smithay::{
+    output::Output,
+    reexports::{
+        wayland_protocols::ext::session_lock::v1::server::{
+            ext_session_lock_manager_v1::{self, ExtSessionLockManagerV1},
+            ext_session_lock_surface_v1::{self, ExtSessionLockSurfaceV1},
+            ext_session_lock_v1::{self, ExtSessionLockV1},
+        },
+        wayland_server::{
+            backend::{ClientId, GlobalId, ObjectId},
+            protocol::wl_surface::WlSurface,
+            Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+        },
+    },
+    utils::{IsAlive, Logical, Serial, Size, SERIAL_COUNTER},
+    wayland::compositor,
+};
+
+const LOCK_SURFACE_ROLE: &str = "ext_session_lock_surface_v1";
+
+pub struct SessionLockManagerState {
+    global: GlobalId,
+}
+
+pub trait SessionLockHandler {
+    fn session_lock_state(&mut self) -> &mut SessionLockManagerState;
+
+    /// A client requested to lock the session.
+    ///
+    /// Either call [`SessionLocker::lock`] once the session is locked
+    /// or [`SessionLocker::finished`] to deny the request.
+    fn lock(&mut self, locker: SessionLocker);
+    /// A locker, that was told the session is locked, requested to unlock it.
+    ///
+    /// Must be ignored, unless `locker` is the one currently holding the lock.
+    fn unlock(&mut self, locker: SessionLocker);
+    /// The locker created a new lock surface for the given output
+    fn new_surface(&mut self, surface: LockSurface, output: Output);
+}
+
+#[derive(Debug, Default)]
+pub struct SessionLockData {
+    locked: AtomicBool,
+    finished: AtomicBool,
+    outputs: Mutex<Vec<Output>>,
+}
+
+#[derive(Debug)]
+pub struct LockSurfaceData {
+    pub wl_surface: WlSurface,
+    last_configure: Mutex<Option<(Serial, Size<i32, Logical>)>>,
+}
+
+/// Client trying to lock the session
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionLocker {
+    lock: ExtSessionLockV1,
+}
+
+impl SessionLocker {
+    /// Tells the client the session is locked now
+    pub fn lock(&self) {
+        let data = self.lock.data::<SessionLockData>().unwrap();
+        if !data.finished.load(Ordering::SeqCst) && !data.locked.swap(true, Ordering::SeqCst) {
+            self.lock.locked();
+        }
+    }
+
+    /// Tells the client, that it can't lock the session
+    pub fn finished(&self) {
+        let data = self.lock.data::<SessionLockData>().unwrap();
+        if !data.locked.load(Ordering::SeqCst) && !data.finished.swap(true, Ordering::SeqCst) {
+            self.lock.finished();
+        }
+    }
+
+    pub fn client_id(&self) -> Option<ClientId> {
+        self.lock.client().map(|c| c.id())
+    }
+}
+
+impl IsAlive for SessionLocker {
+    fn alive(&self) -> bool {
+        self.lock.is_alive()
+    }
+}
+
+/// Surface shown on a single output while the session is locked
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockSurface {
+    surface: ExtSessionLockSurfaceV1,
+}
+
+impl LockSurface {
+    pub fn wl_surface(&self) -> &WlSurface {
+        &self.surface.data::<LockSurfaceData>().unwrap().wl_surface
+    }
+
+    /// Asks the client to resize the surface, lock surfaces always have the size of their output
+    pub fn send_configure(&self, size: Size<i32, Logical>) -> Serial {
+        let serial = SERIAL_COUNTER.next_serial();
+        *self
+            .surface
+            .data::<LockSurfaceData>()
+            .unwrap()
+            .last_configure
+            .lock()
+            .unwrap() = Some((serial, size));
+        self.surface
+            .configure(serial.into(), size.w as u32, size.h as u32);
+        serial
+    }
+
+    /// Size last send to the client, if any
+    pub fn configured_size(&self) -> Option<Size<i32, Logical>> {
+        self.surface
+            .data::<LockSurfaceData>()
+            .unwrap()
+            .last_configure
+            .lock()
+            .unwrap()
+            .map(|(_, size)| size)
+    }
+}
+
+impl IsAlive for LockSurface {
+    fn alive(&self) -> bool {
+        self.surface.is_alive() && self.wl_surface().is_alive()
+    }
+}
+
+impl SessionLockManagerState {
+    pub fn new<D>(dh: &DisplayHandle) -> SessionLockManagerState
+    where
+        D: GlobalDispatch<ExtSessionLockManagerV1, ()>
+            + Dispatch<ExtSessionLockManagerV1, ()>
+            + Dispatch<ExtSessionLockV1, SessionLockData>
+            + Dispatch<ExtSessionLockSurfaceV1, LockSurfaceData>
+            + SessionLockHandler
+            + 'static,
+    {
+        let global = dh.create_global::<D, ExtSessionLockManagerV1, _>(1, ());
+        SessionLockManagerState { global }
+    }
+
+    pub fn global_id(&self) -> GlobalId {
+        self.global.clone()
+    }
+}
+
+impl<D> GlobalDispatch<ExtSessionLockManagerV1, (), D> for SessionLockManagerState
+where
+    D: GlobalDispatch<ExtSessionLockManagerV1, ()>
+        + Dispatch<ExtSessionLockManagerV1, ()>
+        + Dispatch<ExtSessionLockV1, SessionLockData>
+        + Dispatch<ExtSessionLockSurfaceV1, LockSurfaceData>
+        + SessionLockHandler
+        + 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _dh: &DisplayHandle,
+        _client: &Client,
+        resource: New<ExtSessionLockManagerV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl<D> Dispatch<ExtSessionLockManagerV1, (), D> for SessionLockManagerState
+where
+    D: GlobalDispatch<ExtSessionLockManagerV1, ()>
+        + Dispatch<ExtSessionLockManagerV1, ()>
+        + Dispatch<ExtSessionLockV1, SessionLockData>
+        + Dispatch<ExtSessionLockSurfaceV1, LockSurfaceData>
+        + SessionLockHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _obj: &ExtSessionLockManagerV1,
+        request: ext_session_lock_manager_v1::Request,
+        _data: &(),
+        _dh: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_session_lock_manager_v1::Request::Lock { id } => {
+                let lock = data_init.init(id, SessionLockData::default());
+                state.lock(SessionLocker { lock });
+            }
+            ext_session_lock_manager_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<D> Dispatch<ExtSessionLockV1, SessionLockData, D> for SessionLockManagerState
+where
+    D: GlobalDispatch<ExtSessionLockManagerV1, ()>
+        + Dispatch<ExtSessionLockManagerV1, ()>
+        + Dispatch<ExtSessionLockV1, SessionLockData>
+        + Dispatch<ExtSessionLockSurfaceV1, LockSurfaceData>
+        + SessionLockHandler
+        + 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        obj: &ExtSessionLockV1,
+        request: ext_session_lock_v1::Request,
+        data: &SessionLockData,
+        _dh: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_session_lock_v1::Request::GetLockSurface {
+                id,
+                surface,
+                output,
+            } => {
+                let output = Output::from_resource(&output);
+                let mut outputs = data.outputs.lock().unwrap();
+                if let Some(output) = output.as_ref() {
+                    if outputs.contains(output) {
+                        obj.post_error(
+                            ext_session_lock_v1::Error::DuplicateOutput,
+                            "Output already has a lock surface",
+                        );
+                        return;
+                    }
+                }
+                if compositor::give_role(&surface, LOCK_SURFACE_ROLE).is_err() {
+                    obj.post_error(
+                        ext_session_lock_v1::Error::Role,
+                        "Surface already has a role",
+                    );
+                    return;
+                }
+
+                let lock_surface = LockSurface {
+                    surface: data_init.init(
+                        id,
+                        LockSurfaceData {
+                            wl_surface: surface,
+                            last_configure: Mutex::new(None),
+                        },
+                    ),
+                };
+                // the output might already be gone, in which case the surface is just never shown
+                if let Some(output) = output {
+                    outputs.push(output.clone());
+                    std::mem::drop(outputs);
+                    if !data.finished.load(Ordering::SeqCst) {
+                        state.new_surface(lock_surface, output);
+                    }
+                }
+            }
+            ext_session_lock_v1::Request::UnlockAndDestroy => {
+                if !data.locked.load(Ordering::SeqCst) {
+                    obj.post_error(
+                        ext_session_lock_v1::Error::InvalidUnlock,
+                        "Session was never locked",
+                    );
+                    return;
+                }
+                state.unlock(SessionLocker { lock: obj.clone() });
+            }
+            ext_session_lock_v1::Request::Destroy => {
+                if data.locked.load(Ordering::SeqCst) {
+                    obj.post_error(
+                        ext_session_lock_v1::Error::InvalidDestroy,
+                        "Tried to destroy the lock without unlocking",
+                    );
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn destroyed(_state: &mut D, _client: ClientId, _resource: ObjectId, _data: &SessionLockData) {
+        // A locker crashing must not unlock the session, so there is nothing to do here.
+    }
+}
+
+impl<D> Dispatch<ExtSessionLockSurfaceV1, LockSurfaceData, D> for SessionLockManagerState
+where
+    D: GlobalDispatch<ExtSessionLockManagerV1, ()>
+        + Dispatch<ExtSessionLockManagerV1, ()>
+        + Dispatch<ExtSessionLockV1, SessionLockData>
+        + Dispatch<ExtSessionLockSurfaceV1, LockSurfaceData>
+        + SessionLockHandler
+        + 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        obj: &ExtSessionLockSurfaceV1,
+        request: ext_session_lock_surface_v1::Request,
+        data: &LockSurfaceData,
+        _dh: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_session_lock_surface_v1::Request::AckConfigure { serial } => {
+                let serial = Serial::from(serial);
+                let configured = data.last_configure.lock().unwrap().map(|(s, _)| s);
+                if configured.map_or(true, |configured| serial > configured) {
+                    obj.post_error(
+                        ext_session_lock_surface_v1::Error::InvalidSerial,
+                        "Serial does not match any configure",
+                    );
+                }
+            }
+            ext_session_lock_surface_v1::Request::Destroy => {}
+            _ => unreachable!(),
+        }
+    }
+}
+
+macro_rules! delegate_session_lock {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::session_lock::v1::server::ext_session_lock_manager_v1::ExtSessionLockManagerV1: ()
+        ] => $crate::wayland::protocols::session_lock::SessionLockManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::session_lock::v1::server::ext_session_lock_manager_v1::ExtSessionLockManagerV1: ()
+        ] => $crate::wayland::protocols::session_lock::SessionLockManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::session_lock::v1::server::ext_session_lock_v1::ExtSessionLockV1: $crate::wayland::protocols::session_lock::SessionLockData
+        ] => $crate::wayland::protocols::session_lock::SessionLockManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::session_lock::v1::server::ext_session_lock_surface_v1::ExtSessionLockSurfaceV1: $crate::wayland::protocols::session_lock::LockSurfaceData
+        ] => $crate::wayland::protocols::session_lock::SessionLockManagerState);
+    };
+}
+pub(crate) use delegate_session_lock;
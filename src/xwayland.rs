@@ -219,6 +219,18 @@ impl XwmHandler for Data {
             })
         {
             space.unmap(&element);
+        } else {
+            let surface = CosmicSurface::X11(window.clone());
+            if let Some(space) = self
+                .state
+                .common
+                .shell
+                .workspaces
+                .spaces_mut()
+                .find(|space| space.is_minimized(&surface))
+            {
+                space.forget_minimized(&surface);
+            }
         }
 
         let outputs = if let Some(wl_surface) = window.wl_surface() {
@@ -450,6 +462,25 @@ impl XwmHandler for Data {
         }
     }
 
+    fn minimize_request(&mut self, _xwm: XwmId, window: X11Surface) {
+        self.state
+            .common
+            .shell
+            .minimize(&CosmicSurface::X11(window));
+    }
+
+    fn unminimize_request(&mut self, _xwm: XwmId, window: X11Surface) {
+        let seat = self.state.common.last_active_seat().clone();
+        if let Some(mapped) = self
+            .state
+            .common
+            .shell
+            .unminimize(&CosmicSurface::X11(window), &seat)
+        {
+            Common::set_focus(&mut self.state, Some(&mapped.into()), &seat, None);
+        }
+    }
+
     fn send_selection(
         &mut self,
         _xwm: XwmId,